@@ -0,0 +1,248 @@
+//! Fleet baselines: a named set of settings and the values a corp expects them to
+//! hold, shared as a TOML file. Members load one to see how each of their
+//! characters measures up and apply it in one go. Each setting is checked as a
+//! lint rule expecting its value, and applied through a bulk edit.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::{CharacterFile, FileType};
+use crate::inspect::{self, KeyPath};
+use crate::lint::{self, Finding, Rule, RuleFile};
+use crate::marshal;
+
+/// Extension offered when saving a baseline
+pub const BASELINE_EXTENSION: &str = "toml";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, rename = "setting")]
+    pub settings: Vec<BaselineSetting>,
+}
+
+/// One expected value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineSetting {
+    pub file: RuleFile,
+    /// Settings path, as the inspector shows it
+    pub key: String,
+    pub value: String,
+}
+
+impl Baseline {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            description: String::new(),
+            settings: Vec::new(),
+        }
+    }
+
+    /// Adds the value at `path`, replacing any earlier value for the same setting.
+    /// Launcher files have no decoded settings and are refused.
+    pub fn add(&mut self, file_type: FileType, path: &KeyPath, value: String) -> Result<()> {
+        let file = RuleFile::for_type(file_type)
+            .context("Only character and account settings can go in a baseline")?;
+        let key = path.to_string();
+        self.settings.retain(|s| !(s.file == file && s.key == key));
+        self.settings.push(BaselineSetting { file, key, value });
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let baseline: Self = toml::from_str(&text)
+            .with_context(|| format!("{} is not a baseline file", path.display()))?;
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = toml::to_string_pretty(self).context("Failed to serialize baseline")?;
+        fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// One lint rule per setting, expecting its value.
+    pub fn rules(&self) -> Vec<Rule> {
+        self.settings
+            .iter()
+            .map(|s| Rule {
+                name: format!("{} = {}", s.key, s.value),
+                description: String::new(),
+                file: Some(s.file),
+                key: s.key.clone(),
+                flag_if: None,
+                expect: Some(s.value.clone()),
+                fix: None,
+                enabled: true,
+            })
+            .collect()
+    }
+
+    /// How each character and account file measures up.
+    pub fn check(&self, files: &[CharacterFile]) -> ComplianceReport {
+        let rules = self.rules();
+        let mut report = ComplianceReport::default();
+        for file in files {
+            let Some(kind) = RuleFile::for_type(file.file_type) else {
+                continue;
+            };
+            let tree = match marshal::read_file(&file.path) {
+                Ok(tree) => tree,
+                Err(e) => {
+                    report.errors.push((file.path.clone(), format!("{:#}", e)));
+                    continue;
+                }
+            };
+            let applicable: Vec<&BaselineSetting> =
+                self.settings.iter().filter(|s| s.file == kind).collect();
+            if applicable.is_empty() {
+                continue;
+            }
+            report.characters.push(Compliance {
+                file: file.clone(),
+                checked: applicable.len(),
+                failing: lint::check_tree(&rules, file, &tree),
+                missing: applicable
+                    .iter()
+                    .filter(|s| inspect::resolve(&tree, &s.key).is_none())
+                    .map(|s| s.key.clone())
+                    .collect(),
+            });
+        }
+        report
+    }
+}
+
+/// How one file measures up to a baseline
+#[derive(Debug, Clone)]
+pub struct Compliance {
+    pub file: CharacterFile,
+    /// Baseline settings that apply to this kind of file
+    pub checked: usize,
+    /// Settings holding another value; each fix writes the baseline's
+    pub failing: Vec<Finding>,
+    /// Settings the file does not have, which applying cannot add
+    pub missing: Vec<String>,
+}
+
+impl Compliance {
+    pub fn passed(&self) -> usize {
+        self.checked - self.failing.len() - self.missing.len()
+    }
+
+    pub fn is_compliant(&self) -> bool {
+        self.failing.is_empty() && self.missing.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ComplianceReport {
+    pub characters: Vec<Compliance>,
+    /// Files that could not be decoded, with the reason
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl ComplianceReport {
+    /// Everything applying the baseline would change
+    pub fn fixes(&self) -> Vec<Finding> {
+        self.characters
+            .iter()
+            .flat_map(|c| c.failing.iter().cloned())
+            .collect()
+    }
+
+    /// Plain-text report, one line per file plus one per failing setting.
+    pub fn to_text(
+        &self,
+        baseline: &Baseline,
+        title_of: impl Fn(&CharacterFile) -> String,
+    ) -> String {
+        let compliant = self.characters.iter().filter(|c| c.is_compliant()).count();
+        let mut text = format!(
+            "Baseline \"{}\": {} of {} file(s) compliant\n\n",
+            baseline.name,
+            compliant,
+            self.characters.len()
+        );
+        for c in &self.characters {
+            text.push_str(&format!(
+                "{}: {}/{}\n",
+                title_of(&c.file),
+                c.passed(),
+                c.checked
+            ));
+            for finding in &c.failing {
+                text.push_str(&format!("  {} is {}\n", finding.rule, finding.value));
+            }
+            for key in &c.missing {
+                text.push_str(&format!("  {} is missing\n", key));
+            }
+        }
+        for (path, error) in &self.errors {
+            text.push_str(&format!("Could not check {}: {}\n", path.display(), error));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery;
+    use crate::inspect::Segment;
+    use crate::marshal::Value;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_baseline_compliance() -> Result<()> {
+        let dir = tempdir()?;
+        let write = |id: u64, scale: f64| {
+            let path = dir.path().join(format!("core_user_{}.dat", id));
+            let tree = Value::Dict(vec![(
+                Value::Bytes(b"ui".to_vec()),
+                Value::Dict(vec![(Value::Bytes(b"scale".to_vec()), Value::Float(scale))]),
+            )]);
+            fs::write(&path, marshal::encode(&tree)).unwrap();
+            discovery::settings_file(path).unwrap()
+        };
+        let main = write(1, 0.9);
+        let alt = write(2, 1.25);
+
+        let scale = KeyPath(vec![
+            Segment::Key(Value::Bytes(b"ui".to_vec())),
+            Segment::Key(Value::Bytes(b"scale".to_vec())),
+        ]);
+        let mut baseline = Baseline::new("Fleet".to_string());
+        baseline.add(FileType::User, &scale, "1.0".to_string())?;
+        baseline.add(FileType::User, &scale, "0.9".to_string())?;
+        baseline.add(
+            FileType::User,
+            &scale.child(Segment::Index(0)),
+            "1".to_string(),
+        )?;
+        assert!(baseline
+            .add(FileType::Launcher, &scale, "1".to_string())
+            .is_err());
+        assert_eq!(baseline.settings.len(), 2);
+
+        let file = dir.path().join("fleet.toml");
+        baseline.save(&file)?;
+        let loaded = Baseline::load(&file)?;
+        assert_eq!(loaded, baseline);
+
+        let report = loaded.check(&[main, alt]);
+        assert_eq!(report.characters.len(), 2);
+        assert_eq!(report.characters[0].passed(), 1);
+        assert_eq!(report.characters[0].missing, vec!["ui/scale/[0]"]);
+        assert_eq!(report.characters[1].failing[0].value, "1.25");
+        assert_eq!(report.fixes().len(), 1);
+        assert_eq!(report.fixes()[0].fix.as_deref(), Some("0.9"));
+        Ok(())
+    }
+}
@@ -0,0 +1,1589 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::IgnoreRules;
+use crate::notify::{NtfyTopic, Webhook};
+use crate::presets::SyncPreset;
+use crate::settings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub last_prefix_path: Option<String>,
+
+    /// Settings profile last worked on, if it was not the first found
+    #[serde(default)]
+    pub last_settings_dir: Option<String>,
+
+    #[serde(default = "default_window_x")]
+    pub window_x: f32,
+
+    #[serde(default = "default_window_y")]
+    pub window_y: f32,
+
+    /// Legacy location of the name cache, now kept in the state directory.
+    /// Read so older configs can be migrated, and carries the names in exported
+    /// app settings; never written back to `config.toml`.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_names",
+        deserialize_with = "deserialize_names"
+    )]
+    pub character_name_cache: HashMap<u64, String>,
+
+    #[serde(default)]
+    pub saved_prefixes: Vec<SavedPrefix>,
+
+    /// Opt-in: write crash reports locally and offer to file them on next launch
+    #[serde(default)]
+    pub crash_reporting: bool,
+
+    /// How character and account IDs are displayed
+    #[serde(default)]
+    pub id_format: IdFormat,
+
+    /// Time zone timestamps are shown in
+    #[serde(default)]
+    pub time_display: TimeDisplay,
+
+    /// Colour scheme of the interface
+    #[serde(default)]
+    pub palette: Palette,
+
+    /// What a full backup restore asks for before it runs
+    #[serde(default)]
+    pub restore_guard: RestoreGuard,
+
+    #[serde(default)]
+    pub fonts: FontConfig,
+
+    /// Site that character IDs link to, if any
+    #[serde(default)]
+    pub id_link: Option<LinkSite>,
+
+    #[serde(default)]
+    pub presets: Vec<SyncPreset>,
+
+    /// Large touch-friendly layout; None detects a Steam Deck
+    #[serde(default)]
+    pub deck_mode: Option<bool>,
+
+    /// Keep the window above EVE clients
+    #[serde(default)]
+    pub always_on_top: bool,
+
+    /// Hide the title bar and borders; the app title becomes the drag handle
+    #[serde(default)]
+    pub borderless: bool,
+
+    /// Show only the compact quick-sync view
+    #[serde(default)]
+    pub mini_mode: bool,
+
+    /// Preset last chosen in the quick-sync view
+    #[serde(default)]
+    pub mini_preset: Option<String>,
+
+    /// Where character names come from
+    #[serde(default)]
+    pub name_source: NameSource,
+
+    /// `id,name` file used when `name_source` is `csv`
+    #[serde(default)]
+    pub names_csv: Option<String>,
+
+    /// Discovery ignore patterns, keyed by prefix path; see [`IgnoreRules`]
+    #[serde(default)]
+    pub ignore_patterns: BTreeMap<String, Vec<String>>,
+
+    /// Tab, source and targets last picked in each prefix, keyed by prefix path
+    #[serde(default)]
+    pub prefix_selections: BTreeMap<String, PrefixSelection>,
+
+    /// Localhost port of the JSON status endpoint; None leaves it off
+    #[serde(default)]
+    pub status_port: Option<u16>,
+
+    /// Chat webhook told about syncs, backups and failures
+    #[serde(default)]
+    pub webhook: Option<Webhook>,
+
+    /// ntfy topic told when scheduled presets finish or fail
+    #[serde(default)]
+    pub ntfy: Option<NtfyTopic>,
+
+    /// Opt-in: fetch the news feed shown on the About screen
+    #[serde(default)]
+    pub news_feed: bool,
+
+    /// How new backup folders are named; see [`settings::BACKUP_NAME_TOKENS`]
+    #[serde(default = "default_backup_name_format")]
+    pub backup_name_format: String,
+
+    /// Backups of each trigger to keep per profile; unlisted triggers keep all
+    #[serde(default)]
+    pub backup_retention: settings::BackupRetention,
+
+    /// App version that last ran with this config; None for builds that did not
+    /// record it
+    #[serde(default)]
+    pub last_run_version: Option<String>,
+}
+
+/// Display style for character and account IDs. Copying always uses the raw digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdFormat {
+    #[default]
+    Plain,
+    /// Digits in groups of three, separated by narrow no-break spaces, which no
+    /// locale reads as a decimal separator
+    Grouped,
+}
+
+impl IdFormat {
+    pub const ALL: [IdFormat; 2] = [IdFormat::Plain, IdFormat::Grouped];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IdFormat::Plain => "Plain",
+            IdFormat::Grouped => "Grouped",
+        }
+    }
+
+    pub fn format(self, id: u64) -> String {
+        let digits = id.to_string();
+        match self {
+            IdFormat::Plain => digits,
+            IdFormat::Grouped => {
+                let mut grouped = String::new();
+                for (i, c) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                        grouped.push('\u{202F}');
+                    }
+                    grouped.push(c);
+                }
+                grouped
+            }
+        }
+    }
+}
+
+/// Time zone timestamps are shown in. Either way dates are ISO 8601 with a 24-hour
+/// time and the zone spelled out, so a time seen while travelling still matches
+/// the backup made then. File and folder names always use UTC, which keeps them
+/// sorting in the order they were made.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeDisplay {
+    /// The system's time zone, with its UTC offset
+    #[default]
+    Local,
+    Utc,
+}
+
+impl TimeDisplay {
+    pub const ALL: [TimeDisplay; 2] = [TimeDisplay::Local, TimeDisplay::Utc];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeDisplay::Local => "Local time",
+            TimeDisplay::Utc => "UTC",
+        }
+    }
+
+    pub fn format<Tz: TimeZone>(self, time: &DateTime<Tz>) -> String {
+        match self {
+            TimeDisplay::Local => time
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M %:z")
+                .to_string(),
+            TimeDisplay::Utc => time
+                .with_timezone(&Utc)
+                .format("%Y-%m-%d %H:%M UTC")
+                .to_string(),
+        }
+    }
+
+    /// The calendar date of `time` in this zone, as `2026-03-01`.
+    pub fn date<Tz: TimeZone>(self, time: &DateTime<Tz>) -> String {
+        match self {
+            TimeDisplay::Local => time.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+            TimeDisplay::Utc => time.with_timezone(&Utc).format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    /// Formats a stored RFC 3339 time, or returns it unchanged if it is not one.
+    pub fn format_rfc3339(self, text: &str) -> String {
+        DateTime::parse_from_rfc3339(text)
+            .map(|time| self.format(&time))
+            .unwrap_or_else(|_| text.to_string())
+    }
+}
+
+/// Colour scheme of the interface
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    /// Green on teal
+    #[default]
+    Pack,
+    /// Blue, yellow and vermilion in place of greens and reds, which look alike
+    /// with deuteranopia and protanopia
+    ColorSafe,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 2] = [Palette::Pack, Palette::ColorSafe];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Pack => "Pack",
+            Palette::ColorSafe => "Color-blind safe",
+        }
+    }
+}
+
+/// Words a [`RestoreGuard::Word`] confirmation picks from
+const RESTORE_WORDS: [&str; 12] = [
+    "capsule",
+    "stargate",
+    "citadel",
+    "drifter",
+    "wormhole",
+    "tritanium",
+    "jovian",
+    "bookmark",
+    "tether",
+    "warpcore",
+    "overview",
+    "undock",
+];
+
+/// What a full backup restore, which replaces every file in the profile, asks
+/// for beyond a click on Yes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreGuard {
+    #[default]
+    Click,
+    /// Typing the date the backup was made
+    Date,
+    /// Typing a word picked for the backup
+    Word,
+}
+
+impl RestoreGuard {
+    pub const ALL: [RestoreGuard; 3] =
+        [RestoreGuard::Click, RestoreGuard::Date, RestoreGuard::Word];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RestoreGuard::Click => "Click Yes",
+            RestoreGuard::Date => "Type the backup's date",
+            RestoreGuard::Word => "Type a word",
+        }
+    }
+
+    /// What has to be typed to restore `backup`, or None if a click will do. The
+    /// date is in `zone`; a backup whose time is unknown asks for a word instead.
+    pub fn phrase(self, backup: &Path, zone: TimeDisplay) -> Option<String> {
+        use std::hash::{Hash, Hasher};
+
+        let created = settings::backup_created(backup);
+        match (self, created) {
+            (RestoreGuard::Click, _) => None,
+            (RestoreGuard::Date, Some(created)) => Some(zone.date(&created)),
+            (RestoreGuard::Date | RestoreGuard::Word, _) => {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                backup.hash(&mut hasher);
+                let word = RESTORE_WORDS[hasher.finish() as usize % RESTORE_WORDS.len()];
+                Some(word.to_string())
+            }
+        }
+    }
+}
+
+/// Provider of character names; see `names::provider`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameSource {
+    /// Tranquility's ESI
+    #[default]
+    Esi,
+    /// Serenity's ESI, for the Chinese server
+    Serenity,
+    /// A local `id,name` file
+    Csv,
+    /// No lookups; characters are shown by ID
+    IdsOnly,
+}
+
+impl NameSource {
+    pub const ALL: [NameSource; 4] = [
+        NameSource::Esi,
+        NameSource::Serenity,
+        NameSource::Csv,
+        NameSource::IdsOnly,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NameSource::Esi => "ESI (Tranquility)",
+            NameSource::Serenity => "ESI (Serenity)",
+            NameSource::Csv => "CSV file",
+            NameSource::IdsOnly => "IDs only",
+        }
+    }
+}
+
+/// Killboard and lookup sites that can show a character by ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkSite {
+    ZKillboard,
+    EveWho,
+}
+
+impl LinkSite {
+    pub const ALL: [LinkSite; 2] = [LinkSite::ZKillboard, LinkSite::EveWho];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LinkSite::ZKillboard => "zKillboard",
+            LinkSite::EveWho => "EveWho",
+        }
+    }
+
+    pub fn character_url(self, id: u64) -> String {
+        match self {
+            LinkSite::ZKillboard => format!("https://zkillboard.com/character/{}/", id),
+            LinkSite::EveWho => format!("https://evewho.com/character/{}", id),
+        }
+    }
+}
+
+/// Body text size egui uses out of the box; other text styles scale with it
+pub const DEFAULT_FONT_SIZE: f32 = 12.5;
+
+/// Interface and monospace fonts, and the size of body text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FontConfig {
+    /// TTF or OTF file for interface text; None keeps the built-in font
+    #[serde(default)]
+    pub ui_font: Option<String>,
+    /// Font for the log, audit log and settings inspector; None keeps the bundled
+    /// Hack
+    #[serde(default)]
+    pub mono_font: Option<String>,
+    /// Body text size in points
+    #[serde(default = "default_font_size")]
+    pub size: f32,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        Self {
+            ui_font: None,
+            mono_font: None,
+            size: DEFAULT_FONT_SIZE,
+        }
+    }
+}
+
+fn default_font_size() -> f32 {
+    DEFAULT_FONT_SIZE
+}
+
+/// A known Wine prefix with a user-assigned alias and tags. Prefixes are
+/// remembered when used; bookmarked ones are favorites and are kept until removed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedPrefix {
+    pub path: String,
+
+    #[serde(default)]
+    pub alias: Option<String>,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Entries saved before recent prefixes were remembered were all bookmarks
+    #[serde(default = "default_favorite")]
+    pub favorite: bool,
+
+    /// When the prefix was last selected, as a Unix timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<i64>,
+}
+
+/// What was picked in a prefix when it was last left, restored when it is
+/// selected again. Files are kept by path, since list positions change per scan.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrefixSelection {
+    /// Name of the active tab
+    #[serde(default)]
+    pub tab: Option<String>,
+
+    #[serde(default)]
+    pub source: Option<PathBuf>,
+
+    #[serde(default)]
+    pub targets: Vec<PathBuf>,
+}
+
+fn default_favorite() -> bool {
+    true
+}
+
+/// Prefixes that are neither favorites nor nicknamed are forgotten past this many
+pub const MAX_RECENT_PREFIXES: usize = 8;
+
+/// Portable JSON bundle of bookmarked prefixes and sync presets, for moving to
+/// another machine.
+#[derive(Debug, Serialize, Deserialize)]
+struct PrefixBundle {
+    version: u32,
+    prefixes: Vec<SavedPrefix>,
+    /// Missing from bundles written before presets were included
+    #[serde(default)]
+    presets: Vec<SyncPreset>,
+}
+
+const PREFIX_BUNDLE_VERSION: u32 = 1;
+
+/// System-wide prefix registry for machines shared by several OS users
+pub const SHARED_REGISTRY_PATH: &str = "/etc/pack-preferences/prefixes.toml";
+
+/// Overrides [`SHARED_REGISTRY_PATH`], e.g. to point at a network share
+pub const SHARED_REGISTRY_ENV: &str = "PACK_PREFERENCES_SHARED_PREFIXES";
+
+/// Read-only list of prefixes an administrator registered for every user.
+#[derive(Debug, Default, Deserialize)]
+struct SharedRegistry {
+    #[serde(default)]
+    prefixes: Vec<SavedPrefix>,
+}
+
+/// Describes how an unparseable config file was handled at startup.
+#[derive(Debug, Clone)]
+pub struct ConfigRecovery {
+    pub error: String,
+    /// Where the unparseable file was copied before anything overwrites it
+    pub backup_path: Option<PathBuf>,
+    /// Top-level settings that could still be read from the damaged file
+    pub recovered: Vec<String>,
+}
+
+fn default_backup_name_format() -> String {
+    settings::DEFAULT_BACKUP_NAME_FORMAT.to_string()
+}
+
+/// TOML table keys must be strings, so character IDs are written as text.
+fn serialize_names<S: serde::Serializer>(
+    names: &HashMap<u64, String>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(names.iter().map(|(id, name)| (id.to_string(), name)))
+}
+
+fn deserialize_names<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<HashMap<u64, String>, D::Error> {
+    let names = HashMap::<String, String>::deserialize(deserializer)?;
+    names
+        .into_iter()
+        .map(|(id, name)| Ok((id.parse().map_err(serde::de::Error::custom)?, name)))
+        .collect()
+}
+
+fn default_window_x() -> f32 {
+    100.0
+}
+
+fn default_window_y() -> f32 {
+    100.0
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            last_prefix_path: None,
+            last_settings_dir: None,
+            window_x: default_window_x(),
+            window_y: default_window_y(),
+            character_name_cache: HashMap::new(),
+            saved_prefixes: Vec::new(),
+            crash_reporting: false,
+            id_format: IdFormat::default(),
+            time_display: TimeDisplay::default(),
+            palette: Palette::default(),
+            restore_guard: RestoreGuard::default(),
+            fonts: FontConfig::default(),
+            id_link: None,
+            presets: Vec::new(),
+            deck_mode: None,
+            always_on_top: false,
+            borderless: false,
+            mini_mode: false,
+            mini_preset: None,
+            name_source: NameSource::default(),
+            names_csv: None,
+            ignore_patterns: BTreeMap::new(),
+            prefix_selections: BTreeMap::new(),
+            status_port: None,
+            webhook: None,
+            ntfy: None,
+            news_feed: false,
+            backup_name_format: default_backup_name_format(),
+            backup_retention: BTreeMap::new(),
+            last_run_version: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn config_dir() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .context("Could not determine config directory")?
+            .join("pack-preferences");
+
+        if !config_dir.exists() {
+            fs::create_dir_all(&config_dir).context("Failed to create config directory")?;
+        }
+
+        Ok(config_dir)
+    }
+
+    /// Directory for machine-written state (caches, history, logs, crash reports),
+    /// kept separate so `config.toml` stays small and hand-editable.
+    pub fn state_dir() -> Result<PathBuf> {
+        let state_dir = dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .context("Could not determine state directory")?
+            .join("pack-preferences");
+
+        if !state_dir.exists() {
+            fs::create_dir_all(&state_dir).context("Failed to create state directory")?;
+        }
+
+        Ok(state_dir)
+    }
+
+    /// Discovery ignore rules configured for `prefix`.
+    pub fn ignore_rules(&self, prefix: &Path) -> IgnoreRules {
+        self.ignore_patterns
+            .get(prefix.to_string_lossy().as_ref())
+            .map(|patterns| IgnoreRules::new(patterns))
+            .unwrap_or_default()
+    }
+
+    pub fn config_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("config.toml"))
+    }
+
+    pub fn load() -> Result<Self> {
+        let config_path = Self::config_path()?;
+
+        if config_path.exists() {
+            let contents =
+                fs::read_to_string(&config_path).context("Failed to read config file")?;
+            Self::from_toml(&contents)
+        } else {
+            let config = Config::default();
+            config.save()?;
+            Ok(config)
+        }
+    }
+
+    /// Loads the config, recovering what it can from a damaged file instead of
+    /// silently discarding it. The damaged file is backed up first.
+    pub fn load_or_recover() -> (Self, Option<ConfigRecovery>) {
+        let Ok(config_path) = Self::config_path() else {
+            return (Self::default(), None);
+        };
+        if !config_path.exists() {
+            return (Self::load().unwrap_or_default(), None);
+        }
+        Self::recover_from(&config_path)
+    }
+
+    /// Reads an existing config file. A file that can't be read, isn't UTF-8 or
+    /// doesn't parse is copied aside byte for byte before anything is recovered.
+    fn recover_from(config_path: &Path) -> (Self, Option<ConfigRecovery>) {
+        let bytes = fs::read(config_path);
+        let parsed = match &bytes {
+            Ok(bytes) => std::str::from_utf8(bytes)
+                .context("Config file is not valid UTF-8")
+                .and_then(Self::from_toml),
+            Err(e) => Err(anyhow::anyhow!("Failed to read config file: {}", e)),
+        };
+        let error = match parsed {
+            Ok(config) => return (config, None),
+            Err(e) => e,
+        };
+
+        let backup_path =
+            config_path.with_file_name(format!("config.toml.bad-{}", settings::name_stamp()));
+        let saved = match &bytes {
+            Ok(bytes) => fs::write(&backup_path, bytes),
+            Err(_) => fs::copy(config_path, &backup_path).map(|_| ()),
+        };
+        let backup_path = saved.ok().map(|_| backup_path);
+
+        let contents = bytes
+            .as_deref()
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+        let (config, recovered) = Self::from_toml_lenient(&contents);
+        let recovery = ConfigRecovery {
+            error: format!("{:#}", error),
+            backup_path,
+            recovered,
+        };
+        (config, Some(recovery))
+    }
+
+    /// Best-effort parse of a damaged config: each `[section]` is parsed on its own
+    /// and each field is read independently, so one bad value doesn't lose the rest.
+    /// Returns the config plus the names of the fields that were recovered.
+    pub fn from_toml_lenient(contents: &str) -> (Self, Vec<String>) {
+        let mut table = toml::Table::new();
+        let mut section = String::new();
+
+        for line in contents.lines() {
+            if line.trim_start().starts_with('[') && !section.is_empty() {
+                merge_section(&mut table, &section);
+                section.clear();
+            }
+            section.push_str(line);
+            section.push('\n');
+        }
+        merge_section(&mut table, &section);
+
+        let mut config = Self::default();
+        let mut recovered = Vec::new();
+
+        if let Some(v) = lenient_field(&table, "last_prefix_path") {
+            config.last_prefix_path = v;
+            recovered.push("last_prefix_path".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "last_settings_dir") {
+            config.last_settings_dir = Some(v);
+            recovered.push("last_settings_dir".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "window_x") {
+            config.window_x = v;
+            recovered.push("window_x".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "window_y") {
+            config.window_y = v;
+            recovered.push("window_y".to_string());
+        }
+        if let Some(toml::Value::Table(cache)) = table.get("character_name_cache") {
+            for (id, name) in cache {
+                if let (Ok(id), Some(name)) = (id.parse(), name.as_str()) {
+                    config.character_name_cache.insert(id, name.to_string());
+                }
+            }
+            recovered.push(format!(
+                "character_name_cache ({} names)",
+                config.character_name_cache.len()
+            ));
+        }
+        if let Some(toml::Value::Array(prefixes)) = table.get("saved_prefixes") {
+            config.saved_prefixes = prefixes
+                .iter()
+                .filter_map(|v| v.clone().try_into().ok())
+                .collect();
+            recovered.push("saved_prefixes".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "crash_reporting") {
+            config.crash_reporting = v;
+            recovered.push("crash_reporting".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "id_format") {
+            config.id_format = v;
+            recovered.push("id_format".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "time_display") {
+            config.time_display = v;
+            recovered.push("time_display".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "palette") {
+            config.palette = v;
+            recovered.push("palette".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "restore_guard") {
+            config.restore_guard = v;
+            recovered.push("restore_guard".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "fonts") {
+            config.fonts = v;
+            recovered.push("fonts".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "id_link") {
+            config.id_link = Some(v);
+            recovered.push("id_link".to_string());
+        }
+        if let Some(toml::Value::Array(presets)) = table.get("presets") {
+            config.presets = presets
+                .iter()
+                .filter_map(|v| v.clone().try_into().ok())
+                .collect();
+            recovered.push("presets".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "deck_mode") {
+            config.deck_mode = Some(v);
+            recovered.push("deck_mode".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "always_on_top") {
+            config.always_on_top = v;
+            recovered.push("always_on_top".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "borderless") {
+            config.borderless = v;
+            recovered.push("borderless".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "mini_mode") {
+            config.mini_mode = v;
+            recovered.push("mini_mode".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "mini_preset") {
+            config.mini_preset = Some(v);
+            recovered.push("mini_preset".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "name_source") {
+            config.name_source = v;
+            recovered.push("name_source".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "names_csv") {
+            config.names_csv = Some(v);
+            recovered.push("names_csv".to_string());
+        }
+        if let Some(toml::Value::Table(prefixes)) = table.get("ignore_patterns") {
+            for (prefix, patterns) in prefixes {
+                if let Ok(patterns) = patterns.clone().try_into() {
+                    config.ignore_patterns.insert(prefix.clone(), patterns);
+                }
+            }
+            recovered.push("ignore_patterns".to_string());
+        }
+        if let Some(toml::Value::Table(prefixes)) = table.get("prefix_selections") {
+            for (prefix, selection) in prefixes {
+                if let Ok(selection) = selection.clone().try_into() {
+                    config.prefix_selections.insert(prefix.clone(), selection);
+                }
+            }
+            recovered.push("prefix_selections".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "status_port") {
+            config.status_port = Some(v);
+            recovered.push("status_port".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "webhook") {
+            config.webhook = Some(v);
+            recovered.push("webhook".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "ntfy") {
+            config.ntfy = Some(v);
+            recovered.push("ntfy".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "news_feed") {
+            config.news_feed = v;
+            recovered.push("news_feed".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "backup_name_format") {
+            config.backup_name_format = v;
+            recovered.push("backup_name_format".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "backup_retention") {
+            config.backup_retention = v;
+            recovered.push("backup_retention".to_string());
+        }
+        if let Some(v) = lenient_field(&table, "last_run_version") {
+            config.last_run_version = Some(v);
+            recovered.push("last_run_version".to_string());
+        }
+
+        (config, recovered)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        toml::from_str(contents).context("Failed to parse config file")
+    }
+
+    /// Writes a copy of this config, with the cached character `names`, to an
+    /// arbitrary path (for user-initiated export).
+    pub fn export_to(&self, names: &HashMap<u64, String>, path: &Path) -> Result<()> {
+        let exported = Config {
+            character_name_cache: names.clone(),
+            ..self.clone()
+        };
+        let contents = toml::to_string_pretty(&exported).context("Failed to serialize config")?;
+        fs::write(path, contents).context("Failed to write exported config")?;
+        Ok(())
+    }
+
+    /// Reads a config previously written by `export_to`; its names are left in
+    /// `character_name_cache` for the caller to move into the name cache.
+    pub fn import_from(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).context("Failed to read config file")?;
+        Self::from_toml(&contents)
+    }
+
+    /// How new backups are named and pruned.
+    pub fn backup_options(&self) -> settings::BackupOptions {
+        settings::BackupOptions {
+            name_format: self.backup_name_format.clone(),
+            retention: self.backup_retention.clone(),
+        }
+    }
+
+    /// Describes, line by line, what replacing this config with `other` would change.
+    pub fn diff(&self, other: &Config) -> Vec<String> {
+        let mut changes = Vec::new();
+
+        if self.last_prefix_path != other.last_prefix_path {
+            changes.push(format!(
+                "Last prefix: {} -> {}",
+                self.last_prefix_path.as_deref().unwrap_or("none"),
+                other.last_prefix_path.as_deref().unwrap_or("none")
+            ));
+        }
+
+        if self.last_settings_dir != other.last_settings_dir {
+            changes.push(format!(
+                "Last profile: {} -> {}",
+                self.last_settings_dir.as_deref().unwrap_or("none"),
+                other.last_settings_dir.as_deref().unwrap_or("none")
+            ));
+        }
+
+        if self.window_x != other.window_x || self.window_y != other.window_y {
+            changes.push(format!(
+                "Window position: ({}, {}) -> ({}, {})",
+                self.window_x, self.window_y, other.window_x, other.window_y
+            ));
+        }
+
+        let added = other
+            .character_name_cache
+            .keys()
+            .filter(|id| !self.character_name_cache.contains_key(id))
+            .count();
+        let removed = self
+            .character_name_cache
+            .keys()
+            .filter(|id| !other.character_name_cache.contains_key(id))
+            .count();
+        let renamed = other
+            .character_name_cache
+            .iter()
+            .filter(|(id, name)| {
+                self.character_name_cache
+                    .get(id)
+                    .is_some_and(|old| old != *name)
+            })
+            .count();
+        if added + removed + renamed > 0 {
+            changes.push(format!(
+                "Name cache: {} added, {} removed, {} renamed",
+                added, removed, renamed
+            ));
+        }
+
+        if self.crash_reporting != other.crash_reporting {
+            changes.push(format!(
+                "Crash reports: {} -> {}",
+                self.crash_reporting, other.crash_reporting
+            ));
+        }
+
+        if self.id_format != other.id_format {
+            changes.push(format!(
+                "ID format: {} -> {}",
+                self.id_format.name(),
+                other.id_format.name()
+            ));
+        }
+
+        if self.time_display != other.time_display {
+            changes.push(format!(
+                "Times shown in: {} -> {}",
+                self.time_display.name(),
+                other.time_display.name()
+            ));
+        }
+
+        if self.palette != other.palette {
+            changes.push(format!(
+                "Palette: {} -> {}",
+                self.palette.name(),
+                other.palette.name()
+            ));
+        }
+
+        if self.restore_guard != other.restore_guard {
+            changes.push(format!(
+                "Before a full restore: {} -> {}",
+                self.restore_guard.name(),
+                other.restore_guard.name()
+            ));
+        }
+
+        if self.fonts != other.fonts {
+            let name =
+                |font: &Option<String>| font.clone().unwrap_or_else(|| "default".to_string());
+            changes.push(format!(
+                "Fonts: {}, {}, {}pt -> {}, {}, {}pt",
+                name(&self.fonts.ui_font),
+                name(&self.fonts.mono_font),
+                self.fonts.size,
+                name(&other.fonts.ui_font),
+                name(&other.fonts.mono_font),
+                other.fonts.size
+            ));
+        }
+
+        if self.id_link != other.id_link {
+            let name = |site: Option<LinkSite>| site.map_or("none", LinkSite::name);
+            changes.push(format!(
+                "ID links: {} -> {}",
+                name(self.id_link),
+                name(other.id_link)
+            ));
+        }
+
+        for saved in &other.saved_prefixes {
+            match self.saved_prefixes.iter().find(|p| p.path == saved.path) {
+                None => changes.push(format!("Bookmark added: {}", saved.path)),
+                Some(existing) if existing != saved => {
+                    changes.push(format!("Bookmark changed: {}", saved.path))
+                }
+                Some(_) => {}
+            }
+        }
+        for saved in &self.saved_prefixes {
+            if !other.saved_prefixes.iter().any(|p| p.path == saved.path) {
+                changes.push(format!("Bookmark removed: {}", saved.path));
+            }
+        }
+
+        if self.deck_mode != other.deck_mode {
+            let name = |mode: Option<bool>| match mode {
+                None => "auto",
+                Some(true) => "on",
+                Some(false) => "off",
+            };
+            changes.push(format!(
+                "Deck mode: {} -> {}",
+                name(self.deck_mode),
+                name(other.deck_mode)
+            ));
+        }
+
+        if self.always_on_top != other.always_on_top {
+            changes.push(format!(
+                "Always on top: {} -> {}",
+                self.always_on_top, other.always_on_top
+            ));
+        }
+
+        if self.borderless != other.borderless {
+            changes.push(format!(
+                "Borderless window: {} -> {}",
+                self.borderless, other.borderless
+            ));
+        }
+
+        if self.name_source != other.name_source || self.names_csv != other.names_csv {
+            let name = |config: &Config| match (config.name_source, &config.names_csv) {
+                (NameSource::Csv, Some(path)) => format!("CSV file {}", path),
+                (source, _) => source.name().to_string(),
+            };
+            changes.push(format!("Names: {} -> {}", name(self), name(other)));
+        }
+
+        for (prefix, patterns) in &other.ignore_patterns {
+            if self.ignore_patterns.get(prefix) != Some(patterns) {
+                changes.push(format!(
+                    "Ignore rules for {}: {}",
+                    prefix,
+                    patterns.join(", ")
+                ));
+            }
+        }
+        for prefix in self.ignore_patterns.keys() {
+            if !other.ignore_patterns.contains_key(prefix) {
+                changes.push(format!("Ignore rules removed: {}", prefix));
+            }
+        }
+
+        for (prefix, selection) in &other.prefix_selections {
+            if self.prefix_selections.get(prefix) != Some(selection) {
+                changes.push(format!(
+                    "Selection for {}: {} target(s)",
+                    prefix,
+                    selection.targets.len()
+                ));
+            }
+        }
+        for prefix in self.prefix_selections.keys() {
+            if !other.prefix_selections.contains_key(prefix) {
+                changes.push(format!("Selection removed: {}", prefix));
+            }
+        }
+
+        if self.status_port != other.status_port {
+            let name = |port: Option<u16>| match port {
+                Some(port) => format!("port {}", port),
+                None => "off".to_string(),
+            };
+            changes.push(format!(
+                "Status endpoint: {} -> {}",
+                name(self.status_port),
+                name(other.status_port)
+            ));
+        }
+
+        if self.webhook != other.webhook {
+            let name = |webhook: &Option<Webhook>| match webhook {
+                Some(webhook) => webhook.url.clone(),
+                None => "none".to_string(),
+            };
+            changes.push(format!(
+                "Webhook: {} -> {}",
+                name(&self.webhook),
+                name(&other.webhook)
+            ));
+        }
+
+        if self.ntfy != other.ntfy {
+            let name = |ntfy: &Option<NtfyTopic>| match ntfy {
+                Some(ntfy) => ntfy.url(),
+                None => "none".to_string(),
+            };
+            changes.push(format!(
+                "ntfy topic: {} -> {}",
+                name(&self.ntfy),
+                name(&other.ntfy)
+            ));
+        }
+
+        if self.news_feed != other.news_feed {
+            changes.push(format!(
+                "News feed: {} -> {}",
+                self.news_feed, other.news_feed
+            ));
+        }
+
+        if self.backup_name_format != other.backup_name_format {
+            changes.push(format!(
+                "Backup names: {} -> {}",
+                self.backup_name_format, other.backup_name_format
+            ));
+        }
+
+        if self.backup_retention != other.backup_retention {
+            let describe = |retention: &settings::BackupRetention| {
+                if retention.is_empty() {
+                    return "keep all".to_string();
+                }
+                retention
+                    .iter()
+                    .map(|(trigger, keep)| format!("{} {}", trigger.tag(), keep))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            changes.push(format!(
+                "Backup retention: {} -> {}",
+                describe(&self.backup_retention),
+                describe(&other.backup_retention)
+            ));
+        }
+
+        if self.mini_mode != other.mini_mode {
+            changes.push(format!(
+                "Quick-sync view: {} -> {}",
+                self.mini_mode, other.mini_mode
+            ));
+        }
+
+        if self.mini_preset != other.mini_preset {
+            changes.push(format!(
+                "Quick-sync preset: {} -> {}",
+                self.mini_preset.as_deref().unwrap_or("none"),
+                other.mini_preset.as_deref().unwrap_or("none")
+            ));
+        }
+
+        for preset in &other.presets {
+            match self.presets.iter().find(|p| p.name == preset.name) {
+                None => changes.push(format!("Preset added: {}", preset.name)),
+                Some(existing) if existing != preset => {
+                    changes.push(format!("Preset changed: {}", preset.name))
+                }
+                Some(_) => {}
+            }
+        }
+        for preset in &self.presets {
+            if !other.presets.iter().any(|p| p.name == preset.name) {
+                changes.push(format!("Preset removed: {}", preset.name));
+            }
+        }
+
+        changes
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let config_path = Self::config_path()?;
+        let contents = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        fs::write(&config_path, contents).context("Failed to write config file")?;
+        Ok(())
+    }
+
+    /// Bookmarks a prefix, keeping the existing alias and tags if already saved.
+    pub fn save_prefix(&mut self, path: String) {
+        match self.saved_prefixes.iter_mut().find(|p| p.path == path) {
+            Some(saved) => saved.favorite = true,
+            None => self.saved_prefixes.push(SavedPrefix {
+                path,
+                favorite: true,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Remembers that `path` was selected at `now`, forgetting the oldest recent
+    /// prefixes past [`MAX_RECENT_PREFIXES`] unless they are favorites or nicknamed.
+    pub fn record_prefix_use(&mut self, path: String, now: i64) {
+        match self.saved_prefixes.iter_mut().find(|p| p.path == path) {
+            Some(saved) => saved.last_used = Some(now),
+            None => self.saved_prefixes.push(SavedPrefix {
+                path,
+                last_used: Some(now),
+                ..Default::default()
+            }),
+        }
+
+        let kept = |p: &SavedPrefix| p.favorite || p.alias.is_some();
+        let mut recent: Vec<i64> = self
+            .saved_prefixes
+            .iter()
+            .filter(|p| !kept(p))
+            .map(|p| p.last_used.unwrap_or_default())
+            .collect();
+        if recent.len() > MAX_RECENT_PREFIXES {
+            recent.sort_unstable_by(|a, b| b.cmp(a));
+            let cutoff = recent[MAX_RECENT_PREFIXES - 1];
+            self.saved_prefixes
+                .retain(|p| kept(p) || p.last_used.unwrap_or_default() >= cutoff);
+        }
+    }
+
+    /// Known prefixes that have been used, most recent first.
+    pub fn recent_prefixes(&self) -> Vec<&SavedPrefix> {
+        let mut recent: Vec<&SavedPrefix> = self
+            .saved_prefixes
+            .iter()
+            .filter(|p| p.last_used.is_some())
+            .collect();
+        recent.sort_by_key(|p| std::cmp::Reverse(p.last_used));
+        recent.truncate(MAX_RECENT_PREFIXES);
+        recent
+    }
+
+    /// Location of the shared prefix registry, honouring [`SHARED_REGISTRY_ENV`].
+    pub fn shared_registry_path() -> PathBuf {
+        std::env::var_os(SHARED_REGISTRY_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(SHARED_REGISTRY_PATH))
+    }
+
+    /// Reads the system-wide prefix registry. A missing file means no shared prefixes.
+    /// The registry is never written by the app; per-user bookmarks stay in `config.toml`.
+    pub fn load_shared_prefixes() -> Result<Vec<SavedPrefix>> {
+        let path = Self::shared_registry_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        parse_shared_prefixes(&contents)
+    }
+
+    /// Serializes the bookmarked prefixes and presets as a JSON bundle.
+    pub fn export_prefix_bundle(&self) -> Result<String> {
+        let bundle = PrefixBundle {
+            version: PREFIX_BUNDLE_VERSION,
+            prefixes: self.saved_prefixes.clone(),
+            presets: self.presets.clone(),
+        };
+        serde_json::to_string_pretty(&bundle).context("Failed to serialize prefix bundle")
+    }
+
+    /// Merges a JSON bundle into the bookmarked prefixes and presets. Entries for a
+    /// path that is already saved take the imported alias and tags, and presets
+    /// replace any of the same name. Returns the number of prefixes and presets read.
+    pub fn import_prefix_bundle(&mut self, json: &str) -> Result<(usize, usize)> {
+        let bundle: PrefixBundle =
+            serde_json::from_str(json).context("Failed to parse prefix bundle")?;
+
+        if bundle.version > PREFIX_BUNDLE_VERSION {
+            anyhow::bail!("Unsupported prefix bundle version {}", bundle.version);
+        }
+
+        let counts = (bundle.prefixes.len(), bundle.presets.len());
+        for imported in bundle.prefixes {
+            match self
+                .saved_prefixes
+                .iter_mut()
+                .find(|p| p.path == imported.path)
+            {
+                Some(existing) => *existing = imported,
+                None => self.saved_prefixes.push(imported),
+            }
+        }
+        for imported in bundle.presets {
+            match self.presets.iter_mut().find(|p| p.name == imported.name) {
+                Some(existing) => *existing = imported,
+                None => self.presets.push(imported),
+            }
+        }
+
+        Ok(counts)
+    }
+}
+
+/// Parses a shared registry file: a list of `[[prefixes]]` tables with the same
+/// fields as a bookmark.
+pub fn parse_shared_prefixes(contents: &str) -> Result<Vec<SavedPrefix>> {
+    let registry: SharedRegistry =
+        toml::from_str(contents).context("Failed to parse shared prefix registry")?;
+    Ok(registry.prefixes)
+}
+
+/// Parses one section of a damaged file and merges whatever is valid into `table`.
+fn merge_section(table: &mut toml::Table, section: &str) {
+    if let Ok(parsed) = section.parse::<toml::Table>() {
+        merge_tables(table, parsed);
+        return;
+    }
+
+    // Whole section is broken: salvage the lines that parse on their own
+    let mut lines = section.lines().peekable();
+    let header = lines
+        .next_if(|l| l.trim_start().starts_with('['))
+        .unwrap_or("");
+    for line in lines {
+        if let Ok(parsed) = format!("{}\n{}\n", header, line).parse::<toml::Table>() {
+            merge_tables(table, parsed);
+        }
+    }
+}
+
+/// Merges `more` into `table`, extending tables and arrays rather than replacing them.
+fn merge_tables(table: &mut toml::Table, more: toml::Table) {
+    for (key, value) in more {
+        match (table.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(more)) => existing.extend(more),
+            (Some(toml::Value::Array(existing)), toml::Value::Array(more)) => existing.extend(more),
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+fn lenient_field<T: DeserializeOwned>(table: &toml::Table, key: &str) -> Option<T> {
+    table.get(key).cloned().and_then(|v| v.try_into().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_bundle_round_trip() -> Result<()> {
+        let mut source = Config::default();
+        source.saved_prefixes.push(SavedPrefix {
+            path: "/games/eve/drive_c".to_string(),
+            alias: Some("Main".to_string()),
+            tags: vec!["pvp".to_string()],
+            favorite: true,
+            last_used: None,
+        });
+        let preset = |targets: usize| SyncPreset {
+            name: "weekly push".to_string(),
+            source: PathBuf::from("/games/eve/drive_c/core_char_1.dat"),
+            targets: vec![PathBuf::from("/games/eve/drive_c/core_char_2.dat"); targets],
+            schedule: None,
+            last_run: None,
+            plugins: Vec::new(),
+        };
+        source.presets.push(preset(1));
+        let json = source.export_prefix_bundle()?;
+
+        let mut target = Config::default();
+        target.save_prefix("/games/eve/drive_c".to_string());
+        target.save_prefix("/games/alts/drive_c".to_string());
+        target.presets.push(preset(2));
+
+        assert_eq!(target.import_prefix_bundle(&json)?, (1, 1));
+        assert_eq!(target.saved_prefixes.len(), 2);
+        assert_eq!(target.saved_prefixes[0], source.saved_prefixes[0]);
+        assert_eq!(target.presets, source.presets);
+
+        let older = r#"{"version": 1, "prefixes": []}"#;
+        assert_eq!(target.import_prefix_bundle(older)?, (0, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_recent_prefixes() {
+        let mut config = Config::default();
+        config.save_prefix("/games/main/drive_c".to_string());
+        for n in 0..=MAX_RECENT_PREFIXES as i64 {
+            config.record_prefix_use(format!("/games/alt{}/drive_c", n), n);
+        }
+        config.record_prefix_use("/games/main/drive_c".to_string(), 100);
+
+        // The oldest recent prefix is forgotten; the bookmark stays and is newest
+        assert_eq!(config.saved_prefixes.len(), MAX_RECENT_PREFIXES + 1);
+        assert!(!config
+            .saved_prefixes
+            .iter()
+            .any(|p| p.path == "/games/alt0/drive_c"));
+        let recent = config.recent_prefixes();
+        assert_eq!(recent.len(), MAX_RECENT_PREFIXES);
+        assert_eq!(recent[0].path, "/games/main/drive_c");
+        assert!(recent[0].favorite && !recent[1].favorite);
+    }
+
+    #[test]
+    fn test_parse_shared_prefixes() -> Result<()> {
+        let prefixes = parse_shared_prefixes(
+            r#"
+            [[prefixes]]
+            path = "/srv/eve/tranquility"
+            alias = "LAN"
+            tags = ["shared"]
+
+            [[prefixes]]
+            path = "/srv/eve/singularity"
+            "#,
+        )?;
+        assert_eq!(prefixes.len(), 2);
+        assert_eq!(prefixes[0].alias.as_deref(), Some("LAN"));
+        assert!(prefixes[1].tags.is_empty());
+
+        assert!(prefixes[1].favorite);
+
+        assert!(parse_shared_prefixes("").unwrap().is_empty());
+        assert!(parse_shared_prefixes("prefixes = 3").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_id_format() {
+        assert_eq!(IdFormat::Plain.format(2112345678), "2112345678");
+        assert_eq!(
+            IdFormat::Grouped.format(2112345678),
+            "2\u{202F}112\u{202F}345\u{202F}678"
+        );
+        assert_eq!(IdFormat::Grouped.format(123), "123");
+        assert_eq!(
+            TimeDisplay::Utc.format_rfc3339("2026-03-01T23:30:00-02:00"),
+            "2026-03-02 01:30 UTC"
+        );
+        assert_eq!(TimeDisplay::Utc.format_rfc3339("yesterday"), "yesterday");
+        let time = DateTime::parse_from_rfc3339("2026-03-01T23:30:00-02:00").unwrap();
+        assert_eq!(TimeDisplay::Utc.date(&time), "2026-03-02");
+        assert_eq!(
+            LinkSite::EveWho.character_url(90000001),
+            "https://evewho.com/character/90000001"
+        );
+    }
+
+    #[test]
+    fn test_restore_guard() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        let backup = settings::create_backup(
+            &profile,
+            settings::BackupTrigger::Manual,
+            &settings::BackupOptions::default(),
+        )?;
+        let created = settings::backup_created(&backup).unwrap();
+
+        assert_eq!(RestoreGuard::Click.phrase(&backup, TimeDisplay::Utc), None);
+        assert_eq!(
+            RestoreGuard::Date.phrase(&backup, TimeDisplay::Utc),
+            Some(TimeDisplay::Utc.date(&created))
+        );
+        // Without a known time the date guard falls back to the backup's word
+        let unknown = dir.path().join("copy of settings");
+        let word = RestoreGuard::Word
+            .phrase(&unknown, TimeDisplay::Utc)
+            .unwrap();
+        assert!(RESTORE_WORDS.contains(&word.as_str()));
+        assert_eq!(
+            RestoreGuard::Date.phrase(&unknown, TimeDisplay::Utc),
+            Some(word)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut current = Config::default();
+        current.character_name_cache.insert(1, "Alpha".to_string());
+        current.character_name_cache.insert(2, "Bravo".to_string());
+
+        let mut imported = current.clone();
+        imported.last_prefix_path = Some("/games/eve/drive_c".to_string());
+        imported
+            .character_name_cache
+            .insert(2, "Bravo Renamed".to_string());
+        imported
+            .character_name_cache
+            .insert(3, "Charlie".to_string());
+        imported.save_prefix("/games/eve/drive_c".to_string());
+        imported
+            .backup_retention
+            .insert(settings::BackupTrigger::PreSync, 10);
+        imported.crash_reporting = true;
+        imported.mini_preset = Some("Fleet".to_string());
+
+        assert_eq!(
+            current.diff(&imported),
+            vec![
+                "Last prefix: none -> /games/eve/drive_c".to_string(),
+                "Name cache: 1 added, 0 removed, 1 renamed".to_string(),
+                "Crash reports: false -> true".to_string(),
+                "Bookmark added: /games/eve/drive_c".to_string(),
+                "Backup retention: keep all -> pre-sync 10".to_string(),
+                "Quick-sync preset: none -> Fleet".to_string(),
+            ]
+        );
+        assert!(current.diff(&current).is_empty());
+
+        let retained = Config {
+            backup_retention: imported.backup_retention.clone(),
+            ..Config::default()
+        };
+        let saved = toml::to_string(&retained).unwrap();
+        assert!(saved.contains("pre-sync = 10"));
+        assert_eq!(
+            Config::from_toml(&saved).unwrap().backup_retention,
+            retained.backup_retention
+        );
+    }
+
+    #[test]
+    fn test_export_round_trip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("exported.toml");
+        let mut config = Config::default();
+        config.save_prefix("/games/eve/drive_c".to_string());
+        let names = HashMap::from([
+            (90000001, "Alpha".to_string()),
+            (90000002, "Bravo".to_string()),
+        ]);
+
+        config.export_to(&names, &path)?;
+        let imported = Config::import_from(&path)?;
+
+        assert_eq!(imported.character_name_cache, names);
+        assert!(config.character_name_cache.is_empty());
+        assert_eq!(imported.saved_prefixes.len(), 1);
+        assert_eq!(imported.saved_prefixes[0].path, "/games/eve/drive_c");
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_selection_round_trip() {
+        let mut config = Config::default();
+        config.prefix_selections.insert(
+            "/games/eve/drive_c".to_string(),
+            PrefixSelection {
+                tab: Some("Accounts".to_string()),
+                source: Some(PathBuf::from("/games/settings_Default/core_user_1.dat")),
+                targets: vec![PathBuf::from("/games/settings_Default/core_user_2.dat")],
+            },
+        );
+
+        let saved = toml::to_string(&config).unwrap();
+        let loaded = Config::from_toml(&saved).unwrap();
+        assert_eq!(loaded.prefix_selections, config.prefix_selections);
+        assert_eq!(
+            Config::default().diff(&config),
+            vec!["Selection for /games/eve/drive_c: 1 target(s)".to_string()]
+        );
+        assert_eq!(
+            config.diff(&Config::default()),
+            vec!["Selection removed: /games/eve/drive_c".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_recover_copies_undecodable_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config.toml");
+        let damaged = b"window_x = 5.0\nlast_prefix_path = \"/games/\xff\"\n";
+        fs::write(&path, damaged)?;
+
+        let (config, recovery) = Config::recover_from(&path);
+        let recovery = recovery.expect("damaged config is reported");
+
+        assert!(recovery.error.contains("UTF-8"));
+        let backup = recovery
+            .backup_path
+            .expect("damaged config is copied aside");
+        assert_eq!(fs::read(backup)?, damaged);
+        assert_eq!(config.window_x, 5.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lenient_parse_recovers_valid_parts() {
+        let damaged = r#"
+last_prefix_path = "/games/eve/drive_c"
+window_x = "not a number"
+window_y = 250.0
+
+[character_name_cache]
+90000001 = "Alpha"
+90000002 = "Bravo
+90000003 = "Charlie"
+"#;
+        assert!(Config::from_toml(damaged).is_err());
+
+        let (config, recovered) = Config::from_toml_lenient(damaged);
+        assert_eq!(
+            config.last_prefix_path.as_deref(),
+            Some("/games/eve/drive_c")
+        );
+        assert_eq!(config.window_x, default_window_x());
+        assert_eq!(config.window_y, 250.0);
+        assert_eq!(config.character_name_cache[&90000001], "Alpha");
+        assert_eq!(config.character_name_cache[&90000003], "Charlie");
+        assert!(!config.character_name_cache.contains_key(&90000002));
+        assert!(!recovered.contains(&"window_x".to_string()));
+    }
+
+    #[test]
+    fn test_prefix_bundle_rejects_newer_version() {
+        let mut config = Config::default();
+        let json = r#"{"version": 99, "prefixes": []}"#;
+        assert!(config.import_prefix_bundle(json).is_err());
+    }
+}
@@ -0,0 +1,495 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marker file written into every backup directory created by this app.
+pub const BACKUP_MARKER: &str = ".pack-preferences-backup";
+
+/// Infix used in backup directory names (`settings_Default_backup_<timestamp>`).
+pub const BACKUP_INFIX: &str = "_backup_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    Character,
+    User,
+    /// Per-account EVE launcher settings (launch arguments, DX mode)
+    Launcher,
+}
+
+#[derive(Debug, Clone)]
+pub struct CharacterFile {
+    pub path: PathBuf,
+    pub character_id: u64,
+    pub file_type: FileType,
+    /// True if this is a default file (core_char__.dat or core_user__.dat)
+    pub is_default: bool,
+}
+
+impl CharacterFile {
+    /// Returns the settings profile directory containing this file.
+    pub fn settings_dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
+}
+
+/// Returns a human-readable label for a settings profile directory,
+/// e.g. "settings_PvP (c_ccp_eve_tq_tranquility)".
+pub fn profile_label(settings_dir: &Path) -> String {
+    let profile = settings_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match settings_dir.parent().and_then(|p| p.file_name()) {
+        Some(install) => format!("{} ({})", profile, install.to_string_lossy()),
+        None => profile,
+    }
+}
+
+/// Returns true if the directory is a settings backup rather than a live profile.
+/// Backups are recognized by their marker; the name only counts for backups made
+/// before markers existed, which all have the default name.
+pub fn is_backup_dir(dir: &Path) -> bool {
+    if dir.join(BACKUP_MARKER).exists() {
+        return true;
+    }
+
+    dir.file_name()
+        .is_some_and(|n| has_default_backup_name(&n.to_string_lossy()))
+}
+
+/// True for `<profile>_backup_YYYYMMDD_HHMMSS`, with or without a trailing `Z`.
+fn has_default_backup_name(name: &str) -> bool {
+    let Some((profile, stamp)) = name.rsplit_once(BACKUP_INFIX) else {
+        return false;
+    };
+    let stamp = stamp.strip_suffix('Z').unwrap_or(stamp);
+    let digits = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_digit());
+    !profile.is_empty()
+        && stamp
+            .split_once('_')
+            .is_some_and(|(date, time)| digits(date, 8) && digits(time, 6))
+}
+
+/// Game server a client install connects to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Server {
+    Tranquility,
+    Singularity,
+    Duality,
+    Thunderdome,
+    Serenity,
+}
+
+impl Server {
+    pub const ALL: [Server; 5] = [
+        Server::Tranquility,
+        Server::Singularity,
+        Server::Duality,
+        Server::Thunderdome,
+        Server::Serenity,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Server::Tranquility => "Tranquility",
+            Server::Singularity => "Singularity",
+            Server::Duality => "Duality",
+            Server::Thunderdome => "Thunderdome",
+            Server::Serenity => "Serenity",
+        }
+    }
+}
+
+/// Returns the server a settings profile belongs to, read from its install
+/// directory name (e.g. `c_ccp_eve_sisi_singularity`).
+pub fn server_for(settings_dir: &Path) -> Option<Server> {
+    let install = settings_dir
+        .parent()?
+        .file_name()?
+        .to_string_lossy()
+        .to_lowercase();
+
+    Server::ALL
+        .into_iter()
+        .find(|server| install.ends_with(&server.name().to_lowercase()))
+}
+
+/// Glob-style patterns for settings discovery to skip, e.g. leftovers from old
+/// migrations. Each pattern is matched against a file's name, its profile directory
+/// name and its character or account ID, so `settings_old_*`, `core_char_9*.dat`
+/// and `90000001` all work. `*` matches any run of characters, `?` any one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgnoreRules {
+    patterns: Vec<String>,
+}
+
+impl IgnoreRules {
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Returns true if a whole profile directory is ignored.
+    pub fn ignores_dir(&self, dir: &Path) -> bool {
+        let Some(name) = dir.file_name() else {
+            return false;
+        };
+        let name = name.to_string_lossy();
+        self.patterns.iter().any(|p| glob_match(p, &name))
+    }
+
+    pub fn ignores(&self, file: &CharacterFile) -> bool {
+        let name = file
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let id = file.character_id.to_string();
+        self.ignores_dir(file.settings_dir())
+            || self
+                .patterns
+                .iter()
+                .any(|p| glob_match(p, &name) || (!file.is_default && glob_match(p, &id)))
+    }
+}
+
+/// Matches `text` against a pattern where `*` is any run of characters and `?` is
+/// any single character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character
+                Some((after_star, tried)) => {
+                    p = after_star;
+                    t = tried + 1;
+                    backtrack = Some((after_star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Discovers character and user settings files in a settings directory, leaving out
+/// anything `ignore` matches. Backup directories are never treated as live profiles
+/// and yield no files.
+pub fn discover_character_files(
+    settings_dir: &Path,
+    ignore: &IgnoreRules,
+) -> Result<Vec<CharacterFile>> {
+    if !settings_dir.exists() || is_backup_dir(settings_dir) || ignore.ignores_dir(settings_dir) {
+        return Ok(Vec::new());
+    }
+
+    let mut files = list_settings_files(settings_dir)?;
+    files.retain(|f| !ignore.ignores(f));
+    Ok(files)
+}
+
+/// Lists the character and user files saved in a backup directory.
+pub fn discover_backup_files(backup_dir: &Path) -> Result<Vec<CharacterFile>> {
+    list_settings_files(backup_dir)
+}
+
+fn list_settings_files(dir: &Path) -> Result<Vec<CharacterFile>> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if !path.is_file() {
+            continue;
+        }
+
+        files.extend(settings_file(path));
+    }
+
+    // Sort by character ID, with defaults at the end
+    files.sort_by_key(|f| (f.is_default, f.character_id));
+
+    Ok(files)
+}
+
+/// Identifies a character or user settings file from its name. The file need not exist.
+pub fn settings_file(path: PathBuf) -> Option<CharacterFile> {
+    let filename = path.file_name()?.to_string_lossy().to_string();
+
+    // Parse character files: core_char_*.dat
+    if let Some((id, is_default)) = parse_char_file(&filename) {
+        Some(CharacterFile {
+            path,
+            character_id: id,
+            file_type: FileType::Character,
+            is_default,
+        })
+    }
+    // Parse user files: core_user_*.dat
+    else if let Some((id, is_default)) = parse_user_file(&filename) {
+        Some(CharacterFile {
+            path,
+            character_id: id,
+            file_type: FileType::User,
+            is_default,
+        })
+    } else {
+        None
+    }
+}
+
+/// Discovers character and user files across several profile directories.
+/// Files are grouped by profile, in the order the directories are given.
+pub fn discover_across_profiles(
+    settings_dirs: &[PathBuf],
+    ignore: &IgnoreRules,
+) -> Result<Vec<CharacterFile>> {
+    let mut files = Vec::new();
+
+    for dir in settings_dirs {
+        files.extend(discover_character_files(dir, ignore)?);
+    }
+
+    Ok(files)
+}
+
+/// Discovers per-account launcher settings files (`<account id>.json`) in a launcher directory.
+pub fn discover_launcher_files(launcher_dir: &Path) -> Result<Vec<CharacterFile>> {
+    let mut files = Vec::new();
+
+    if !launcher_dir.is_dir() || is_backup_dir(launcher_dir) {
+        return Ok(files);
+    }
+
+    for entry in fs::read_dir(launcher_dir)? {
+        let path = entry?.path();
+
+        if !path.is_file() {
+            continue;
+        }
+
+        let filename = match path.file_name() {
+            Some(n) => n.to_string_lossy().to_string(),
+            None => continue,
+        };
+
+        if let Some(id) = parse_launcher_file(&filename) {
+            files.push(CharacterFile {
+                path,
+                character_id: id,
+                file_type: FileType::Launcher,
+                is_default: false,
+            });
+        }
+    }
+
+    files.sort_by_key(|f| f.character_id);
+
+    Ok(files)
+}
+
+/// Parses a launcher `<account id>.json` filename and returns the account ID.
+fn parse_launcher_file(filename: &str) -> Option<u64> {
+    filename.strip_suffix(".json")?.parse().ok()
+}
+
+/// Name of the settings file `file_type` keeps for `id`, e.g. `core_char_123.dat`.
+/// None for launcher files, which are named by the launcher.
+pub fn settings_file_name(file_type: FileType, id: u64) -> Option<String> {
+    match file_type {
+        FileType::Character => Some(format!("core_char_{}.dat", id)),
+        FileType::User => Some(format!("core_user_{}.dat", id)),
+        FileType::Launcher => None,
+    }
+}
+
+/// Parses core_char_*.dat filename and returns (character ID, is_default).
+/// Returns id=0 for default file core_char__.dat
+fn parse_char_file(filename: &str) -> Option<(u64, bool)> {
+    if !filename.starts_with("core_char_") || !filename.ends_with(".dat") {
+        return None;
+    }
+
+    let id_part = filename.strip_prefix("core_char_")?.strip_suffix(".dat")?;
+
+    // Default file has underscore as placeholder (core_char__.dat -> "_")
+    if id_part.is_empty() || id_part == "_" {
+        return Some((0, true));
+    }
+
+    id_part.parse().ok().map(|id| (id, false))
+}
+
+/// Parses core_user_*.dat filename and returns (user ID, is_default).
+/// Returns id=0 for default file core_user__.dat
+fn parse_user_file(filename: &str) -> Option<(u64, bool)> {
+    if !filename.starts_with("core_user_") || !filename.ends_with(".dat") {
+        return None;
+    }
+
+    let id_part = filename.strip_prefix("core_user_")?.strip_suffix(".dat")?;
+
+    // Default file has underscore as placeholder (core_user__.dat -> "_")
+    if id_part.is_empty() || id_part == "_" {
+        return Some((0, true));
+    }
+
+    id_part.parse().ok().map(|id| (id, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_char_file() {
+        assert_eq!(
+            parse_char_file("core_char_123456789.dat"),
+            Some((123456789, false))
+        );
+        assert_eq!(parse_char_file("core_char__.dat"), Some((0, true)));
+        assert_eq!(parse_char_file("core_user_123.dat"), None);
+        assert_eq!(parse_char_file("other.dat"), None);
+    }
+
+    #[test]
+    fn test_parse_user_file() {
+        assert_eq!(
+            parse_user_file("core_user_987654321.dat"),
+            Some((987654321, false))
+        );
+        assert_eq!(parse_user_file("core_user__.dat"), Some((0, true)));
+        assert_eq!(parse_user_file("core_char_123.dat"), None);
+    }
+
+    #[test]
+    fn test_server_for() {
+        let eve = Path::new("/home/u/AppData/Local/CCP/EVE");
+        assert_eq!(
+            server_for(&eve.join("c_ccp_eve_tq_tranquility/settings_Default")),
+            Some(Server::Tranquility)
+        );
+        assert_eq!(
+            server_for(&eve.join("c_ccp_eve_sisi_singularity/settings_PvP")),
+            Some(Server::Singularity)
+        );
+        assert_eq!(
+            server_for(&eve.join("c_ccp_eve_duality_duality/settings_Default")),
+            Some(Server::Duality)
+        );
+        assert_eq!(server_for(Path::new("settings_Default")), None);
+    }
+
+    #[test]
+    fn test_parse_launcher_file() {
+        assert_eq!(parse_launcher_file("12345678.json"), Some(12345678));
+        assert_eq!(parse_launcher_file("state.json"), None);
+        assert_eq!(parse_launcher_file("12345678.dat"), None);
+    }
+
+    #[test]
+    fn test_backup_dirs_are_skipped() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let named = dir.path().join("settings_Default_backup_20240101_120000");
+        fs::create_dir(&named)?;
+        fs::write(named.join("core_char_1.dat"), b"")?;
+        assert!(is_backup_dir(&named));
+        assert!(discover_character_files(&named, &IgnoreRules::default())?.is_empty());
+        assert_eq!(discover_backup_files(&named)?.len(), 1);
+
+        let marked = dir.path().join("settings_Old");
+        fs::create_dir(&marked)?;
+        fs::write(marked.join(BACKUP_MARKER), b"")?;
+        fs::write(marked.join("core_char_1.dat"), b"")?;
+        assert!(discover_character_files(&marked, &IgnoreRules::default())?.is_empty());
+
+        let live = dir.path().join("settings_Default");
+        fs::create_dir(&live)?;
+        fs::write(live.join("core_char_1.dat"), b"")?;
+        assert!(!is_backup_dir(&live));
+        assert!(has_default_backup_name(
+            "settings_Default_backup_20260301_120000Z"
+        ));
+        assert!(!has_default_backup_name("settings_my_backup_profile"));
+        assert!(!has_default_backup_name("settings_Default_backup_2026"));
+        assert_eq!(
+            discover_character_files(&live, &IgnoreRules::default())?.len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_across_profiles() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let default = dir.path().join("settings_Default");
+        let pvp = dir.path().join("settings_PvP");
+        fs::create_dir(&default)?;
+        fs::create_dir(&pvp)?;
+        fs::write(default.join("core_char_1.dat"), b"")?;
+        fs::write(pvp.join("core_char_1.dat"), b"")?;
+        fs::write(pvp.join("core_char_2.dat"), b"")?;
+
+        let files =
+            discover_across_profiles(&[default.clone(), pvp.clone()], &IgnoreRules::default())?;
+        let dirs: Vec<&Path> = files.iter().map(|f| f.settings_dir()).collect();
+        assert_eq!(dirs, vec![default.as_path(), pvp.as_path(), pvp.as_path()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("settings_old_*", "settings_old_2019"));
+        assert!(glob_match("core_char_9*.dat", "core_char_90000001.dat"));
+        assert!(glob_match("core_?ser_*", "core_user_1.dat"));
+        assert!(glob_match("*a*b", "xaab"));
+        assert!(!glob_match("settings_old_*", "settings_Default"));
+        assert!(!glob_match("9000", "90000001"));
+    }
+
+    #[test]
+    fn test_ignore_rules() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let default = dir.path().join("settings_Default");
+        let old = dir.path().join("settings_old_2019");
+        fs::create_dir(&default)?;
+        fs::create_dir(&old)?;
+        for name in ["core_char_1.dat", "core_char_2.dat", "core_char__.dat"] {
+            fs::write(default.join(name), b"")?;
+        }
+        fs::write(old.join("core_char_1.dat"), b"")?;
+
+        let ignore = IgnoreRules::new(&["settings_old_*".to_string(), " 2 ".to_string()]);
+        let files = discover_across_profiles(&[default.clone(), old], &ignore)?;
+        let ids: Vec<(u64, bool)> = files
+            .iter()
+            .map(|f| (f.character_id, f.is_default))
+            .collect();
+        assert_eq!(ids, vec![(1, false), (0, true)]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::discovery::{self, FileType, IgnoreRules};
+use crate::journal::SyncJournal;
+use crate::settings::{self, BackupTrigger};
+
+/// Profiles of the same name in different install folders of one prefix that hold
+/// settings for the same characters, e.g. `settings_Default` under both
+/// `c_ccp_eve_tq_tranquility` and `c_ccp_eve_online_tq_tranquility`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// Profile directory name shared by every member
+    pub profile: String,
+    pub dirs: Vec<PathBuf>,
+    /// Character IDs with settings in more than one member
+    pub shared_ids: Vec<u64>,
+}
+
+/// What merging a group did
+#[derive(Debug)]
+pub struct MergeOutcome {
+    pub backups: Vec<PathBuf>,
+    /// Files copied into the canonical directory
+    pub copied: Vec<PathBuf>,
+    /// Duplicate profile directories removed after the merge
+    pub removed: Vec<PathBuf>,
+}
+
+/// Groups the given profile directories into duplicates: same profile name, and at
+/// least one character in common with another directory of that name.
+pub fn find_duplicates(profile_dirs: &[PathBuf]) -> Vec<DuplicateGroup> {
+    let mut by_name: BTreeMap<String, Vec<(PathBuf, BTreeSet<u64>)>> = BTreeMap::new();
+    for dir in profile_dirs {
+        let Some(name) = dir.file_name() else {
+            continue;
+        };
+        let ids = discovery::discover_character_files(dir, &IgnoreRules::default())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|f| f.file_type == FileType::Character && !f.is_default)
+            .map(|f| f.character_id)
+            .collect();
+        by_name
+            .entry(name.to_string_lossy().to_string())
+            .or_default()
+            .push((dir.clone(), ids));
+    }
+
+    let mut groups = Vec::new();
+    for (profile, members) in by_name {
+        let mut dirs = Vec::new();
+        let mut shared = BTreeSet::new();
+        for (i, (dir, ids)) in members.iter().enumerate() {
+            let overlap: Vec<u64> = members
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .flat_map(|(_, (_, other))| ids.intersection(other).copied())
+                .collect();
+            if !overlap.is_empty() {
+                dirs.push(dir.clone());
+                shared.extend(overlap);
+            }
+        }
+        if dirs.len() > 1 {
+            groups.push(DuplicateGroup {
+                profile,
+                dirs,
+                shared_ids: shared.into_iter().collect(),
+            });
+        }
+    }
+    groups
+}
+
+impl DuplicateGroup {
+    /// The member the client wrote to most recently, which is most likely the install
+    /// in use and the natural one to keep.
+    pub fn suggested_canonical(&self) -> &Path {
+        self.dirs
+            .iter()
+            .max_by_key(|dir| newest_write(dir))
+            .map(PathBuf::as_path)
+            .unwrap_or(Path::new(""))
+    }
+
+    /// Files a merge into `canonical` would copy, as (from, to): for each settings file
+    /// in the group, the most recently written copy, unless that is already the
+    /// canonical one.
+    pub fn plan(&self, canonical: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+        if !self.dirs.iter().any(|d| d == canonical) {
+            bail!("{} is not part of this group", canonical.display());
+        }
+
+        let mut newest: BTreeMap<String, (SystemTime, PathBuf)> = BTreeMap::new();
+        for dir in &self.dirs {
+            for file in discovery::discover_character_files(dir, &IgnoreRules::default())? {
+                let Some(name) = file.path.file_name() else {
+                    continue;
+                };
+                let modified = modified(&file.path);
+                let name = name.to_string_lossy().to_string();
+                // Ties go to the canonical copy, so nothing is copied needlessly
+                let replace = newest.get(&name).is_none_or(|(time, _)| {
+                    modified > *time || (modified == *time && dir == canonical)
+                });
+                if replace {
+                    newest.insert(name, (modified, file.path));
+                }
+            }
+        }
+
+        Ok(newest
+            .into_iter()
+            .filter(|(_, (_, path))| path.parent() != Some(canonical))
+            .map(|(name, (_, path))| (path, canonical.join(name)))
+            .collect())
+    }
+
+    /// Backs up every member, copies the newest version of each file into
+    /// `canonical` and removes the other members, whose backups remain. Every step
+    /// goes into `journal` first.
+    pub fn merge(&self, canonical: &Path, journal: &mut SyncJournal) -> Result<MergeOutcome> {
+        let plan = self.plan(canonical)?;
+
+        let mut backups = Vec::new();
+        for dir in &self.dirs {
+            backups.push(journal.backup(dir, BackupTrigger::PreSync)?);
+        }
+
+        let mut copied = Vec::new();
+        for (from, to) in plan {
+            settings::copy_file_atomic(&from, &to, Some(&mut *journal))
+                .with_context(|| format!("Failed to copy {}", from.display()))?;
+            copied.push(to);
+        }
+
+        let mut removed = Vec::new();
+        for dir in self.dirs.iter().filter(|d| *d != canonical) {
+            journal.touch(dir)?;
+            fs::remove_dir_all(dir)
+                .with_context(|| format!("Failed to remove {}", dir.display()))?;
+            removed.push(dir.clone());
+        }
+
+        Ok(MergeOutcome {
+            backups,
+            copied,
+            removed,
+        })
+    }
+}
+
+fn modified(path: &Path) -> SystemTime {
+    path.metadata()
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Most recent write to any settings file in `dir`.
+fn newest_write(dir: &Path) -> SystemTime {
+    discovery::discover_character_files(dir, &IgnoreRules::default())
+        .unwrap_or_default()
+        .iter()
+        .map(|f| modified(&f.path))
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal;
+    use std::fs::File;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_and_merge_duplicates() -> Result<()> {
+        let dir = tempdir()?;
+        let old = dir.path().join("c_ccp_eve_tq_tranquility/settings_Default");
+        let new = dir
+            .path()
+            .join("c_ccp_eve_online_tq_tranquility/settings_Default");
+        let pvp = dir.path().join("c_ccp_eve_tq_tranquility/settings_PvP");
+        for profile in [&old, &new, &pvp] {
+            fs::create_dir_all(profile)?;
+        }
+
+        let base = SystemTime::now() - Duration::from_secs(3600);
+        let write = |path: PathBuf, contents: &str, age: u64| -> Result<()> {
+            fs::write(&path, contents)?;
+            File::options()
+                .write(true)
+                .open(&path)?
+                .set_modified(base - Duration::from_secs(age))?;
+            Ok(())
+        };
+        write(old.join("core_char_1.dat"), "old main", 100)?;
+        write(old.join("core_char_2.dat"), "alt only in old", 50)?;
+        write(new.join("core_char_1.dat"), "new main", 0)?;
+        write(pvp.join("core_char_1.dat"), "pvp", 0)?;
+
+        let groups = find_duplicates(&[old.clone(), new.clone(), pvp.clone()]);
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.profile, "settings_Default");
+        assert_eq!(group.shared_ids, vec![1]);
+        assert_eq!(group.suggested_canonical(), new.as_path());
+
+        assert_eq!(
+            group.plan(&new)?,
+            vec![(old.join("core_char_2.dat"), new.join("core_char_2.dat"))]
+        );
+        assert!(group.plan(&pvp).is_err());
+
+        let outcome = group.merge(&new, &mut journal::scratch(dir.path()))?;
+        assert_eq!(outcome.backups.len(), 2);
+        assert_eq!(outcome.removed, vec![old.clone()]);
+        assert!(!old.exists());
+        assert_eq!(fs::read_to_string(new.join("core_char_1.dat"))?, "new main");
+        assert_eq!(
+            fs::read_to_string(new.join("core_char_2.dat"))?,
+            "alt only in old"
+        );
+        assert!(outcome.backups.iter().all(|b| b.exists()));
+        Ok(())
+    }
+}
@@ -0,0 +1,468 @@
+//! Syncs into profiles this account can't write to by running the copy as root
+//! through `pkexec`. Prefixes sometimes end up owned by root after an install run
+//! with sudo; rather than failing with permission errors, just the files being
+//! synced are copied once polkit has asked for the password. Linux only.
+
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tempfile::NamedTempFile;
+
+use crate::discovery::BACKUP_MARKER;
+use crate::journal::{self, Step, SyncJournal};
+use crate::process;
+use crate::settings::{self, BackupOptions, BackupTrigger};
+
+/// Runs the actions passed after the journal path, stopping at the first that
+/// fails. Journal lines are appended before the step they name, as a normal sync
+/// writes them. A copy is staged in a file root creates next to its target and
+/// renamed over it, keeping the replaced file's owner and mode so the prefix isn't
+/// left with a mix of owners; a target or journal that is a symbolic link is
+/// refused rather than followed. Locks and a staged file are cleaned up however
+/// the script ends, and any failure exits with [`SCRIPT_FAILED`].
+const SCRIPT: &str = r#"set -e
+journal="$1"
+shift
+locks=$(mktemp)
+staged=
+finish() {
+    status=$?
+    while IFS= read -r lock; do
+        rm -f -- "$lock"
+    done < "$locks"
+    rm -f -- "$locks"
+    if [ -n "$staged" ]; then
+        rm -f -- "$staged"
+    fi
+    if [ "$status" -ne 0 ]; then
+        exit 70
+    fi
+}
+trap finish EXIT
+refuse_link() {
+    if [ -L "$1" ]; then
+        echo "$1 is a symbolic link" >&2
+        exit 1
+    fi
+}
+refuse_link "$journal"
+while [ $# -gt 0 ]; do
+    case "$1" in
+        lock)
+            if ! (set -C; cat -- "$2" > "$3") 2>/dev/null; then
+                echo "$3 is held by another operation" >&2
+                exit 1
+            fi
+            printf '%s\n' "$3" >> "$locks"
+            shift 3
+            ;;
+        journal)
+            printf '%s\n' "$2" >> "$journal"
+            shift 2
+            ;;
+        backup)
+            mkdir -- "$3"
+            cp -R -p -- "$2/." "$3"
+            cp -- "$4" "$5"
+            shift 5
+            ;;
+        stage)
+            refuse_link "$2"
+            refuse_link "$3"
+            staged=$(mktemp --suffix=.tmp -- "${3%.*}.XXXXXX")
+            install -T -m 600 -- "$2" "$staged"
+            if [ -e "$3" ]; then
+                chown --reference="$3" -- "$staged"
+                chmod --reference="$3" -- "$staged"
+            fi
+            shift 3
+            ;;
+        rename)
+            mv -f -T -- "$staged" "$2"
+            staged=
+            shift 2
+            ;;
+        *)
+            echo "Unknown step: $1" >&2
+            exit 1
+            ;;
+    esac
+done
+"#;
+
+/// [`SCRIPT`] exits with this when a step fails, apart from the codes `pkexec`
+/// and `sh` use themselves
+const SCRIPT_FAILED: i32 = 70;
+
+/// `pkexec` exits with this when the password prompt is dismissed
+const PKEXEC_DISMISSED: i32 = 126;
+
+/// `pkexec` exits with this when authorization is refused
+const PKEXEC_NOT_AUTHORIZED: i32 = 127;
+
+/// The only owner a profile is written for as root: one left behind by an
+/// install run with sudo
+const ROOT_UID: u32 = 0;
+
+/// User ID owning `path`; None where ownership can't be read.
+pub fn owner_of(path: &Path) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        fs::metadata(path).ok().map(|m| m.uid())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// User ID this app runs as, read from the owner of its own `/proc` entry.
+pub fn current_uid() -> Option<u32> {
+    owner_of(Path::new("/proc/self"))
+}
+
+/// Profiles among `dirs` that only root can write to and an elevated sync may:
+/// owned by root, while this app is not, and not a symbolic link.
+pub fn root_owned(dirs: &[&Path]) -> Vec<PathBuf> {
+    if current_uid().is_none_or(|uid| uid == ROOT_UID) {
+        return Vec::new();
+    }
+    dirs.iter()
+        .filter(|dir| !dir.is_symlink() && owner_of(dir) == Some(ROOT_UID))
+        .map(|dir| dir.to_path_buf())
+        .collect()
+}
+
+/// Returns true when an elevated copy can be offered: on Linux outside Flatpak,
+/// with `pkexec` on the PATH.
+pub fn available() -> bool {
+    if !cfg!(target_os = "linux") || process::in_flatpak() {
+        return false;
+    }
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| dir.join("pkexec").is_file()))
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    /// Take the lock at `path` for a profile whose parent only root can write
+    Lock { path: PathBuf, contents: String },
+    /// Append a line to the sync journal
+    Journal(String),
+    /// Copy a whole profile to `dest` and mark it as a backup
+    Backup {
+        dir: PathBuf,
+        dest: PathBuf,
+        marker: String,
+    },
+    /// Write a copy of `source` to a new temporary file next to `target`
+    Stage { source: PathBuf, target: PathBuf },
+    /// Rename the temporary file last staged over `target`
+    Rename { target: PathBuf },
+}
+
+impl Action {
+    /// Text root reads from a temporary file, for the actions that need one.
+    fn contents(&self) -> Option<&str> {
+        match self {
+            Action::Lock { contents, .. } => Some(contents),
+            Action::Backup { marker, .. } => Some(marker),
+            _ => None,
+        }
+    }
+}
+
+/// What an elevated sync will do, worked out before the password is asked for
+#[derive(Debug, Clone)]
+pub struct ElevatedSync {
+    pub source: PathBuf,
+    pub targets: Vec<PathBuf>,
+    /// Each target profile and the backup it gets, when backups are taken
+    pub backups: Vec<(PathBuf, PathBuf)>,
+    actions: Vec<Action>,
+}
+
+impl ElevatedSync {
+    /// Plans a sync of `source` over `targets`, backing up each target profile
+    /// first, named by `backup`, when it is given.
+    pub fn plan(
+        source: &Path,
+        targets: &[PathBuf],
+        backup: Option<&BackupOptions>,
+    ) -> Result<Self> {
+        let mut actions = Vec::new();
+        let mut backups = Vec::new();
+        if let Some(options) = backup {
+            for dir in target_dirs(targets) {
+                let dest = settings::next_backup_path(dir, BackupTrigger::PreSync, None, options)?;
+                actions.push(Action::Journal(journal::entry(&Step::BackupStarted {
+                    dir: dir.to_path_buf(),
+                })?));
+                actions.push(Action::Backup {
+                    dir: dir.to_path_buf(),
+                    dest: dest.clone(),
+                    marker: settings::backup_marker(dir, BackupTrigger::PreSync, None)?,
+                });
+                actions.push(Action::Journal(journal::entry(&Step::BackedUp {
+                    dir: dir.to_path_buf(),
+                    backup: dest.clone(),
+                })?));
+                backups.push((dir.to_path_buf(), dest));
+            }
+        }
+        for target in targets {
+            actions.push(Action::Journal(journal::entry(&Step::Staged {
+                target: target.clone(),
+            })?));
+            actions.push(Action::Stage {
+                source: source.to_path_buf(),
+                target: target.clone(),
+            });
+            actions.push(Action::Journal(journal::entry(&Step::Renamed {
+                target: target.clone(),
+            })?));
+            actions.push(Action::Rename {
+                target: target.clone(),
+            });
+        }
+
+        Ok(Self {
+            source: source.to_path_buf(),
+            targets: targets.to_vec(),
+            backups,
+            actions,
+        })
+    }
+
+    /// Runs the sync as root, asking for the password through polkit. Blocks until
+    /// the prompt is answered and the copy is done.
+    ///
+    /// Only profiles owned by root are written, see [`root_owned`]. Each target
+    /// profile is locked as for any sync: by this account where it can write the
+    /// lock, otherwise by root inside the script. Root copies the source from a
+    /// private copy taken first. Steps go to `journal`, which is finished when the
+    /// sync completes or never started, and left for recovery when it failed part
+    /// way.
+    pub fn run(&self, journal: SyncJournal) -> Result<()> {
+        let dirs = target_dirs(&self.targets);
+        if let Some(dir) = dirs.iter().find(|dir| root_owned(&[dir]).is_empty()) {
+            bail!(
+                "{} is not owned by root, so it is not synced as administrator",
+                dir.display()
+            );
+        }
+        if let Some(target) = self.targets.iter().find(|t| t.is_symlink()) {
+            bail!("{} is a symbolic link", target.display());
+        }
+
+        // Root reads what it copies from files only this account can reach, kept
+        // until pkexec is done
+        let private = tempfile::Builder::new()
+            .prefix("pack-preferences-")
+            .tempdir()
+            .context("Failed to create a temporary directory")?;
+        let source = private.path().join("source");
+        fs::copy(&self.source, &source)
+            .with_context(|| format!("Failed to read {}", self.source.display()))?;
+
+        let mut held = Vec::new();
+        let mut actions = Vec::new();
+        for dir in dirs {
+            match settings::lock_dir(dir) {
+                Ok(lock) => held.push(lock),
+                Err(e) if permission_denied(&e) => actions.push(Action::Lock {
+                    path: settings::lock_path(dir)?,
+                    contents: settings::lock_contents(),
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+        actions.extend(self.actions.iter().map(|action| match action {
+            Action::Stage { target, .. } => Action::Stage {
+                source: source.clone(),
+                target: target.clone(),
+            },
+            action => action.clone(),
+        }));
+
+        let mut files = Vec::new();
+        for contents in actions.iter().filter_map(Action::contents) {
+            let mut file = NamedTempFile::new_in(private.path())
+                .context("Failed to create a temporary file")?;
+            file.write_all(contents.as_bytes())
+                .context("Failed to write a temporary file")?;
+            files.push(file);
+        }
+        let paths: Vec<PathBuf> = files.iter().map(|f| f.path().to_path_buf()).collect();
+
+        let output = Command::new("pkexec")
+            .arg("/bin/sh")
+            .arg("-c")
+            .arg(SCRIPT)
+            .arg("sh")
+            .args(script_args(journal.path(), &actions, &paths))
+            .output()
+            .context("Failed to run pkexec")?;
+        drop(files);
+        drop(held);
+
+        let status = output.status;
+        match status.code() {
+            Some(0) => journal.finish(),
+            Some(PKEXEC_DISMISSED) => {
+                journal.finish()?;
+                bail!("The password prompt was dismissed")
+            }
+            Some(PKEXEC_NOT_AUTHORIZED) => {
+                journal.finish()?;
+                bail!("Authorization was refused")
+            }
+            Some(SCRIPT_FAILED) => bail!(
+                "The elevated copy failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            _ => bail!("The elevated copy failed ({})", status),
+        }
+    }
+}
+
+/// The profiles holding `targets`, each once.
+fn target_dirs(targets: &[PathBuf]) -> Vec<&Path> {
+    let dirs: BTreeSet<&Path> = targets.iter().filter_map(|t| t.parent()).collect();
+    dirs.into_iter().collect()
+}
+
+/// Whether `error` comes from the OS refusing access.
+fn permission_denied(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == ErrorKind::PermissionDenied)
+    })
+}
+
+/// Arguments for [`SCRIPT`]. `files` holds the temporary file of each action
+/// with [`Action::contents`], in order.
+fn script_args(journal: &Path, actions: &[Action], files: &[PathBuf]) -> Vec<OsString> {
+    let mut files = files.iter();
+    let mut file = || files.next().cloned().unwrap_or_default().into_os_string();
+    let mut args = vec![journal.as_os_str().to_os_string()];
+    for action in actions {
+        match action {
+            Action::Lock { path, .. } => {
+                args.push("lock".into());
+                args.push(file());
+                args.push(path.into());
+            }
+            Action::Journal(line) => {
+                args.push("journal".into());
+                args.push(line.into());
+            }
+            Action::Backup { dir, dest, .. } => {
+                args.push("backup".into());
+                args.push(dir.into());
+                args.push(dest.into());
+                args.push(file());
+                args.push(dest.join(BACKUP_MARKER).into());
+            }
+            Action::Stage { source, target } => {
+                args.push("stage".into());
+                args.push(source.into());
+                args.push(target.into());
+            }
+            Action::Rename { target } => {
+                args.push("rename".into());
+                args.push(target.into());
+            }
+        }
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_script_args() -> Result<()> {
+        let dir = tempdir()?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        let source = dir.path().join("core_char_1.dat");
+        let targets = vec![
+            profile.join("core_char_2.dat"),
+            profile.join("core_char_3.dat"),
+        ];
+        let journal = dir.path().join("sync.jsonl");
+
+        let plain = ElevatedSync::plan(&source, &targets, None)?;
+        assert!(plain.backups.is_empty());
+        let args = script_args(&journal, &plain.actions, &[]);
+        assert_eq!(args.len(), 1 + 2 * (2 + 3 + 2 + 2));
+        assert_eq!(args[0], journal.as_os_str());
+        assert_eq!(args[1], "journal");
+        assert_eq!(
+            args[2],
+            journal::entry(&Step::Staged {
+                target: targets[0].clone()
+            })?
+            .as_str()
+        );
+        assert_eq!(args[3], "stage");
+        assert_eq!(args[5], targets[0].as_os_str());
+        assert_eq!(args[6], "journal");
+        assert_eq!(args[8], "rename");
+        assert_eq!(args[9], targets[0].as_os_str());
+        assert!(SCRIPT.contains(&format!("exit {}", SCRIPT_FAILED)));
+
+        let backed_up = ElevatedSync::plan(&source, &targets, Some(&BackupOptions::default()))?;
+        assert_eq!(backed_up.backups.len(), 1);
+        let (backed_dir, backup) = &backed_up.backups[0];
+        assert_eq!(backed_dir, &profile);
+        let lock = settings::lock_path(&profile)?;
+        let mut actions = vec![Action::Lock {
+            path: lock.clone(),
+            contents: settings::lock_contents(),
+        }];
+        actions.extend(backed_up.actions.iter().cloned());
+        let files = [dir.path().join("lock"), dir.path().join("marker")];
+        let args = script_args(&journal, &actions, &files);
+        assert_eq!(
+            args[1..4],
+            [OsString::from("lock"), files[0].clone().into(), lock.into()]
+        );
+        assert_eq!(args[4], "journal");
+        assert_eq!(args[6], "backup");
+        assert_eq!(args[8], backup.as_os_str());
+        assert_eq!(args[9], files[1].as_os_str());
+        assert_eq!(args[10], backup.join(BACKUP_MARKER).as_os_str());
+        assert_eq!(args[11], "journal");
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_root_owned() -> Result<()> {
+        let dir = tempdir()?;
+        assert!(root_owned(&[dir.path()]).is_empty());
+        let root = Path::new("/");
+        if current_uid() != Some(ROOT_UID) && owner_of(root) == Some(ROOT_UID) {
+            assert_eq!(root_owned(&[root]), vec![root.to_path_buf()]);
+
+            // Root owning a link's target doesn't make the link elevatable
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(root, &link)?;
+            assert!(root_owned(&[&link]).is_empty());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Name lookup on Tranquility's ESI
+pub const TRANQUILITY_NAMES_ENDPOINT: &str = "https://esi.evetech.net/latest/universe/names/";
+/// Name lookup on Serenity's ESI, which has its own character IDs
+pub const SERENITY_NAMES_ENDPOINT: &str = "https://ali-esi.evepc.163.com/latest/universe/names/";
+/// Name-to-ID lookup on Tranquility's ESI
+pub const TRANQUILITY_IDS_ENDPOINT: &str = "https://esi.evetech.net/latest/universe/ids/";
+/// Name-to-ID lookup on Serenity's ESI
+pub const SERENITY_IDS_ENDPOINT: &str = "https://ali-esi.evepc.163.com/latest/universe/ids/";
+const BATCH_LIMIT: usize = 500;
+
+#[derive(Debug, Deserialize)]
+struct EsiNameResult {
+    id: u64,
+    name: String,
+    #[allow(dead_code)]
+    category: String,
+}
+
+/// Response of the IDs endpoint; categories with no match are left out
+#[derive(Debug, Default, Deserialize)]
+struct EsiIdsResult {
+    #[serde(default)]
+    characters: Vec<EsiIdMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EsiIdMatch {
+    id: u64,
+    name: String,
+}
+
+/// Outcome of a name lookup.
+#[derive(Debug, Default)]
+pub struct Resolution {
+    pub names: HashMap<u64, String>,
+    /// IDs ESI reported as nonexistent (deleted or biomassed characters)
+    pub not_found: Vec<u64>,
+}
+
+impl Resolution {
+    fn extend(&mut self, other: Resolution) {
+        self.names.extend(other.names);
+        self.not_found.extend(other.not_found);
+    }
+}
+
+/// Resolves character IDs to names via ESI API.
+pub fn resolve_character_names(character_ids: &[u64]) -> Result<Resolution> {
+    resolve_character_names_at(TRANQUILITY_NAMES_ENDPOINT, character_ids)
+}
+
+/// Resolves character IDs to names via the ESI names endpoint at `endpoint`.
+pub fn resolve_character_names_at(endpoint: &str, character_ids: &[u64]) -> Result<Resolution> {
+    let mut results = Resolution::default();
+
+    if character_ids.is_empty() {
+        return Ok(results);
+    }
+
+    // Process in batches of 500
+    for chunk in character_ids.chunks(BATCH_LIMIT) {
+        let batch_results = fetch_names_batch(endpoint, chunk)?;
+        results.extend(batch_results);
+    }
+
+    Ok(results)
+}
+
+fn fetch_names_batch(endpoint: &str, ids: &[u64]) -> Result<Resolution> {
+    let client = reqwest::blocking::Client::new();
+
+    let response = client
+        .post(endpoint)
+        .json(&ids)
+        .send()
+        .context("Failed to send ESI request")?;
+
+    if !response.status().is_success() {
+        // ESI rejects the whole batch if any ID doesn't exist; split it so the
+        // valid IDs still resolve and only the bad ones are left out
+        if response.status().as_u16() == 404 {
+            if ids.len() == 1 {
+                return Ok(Resolution {
+                    names: HashMap::new(),
+                    not_found: ids.to_vec(),
+                });
+            }
+            let (left, right) = ids.split_at(ids.len() / 2);
+            let mut results = fetch_names_batch(endpoint, left)?;
+            results.extend(fetch_names_batch(endpoint, right)?);
+            return Ok(results);
+        }
+        anyhow::bail!("ESI request failed with status: {}", response.status());
+    }
+
+    let names: Vec<EsiNameResult> = response.json().context("Failed to parse ESI response")?;
+
+    Ok(Resolution {
+        names: names
+            .into_iter()
+            .filter(|n| n.category == "character")
+            .map(|n| (n.id, n.name))
+            .collect(),
+        not_found: Vec::new(),
+    })
+}
+
+/// Finds the character named exactly `name`, ignoring case, via the ESI IDs
+/// endpoint at `endpoint`. Returns its ID and the name as ESI spells it.
+pub fn find_character_at(endpoint: &str, name: &str) -> Result<Option<(u64, String)>> {
+    let response = reqwest::blocking::Client::new()
+        .post(endpoint)
+        .json(&[name.trim()])
+        .send()
+        .context("Failed to send ESI request")?;
+    if !response.status().is_success() {
+        anyhow::bail!("ESI request failed with status: {}", response.status());
+    }
+
+    let result: EsiIdsResult = response.json().context("Failed to parse ESI response")?;
+    Ok(result
+        .characters
+        .into_iter()
+        .next()
+        .map(|found| (found.id, found.name)))
+}
+
+/// Removes and returns the next batch of up to `size` IDs from `pending`, taking the
+/// ones listed in `priority` first (in that order) and then from the front.
+pub fn take_batch(pending: &mut Vec<u64>, priority: &[u64], size: usize) -> Vec<u64> {
+    let mut batch: Vec<u64> = Vec::with_capacity(size);
+    for id in priority {
+        if batch.len() == size {
+            break;
+        }
+        if pending.contains(id) && !batch.contains(id) {
+            batch.push(*id);
+        }
+    }
+    pending.retain(|id| !batch.contains(id));
+
+    let rest = (size - batch.len()).min(pending.len());
+    batch.extend(pending.drain(..rest));
+    batch
+}
+
+/// Resolves character names with caching support.
+/// Returns updated cache entries.
+pub fn resolve_with_cache(
+    character_ids: &[u64],
+    is_cached: impl Fn(u64) -> bool,
+) -> Result<Resolution> {
+    // Find IDs not in cache
+    let uncached: Vec<u64> = character_ids
+        .iter()
+        .copied()
+        .filter(|&id| !is_cached(id))
+        .collect();
+
+    if uncached.is_empty() {
+        return Ok(Resolution::default());
+    }
+
+    resolve_character_names(&uncached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_ids() {
+        let result = resolve_character_names(&[]).unwrap();
+        assert!(result.names.is_empty());
+        assert!(result.not_found.is_empty());
+    }
+
+    #[test]
+    fn test_take_batch_prefers_priority() {
+        let mut pending = vec![1, 2, 3, 4, 5, 6];
+        assert_eq!(take_batch(&mut pending, &[5, 9, 5], 3), vec![5, 1, 2]);
+        assert_eq!(take_batch(&mut pending, &[], 3), vec![3, 4, 6]);
+        assert!(pending.is_empty());
+        assert!(take_batch(&mut pending, &[1], 3).is_empty());
+    }
+}
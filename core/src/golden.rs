@@ -0,0 +1,323 @@
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::discovery::{self, CharacterFile, FileType, IgnoreRules};
+use crate::journal::SyncJournal;
+use crate::process;
+use crate::settings::{self, BackupTrigger, SyncResult};
+
+/// Manifest written into every golden image directory
+const MANIFEST: &str = "golden.json";
+
+/// A named snapshot of a settings profile kept in the app's state directory, used as
+/// the reference setup to compare profiles against and reset characters to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenImage {
+    pub name: String,
+    /// RFC 3339
+    pub created: String,
+    /// Profile the image was taken from
+    pub source: PathBuf,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// How a file in a profile compares with the golden image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoldenState {
+    Same,
+    Changed,
+    /// In the image but not in the profile
+    Missing,
+    /// In the profile but not in the image
+    Extra,
+}
+
+impl GoldenState {
+    pub fn label(self) -> &'static str {
+        match self {
+            GoldenState::Same => "same",
+            GoldenState::Changed => "changed",
+            GoldenState::Missing => "missing",
+            GoldenState::Extra => "not in image",
+        }
+    }
+}
+
+pub fn golden_root() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join("golden"))
+}
+
+/// Lists the stored golden images, sorted by name.
+pub fn list_golden() -> Result<Vec<GoldenImage>> {
+    list_golden_in(&golden_root()?)
+}
+
+/// Stores the settings files of `settings_dir` as a golden image called `name`,
+/// replacing any earlier image of that name.
+pub fn create_golden(name: &str, settings_dir: &Path) -> Result<GoldenImage> {
+    create_golden_in(&golden_root()?, name, settings_dir)
+}
+
+fn list_golden_in(root: &Path) -> Result<Vec<GoldenImage>> {
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut images = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        let Ok(manifest) = fs::read_to_string(path.join(MANIFEST)) else {
+            continue;
+        };
+        if let Ok(mut image) = serde_json::from_str::<GoldenImage>(&manifest) {
+            image.path = path;
+            images.push(image);
+        }
+    }
+    images.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(images)
+}
+
+fn create_golden_in(root: &Path, name: &str, settings_dir: &Path) -> Result<GoldenImage> {
+    let name = name.trim();
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    if slug.is_empty() {
+        bail!("Golden image needs a name");
+    }
+
+    let files = discovery::discover_character_files(settings_dir, &IgnoreRules::default())?;
+    if files.is_empty() {
+        bail!("No settings files in {}", settings_dir.display());
+    }
+
+    let path = root.join(slug);
+    if path.exists() {
+        fs::remove_dir_all(&path).context("Failed to replace golden image")?;
+    }
+    fs::create_dir_all(&path).context("Failed to create golden image directory")?;
+    for file in &files {
+        let name = file.path.file_name().context("Settings file has no name")?;
+        fs::copy(&file.path, path.join(name))
+            .with_context(|| format!("Failed to copy {:?}", file.path))?;
+    }
+
+    let image = GoldenImage {
+        name: name.to_string(),
+        created: Local::now().to_rfc3339(),
+        source: settings_dir.to_path_buf(),
+        path,
+    };
+    let manifest = serde_json::to_string_pretty(&image).context("Failed to write manifest")?;
+    fs::write(image.path.join(MANIFEST), manifest).context("Failed to write manifest")?;
+    Ok(image)
+}
+
+impl GoldenImage {
+    /// The character and user files stored in the image.
+    pub fn files(&self) -> Result<Vec<CharacterFile>> {
+        discovery::discover_backup_files(&self.path)
+    }
+
+    /// File in the image that a reset of `target` copies from: the file with the same
+    /// name if the image has one, otherwise the image's default file of that type.
+    pub fn file_for(&self, target: &CharacterFile) -> Option<PathBuf> {
+        let name = target.path.file_name()?;
+        let default = match target.file_type {
+            FileType::Character => "core_char__.dat",
+            FileType::User => "core_user__.dat",
+            FileType::Launcher => return None,
+        };
+        [self.path.join(name), self.path.join(default)]
+            .into_iter()
+            .find(|p| p.is_file())
+    }
+
+    /// Compares every settings file in `settings_dir` with the image, by name and hash.
+    pub fn compare(&self, settings_dir: &Path) -> Result<Vec<(String, GoldenState)>> {
+        let file_name = |f: &CharacterFile| {
+            f.path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        let image: BTreeSet<String> = self.files()?.iter().map(file_name).collect();
+        let live: BTreeSet<String> =
+            discovery::discover_character_files(settings_dir, &IgnoreRules::default())?
+                .iter()
+                .map(file_name)
+                .collect();
+
+        let mut states = Vec::new();
+        for name in image.union(&live) {
+            let state = match (image.contains(name), live.contains(name)) {
+                (true, false) => GoldenState::Missing,
+                (false, _) => GoldenState::Extra,
+                (true, true) => {
+                    let golden = settings::file_hash(&self.path.join(name))?;
+                    let current = settings::file_hash(&settings_dir.join(name))?;
+                    if golden == current {
+                        GoldenState::Same
+                    } else {
+                        GoldenState::Changed
+                    }
+                }
+            };
+            states.push((name.clone(), state));
+        }
+        Ok(states)
+    }
+
+    /// Overwrites each target with its file from the image, backing up every affected
+    /// profile first. Both go into `journal`. Returns the backups and one result per
+    /// target.
+    pub fn reset(
+        &self,
+        targets: &[&CharacterFile],
+        journal: &mut SyncJournal,
+    ) -> Result<(Vec<PathBuf>, Vec<SyncResult>)> {
+        let dirs: BTreeSet<&Path> = targets.iter().map(|t| t.settings_dir()).collect();
+        let mut backups = Vec::new();
+        for dir in dirs {
+            backups.push(journal.backup(dir, BackupTrigger::PreSync)?);
+        }
+
+        let results = targets
+            .iter()
+            .map(|target| {
+                let outcome = match self.file_for(target) {
+                    Some(golden) => {
+                        { settings::copy_file_atomic(&golden, &target.path, Some(&mut *journal)) }
+                            .map(|()| "Reset to golden".to_string())
+                    }
+                    None => Err(anyhow::anyhow!("No matching file in the golden image")),
+                };
+                SyncResult {
+                    target_file: target.path.clone(),
+                    success: outcome.is_ok(),
+                    message: outcome.unwrap_or_else(|e| format!("Failed: {}", e)),
+                }
+            })
+            .collect();
+        Ok((backups, results))
+    }
+
+    /// Settings directory a bootstrap of `prefix` writes to: the image's install and
+    /// profile names under the prefix's EVE base directory, which need not exist yet.
+    pub fn bootstrap_dir(&self, prefix: &Path) -> Result<PathBuf> {
+        let profile = self
+            .source
+            .file_name()
+            .context("Golden image has no profile name")?;
+        let install = self
+            .source
+            .parent()
+            .and_then(Path::file_name)
+            .context("Golden image has no install name")?;
+        let base = process::find_eve_base_dirs(prefix)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| process::default_eve_base_dir(prefix));
+        Ok(base.join(install).join(profile))
+    }
+
+    /// Populates a prefix that has never run the client with the image's files,
+    /// creating the CCP/EVE folder structure as needed. Refuses to touch a profile
+    /// that already has settings. Returns the settings directory written.
+    pub fn bootstrap(&self, prefix: &Path) -> Result<PathBuf> {
+        if !prefix.is_dir() {
+            bail!("Prefix does not exist: {}", prefix.display());
+        }
+
+        let settings_dir = self.bootstrap_dir(prefix)?;
+        if !discovery::discover_character_files(&settings_dir, &IgnoreRules::default())?.is_empty()
+        {
+            bail!(
+                "{} already has settings; reset characters instead",
+                settings_dir.display()
+            );
+        }
+
+        fs::create_dir_all(&settings_dir).context("Failed to create settings directory")?;
+        let network = settings::on_network_fs(&settings_dir);
+        for file in self.files()? {
+            let name = file.path.file_name().context("Settings file has no name")?;
+            settings::copy_contents(&file.path, &settings_dir.join(name), network)
+                .with_context(|| format!("Failed to copy {:?}", file.path))?;
+        }
+        Ok(settings_dir)
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        fs::remove_dir_all(&self.path).context("Failed to delete golden image")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_golden_compare_and_reset() -> Result<()> {
+        let dir = tempdir()?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir_all(&profile)?;
+        fs::write(profile.join("core_char_1.dat"), b"trainer")?;
+        fs::write(profile.join("core_char__.dat"), b"template")?;
+
+        let root = dir.path().join("golden");
+        let image = create_golden_in(&root, "Corp Standard", &profile)?;
+        assert_eq!(list_golden_in(&root)?, vec![image.clone()]);
+
+        fs::write(profile.join("core_char_1.dat"), b"changed")?;
+        fs::write(profile.join("core_char_2.dat"), b"recruit")?;
+        let states = image.compare(&profile)?;
+        assert!(states.contains(&("core_char_1.dat".to_string(), GoldenState::Changed)));
+        assert!(states.contains(&("core_char_2.dat".to_string(), GoldenState::Extra)));
+        assert!(states.contains(&("core_char__.dat".to_string(), GoldenState::Same)));
+
+        // A character the image does not know gets the image's default file
+        let files = discovery::discover_character_files(&profile, &IgnoreRules::default())?;
+        let targets: Vec<&CharacterFile> = files.iter().filter(|f| !f.is_default).collect();
+        let (backups, results) = image.reset(&targets, &mut journal::scratch(dir.path()))?;
+        assert_eq!(backups.len(), 1);
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(fs::read(profile.join("core_char_1.dat"))?, b"trainer");
+        assert_eq!(fs::read(profile.join("core_char_2.dat"))?, b"template");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bootstrap_new_prefix() -> Result<()> {
+        let dir = tempdir()?;
+        let profile = dir
+            .path()
+            .join("c_ccp_eve_tq_tranquility")
+            .join("settings_Default");
+        fs::create_dir_all(&profile)?;
+        fs::write(profile.join("core_user__.dat"), b"defaults")?;
+        let image = create_golden_in(&dir.path().join("golden"), "New Install", &profile)?;
+
+        let prefix = dir.path().join("drive_c");
+        fs::create_dir(&prefix)?;
+        let settings_dir = image.bootstrap(&prefix)?;
+        assert!(settings_dir.ends_with("CCP/EVE/c_ccp_eve_tq_tranquility/settings_Default"));
+        assert_eq!(fs::read(settings_dir.join("core_user__.dat"))?, b"defaults");
+        assert!(!settings_dir.join(MANIFEST).exists());
+
+        // A second bootstrap would overwrite the now-populated profile
+        assert!(image.bootstrap(&prefix).is_err());
+        assert!(image.bootstrap(&dir.path().join("missing")).is_err());
+        Ok(())
+    }
+}
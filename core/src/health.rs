@@ -0,0 +1,153 @@
+//! Post-operation health check: which settings files a set of profiles should hold
+//! once a sync or restore is done, compared with what discovery finds afterwards,
+//! so a file that went missing shows up straight away rather than at login.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::{self, CharacterFile, FileType, IgnoreRules};
+
+/// Files listed by name in a finding before the rest are summed up
+const MAX_LISTED: usize = 5;
+
+/// The character and user files of some profiles, by profile directory and file
+/// name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileCensus {
+    files: BTreeMap<(PathBuf, String), FileType>,
+}
+
+impl FileCensus {
+    /// Counts `files` under the profile each lives in.
+    pub fn of(files: &[CharacterFile]) -> Self {
+        let files = files
+            .iter()
+            .filter(|f| f.file_type != FileType::Launcher)
+            .filter_map(|f| {
+                let name = f.path.file_name()?.to_string_lossy().to_string();
+                Some(((f.settings_dir().to_path_buf(), name), f.file_type))
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// Counts what is on disk in `settings_dirs` now. Ignore rules are not applied,
+    /// so every file is counted.
+    pub fn take(settings_dirs: &[PathBuf]) -> Result<Self> {
+        let mut files = Vec::new();
+        for dir in settings_dirs {
+            files.extend(discovery::discover_character_files(
+                dir,
+                &IgnoreRules::default(),
+            )?);
+        }
+        Ok(Self::of(&files))
+    }
+
+    /// What restoring a backup into `settings_dir` should leave: the backup's
+    /// files, moved into the profile.
+    pub fn restored(backup: &Path, settings_dir: &Path) -> Result<Self> {
+        let census = Self::of(&discovery::discover_backup_files(backup)?);
+        let files = census
+            .files
+            .into_iter()
+            .map(|((_, name), file_type)| ((settings_dir.to_path_buf(), name), file_type))
+            .collect();
+        Ok(Self { files })
+    }
+
+    /// How many files of `file_type` are counted
+    pub fn count(&self, file_type: FileType) -> usize {
+        self.files.values().filter(|t| **t == file_type).count()
+    }
+
+    /// How `found` differs from this expectation, one finding per line; empty when
+    /// every expected file is there and nothing else is.
+    pub fn compare(&self, found: &FileCensus) -> Vec<String> {
+        let mut findings = Vec::new();
+        for (file_type, name) in [(FileType::Character, "character"), (FileType::User, "user")] {
+            let (expected, actual) = (self.count(file_type), found.count(file_type));
+            if expected != actual {
+                findings.push(format!(
+                    "Expected {} {} file(s), found {}",
+                    expected, name, actual
+                ));
+            }
+        }
+
+        let missing: Vec<&(PathBuf, String)> = self
+            .files
+            .keys()
+            .filter(|key| !found.files.contains_key(*key))
+            .collect();
+        let unexpected: Vec<&(PathBuf, String)> = found
+            .files
+            .keys()
+            .filter(|key| !self.files.contains_key(*key))
+            .collect();
+        findings.extend(list_files("Missing", &missing));
+        findings.extend(list_files("Unexpected", &unexpected));
+        findings
+    }
+}
+
+/// One line per file, up to [`MAX_LISTED`], then a count of the rest.
+fn list_files(what: &str, files: &[&(PathBuf, String)]) -> Vec<String> {
+    let mut lines: Vec<String> = files
+        .iter()
+        .take(MAX_LISTED)
+        .map(|(dir, name)| format!("{}: {} in {}", what, name, discovery::profile_label(dir)))
+        .collect();
+    if files.len() > MAX_LISTED {
+        lines.push(format!(
+            "{}: {} more file(s)",
+            what,
+            files.len() - MAX_LISTED
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_census_compare() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        for name in ["core_char_1.dat", "core_char_2.dat", "core_user_9.dat"] {
+            fs::write(settings_dir.join(name), "x")?;
+        }
+        let dirs = vec![settings_dir.clone()];
+
+        let before = FileCensus::take(&dirs)?;
+        assert_eq!(before.count(FileType::Character), 2);
+        assert!(before.compare(&FileCensus::take(&dirs)?).is_empty());
+
+        fs::remove_file(settings_dir.join("core_char_2.dat"))?;
+        fs::write(settings_dir.join("core_user_8.dat"), "x")?;
+        let findings = before.compare(&FileCensus::take(&dirs)?);
+        assert_eq!(findings[0], "Expected 2 character file(s), found 1");
+        assert_eq!(findings[1], "Expected 1 user file(s), found 2");
+        assert!(findings[2].starts_with("Missing: core_char_2.dat in settings_Default"));
+        assert!(findings[3].starts_with("Unexpected: core_user_8.dat in settings_Default"));
+        assert_eq!(findings.len(), 4);
+
+        // A restore is expected to leave the backup's files in the profile
+        let backup = dir.path().join("settings_Default_backup_20260301_120000Z");
+        fs::create_dir(&backup)?;
+        fs::write(backup.join("core_char_1.dat"), "x")?;
+        let expected = FileCensus::restored(&backup, &settings_dir)?;
+        assert_eq!(expected.count(FileType::Character), 1);
+        let findings = expected.compare(&FileCensus::take(&dirs)?);
+        assert_eq!(findings[0], "Expected 0 user file(s), found 2");
+        assert!(findings[1].starts_with("Unexpected: core_user_8.dat"));
+        assert_eq!(findings.len(), 3);
+        Ok(())
+    }
+}
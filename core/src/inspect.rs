@@ -0,0 +1,577 @@
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::discovery::CharacterFile;
+use crate::journal::SyncJournal;
+use crate::marshal::{self, Value};
+use crate::settings::{self, BackupTrigger, SyncResult};
+
+/// One step into a decoded settings tree: a dict entry by key, or a tuple or list
+/// item by position. Keys rather than positions are used for dicts so that a path
+/// means the same setting in every character's file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(Value),
+    Index(usize),
+}
+
+/// Location of a value in a decoded settings tree
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyPath(pub Vec<Segment>);
+
+impl fmt::Display for KeyPath {
+    /// Slash-separated keys, with positions in brackets: `ui/windows/[2]`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "/");
+        }
+        for (i, segment) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            match segment {
+                Segment::Key(key) => write!(f, "{}", display_scalar(key))?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl KeyPath {
+    pub fn child(&self, segment: Segment) -> Self {
+        let mut path = self.clone();
+        path.0.push(segment);
+        path
+    }
+}
+
+/// The value at `path`, if the tree has one there.
+pub fn get<'a>(value: &'a Value, path: &KeyPath) -> Option<&'a Value> {
+    path.0
+        .iter()
+        .try_fold(value, |current, segment| match (current, segment) {
+            (Value::Dict(entries), Segment::Key(key)) => {
+                entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            (Value::Tuple(items) | Value::List(items), Segment::Index(i)) => items.get(*i),
+            _ => None,
+        })
+}
+
+fn get_mut<'a>(value: &'a mut Value, path: &KeyPath) -> Option<&'a mut Value> {
+    path.0
+        .iter()
+        .try_fold(value, |current, segment| match (current, segment) {
+            (Value::Dict(entries), Segment::Key(key)) => {
+                entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            (Value::Tuple(items) | Value::List(items), Segment::Index(i)) => items.get_mut(*i),
+            _ => None,
+        })
+}
+
+/// Finds the path written as `text` in the form [`KeyPath`] displays, e.g.
+/// `ui/window/[0]`. Keys are matched by their text, so one path names the same
+/// setting whatever string type a file stores its keys as.
+pub fn resolve(tree: &Value, text: &str) -> Option<KeyPath> {
+    let mut path = KeyPath::default();
+    let mut current = tree;
+    for part in text.trim().trim_matches('/').split('/') {
+        let index = part
+            .strip_prefix('[')
+            .and_then(|p| p.strip_suffix(']'))
+            .and_then(|p| p.parse::<usize>().ok());
+        let (segment, next) = match (current, index) {
+            (Value::Tuple(items) | Value::List(items), Some(i)) => {
+                (Segment::Index(i), items.get(i)?)
+            }
+            (Value::Dict(entries), _) => {
+                let (key, value) = entries.iter().find(|(k, _)| display_scalar(k) == part)?;
+                (Segment::Key(key.clone()), value)
+            }
+            _ => return None,
+        };
+        path.0.push(segment);
+        current = next;
+    }
+    Some(path)
+}
+
+/// True for values edited as one line of text rather than expanded.
+pub fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Tuple(_) | Value::List(_) | Value::Dict(_))
+}
+
+/// One-line text of a scalar, in the form [`parse_as`] reads back. Containers
+/// show their size.
+pub fn display_scalar(value: &Value) -> String {
+    match value {
+        Value::None => "None".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int(i) => i.to_string(),
+        Value::Float(f) => format!("{:?}", f),
+        Value::Bytes(_) | Value::Unicode(_) | Value::Global(_) => match value.as_str() {
+            Some(s) => s.to_string(),
+            None => format!("<{} bytes>", value_len(value)),
+        },
+        Value::Tuple(items) | Value::List(items) => format!("{} items", items.len()),
+        Value::Dict(entries) => format!("{} entries", entries.len()),
+    }
+}
+
+fn value_len(value: &Value) -> usize {
+    match value {
+        Value::Bytes(bytes) => bytes.len(),
+        _ => 0,
+    }
+}
+
+/// A scalar found by [`search`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub path: KeyPath,
+    pub value: Value,
+}
+
+/// Finds scalars whose path or value contains `query`, ignoring case. A query of
+/// the form `path=value` needs both halves to match, e.g. `drone=passive`.
+pub fn search(tree: &Value, query: &str) -> Vec<Match> {
+    let query = query.trim().to_lowercase();
+    let (path_part, value_part) = match query.split_once('=') {
+        Some((path, value)) => (Some(path.trim()), Some(value.trim())),
+        None => (None, None),
+    };
+
+    let mut matches = Vec::new();
+    walk(tree, &KeyPath::default(), &mut |path, value| {
+        let path_text = path.to_string().to_lowercase();
+        let value_text = display_scalar(value).to_lowercase();
+        let found = match (path_part, value_part) {
+            (Some(p), Some(v)) => path_text.contains(p) && value_text.contains(v),
+            _ => path_text.contains(&query) || value_text.contains(&query),
+        };
+        if found {
+            matches.push(Match {
+                path: path.clone(),
+                value: value.clone(),
+            });
+        }
+    });
+    matches
+}
+
+/// Calls `visit` for every scalar in the tree, depth first.
+fn walk(value: &Value, path: &KeyPath, visit: &mut impl FnMut(&KeyPath, &Value)) {
+    match value {
+        Value::Dict(entries) => {
+            for (key, child) in entries {
+                walk(child, &path.child(Segment::Key(key.clone())), visit);
+            }
+        }
+        Value::Tuple(items) | Value::List(items) => {
+            for (i, child) in items.iter().enumerate() {
+                walk(child, &path.child(Segment::Index(i)), visit);
+            }
+        }
+        scalar => visit(path, scalar),
+    }
+}
+
+/// Keys that differ between two decoded settings trees
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TreeDiff {
+    /// In the new tree only
+    pub added: Vec<KeyPath>,
+    /// In the old tree only
+    pub removed: Vec<KeyPath>,
+    /// In both, with different values
+    pub changed: Vec<KeyPath>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares two trees dict key by dict key. Anything other than a dict is compared
+/// whole, so a list that gained an item is one changed path.
+pub fn diff(old: &Value, new: &Value) -> TreeDiff {
+    let mut result = TreeDiff::default();
+    diff_at(old, new, &KeyPath::default(), &mut result);
+    result
+}
+
+fn diff_at(old: &Value, new: &Value, path: &KeyPath, result: &mut TreeDiff) {
+    match (old, new) {
+        (Value::Dict(old_entries), Value::Dict(new_entries)) => {
+            for (key, old_value) in old_entries {
+                let child = path.child(Segment::Key(key.clone()));
+                match new_entries.iter().find(|(k, _)| k == key) {
+                    Some((_, new_value)) => diff_at(old_value, new_value, &child, result),
+                    None => result.removed.push(child),
+                }
+            }
+            for (key, _) in new_entries {
+                if !old_entries.iter().any(|(k, _)| k == key) {
+                    result.added.push(path.child(Segment::Key(key.clone())));
+                }
+            }
+        }
+        _ if old != new => result.changed.push(path.clone()),
+        _ => {}
+    }
+}
+
+/// Reads `text` as a new value of the same type as `current`. Only scalars can be
+/// edited, and a value never changes type, since the client expects each setting
+/// in a particular form.
+pub fn parse_as(current: &Value, text: &str) -> Result<Value> {
+    let text = text.trim();
+    Ok(match current {
+        Value::Bool(_) => match text.to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => Value::Bool(true),
+            "false" | "0" | "no" | "off" => Value::Bool(false),
+            _ => bail!("Expected true or false"),
+        },
+        Value::Int(_) => Value::Int(text.parse().context("Expected a whole number")?),
+        Value::Float(_) => {
+            let f: f64 = text.parse().context("Expected a number")?;
+            if !f.is_finite() {
+                bail!("Expected a finite number");
+            }
+            Value::Float(f)
+        }
+        Value::Bytes(bytes) => {
+            if std::str::from_utf8(bytes).is_err() {
+                bail!("Binary strings cannot be edited");
+            }
+            if !text.is_ascii() {
+                bail!("This setting only holds plain ASCII text");
+            }
+            Value::Bytes(text.as_bytes().to_vec())
+        }
+        Value::Unicode(_) => Value::Unicode(text.to_string()),
+        other => bail!("{} values cannot be edited", other.type_name()),
+    })
+}
+
+/// Replaces the value at `path`, checking that it keeps its type.
+pub fn set(tree: &mut Value, path: &KeyPath, new: Value) -> Result<()> {
+    let slot = get_mut(tree, path).with_context(|| format!("No setting at {}", path))?;
+    if std::mem::discriminant(slot) != std::mem::discriminant(&new) {
+        bail!(
+            "{} holds a {}, not a {}",
+            path,
+            slot.type_name(),
+            new.type_name()
+        );
+    }
+    *slot = new;
+    Ok(())
+}
+
+/// Writes a modified tree back to `file`, backing its profile up first. Both go
+/// into `journal`. Returns the backup.
+pub fn write_tree(
+    file: &CharacterFile,
+    tree: &Value,
+    journal: &mut SyncJournal,
+) -> Result<PathBuf> {
+    let data = marshal::encode(tree);
+    // Make sure what is about to be written reads back as the same tree
+    if marshal::decode(&data).ok().as_ref() != Some(tree) {
+        bail!("Re-encoded settings did not read back the same; nothing was written");
+    }
+    let backup = journal.backup(file.settings_dir(), BackupTrigger::PreSync)?;
+    settings::write_file_atomic(&data, &file.path, Some(journal))?;
+    Ok(backup)
+}
+
+/// Sets one value in a settings file: decodes it, validates `text` against the
+/// current value, backs the profile up and writes the re-encoded file, journaling
+/// both. Returns the backup.
+pub fn edit_file(
+    file: &CharacterFile,
+    path: &KeyPath,
+    text: &str,
+    journal: &mut SyncJournal,
+) -> Result<PathBuf> {
+    let mut tree = marshal::read_file(&file.path)?;
+    let current = get(&tree, path).with_context(|| format!("No setting at {}", path))?;
+    let new = parse_as(current, text).with_context(|| format!("Invalid value for {}", path))?;
+    set(&mut tree, path, new)?;
+    write_tree(file, &tree, journal)
+}
+
+/// Result of [`bulk_edit`]
+#[derive(Debug, Default)]
+pub struct BulkEdit {
+    /// One per target, in the order given
+    pub results: Vec<SyncResult>,
+    /// One per profile that had a file rewritten
+    pub backups: Vec<PathBuf>,
+    /// Files that now hold the new value and did not before
+    pub changed: Vec<PathBuf>,
+    /// False if no target was changed because one of them failed
+    pub committed: bool,
+}
+
+/// A target that accepted the new value, ready to be written
+struct Prepared {
+    original: Vec<u8>,
+    data: Vec<u8>,
+    previous: String,
+}
+
+/// Sets the value at `path` in every target as one transaction. Each file is
+/// decoded and checked first, and nothing is written unless all of them accept
+/// the value. Every profile involved is backed up once before writing, and if a
+/// write fails the files already written are put back. Backups and writes go
+/// into `journal` first.
+pub fn bulk_edit(
+    targets: &[CharacterFile],
+    path: &KeyPath,
+    text: &str,
+    journal: &mut SyncJournal,
+) -> Result<BulkEdit> {
+    let prepared: Vec<Result<Option<Prepared>>> = targets
+        .iter()
+        .map(|target| prepare_edit(target, path, text))
+        .collect();
+
+    let result = |target: &CharacterFile, success: bool, message: String| SyncResult {
+        target_file: target.path.clone(),
+        success,
+        message,
+    };
+
+    if prepared.iter().any(|p| p.is_err()) {
+        let results = targets
+            .iter()
+            .zip(&prepared)
+            .map(|(target, p)| match p {
+                Err(e) => result(target, false, format!("Failed: {:#}", e)),
+                Ok(_) => result(target, false, "Not written: another target failed".into()),
+            })
+            .collect();
+        return Ok(BulkEdit {
+            results,
+            ..BulkEdit::default()
+        });
+    }
+    let prepared: Vec<Option<Prepared>> = prepared.into_iter().map(Result::unwrap).collect();
+
+    let mut backups = Vec::new();
+    let mut backed_up = Vec::new();
+    for (target, _) in targets.iter().zip(&prepared).filter(|(_, p)| p.is_some()) {
+        let dir = target.settings_dir();
+        if !backed_up.contains(&dir) {
+            backups.push(journal.backup(dir, BackupTrigger::PreSync)?);
+            backed_up.push(dir);
+        }
+    }
+
+    let mut written: Vec<usize> = Vec::new();
+    for (i, (target, p)) in targets.iter().zip(&prepared).enumerate() {
+        let Some(p) = p else { continue };
+        if let Err(e) = settings::write_file_atomic(&p.data, &target.path, Some(&mut *journal)) {
+            for &j in &written {
+                if let Some(p) = &prepared[j] {
+                    // Best effort; the backups still hold every original
+                    let _ = settings::write_file_atomic(&p.original, &targets[j].path, None);
+                }
+            }
+            let results = targets
+                .iter()
+                .enumerate()
+                .map(|(j, target)| {
+                    if j == i {
+                        result(target, false, format!("Failed: {:#}", e))
+                    } else if written.contains(&j) {
+                        result(target, false, "Rolled back".into())
+                    } else {
+                        result(target, false, "Not written: another target failed".into())
+                    }
+                })
+                .collect();
+            return Ok(BulkEdit {
+                results,
+                backups,
+                changed: Vec::new(),
+                committed: false,
+            });
+        }
+        written.push(i);
+    }
+
+    let results = targets
+        .iter()
+        .zip(&prepared)
+        .map(|(target, p)| match p {
+            Some(p) => result(target, true, format!("Changed from {}", p.previous)),
+            None => result(target, true, "Already set".into()),
+        })
+        .collect();
+    Ok(BulkEdit {
+        results,
+        backups,
+        changed: written.iter().map(|&i| targets[i].path.clone()).collect(),
+        committed: true,
+    })
+}
+
+/// Decodes `target` and applies the edit in memory. `None` if it already holds
+/// the value.
+fn prepare_edit(target: &CharacterFile, path: &KeyPath, text: &str) -> Result<Option<Prepared>> {
+    let original = fs::read(&target.path)
+        .with_context(|| format!("Failed to read {}", target.path.display()))?;
+    let mut tree = marshal::decode(&original)?;
+    let current = get(&tree, path).with_context(|| format!("No setting at {}", path))?;
+    let new = parse_as(current, text).with_context(|| format!("Invalid value for {}", path))?;
+    if current == &new {
+        return Ok(None);
+    }
+    let previous = display_scalar(current);
+    set(&mut tree, path, new)?;
+    let data = marshal::encode(&tree);
+    if marshal::decode(&data).ok().as_ref() != Some(&tree) {
+        bail!("Re-encoded settings did not read back the same");
+    }
+    Ok(Some(Prepared {
+        original,
+        data,
+        previous,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery;
+    use crate::journal;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn key(s: &str) -> Segment {
+        Segment::Key(Value::Bytes(s.as_bytes().to_vec()))
+    }
+
+    #[test]
+    fn test_diff() {
+        let entry = |k: &str, v: Value| (Value::Bytes(k.as_bytes().to_vec()), v);
+        let old = Value::Dict(vec![
+            entry("kept", Value::Int(1)),
+            entry("gone", Value::Bool(true)),
+            entry(
+                "ui",
+                Value::Dict(vec![
+                    entry("zoom", Value::Float(1.0)),
+                    entry("window", Value::List(vec![Value::Int(1)])),
+                ]),
+            ),
+        ]);
+        let new = Value::Dict(vec![
+            entry("kept", Value::Int(1)),
+            entry(
+                "ui",
+                Value::Dict(vec![
+                    entry("zoom", Value::Float(1.5)),
+                    entry("window", Value::List(vec![Value::Int(1), Value::Int(2)])),
+                    entry("theme", Value::Unicode("dark".into())),
+                ]),
+            ),
+        ]);
+
+        let changes = diff(&old, &new);
+        let text = |paths: &[KeyPath]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(text(&changes.added), ["ui/theme"]);
+        assert_eq!(text(&changes.removed), ["gone"]);
+        assert_eq!(text(&changes.changed), ["ui/zoom", "ui/window"]);
+        assert!(diff(&old, &old).is_empty());
+        assert_eq!(text(&diff(&Value::Int(1), &Value::Int(2)).changed), ["/"]);
+    }
+
+    #[test]
+    fn test_edit_file() -> Result<()> {
+        let dir = tempdir()?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        let tree = Value::Dict(vec![(
+            Value::Bytes(b"ui".to_vec()),
+            Value::Dict(vec![
+                (Value::Bytes(b"autopilot".to_vec()), Value::Bool(true)),
+                (
+                    Value::Bytes(b"window".to_vec()),
+                    Value::Tuple(vec![Value::Int(10), Value::Int(20)]),
+                ),
+            ]),
+        )]);
+        let path = profile.join("core_char_1.dat");
+        fs::write(&path, marshal::encode(&tree))?;
+        let file = discovery::settings_file(path.clone()).unwrap();
+
+        let autopilot = KeyPath(vec![key("ui"), key("autopilot")]);
+        let window_x = KeyPath(vec![key("ui"), key("window"), Segment::Index(0)]);
+        assert_eq!(window_x.to_string(), "ui/window/[0]");
+        assert_eq!(resolve(&tree, "ui/window/[0]"), Some(window_x.clone()));
+        assert_eq!(resolve(&tree, "ui/missing"), None);
+
+        let mut journal = journal::scratch(dir.path());
+        assert!(edit_file(&file, &autopilot, "maybe", &mut journal).is_err());
+        assert!(edit_file(&file, &window_x, "12.5", &mut journal).is_err());
+        assert!(edit_file(&file, &KeyPath(vec![key("missing")]), "1", &mut journal).is_err());
+
+        let backup = edit_file(&file, &autopilot, "off", &mut journal)?;
+        edit_file(&file, &window_x, "-40", &mut journal)?;
+        assert!(backup.exists());
+
+        let edited = marshal::read_file(&path)?;
+        assert_eq!(get(&edited, &autopilot), Some(&Value::Bool(false)));
+        assert_eq!(get(&edited, &window_x), Some(&Value::Int(-40)));
+
+        let found = search(&edited, "AUTOpilot");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, autopilot);
+        assert_eq!(search(&edited, "window=-40").len(), 1);
+        assert!(search(&edited, "window=99").is_empty());
+        assert_eq!(search(&edited, "20")[0].path.to_string(), "ui/window/[1]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_edit_is_all_or_nothing() -> Result<()> {
+        let dir = tempdir()?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        let with_key =
+            |on: bool| Value::Dict(vec![(Value::Bytes(b"passive".to_vec()), Value::Bool(on))]);
+        let write = |id: u64, tree: &Value| {
+            let path = profile.join(format!("core_char_{}.dat", id));
+            fs::write(&path, marshal::encode(tree)).unwrap();
+            discovery::settings_file(path).unwrap()
+        };
+        let a = write(1, &with_key(true));
+        let b = write(2, &with_key(false));
+        let c = write(3, &Value::Dict(Vec::new()));
+        let passive = KeyPath(vec![key("passive")]);
+
+        let mut journal = journal::scratch(dir.path());
+        let failed = bulk_edit(&[a.clone(), b.clone(), c], &passive, "false", &mut journal)?;
+        assert!(!failed.committed && failed.backups.is_empty());
+        assert!(failed.results.iter().all(|r| !r.success));
+        assert_eq!(marshal::read_file(&a.path)?, with_key(true));
+
+        let done = bulk_edit(&[a.clone(), b.clone()], &passive, "false", &mut journal)?;
+        assert!(done.committed);
+        assert_eq!(done.backups.len(), 1);
+        assert_eq!(done.changed, vec![a.path.clone()]);
+        assert_eq!(done.results[0].message, "Changed from true");
+        assert_eq!(done.results[1].message, "Already set");
+        assert_eq!(marshal::read_file(&a.path)?, with_key(false));
+        Ok(())
+    }
+}
@@ -0,0 +1,667 @@
+//! Journal of a sync in progress, so a crash part way through can be recovered
+//! from. Each step is appended to a file in the state directory before it is
+//! taken: backing up a profile, staging a target's new contents in its temporary
+//! file, and renaming that file over the target. A finished sync deletes its
+//! journal, so one found at startup belongs to a sync that never completed, and
+//! can be finished from its source or rolled back from its backups.
+//!
+//! Every other operation that backs up a profile and then writes to it (restores,
+//! resets, merges, edits, ...) is journaled the same way, through [`run`].
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::discovery::CharacterFile;
+use crate::plugins::Plugin;
+use crate::settings::{self, BackupOptions, BackupTrigger, TransformedSync};
+
+/// Journals live here, in the state directory
+const JOURNAL_DIR: &str = "journals";
+
+const JOURNAL_PREFIX: &str = "sync-";
+const JOURNAL_EXTENSION: &str = "jsonl";
+
+/// Operation recorded by journals written before operations were named
+const SYNC_OPERATION: &str = "Sync";
+
+fn sync_operation() -> String {
+    SYNC_OPERATION.to_string()
+}
+
+/// One line of a journal, written before the step it names is taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum Step {
+    /// Always the first line
+    Started {
+        /// What was being done, as named in the audit log
+        #[serde(default = "sync_operation")]
+        operation: String,
+        source: PathBuf,
+        targets: Vec<PathBuf>,
+        /// The targets' new contents are not a plain copy of the source (it went
+        /// through plugins, or the operation is not a copy), so they can't be
+        /// written again and the operation can only be rolled back
+        transformed: bool,
+    },
+    BackupStarted {
+        dir: PathBuf,
+    },
+    BackedUp {
+        dir: PathBuf,
+        backup: PathBuf,
+    },
+    Staged {
+        target: PathBuf,
+    },
+    /// A target that did not exist before, which rolling back removes
+    Created {
+        target: PathBuf,
+    },
+    Renamed {
+        target: PathBuf,
+    },
+}
+
+fn journal_dir() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join(JOURNAL_DIR))
+}
+
+/// The line recording `step`, without its newline.
+pub(crate) fn entry(step: &Step) -> Result<String> {
+    serde_json::to_string(step).context("Failed to serialize journal entry")
+}
+
+/// Journal of the sync being run. Dropping it without calling
+/// [`SyncJournal::finish`] leaves the file behind, as a crash would.
+#[derive(Debug)]
+pub struct SyncJournal {
+    path: PathBuf,
+    file: File,
+    /// How the backups taken through the journal are named and pruned
+    backup_options: BackupOptions,
+}
+
+impl SyncJournal {
+    /// Starts the journal for a sync from `source` to `targets`.
+    pub fn begin(
+        source: &Path,
+        targets: &[PathBuf],
+        transformed: bool,
+        backup_options: &BackupOptions,
+    ) -> Result<Self> {
+        Self::begin_in(
+            &journal_dir()?,
+            SYNC_OPERATION,
+            source,
+            targets,
+            transformed,
+            backup_options,
+        )
+    }
+
+    pub(crate) fn begin_in(
+        dir: &Path,
+        operation: &str,
+        source: &Path,
+        targets: &[PathBuf],
+        transformed: bool,
+        backup_options: &BackupOptions,
+    ) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let stamp = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default();
+        let path = dir.join(format!(
+            "{}{}-{}.{}",
+            JOURNAL_PREFIX,
+            std::process::id(),
+            stamp,
+            JOURNAL_EXTENSION
+        ));
+        let file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+
+        let mut journal = Self {
+            path,
+            file,
+            backup_options: backup_options.clone(),
+        };
+        journal.record(&Step::Started {
+            operation: operation.to_string(),
+            source: source.to_path_buf(),
+            targets: targets.to_vec(),
+            transformed,
+        })?;
+        Ok(journal)
+    }
+
+    /// Appends `step`, returning once it has reached the disk.
+    pub fn record(&mut self, step: &Step) -> Result<()> {
+        let line = entry(step)? + "\n";
+        self.file
+            .write_all(line.as_bytes())
+            .and_then(|()| self.file.sync_data())
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Records that `target`, a file or a directory, is about to be written or
+    /// removed: as [`Step::Created`] if it does not exist yet, otherwise as
+    /// [`Step::Staged`].
+    pub fn touch(&mut self, target: &Path) -> Result<()> {
+        let target = target.to_path_buf();
+        self.record(&if target.exists() {
+            Step::Staged { target }
+        } else {
+            Step::Created { target }
+        })
+    }
+
+    /// Backs up `settings_dir`, journaling the backup.
+    pub fn backup(&mut self, settings_dir: &Path, trigger: BackupTrigger) -> Result<PathBuf> {
+        self.backup_with(settings_dir, |options| {
+            settings::create_backup(settings_dir, trigger, options)
+        })
+    }
+
+    /// [`SyncJournal::backup`] for a caller that holds the directory's lock.
+    pub(crate) fn backup_locked(
+        &mut self,
+        settings_dir: &Path,
+        trigger: BackupTrigger,
+    ) -> Result<PathBuf> {
+        self.backup_with(settings_dir, |options| {
+            settings::backup_locked(settings_dir, trigger, None, options)
+        })
+    }
+
+    fn backup_with(
+        &mut self,
+        settings_dir: &Path,
+        take: impl FnOnce(&BackupOptions) -> Result<PathBuf>,
+    ) -> Result<PathBuf> {
+        self.record(&Step::BackupStarted {
+            dir: settings_dir.to_path_buf(),
+        })?;
+        let backup = take(&self.backup_options)?;
+        self.record(&Step::BackedUp {
+            dir: settings_dir.to_path_buf(),
+            backup: backup.clone(),
+        })?;
+        Ok(backup)
+    }
+
+    /// Where the journal is written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Marks the sync complete by deleting the journal.
+    pub fn finish(self) -> Result<()> {
+        fs::remove_file(&self.path)
+            .with_context(|| format!("Failed to remove {}", self.path.display()))
+    }
+}
+
+/// Runs `operation`, named `name` as in the audit log, under a journal of it
+/// writing `targets` from `source`, as a sync is journaled. `operation` backs up
+/// what it is about to change with [`SyncJournal::backup`], named and pruned by
+/// `backup_options`, and records each target before writing it. The journal is
+/// closed once `operation` returns, whether or not it succeeded.
+pub fn run<T>(
+    name: &str,
+    source: &Path,
+    targets: &[PathBuf],
+    transformed: bool,
+    backup_options: &BackupOptions,
+    operation: impl FnOnce(&mut SyncJournal) -> Result<T>,
+) -> Result<T> {
+    let mut journal = SyncJournal::begin_in(
+        &journal_dir()?,
+        name,
+        source,
+        targets,
+        transformed,
+        backup_options,
+    )?;
+    let outcome = operation(&mut journal);
+    let closed = journal.finish();
+    let value = outcome?;
+    closed?;
+    Ok(value)
+}
+
+/// Result of [`sync`]
+#[derive(Debug, Default)]
+pub struct JournaledSync {
+    /// Backups taken, by the profile they were taken of
+    pub backups: HashMap<PathBuf, PathBuf>,
+    pub sync: TransformedSync,
+}
+
+/// Syncs `source` over `targets` through `plugins` as the app does: journaled,
+/// with every target profile backed up first, the backups tagged `trigger` and
+/// named and pruned by `backup_options`.
+/// `backed_up(dir, backup, elapsed)` is called after each backup and `progress` as in
+/// [`settings::sync_settings_with_progress`]. Refusing a sync a running client
+/// would undo is left to the caller, see [`crate::preflight::guard`].
+pub fn sync(
+    source: &CharacterFile,
+    targets: &[&CharacterFile],
+    plugins: &[Plugin],
+    trigger: BackupTrigger,
+    backup_options: &BackupOptions,
+    mut backed_up: impl FnMut(&Path, &Path, Duration),
+    progress: impl FnMut(usize, usize),
+) -> Result<JournaledSync> {
+    let paths: Vec<PathBuf> = targets.iter().map(|t| t.path.clone()).collect();
+    run(
+        SYNC_OPERATION,
+        &source.path,
+        &paths,
+        !plugins.is_empty(),
+        backup_options,
+        |journal| {
+            let mut backups = HashMap::new();
+            let dirs: BTreeSet<&Path> = targets.iter().map(|t| t.settings_dir()).collect();
+            for dir in dirs {
+                let started = Instant::now();
+                let backup = journal.backup(dir, trigger).context("Backup failed")?;
+                backed_up(dir, &backup, started.elapsed());
+                backups.insert(dir.to_path_buf(), backup);
+            }
+            let sync = settings::sync_settings_transformed(
+                source,
+                targets,
+                false,
+                plugins,
+                Some(journal),
+                progress,
+            )?;
+            Ok(JournaledSync { backups, sync })
+        },
+    )
+}
+
+/// A sync whose journal was left behind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncompleteSync {
+    pub journal: PathBuf,
+    /// What was being done, as named in the audit log
+    pub operation: String,
+    pub source: PathBuf,
+    pub targets: Vec<PathBuf>,
+    pub transformed: bool,
+    /// Finished backups, by the settings directory they were taken of
+    pub backups: BTreeMap<PathBuf, PathBuf>,
+    /// Targets the sync had started writing, which may be half updated
+    pub touched: Vec<PathBuf>,
+    /// Those of `touched` that did not exist before
+    pub created: Vec<PathBuf>,
+}
+
+impl IncompleteSync {
+    /// Reads a journal. A line cut short by the crash ends it.
+    fn load(journal: &Path) -> Result<Self> {
+        let text = fs::read_to_string(journal)
+            .with_context(|| format!("Failed to read {}", journal.display()))?;
+        let mut steps = text
+            .lines()
+            .map_while(|line| serde_json::from_str::<Step>(line).ok());
+        let Some(Step::Started {
+            operation,
+            source,
+            targets,
+            transformed,
+        }) = steps.next()
+        else {
+            bail!(
+                "{} does not start with the sync it records",
+                journal.display()
+            );
+        };
+
+        let mut sync = Self {
+            journal: journal.to_path_buf(),
+            operation,
+            source,
+            targets,
+            transformed,
+            backups: BTreeMap::new(),
+            touched: Vec::new(),
+            created: Vec::new(),
+        };
+        for step in steps {
+            match step {
+                Step::BackedUp { dir, backup } => {
+                    sync.backups.insert(dir, backup);
+                }
+                Step::Staged { target } if !sync.touched.contains(&target) => {
+                    sync.touched.push(target);
+                }
+                Step::Created { target } if !sync.touched.contains(&target) => {
+                    sync.created.push(target.clone());
+                    sync.touched.push(target);
+                }
+                _ => {}
+            }
+        }
+        Ok(sync)
+    }
+
+    /// Whether the sync can be run again: the source still exists and was copied
+    /// as is.
+    pub fn can_finish(&self) -> bool {
+        !self.transformed && self.source.is_file()
+    }
+
+    /// Whether every target the sync touched was created by it or has a backup to
+    /// go back to.
+    pub fn can_roll_back(&self) -> bool {
+        self.touched
+            .iter()
+            .all(|t| self.created.contains(t) || self.backup_of(t).is_some())
+    }
+
+    /// The backed-up copy of `target`: the backup itself when the target is a
+    /// directory that was backed up whole.
+    fn backup_of(&self, target: &Path) -> Option<PathBuf> {
+        if let Some(backup) = self.backups.get(target) {
+            return Some(backup.clone());
+        }
+        let backup = self.backups.get(target.parent()?)?;
+        Some(backup.join(target.file_name()?))
+    }
+
+    /// Completes the sync: backs up the profiles it had not backed up yet, then
+    /// copies the source over every target. Returns how many were written.
+    pub fn finish(&self, backup_options: &BackupOptions) -> Result<usize> {
+        if self.transformed {
+            bail!("This sync ran through plugins, so it can only be rolled back");
+        }
+        if !self.source.is_file() {
+            bail!("The source {} no longer exists", self.source.display());
+        }
+
+        let mut dirs: Vec<&Path> = self.targets.iter().filter_map(|t| t.parent()).collect();
+        dirs.sort();
+        dirs.dedup();
+        for dir in dirs {
+            if !self.backups.contains_key(dir) {
+                settings::create_backup(dir, BackupTrigger::PreSync, backup_options)?;
+            }
+        }
+
+        let _locks = settings::lock_dirs(self.targets.iter().filter_map(|t| t.parent()))?;
+        for target in &self.targets {
+            settings::copy_file_atomic(&self.source, target, None)?;
+        }
+        self.discard()?;
+        Ok(self.targets.len())
+    }
+
+    /// Undoes the sync: puts back every target it touched from the backup taken
+    /// before it, removes those it created and their temporary files. Returns the
+    /// targets put back or removed.
+    pub fn roll_back(&self) -> Result<Vec<PathBuf>> {
+        let _locks = settings::lock_dirs(
+            self.touched
+                .iter()
+                .filter_map(|t| {
+                    if t.is_dir() {
+                        Some(t.as_path())
+                    } else {
+                        t.parent()
+                    }
+                })
+                .filter(|dir| dir.parent().is_some_and(Path::is_dir)),
+        )?;
+        let mut restored = Vec::new();
+        for target in &self.touched {
+            if self.created.contains(target) {
+                if target.is_dir() {
+                    fs::remove_dir_all(target)
+                } else if target.exists() {
+                    fs::remove_file(target)
+                } else {
+                    Ok(())
+                }
+                .with_context(|| format!("Failed to remove {}", target.display()))?;
+                restored.push(target.clone());
+            } else {
+                let Some(backup) = self.backup_of(target) else {
+                    bail!("No backup was finished for {}", target.display());
+                };
+                if backup.is_dir() {
+                    fs::create_dir_all(target)
+                        .with_context(|| format!("Failed to create {}", target.display()))?;
+                    settings::restore_contents(&backup, target)?;
+                    restored.push(target.clone());
+                } else if backup.is_file() {
+                    settings::copy_file_atomic(&backup, target, None)?;
+                    restored.push(target.clone());
+                }
+            }
+            let tmp = target.with_extension("tmp");
+            if tmp.exists() {
+                fs::remove_file(&tmp)
+                    .with_context(|| format!("Failed to remove {}", tmp.display()))?;
+            }
+        }
+        self.discard()?;
+        Ok(restored)
+    }
+
+    /// Forgets the sync, leaving its files as they are.
+    pub fn discard(&self) -> Result<()> {
+        fs::remove_file(&self.journal)
+            .with_context(|| format!("Failed to remove {}", self.journal.display()))
+    }
+}
+
+/// Syncs that were interrupted, oldest first. Journals of syncs still running in
+/// another instance are left alone.
+pub fn incomplete() -> Result<Vec<IncompleteSync>> {
+    incomplete_in(&journal_dir()?)
+}
+
+/// A journal kept in `dir`, for tests of operations that write through one.
+#[cfg(test)]
+pub(crate) fn scratch(dir: &Path) -> SyncJournal {
+    SyncJournal::begin_in(
+        &dir.join(JOURNAL_DIR),
+        "Test",
+        dir,
+        &[],
+        true,
+        &BackupOptions::default(),
+    )
+    .expect("Failed to start a journal")
+}
+
+fn incomplete_in(dir: &Path) -> Result<Vec<IncompleteSync>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut journals = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(JOURNAL_PREFIX)
+            || path.extension().and_then(|e| e.to_str()) != Some(JOURNAL_EXTENSION)
+        {
+            continue;
+        }
+        let pid = name[JOURNAL_PREFIX.len()..]
+            .split('-')
+            .next()
+            .and_then(|pid| pid.parse().ok());
+        let written = entry.metadata().and_then(|m| m.modified()).ok();
+        if pid != Some(std::process::id()) && !settings::lock_is_stale(pid, written) {
+            continue;
+        }
+        journals.push((written, path));
+    }
+    journals.sort();
+
+    let mut syncs = Vec::new();
+    for (_, path) in journals {
+        match IncompleteSync::load(&path) {
+            Ok(sync) => syncs.push(sync),
+            // Cut off before its first line: nothing had been done yet
+            Err(_) if path.metadata().is_ok_and(|m| m.len() == 0) => {
+                let _ = fs::remove_file(&path);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(syncs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_journal_recovery() -> Result<()> {
+        let dir = tempdir()?;
+        let journals = dir.path().join("journals");
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir_all(&settings_dir)?;
+        let source = settings_dir.join("core_char_1.dat");
+        let first = settings_dir.join("core_char_2.dat");
+        let second = settings_dir.join("core_char_3.dat");
+        fs::write(&source, "new")?;
+        fs::write(&first, "old 2")?;
+        fs::write(&second, "old 3")?;
+        let targets = vec![first.clone(), second.clone()];
+
+        // Crash after the first target is renamed into place and the second staged
+        let mut journal = SyncJournal::begin_in(
+            &journals,
+            SYNC_OPERATION,
+            &source,
+            &targets,
+            false,
+            &BackupOptions::default(),
+        )?;
+        journal.backup(&settings_dir, BackupTrigger::PreSync)?;
+        for target in &targets {
+            journal.record(&Step::Staged {
+                target: target.clone(),
+            })?;
+        }
+        settings::copy_file_atomic(&source, &first, None)?;
+        fs::write(second.with_extension("tmp"), "ne")?;
+        drop(journal);
+
+        let syncs = incomplete_in(&journals)?;
+        assert_eq!(syncs.len(), 1);
+        assert_eq!(syncs[0].touched, targets);
+        assert!(syncs[0].can_finish() && syncs[0].can_roll_back());
+
+        assert_eq!(syncs[0].roll_back()?, targets);
+        assert_eq!(fs::read_to_string(&first)?, "old 2");
+        assert!(!second.with_extension("tmp").exists());
+        assert!(incomplete_in(&journals)?.is_empty());
+
+        // A transformed sync can't be finished; an ordinary one copies the source again
+        let journal = SyncJournal::begin_in(
+            &journals,
+            SYNC_OPERATION,
+            &source,
+            &targets,
+            true,
+            &BackupOptions::default(),
+        )?;
+        drop(journal);
+        let sync = incomplete_in(&journals)?.remove(0);
+        assert!(!sync.can_finish() && sync.finish(&BackupOptions::default()).is_err());
+        sync.discard()?;
+
+        let journal = SyncJournal::begin_in(
+            &journals,
+            SYNC_OPERATION,
+            &source,
+            &targets,
+            false,
+            &BackupOptions::default(),
+        )?;
+        drop(journal);
+        assert_eq!(
+            incomplete_in(&journals)?
+                .remove(0)
+                .finish(&BackupOptions::default())?,
+            2
+        );
+        assert_eq!(fs::read_to_string(&second)?, "new");
+        assert!(incomplete_in(&journals)?.is_empty());
+
+        // A finished sync leaves nothing behind
+        SyncJournal::begin_in(
+            &journals,
+            SYNC_OPERATION,
+            &source,
+            &targets,
+            false,
+            &BackupOptions::default(),
+        )?
+        .finish()?;
+        assert!(incomplete_in(&journals)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_roll_back_created_and_directories() -> Result<()> {
+        let dir = tempdir()?;
+        let journals = dir.path().join("journals");
+        let restored_dir = dir.path().join("settings_Default");
+        let new_dir = dir.path().join("settings_New");
+        fs::create_dir_all(&restored_dir)?;
+        fs::write(restored_dir.join("core_char_1.dat"), "old")?;
+        let new_file = restored_dir.join("core_char_2.dat");
+        let targets = vec![restored_dir.clone(), new_file.clone(), new_dir.clone()];
+
+        // Crash after replacing a whole directory and creating a file and a directory
+        let mut journal = SyncJournal::begin_in(
+            &journals,
+            "Restore backup",
+            dir.path(),
+            &targets,
+            true,
+            &BackupOptions::default(),
+        )?;
+        journal.backup(&restored_dir, BackupTrigger::PreRestore)?;
+        for target in &targets {
+            journal.touch(target)?;
+        }
+        fs::write(restored_dir.join("core_char_1.dat"), "new")?;
+        fs::write(restored_dir.join("core_user_1.dat"), "new")?;
+        fs::write(&new_file, "new")?;
+        fs::create_dir_all(&new_dir)?;
+        drop(journal);
+
+        let sync = incomplete_in(&journals)?.remove(0);
+        assert_eq!(sync.operation, "Restore backup");
+        assert_eq!(sync.created, vec![new_file.clone(), new_dir.clone()]);
+        assert!(!sync.can_finish() && sync.can_roll_back());
+
+        assert_eq!(sync.roll_back()?, targets);
+        assert_eq!(
+            fs::read_to_string(restored_dir.join("core_char_1.dat"))?,
+            "old"
+        );
+        assert!(!restored_dir.join("core_user_1.dat").exists());
+        assert!(!new_file.exists() && !new_dir.exists());
+        Ok(())
+    }
+}
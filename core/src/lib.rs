@@ -0,0 +1,30 @@
+//! EVE Online settings discovery, sync and backup, shared by the GUI and any other
+//! frontend. Nothing in here depends on eframe.
+
+pub mod baseline;
+pub mod config;
+pub mod discovery;
+pub mod duplicates;
+pub mod elevate;
+pub mod esi;
+pub mod golden;
+pub mod health;
+pub mod inspect;
+pub mod journal;
+pub mod lint;
+pub mod marshal;
+pub mod migrate;
+pub mod names;
+pub mod news;
+pub mod notify;
+pub mod plugins;
+pub mod preflight;
+pub mod presets;
+pub mod process;
+pub mod report;
+pub mod scripting;
+pub mod settings;
+pub mod state;
+pub mod status;
+pub mod troubleshoot;
+pub mod windows_settings;
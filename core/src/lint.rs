@@ -0,0 +1,334 @@
+//! Settings lint: rules that flag questionable values across characters, such as
+//! autopilot left on or a UI scale that differs from the fleet standard. A few
+//! rules ship with the app; more go in `lint_rules.toml` next to the config file,
+//! where a rule with the same name as a shipped one replaces it:
+//!
+//! ```toml
+//! [[rule]]
+//! name = "UI scale differs from fleet standard"
+//! key = "ui/UIScalingValue"
+//! expect = "0.9"
+//!
+//! [[rule]]
+//! name = "Autopilot enabled"
+//! enabled = false
+//! key = "ui/autopilot"
+//! ```
+//!
+//! `key` is a settings path as the inspector shows it. A rule flags files where the
+//! setting holds `flag_if`, or anything other than `expect`; files without the
+//! setting are not flagged. `fix` is the value a fix writes, defaulting to
+//! `expect`.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::discovery::{CharacterFile, FileType};
+use crate::inspect::{self, KeyPath};
+use crate::marshal::{self, Value};
+
+/// User rules, next to the config file
+pub const RULES_FILE: &str = "lint_rules.toml";
+
+const DEFAULT_RULES: &str = r#"
+[[rule]]
+name = "Autopilot enabled"
+description = "An alt left on autopilot flies on unattended."
+file = "char"
+key = "ui/autopilot"
+flag_if = "true"
+fix = "false"
+
+[[rule]]
+name = "CCP data sharing on"
+description = "Crash reports and usage data are sent to CCP."
+file = "user"
+key = "generic/sendCrashReports"
+flag_if = "true"
+fix = "false"
+
+[[rule]]
+name = "UI scale differs from fleet standard"
+description = "Window layouts synced from another character assume 100% scale."
+file = "user"
+key = "ui/UIScalingValue"
+expect = "1.0"
+"#;
+
+/// Written the first time the user opens their rules file
+pub const RULES_TEMPLATE: &str = r#"# Settings lint rules. A rule with the same name as a shipped one replaces it;
+# set `enabled = false` to turn a shipped rule off.
+#
+# [[rule]]
+# name = "UI scale differs from fleet standard"
+# description = "Our fleet flies at 90%."
+# file = "user"              # "char" or "user"; both if left out
+# key = "ui/UIScalingValue"  # settings path, as shown in the inspector
+# expect = "0.9"             # or flag_if = "<value>"
+# fix = "0.9"                # value a fix writes; defaults to expect
+"#;
+
+/// Which settings files a rule looks at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleFile {
+    Char,
+    User,
+}
+
+impl RuleFile {
+    /// The kind of rule that covers `file_type`; launcher files have none.
+    pub fn for_type(file_type: FileType) -> Option<Self> {
+        match file_type {
+            FileType::Character => Some(Self::Char),
+            FileType::User => Some(Self::User),
+            FileType::Launcher => None,
+        }
+    }
+
+    fn matches(self, file_type: FileType) -> bool {
+        matches!(
+            (self, file_type),
+            (Self::Char, FileType::Character) | (Self::User, FileType::User)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub file: Option<RuleFile>,
+    pub key: String,
+    /// Flag files where the setting holds this value
+    #[serde(default)]
+    pub flag_if: Option<String>,
+    /// Flag files where the setting holds anything else
+    #[serde(default)]
+    pub expect: Option<String>,
+    #[serde(default)]
+    pub fix: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Rule {
+    /// Value a fix writes, if the rule offers one
+    pub fn fix_value(&self) -> Option<&str> {
+        self.fix.as_deref().or(self.expect.as_deref())
+    }
+
+    /// Whether `value` breaks the rule. Values are compared after reading the
+    /// rule's text as the setting's own type, so `"1"` matches a float of 1.0.
+    fn flags(&self, value: &Value) -> bool {
+        let holds = |text: &str| inspect::parse_as(value, text).is_ok_and(|v| &v == value);
+        match (&self.flag_if, &self.expect) {
+            (Some(bad), _) => holds(bad),
+            (None, Some(good)) => !holds(good),
+            (None, None) => false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<Rule>,
+}
+
+/// Parses a rules file, checking each rule says what to flag.
+pub fn parse_rules(text: &str) -> Result<Vec<Rule>> {
+    let file: RulesFile = toml::from_str(text).context("Failed to parse lint rules")?;
+    for rule in &file.rules {
+        if rule.flag_if.is_some() == rule.expect.is_some() {
+            bail!("Rule \"{}\" needs either flag_if or expect", rule.name);
+        }
+    }
+    Ok(file.rules)
+}
+
+/// Rules shipped with the app
+pub fn default_rules() -> Vec<Rule> {
+    parse_rules(DEFAULT_RULES).expect("shipped lint rules parse")
+}
+
+pub fn rules_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join(RULES_FILE))
+}
+
+/// Shipped rules overridden by `user` ones of the same name, with disabled rules
+/// left out.
+pub fn merge_rules(defaults: Vec<Rule>, user: Vec<Rule>) -> Vec<Rule> {
+    let mut rules: Vec<Rule> = defaults
+        .into_iter()
+        .filter(|d| !user.iter().any(|u| u.name == d.name))
+        .collect();
+    rules.extend(user);
+    rules.retain(|r| r.enabled);
+    rules
+}
+
+/// The shipped rules plus the user's, if they have a rules file.
+pub fn load_rules() -> Result<Vec<Rule>> {
+    let path = rules_path()?;
+    let user = if path.exists() {
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        parse_rules(&text).with_context(|| format!("In {}", path.display()))?
+    } else {
+        Vec::new()
+    };
+    Ok(merge_rules(default_rules(), user))
+}
+
+/// A setting that breaks a rule
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule: String,
+    pub file: CharacterFile,
+    pub path: KeyPath,
+    /// Current value, as the inspector shows it
+    pub value: String,
+    pub fix: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct LintReport {
+    pub findings: Vec<Finding>,
+    /// Files that could not be decoded, with the reason
+    pub errors: Vec<(PathBuf, String)>,
+    pub files_checked: usize,
+}
+
+impl LintReport {
+    /// Plain-text report, one line per finding, grouped by rule.
+    pub fn to_text(&self, title_of: impl Fn(&CharacterFile) -> String) -> String {
+        let mut text = format!(
+            "Settings lint: {} finding(s) in {} file(s)\n",
+            self.findings.len(),
+            self.files_checked
+        );
+        let mut rules: Vec<&str> = self.findings.iter().map(|f| f.rule.as_str()).collect();
+        rules.dedup();
+        for rule in rules {
+            text.push_str(&format!("\n{}\n", rule));
+            for finding in self.findings.iter().filter(|f| f.rule == rule) {
+                text.push_str(&format!(
+                    "  {}: {} = {}\n",
+                    title_of(&finding.file),
+                    finding.path,
+                    finding.value
+                ));
+            }
+        }
+        for (path, error) in &self.errors {
+            text.push_str(&format!(
+                "\nCould not check {}: {}\n",
+                path.display(),
+                error
+            ));
+        }
+        text
+    }
+}
+
+/// Findings for one decoded file.
+pub fn check_tree(rules: &[Rule], file: &CharacterFile, tree: &Value) -> Vec<Finding> {
+    rules
+        .iter()
+        .filter(|rule| rule.file.is_none_or(|f| f.matches(file.file_type)))
+        .filter_map(|rule| {
+            let path = inspect::resolve(tree, &rule.key)?;
+            let value = inspect::get(tree, &path)?;
+            rule.flags(value).then(|| Finding {
+                rule: rule.name.clone(),
+                file: file.clone(),
+                path,
+                value: inspect::display_scalar(value),
+                fix: rule.fix_value().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Checks every character and user file against `rules`. Findings are grouped by
+/// rule, in rule order.
+pub fn run(rules: &[Rule], files: &[CharacterFile]) -> LintReport {
+    let mut report = LintReport::default();
+    for file in files.iter().filter(|f| f.file_type != FileType::Launcher) {
+        match marshal::read_file(&file.path) {
+            Ok(tree) => report.findings.extend(check_tree(rules, file, &tree)),
+            Err(e) => report.errors.push((file.path.clone(), format!("{:#}", e))),
+        }
+        report.files_checked += 1;
+    }
+    report.findings.sort_by_key(|f| {
+        rules
+            .iter()
+            .position(|r| r.name == f.rule)
+            .unwrap_or(usize::MAX)
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lint_rules() -> Result<()> {
+        assert_eq!(default_rules().len(), 3);
+        let user = parse_rules(
+            r#"
+            [[rule]]
+            name = "UI scale differs from fleet standard"
+            key = "ui/UIScalingValue"
+            expect = "0.9"
+
+            [[rule]]
+            name = "Autopilot enabled"
+            key = "ui/autopilot"
+            flag_if = "true"
+            enabled = false
+            "#,
+        )?;
+        let rules = merge_rules(default_rules(), user);
+        assert_eq!(rules.len(), 2);
+        assert!(parse_rules("[[rule]]\nname = \"x\"\nkey = \"a\"").is_err());
+
+        let dir = tempdir()?;
+        let path = dir.path().join("core_user_5.dat");
+        let tree = Value::Dict(vec![(
+            Value::Bytes(b"ui".to_vec()),
+            Value::Dict(vec![
+                (Value::Bytes(b"autopilot".to_vec()), Value::Bool(true)),
+                (Value::Bytes(b"UIScalingValue".to_vec()), Value::Float(1.0)),
+            ]),
+        )]);
+        fs::write(&path, marshal::encode(&tree))?;
+        let file = discovery::settings_file(path).unwrap();
+
+        let report = run(&rules, &[file]);
+        assert_eq!(report.files_checked, 1);
+        assert_eq!(report.findings.len(), 1);
+        let finding = &report.findings[0];
+        assert_eq!(finding.path.to_string(), "ui/UIScalingValue");
+        assert_eq!(finding.value, "1.0");
+        assert_eq!(finding.fix.as_deref(), Some("0.9"));
+        assert!(report
+            .to_text(|_| "Main".to_string())
+            .contains("Main: ui/UIScalingValue = 1.0"));
+        Ok(())
+    }
+}
@@ -0,0 +1,580 @@
+//! Decoder and encoder for the marshal format EVE writes its `.dat` settings in.
+//!
+//! A file is a `~` byte, a little-endian `u32` count of shared objects, the
+//! serialized value tree and, if the count is not zero, a table of one `u32` slot
+//! number per shared object. Each value starts with an opcode byte; bit `0x40`
+//! marks an object that later `REF` opcodes may point back to.
+//!
+//! Only the opcodes that occur in settings files are understood: scalars, byte and
+//! unicode strings, tuples, lists, dicts and shared references. Anything else
+//! (pickled class instances, the client's built-in string table) fails to decode
+//! instead of being guessed at. Encoding writes shared objects out in full, so a
+//! re-encoded file can be larger than the original but reads back the same.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+
+const HEADER: u8 = 0x7e;
+const SHARED_FLAG: u8 = 0x40;
+const OPCODE_MASK: u8 = 0x3f;
+
+const OP_NONE: u8 = 0x01;
+const OP_GLOBAL: u8 = 0x02;
+const OP_LONGLONG: u8 = 0x03;
+const OP_INT: u8 = 0x04;
+const OP_SHORT: u8 = 0x05;
+const OP_BYTE: u8 = 0x06;
+const OP_MINUSONE: u8 = 0x07;
+const OP_ZERO: u8 = 0x08;
+const OP_ONE: u8 = 0x09;
+const OP_FLOAT: u8 = 0x0a;
+const OP_FLOAT0: u8 = 0x0b;
+const OP_STRINGL: u8 = 0x0d;
+const OP_STRING0: u8 = 0x0e;
+const OP_STRING1: u8 = 0x0f;
+const OP_STRING: u8 = 0x10;
+const OP_UNICODE: u8 = 0x12;
+const OP_BUFFER: u8 = 0x13;
+const OP_TUPLE: u8 = 0x14;
+const OP_LIST: u8 = 0x15;
+const OP_DICT: u8 = 0x16;
+const OP_REF: u8 = 0x1b;
+const OP_TRUE: u8 = 0x1f;
+const OP_FALSE: u8 = 0x20;
+const OP_TUPLE0: u8 = 0x24;
+const OP_TUPLE1: u8 = 0x25;
+const OP_LIST0: u8 = 0x26;
+const OP_LIST1: u8 = 0x27;
+const OP_UNICODE0: u8 = 0x28;
+const OP_UNICODE1: u8 = 0x29;
+const OP_TUPLE2: u8 = 0x2c;
+const OP_UTF8: u8 = 0x2e;
+const OP_LONG: u8 = 0x2f;
+
+/// Deepest nesting accepted, so a damaged file cannot overflow the stack
+const MAX_DEPTH: usize = 256;
+
+/// A decoded value. Dicts keep their entries in file order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    /// Byte string; settings keys are usually these
+    Bytes(Vec<u8>),
+    Unicode(String),
+    /// Reference to a named global, e.g. a class
+    Global(String),
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+    Dict(Vec<(Value, Value)>),
+}
+
+impl Value {
+    /// Short name of the value's type, for display and validation messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::None => "none",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bytes(_) => "string",
+            Value::Unicode(_) => "unicode",
+            Value::Global(_) => "global",
+            Value::Tuple(_) => "tuple",
+            Value::List(_) => "list",
+            Value::Dict(_) => "dict",
+        }
+    }
+
+    /// Text of a byte or unicode string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Bytes(bytes) => std::str::from_utf8(bytes).ok(),
+            Value::Unicode(s) | Value::Global(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Lossless-enough JSON for reading and diffing: dicts whose keys are all
+    /// strings become objects, other dicts become arrays of `[key, value]` pairs,
+    /// and byte strings that are not UTF-8 become `{"$bytes": "<hex>"}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value as Json;
+        match self {
+            Value::None => Json::Null,
+            Value::Bool(b) => Json::Bool(*b),
+            Value::Int(i) => Json::from(*i),
+            Value::Float(f) => serde_json::Number::from_f64(*f)
+                .map(Json::Number)
+                .unwrap_or_else(|| Json::String(f.to_string())),
+            Value::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(s) => Json::String(s.to_string()),
+                Err(_) => serde_json::json!({ "$bytes": hex(bytes) }),
+            },
+            Value::Unicode(s) => Json::String(s.clone()),
+            Value::Global(s) => serde_json::json!({ "$global": s }),
+            Value::Tuple(items) | Value::List(items) => {
+                Json::Array(items.iter().map(Value::to_json).collect())
+            }
+            Value::Dict(entries) => {
+                let keys: Option<Vec<&str>> = entries.iter().map(|(k, _)| k.as_str()).collect();
+                match keys {
+                    Some(keys) => Json::Object(
+                        keys.into_iter()
+                            .zip(entries.iter().map(|(_, v)| v.to_json()))
+                            .map(|(k, v)| (k.to_string(), v))
+                            .collect(),
+                    ),
+                    None => Json::Array(
+                        entries
+                            .iter()
+                            .map(|(k, v)| Json::Array(vec![k.to_json(), v.to_json()]))
+                            .collect(),
+                    ),
+                }
+            }
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Reads and decodes a settings file.
+pub fn read_file(path: &Path) -> Result<Value> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    decode(&data).with_context(|| format!("Failed to decode {}", path.display()))
+}
+
+/// Decodes a whole marshal stream, header included.
+pub fn decode(data: &[u8]) -> Result<Value> {
+    if data.first() != Some(&HEADER) {
+        bail!("Not a marshal stream (no '~' header)");
+    }
+    if data.len() < 5 {
+        bail!("Truncated header");
+    }
+    let shared_count = u32::from_le_bytes([data[1], data[2], data[3], data[4]]) as usize;
+    let table_len = shared_count
+        .checked_mul(4)
+        .filter(|len| *len <= data.len() - 5)
+        .context("Shared object table is larger than the file")?;
+    let (stream, table) = data[5..].split_at(data.len() - 5 - table_len);
+    let slots = table
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as usize)
+        .collect();
+
+    let mut decoder = Decoder {
+        data: stream,
+        pos: 0,
+        slots,
+        stored: 0,
+        shared: vec![None; shared_count],
+    };
+    let value = decoder.value(0)?;
+    if decoder.pos != stream.len() {
+        bail!(
+            "{} unexpected byte(s) after the value",
+            stream.len() - decoder.pos
+        );
+    }
+    Ok(value)
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Slot each shared object goes into, in the order they are completed
+    slots: Vec<usize>,
+    stored: usize,
+    shared: Vec<Option<Value>>,
+}
+
+impl Decoder<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .with_context(|| format!("Unexpected end of data at byte {}", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    /// Length prefix: one byte, or `0xff` followed by a `u32`.
+    fn size(&mut self) -> Result<usize> {
+        match self.array::<1>()?[0] {
+            0xff => Ok(u32::from_le_bytes(self.array()?) as usize),
+            n => Ok(n as usize),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.size()?;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    fn items(&mut self, n: usize, depth: usize) -> Result<Vec<Value>> {
+        // Every item takes at least one byte, which bounds a bogus count
+        if n > self.data.len() - self.pos {
+            bail!("Container claims {} items at byte {}", n, self.pos);
+        }
+        (0..n).map(|_| self.value(depth + 1)).collect()
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            bail!("Values nested too deeply");
+        }
+        let at = self.pos;
+        let op = self.array::<1>()?[0];
+        let value = match op & OPCODE_MASK {
+            OP_NONE => Value::None,
+            OP_GLOBAL => Value::Global(String::from_utf8_lossy(&self.bytes()?).to_string()),
+            OP_LONGLONG => Value::Int(i64::from_le_bytes(self.array()?)),
+            OP_INT => Value::Int(i32::from_le_bytes(self.array()?) as i64),
+            OP_SHORT => Value::Int(i16::from_le_bytes(self.array()?) as i64),
+            OP_BYTE => Value::Int(i8::from_le_bytes(self.array()?) as i64),
+            OP_MINUSONE => Value::Int(-1),
+            OP_ZERO => Value::Int(0),
+            OP_ONE => Value::Int(1),
+            OP_FLOAT => Value::Float(f64::from_le_bytes(self.array()?)),
+            OP_FLOAT0 => Value::Float(0.0),
+            OP_STRINGL | OP_STRING | OP_BUFFER => Value::Bytes(self.bytes()?),
+            OP_STRING0 => Value::Bytes(Vec::new()),
+            OP_STRING1 => Value::Bytes(self.take(1)?.to_vec()),
+            OP_UNICODE => {
+                let chars = self.size()?;
+                let raw = self.take(chars.checked_mul(2).context("String too long")?)?;
+                let units: Vec<u16> = raw
+                    .chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .collect();
+                Value::Unicode(String::from_utf16_lossy(&units))
+            }
+            OP_UNICODE0 => Value::Unicode(String::new()),
+            OP_UNICODE1 => {
+                let unit = u16::from_le_bytes(self.array()?);
+                Value::Unicode(String::from_utf16_lossy(&[unit]))
+            }
+            OP_UTF8 => Value::Unicode(String::from_utf8_lossy(&self.bytes()?).to_string()),
+            OP_LONG => {
+                let raw = self.bytes()?;
+                if raw.len() > 8 {
+                    bail!("Integer of {} bytes at byte {} is too large", raw.len(), at);
+                }
+                // Little-endian two's complement, sign-extended to 64 bits
+                let fill = if raw.last().is_some_and(|b| b & 0x80 != 0) {
+                    0xff
+                } else {
+                    0
+                };
+                let mut bytes = [fill; 8];
+                bytes[..raw.len()].copy_from_slice(&raw);
+                Value::Int(i64::from_le_bytes(bytes))
+            }
+            OP_TRUE => Value::Bool(true),
+            OP_FALSE => Value::Bool(false),
+            OP_TUPLE => {
+                let n = self.size()?;
+                Value::Tuple(self.items(n, depth)?)
+            }
+            OP_TUPLE0 => Value::Tuple(Vec::new()),
+            OP_TUPLE1 => Value::Tuple(self.items(1, depth)?),
+            OP_TUPLE2 => Value::Tuple(self.items(2, depth)?),
+            OP_LIST => {
+                let n = self.size()?;
+                Value::List(self.items(n, depth)?)
+            }
+            OP_LIST0 => Value::List(Vec::new()),
+            OP_LIST1 => Value::List(self.items(1, depth)?),
+            OP_DICT => {
+                let n = self.size()?;
+                if n > self.data.len() - self.pos {
+                    bail!("Dict claims {} entries at byte {}", n, at);
+                }
+                let mut entries = Vec::with_capacity(n);
+                for _ in 0..n {
+                    // Values come before their keys
+                    let value = self.value(depth + 1)?;
+                    let key = self.value(depth + 1)?;
+                    entries.push((key, value));
+                }
+                Value::Dict(entries)
+            }
+            OP_REF => {
+                let slot = self.size()?;
+                return slot
+                    .checked_sub(1)
+                    .and_then(|i| self.shared.get(i).cloned().flatten())
+                    .with_context(|| {
+                        format!("Reference to unknown object {} at byte {}", slot, at)
+                    });
+            }
+            other => bail!("Unsupported opcode {:#04x} at byte {}", other, at),
+        };
+
+        if op & SHARED_FLAG != 0 {
+            let slot = self
+                .slots
+                .get(self.stored)
+                .and_then(|slot| slot.checked_sub(1))
+                .filter(|slot| *slot < self.shared.len())
+                .with_context(|| format!("Shared object at byte {} has no slot", at))?;
+            self.shared[slot] = Some(value.clone());
+            self.stored += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// Encodes a value as a complete marshal stream with no shared objects.
+pub fn encode(value: &Value) -> Vec<u8> {
+    let mut out = vec![HEADER, 0, 0, 0, 0];
+    encode_value(value, &mut out);
+    out
+}
+
+fn write_size(len: usize, out: &mut Vec<u8>) {
+    if len < 0xff {
+        out.push(len as u8);
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+}
+
+fn write_items(op: u8, items: &[Value], out: &mut Vec<u8>) {
+    out.push(op);
+    write_size(items.len(), out);
+    for item in items {
+        encode_value(item, out);
+    }
+}
+
+fn encode_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::None => out.push(OP_NONE),
+        Value::Bool(true) => out.push(OP_TRUE),
+        Value::Bool(false) => out.push(OP_FALSE),
+        Value::Int(-1) => out.push(OP_MINUSONE),
+        Value::Int(0) => out.push(OP_ZERO),
+        Value::Int(1) => out.push(OP_ONE),
+        Value::Int(i) => {
+            if let Ok(b) = i8::try_from(*i) {
+                out.push(OP_BYTE);
+                out.extend_from_slice(&b.to_le_bytes());
+            } else if let Ok(s) = i16::try_from(*i) {
+                out.push(OP_SHORT);
+                out.extend_from_slice(&s.to_le_bytes());
+            } else if let Ok(n) = i32::try_from(*i) {
+                out.push(OP_INT);
+                out.extend_from_slice(&n.to_le_bytes());
+            } else {
+                out.push(OP_LONGLONG);
+                out.extend_from_slice(&i.to_le_bytes());
+            }
+        }
+        Value::Float(f) if *f == 0.0 && f.is_sign_positive() => out.push(OP_FLOAT0),
+        Value::Float(f) => {
+            out.push(OP_FLOAT);
+            out.extend_from_slice(&f.to_le_bytes());
+        }
+        Value::Bytes(bytes) => match bytes.len() {
+            0 => out.push(OP_STRING0),
+            1 => {
+                out.push(OP_STRING1);
+                out.push(bytes[0]);
+            }
+            len => {
+                out.push(OP_STRING);
+                write_size(len, out);
+                out.extend_from_slice(bytes);
+            }
+        },
+        Value::Unicode(s) if s.is_empty() => out.push(OP_UNICODE0),
+        Value::Unicode(s) => {
+            out.push(OP_UTF8);
+            write_size(s.len(), out);
+            out.extend_from_slice(s.as_bytes());
+        }
+        Value::Global(name) => {
+            out.push(OP_GLOBAL);
+            write_size(name.len(), out);
+            out.extend_from_slice(name.as_bytes());
+        }
+        Value::Tuple(items) => match items.len() {
+            0 => out.push(OP_TUPLE0),
+            1 => {
+                out.push(OP_TUPLE1);
+                encode_value(&items[0], out);
+            }
+            2 => {
+                out.push(OP_TUPLE2);
+                encode_value(&items[0], out);
+                encode_value(&items[1], out);
+            }
+            _ => write_items(OP_TUPLE, items, out),
+        },
+        Value::List(items) => match items.len() {
+            0 => out.push(OP_LIST0),
+            1 => {
+                out.push(OP_LIST1);
+                encode_value(&items[0], out);
+            }
+            _ => write_items(OP_LIST, items, out),
+        },
+        Value::Dict(entries) => {
+            out.push(OP_DICT);
+            write_size(entries.len(), out);
+            for (key, value) in entries {
+                encode_value(value, out);
+                encode_value(key, out);
+            }
+        }
+    }
+}
+
+/// File formats a decoded settings tree can be exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Json, ExportFormat::Yaml];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Yaml => "YAML",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn render(self, value: &Value) -> Result<String> {
+        let json = value.to_json();
+        match self {
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(&json).context("Failed to write JSON")
+            }
+            ExportFormat::Yaml => serde_yaml::to_string(&json).context("Failed to write YAML"),
+        }
+    }
+}
+
+/// Decodes the settings file at `source` and writes it to `dest` as text.
+pub fn export_decoded(source: &Path, dest: &Path, format: ExportFormat) -> Result<()> {
+    let text = format.render(&read_file(source)?)?;
+    fs::write(dest, text).with_context(|| format!("Failed to write {}", dest.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> Value {
+        Value::Bytes(s.as_bytes().to_vec())
+    }
+
+    fn sample() -> Value {
+        Value::Dict(vec![
+            (
+                key("ui"),
+                Value::Dict(vec![
+                    (key("scale"), Value::Float(1.25)),
+                    (key("windowX"), Value::Int(-340)),
+                    (key("autopilot"), Value::Bool(false)),
+                ]),
+            ),
+            (
+                key("overview"),
+                Value::List(vec![
+                    Value::Tuple(vec![Value::Int(70_000), Value::Unicode("Ägir".into())]),
+                    Value::Int(3_000_000_000),
+                    Value::None,
+                ]),
+            ),
+            (Value::Int(7), Value::Bytes(vec![0xff, 0x00])),
+        ])
+    }
+
+    #[test]
+    fn test_round_trip_and_json() -> Result<()> {
+        let value = sample();
+        assert_eq!(decode(&encode(&value))?, value);
+
+        let json = value.to_json();
+        // Mixed key types fall back to pairs
+        assert_eq!(json[0][0], "ui");
+        assert_eq!(json[0][1]["windowX"], -340);
+        assert_eq!(json[1][1][0][1], "Ägir");
+        assert_eq!(json[2][1]["$bytes"], "ff00");
+        assert!(ExportFormat::Yaml.render(&value)?.contains("scale: 1.25"));
+
+        assert!(decode(b"not marshal").is_err());
+        assert!(decode(&[HEADER, 0, 0, 0, 0, OP_LIST, 200]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_shared_and_wide_opcodes() -> Result<()> {
+        let data = [
+            HEADER,
+            1,
+            0,
+            0,
+            0, // one shared object
+            OP_TUPLE,
+            4, //
+            OP_STRING | SHARED_FLAG,
+            3,
+            b'k',
+            b'e',
+            b'y', // stored in slot 1
+            OP_REF,
+            1, //
+            OP_UNICODE,
+            2,
+            b'h',
+            0,
+            b'i',
+            0, //
+            OP_LONG,
+            2,
+            0x00,
+            0x80, // -32768
+            1,
+            0,
+            0,
+            0, // slot table
+        ];
+        assert_eq!(
+            decode(&data)?,
+            Value::Tuple(vec![
+                key("key"),
+                key("key"),
+                Value::Unicode("hi".into()),
+                Value::Int(-32768),
+            ])
+        );
+        Ok(())
+    }
+}
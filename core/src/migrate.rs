@@ -0,0 +1,220 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::journal::SyncJournal;
+use crate::process;
+use crate::settings::{self, BackupTrigger};
+
+/// Wine user Proton runs the client as, and the one a fresh prefix gets
+const DEFAULT_WINE_USER: &str = "steamuser";
+
+/// Windows' shared profile, which never holds EVE settings
+const PUBLIC_WINE_USER: &str = "Public";
+
+/// Launcher data outside the EVE folder, relative to a Wine user's home
+const ROAMING_LAUNCHER_DIR: &[&str] = &["AppData", "Roaming", "EVE Online"];
+
+/// Where the EVE folder goes in the new prefix, relative to the Wine user's home
+const EVE_DIR: &[&str] = &["AppData", "Local", "CCP", "EVE"];
+
+/// One folder a migration copies, with everything below it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationItem {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Number of files below `from`
+    pub files: usize,
+}
+
+/// What migrating one prefix's EVE data into another would copy. The Wine user
+/// names may differ, e.g. a Lutris prefix using the login name moving to Proton's
+/// `steamuser`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationPlan {
+    pub old_user: String,
+    pub new_user: String,
+    pub items: Vec<MigrationItem>,
+}
+
+/// What a migration did
+#[derive(Debug)]
+pub struct MigrationOutcome {
+    /// Backups of folders in the new prefix that were about to be merged into
+    pub backups: Vec<PathBuf>,
+    pub copied: usize,
+}
+
+/// Plans copying the whole CCP/EVE tree (settings of every install and profile,
+/// launcher data included) from `old` to `new`, both `drive_c` folders. The old
+/// tree may use the XP-style `Local Settings` layout; it is written to the current
+/// `AppData` layout in the new prefix.
+pub fn plan_migration(old: &Path, new: &Path) -> Result<MigrationPlan> {
+    if old == new {
+        bail!("The old and new prefix are the same");
+    }
+
+    let eve_dirs = process::eve_dirs_by_user(old);
+    let Some((old_user, old_eve)) = eve_dirs
+        .iter()
+        .find(|(user, _)| user == DEFAULT_WINE_USER)
+        .or_else(|| eve_dirs.first())
+        .cloned()
+    else {
+        bail!("{} has no CCP/EVE folder", old.display());
+    };
+
+    let new_user = new_wine_user(new);
+    let new_home = new.join("users").join(&new_user);
+    let old_home = old.join("users").join(&old_user);
+
+    let mut items = vec![MigrationItem {
+        files: count_files(&old_eve),
+        to: EVE_DIR
+            .iter()
+            .fold(new_home.clone(), |p, part| p.join(part)),
+        from: old_eve,
+    }];
+    let roaming = ROAMING_LAUNCHER_DIR
+        .iter()
+        .fold(old_home, |p, part| p.join(part));
+    if roaming.is_dir() {
+        items.push(MigrationItem {
+            files: count_files(&roaming),
+            to: ROAMING_LAUNCHER_DIR
+                .iter()
+                .fold(new_home, |p, part| p.join(part)),
+            from: roaming,
+        });
+    }
+
+    Ok(MigrationPlan {
+        old_user,
+        new_user,
+        items,
+    })
+}
+
+impl MigrationPlan {
+    pub fn file_count(&self) -> usize {
+        self.items.iter().map(|item| item.files).sum()
+    }
+
+    /// Copies every item into the new prefix. Folders that already exist there are
+    /// backed up first and then merged into, so files only the new prefix has stay.
+    /// Every step goes into `journal` first.
+    pub fn migrate(&self, journal: &mut SyncJournal) -> Result<MigrationOutcome> {
+        let mut backups = Vec::new();
+        for item in &self.items {
+            if item.to.is_dir() {
+                backups.push(journal.backup(&item.to, BackupTrigger::PreSync)?);
+            }
+        }
+
+        for item in &self.items {
+            journal.touch(&item.to)?;
+            settings::copy_dir_recursive(&item.from, &item.to).with_context(|| {
+                format!(
+                    "Failed to copy {} to {}",
+                    item.from.display(),
+                    item.to.display()
+                )
+            })?;
+        }
+
+        Ok(MigrationOutcome {
+            backups,
+            copied: self.file_count(),
+        })
+    }
+}
+
+/// Wine user of a prefix: Proton's if present, otherwise the only real user. A
+/// prefix the client has not run in yet may have none, so Proton's is assumed.
+fn new_wine_user(prefix: &Path) -> String {
+    let users: Vec<String> = fs::read_dir(prefix.join("users"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| name != PUBLIC_WINE_USER)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    match users.as_slice() {
+        [only] => only.clone(),
+        _ => DEFAULT_WINE_USER.to_string(),
+    }
+}
+
+fn count_files(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| {
+                    let path = e.path();
+                    if path.is_dir() {
+                        count_files(&path)
+                    } else {
+                        1
+                    }
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migrate_between_wine_users() -> Result<()> {
+        let dir = tempdir()?;
+        let old = dir.path().join("old/drive_c");
+        let new = dir.path().join("new/drive_c");
+
+        let old_home = old.join("users/alice");
+        let old_profile = old_home
+            .join("Local Settings/Application Data/CCP/EVE/c_eve_tq_tranquility/settings_Default");
+        fs::create_dir_all(&old_profile)?;
+        fs::write(old_profile.join("core_char_1.dat"), "main")?;
+        fs::create_dir_all(old_home.join("AppData/Roaming/EVE Online/accounts"))?;
+        fs::write(
+            old_home.join("AppData/Roaming/EVE Online/accounts/1.json"),
+            "{}",
+        )?;
+        fs::create_dir_all(new.join("users/Public"))?;
+        let new_eve = new.join("users/steamuser/AppData/Local/CCP/EVE");
+        fs::create_dir_all(new_eve.join("c_eve_tq_tranquility/settings_Default"))?;
+        fs::write(
+            new_eve.join("c_eve_tq_tranquility/settings_Default/core_char_2.dat"),
+            "new",
+        )?;
+
+        let plan = plan_migration(&old, &new)?;
+        assert_eq!(plan.old_user, "alice");
+        assert_eq!(plan.new_user, "steamuser");
+        assert_eq!(plan.items.len(), 2);
+        assert_eq!(plan.items[0].to, new_eve);
+        assert_eq!(plan.file_count(), 2);
+
+        let outcome = plan.migrate(&mut journal::scratch(dir.path()))?;
+        assert_eq!(outcome.backups.len(), 1);
+        let profile = new_eve.join("c_eve_tq_tranquility/settings_Default");
+        assert_eq!(fs::read_to_string(profile.join("core_char_1.dat"))?, "main");
+        assert!(profile.join("core_char_2.dat").exists());
+        assert!(new
+            .join("users/steamuser/AppData/Roaming/EVE Online/accounts/1.json")
+            .exists());
+
+        assert!(plan_migration(&old, &old).is_err());
+        assert!(plan_migration(&new.join("missing"), &new).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::{Config, NameSource};
+use crate::esi::{self, Resolution};
+
+/// A source of character names. IDs a provider has no name for are left out of the
+/// resolution; only ESI reports characters as deleted.
+pub trait NameProvider: Send + Sync {
+    fn resolve(&self, ids: &[u64]) -> Result<Resolution>;
+
+    /// Finds the character with `name`, ignoring case, as its ID and the name as
+    /// the provider spells it. Providers that can't search by name find nothing.
+    fn find_character(&self, _name: &str) -> Result<Option<(u64, String)>> {
+        Ok(None)
+    }
+
+    /// Online providers are slow and rate limited, so their names are cached and
+    /// re-checked over time. Local ones are simply asked again.
+    fn is_online(&self) -> bool {
+        false
+    }
+}
+
+/// Names from an ESI server
+pub struct EsiNames {
+    endpoint: &'static str,
+    ids_endpoint: &'static str,
+}
+
+impl EsiNames {
+    pub fn tranquility() -> Self {
+        Self {
+            endpoint: esi::TRANQUILITY_NAMES_ENDPOINT,
+            ids_endpoint: esi::TRANQUILITY_IDS_ENDPOINT,
+        }
+    }
+
+    pub fn serenity() -> Self {
+        Self {
+            endpoint: esi::SERENITY_NAMES_ENDPOINT,
+            ids_endpoint: esi::SERENITY_IDS_ENDPOINT,
+        }
+    }
+}
+
+impl NameProvider for EsiNames {
+    fn resolve(&self, ids: &[u64]) -> Result<Resolution> {
+        esi::resolve_character_names_at(self.endpoint, ids)
+    }
+
+    fn find_character(&self, name: &str) -> Result<Option<(u64, String)>> {
+        esi::find_character_at(self.ids_endpoint, name)
+    }
+
+    fn is_online(&self) -> bool {
+        true
+    }
+}
+
+/// Names from a local `id,name` file, one character per line. Lines that don't
+/// start with an ID (headers, `#` comments) are skipped.
+pub struct CsvNames {
+    names: HashMap<u64, String>,
+}
+
+impl CsvNames {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read names file {}", path.display()))?;
+        Ok(Self::parse(&text))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let names = text
+            .lines()
+            .filter_map(|line| {
+                let (id, name) = line.split_once(',')?;
+                let id = id.trim().trim_matches('"').parse().ok()?;
+                let name = name.trim().trim_matches('"');
+                (!name.is_empty()).then(|| (id, name.to_string()))
+            })
+            .collect();
+        Self { names }
+    }
+}
+
+impl NameProvider for CsvNames {
+    fn resolve(&self, ids: &[u64]) -> Result<Resolution> {
+        Ok(Resolution {
+            names: ids
+                .iter()
+                .filter_map(|id| Some((*id, self.names.get(id)?.clone())))
+                .collect(),
+            not_found: Vec::new(),
+        })
+    }
+
+    fn find_character(&self, name: &str) -> Result<Option<(u64, String)>> {
+        let name = name.trim().to_lowercase();
+        Ok(self
+            .names
+            .iter()
+            .find(|(_, known)| known.to_lowercase() == name)
+            .map(|(id, known)| (*id, known.clone())))
+    }
+}
+
+/// Resolves nothing, so characters are shown by ID. Never goes online.
+pub struct IdsOnly;
+
+impl NameProvider for IdsOnly {
+    fn resolve(&self, _ids: &[u64]) -> Result<Resolution> {
+        Ok(Resolution::default())
+    }
+}
+
+/// Builds the provider selected in the config.
+pub fn provider(config: &Config) -> Result<Arc<dyn NameProvider>> {
+    Ok(match config.name_source {
+        NameSource::Esi => Arc::new(EsiNames::tranquility()),
+        NameSource::Serenity => Arc::new(EsiNames::serenity()),
+        NameSource::Csv => {
+            let Some(path) = &config.names_csv else {
+                bail!("No names file chosen");
+            };
+            Arc::new(CsvNames::load(Path::new(path))?)
+        }
+        NameSource::IdsOnly => Arc::new(IdsOnly),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_names() -> Result<()> {
+        let csv = CsvNames::parse(
+            "id,name\n# alts\n90000001,Main Pilot\n\"90000002\",\"Hauler Alt\"\nbroken line\n90000003,\n",
+        );
+        let resolution = csv.resolve(&[90000001, 90000002, 90000003, 90000004])?;
+        assert_eq!(resolution.names.len(), 2);
+        assert_eq!(resolution.names[&90000001], "Main Pilot");
+        assert_eq!(resolution.names[&90000002], "Hauler Alt");
+        assert!(resolution.not_found.is_empty());
+        assert!(!csv.is_online());
+        assert_eq!(
+            csv.find_character(" hauler alt")?,
+            Some((90000002, "Hauler Alt".to_string()))
+        );
+        assert_eq!(csv.find_character("Nobody")?, None);
+        Ok(())
+    }
+}
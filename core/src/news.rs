@@ -0,0 +1,131 @@
+//! News feed for the About screen: release notes and known issues, such as an EVE
+//! patch that changed a settings format. The feed is a small JSON file:
+//!
+//! ```json
+//! {"items": [
+//!   {"title": "v0.2 released", "date": "2026-09-01", "kind": "release",
+//!    "url": "https://github.com/sopleb/PackPreferences/releases/tag/v0.2"},
+//!   {"title": "Overview settings moved after the September patch",
+//!    "kind": "known_issue"}
+//! ]}
+//! ```
+//!
+//! The last feed fetched is kept in the state directory and shown while offline.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::Config;
+
+pub const FEED_URL: &str =
+    "https://raw.githubusercontent.com/sopleb/PackPreferences/main/news.json";
+
+/// Last feed fetched, in the state directory
+const CACHE_FILE: &str = "news.json";
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewsKind {
+    Release,
+    KnownIssue,
+    /// Anything else, including kinds added after this version
+    #[default]
+    #[serde(other)]
+    Notice,
+}
+
+impl NewsKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            NewsKind::Release => "Release",
+            NewsKind::KnownIssue => "Known issue",
+            NewsKind::Notice => "Notice",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewsItem {
+    pub title: String,
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub kind: NewsKind,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// Feed items, newest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NewsFeed {
+    #[serde(default)]
+    pub items: Vec<NewsItem>,
+}
+
+pub fn parse_feed(text: &str) -> Result<NewsFeed> {
+    serde_json::from_str(text).context("News feed is not valid JSON")
+}
+
+fn cache_path() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join(CACHE_FILE))
+}
+
+/// Downloads and parses the feed, caching it for offline use. Blocks until the
+/// server answers.
+pub fn fetch(url: &str) -> Result<NewsFeed> {
+    let response = reqwest::blocking::Client::new()
+        .get(url)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .context("Failed to reach the news feed")?;
+    if !response.status().is_success() {
+        bail!("News feed answered {}", response.status());
+    }
+    let text = response.text().context("Failed to read the news feed")?;
+    let feed = parse_feed(&text)?;
+    let path = cache_path()?;
+    fs::write(&path, &text).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(feed)
+}
+
+/// The last feed fetched, if there is one.
+pub fn load_cached() -> Result<Option<NewsFeed>> {
+    let path = cache_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_feed(&text).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feed() -> Result<()> {
+        let feed = parse_feed(
+            r#"{"items": [
+                {"title": "v0.2 released", "date": "2026-09-01", "kind": "release",
+                 "url": "https://example.com/v0.2"},
+                {"title": "Overview settings moved", "kind": "known_issue"},
+                {"title": "Survey", "kind": "poll"}
+            ]}"#,
+        )?;
+        assert_eq!(feed.items.len(), 3);
+        assert_eq!(feed.items[0].kind, NewsKind::Release);
+        assert_eq!(feed.items[0].date.as_deref(), Some("2026-09-01"));
+        assert_eq!(feed.items[1].kind, NewsKind::KnownIssue);
+        assert_eq!(feed.items[1].url, None);
+        assert_eq!(feed.items[2].kind, NewsKind::Notice);
+        assert!(parse_feed("{}")?.items.is_empty());
+        assert!(parse_feed("<html>").is_err());
+        Ok(())
+    }
+}
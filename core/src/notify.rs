@@ -0,0 +1,200 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::state;
+
+/// Shown as the sender where the chat service allows it
+const SENDER_NAME: &str = "Pack Preferences";
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Public ntfy server, used unless a self-hosted one is configured
+pub const DEFAULT_NTFY_SERVER: &str = "https://ntfy.sh";
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    /// Files were synced, manually or by a scheduled preset
+    Sync,
+    Backup,
+    /// A sync, backup or scheduled preset failed
+    Failure,
+}
+
+/// A chat webhook that is told about syncs, backups and failures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default = "default_true")]
+    pub on_sync: bool,
+    /// Off by default: a sync backs up every target profile, one message each
+    #[serde(default)]
+    pub on_backup: bool,
+    #[serde(default = "default_true")]
+    pub on_failure: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Webhook {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            on_sync: true,
+            on_backup: false,
+            on_failure: true,
+        }
+    }
+
+    pub fn wants(&self, event: NotifyEvent) -> bool {
+        match event {
+            NotifyEvent::Sync => self.on_sync,
+            NotifyEvent::Backup => self.on_backup,
+            NotifyEvent::Failure => self.on_failure,
+        }
+    }
+
+    /// Posts `message` to the webhook. Blocks until the service answers.
+    pub fn send(&self, message: &str) -> Result<()> {
+        let response = reqwest::blocking::Client::new()
+            .post(&self.url)
+            .timeout(SEND_TIMEOUT)
+            .json(&webhook_payload(message))
+            .send()
+            .context("Failed to reach the webhook")?;
+        if !response.status().is_success() {
+            bail!("Webhook answered {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// An ntfy topic that gets a push notification when a scheduled preset finishes
+/// or fails.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NtfyTopic {
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    pub topic: String,
+    /// Access token for a protected topic
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default = "default_true")]
+    pub on_success: bool,
+    #[serde(default = "default_true")]
+    pub on_failure: bool,
+}
+
+fn default_ntfy_server() -> String {
+    DEFAULT_NTFY_SERVER.to_string()
+}
+
+impl NtfyTopic {
+    pub fn new(topic: String) -> Self {
+        Self {
+            server: default_ntfy_server(),
+            topic,
+            token: None,
+            on_success: true,
+            on_failure: true,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        format!(
+            "{}/{}",
+            self.server.trim_end_matches('/'),
+            self.topic.trim_matches('/')
+        )
+    }
+
+    pub fn wants(&self, failed: bool) -> bool {
+        if failed {
+            self.on_failure
+        } else {
+            self.on_success
+        }
+    }
+
+    /// Publishes `message` to the topic, at high priority if it reports a failure.
+    /// Blocks until the server answers.
+    pub fn send(&self, title: &str, message: &str, failed: bool) -> Result<()> {
+        let mut request = reqwest::blocking::Client::new()
+            .post(self.url())
+            .timeout(SEND_TIMEOUT)
+            .header("Title", title)
+            .header(
+                "Tags",
+                if failed {
+                    "warning"
+                } else {
+                    "white_check_mark"
+                },
+            )
+            .header("Priority", if failed { "high" } else { "default" })
+            .body(format!("{}: {}", state::current_user(), message));
+        if let Some(token) = self.token.as_deref().filter(|t| !t.is_empty()) {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().context("Failed to reach the ntfy server")?;
+        if !response.status().is_success() {
+            bail!("ntfy server answered {}", response.status());
+        }
+        Ok(())
+    }
+}
+
+/// Body understood by both Discord (`content`) and Slack (`text`) incoming
+/// webhooks; each ignores the other's field. The OS account is named so a shared
+/// channel can tell whose machine a message came from.
+pub fn webhook_payload(message: &str) -> serde_json::Value {
+    let text = format!("{}: {}", state::current_user(), message);
+    serde_json::json!({
+        "username": SENDER_NAME,
+        "content": text,
+        "text": text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_config_and_payload() {
+        let webhook: Webhook = toml::from_str("url = \"https://example.invalid/hook\"").unwrap();
+        assert_eq!(
+            webhook,
+            Webhook::new("https://example.invalid/hook".to_string())
+        );
+        assert!(webhook.wants(NotifyEvent::Sync));
+        assert!(!webhook.wants(NotifyEvent::Backup));
+        assert!(webhook.wants(NotifyEvent::Failure));
+
+        let payload = webhook_payload("Synced 3 files");
+        assert_eq!(payload["content"], payload["text"]);
+        assert!(payload["text"]
+            .as_str()
+            .is_some_and(|text| text.ends_with(": Synced 3 files")));
+    }
+
+    #[test]
+    fn test_ntfy_config() {
+        let topic: NtfyTopic = toml::from_str("topic = \"corp-ops\"").unwrap();
+        assert_eq!(topic, NtfyTopic::new("corp-ops".to_string()));
+        assert_eq!(topic.url(), "https://ntfy.sh/corp-ops");
+        assert!(topic.wants(true) && topic.wants(false));
+
+        let hosted = NtfyTopic {
+            server: "https://ntfy.example.invalid/".to_string(),
+            on_success: false,
+            ..topic
+        };
+        assert_eq!(hosted.url(), "https://ntfy.example.invalid/corp-ops");
+        assert!(!hosted.wants(false));
+    }
+}
@@ -0,0 +1,328 @@
+//! Sync plugins: executables in the plugins folder that may rewrite a settings file
+//! on its way to each target, e.g. to force a chat font size or strip channel
+//! passwords.
+//!
+//! A plugin receives the source file's bytes on stdin and must write the bytes to
+//! save to the target on stdout, exiting with status 0. It is told what it is
+//! working on through environment variables:
+//! - `PACK_PREFERENCES_SOURCE` and `PACK_PREFERENCES_TARGET`: the two file paths
+//! - `PACK_PREFERENCES_FILE_TYPE`: `character`, `user` or `launcher`
+//! - `PACK_PREFERENCES_TARGET_ID`: character or account ID of the target, empty for
+//!   a default file
+//!
+//! A plugin that has nothing to change echoes its input. Empty output, a non-zero
+//! exit status, a crash or running longer than [`PLUGIN_TIMEOUT`] fails that
+//! target, which is then left untouched. So does output for a client settings file
+//! that no longer decodes as one.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::discovery::{CharacterFile, FileType};
+use crate::marshal;
+
+/// Longest a plugin may run on one file before it is killed. The target profiles
+/// stay locked while it runs.
+pub const PLUGIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a running plugin is checked on
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A sync plugin found in the plugins folder
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plugin {
+    /// File name, which is also how presets refer to the plugin
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Folder plugins are loaded from, next to the config file.
+pub fn plugins_dir() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("plugins"))
+}
+
+/// Executable files in `dir`, sorted by name. A missing folder has no plugins.
+pub fn discover_plugins(dir: &Path) -> Vec<Plugin> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut plugins: Vec<Plugin> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| is_executable(path))
+        .map(|path| Plugin {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            path,
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// Picks the named plugins out of `available`, in the order given. A name with no
+/// plugin is an error, so a sync never silently runs without a plugin it expects.
+pub fn select(available: &[Plugin], names: &[String]) -> Result<Vec<Plugin>> {
+    names
+        .iter()
+        .map(|name| {
+            available
+                .iter()
+                .find(|p| &p.name == name)
+                .cloned()
+                .with_context(|| format!("Plugin '{}' is not in the plugins folder", name))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+impl Plugin {
+    /// Runs the plugin on `data`, the current contents headed for `target`.
+    pub fn transform(
+        &self,
+        data: &[u8],
+        source: &CharacterFile,
+        target: &CharacterFile,
+    ) -> Result<Vec<u8>> {
+        self.transform_within(data, source, target, PLUGIN_TIMEOUT)
+    }
+
+    fn transform_within(
+        &self,
+        data: &[u8],
+        source: &CharacterFile,
+        target: &CharacterFile,
+        timeout: Duration,
+    ) -> Result<Vec<u8>> {
+        let file_type = match target.file_type {
+            FileType::Character => "character",
+            FileType::User => "user",
+            FileType::Launcher => "launcher",
+        };
+        let target_id = if target.is_default {
+            String::new()
+        } else {
+            target.character_id.to_string()
+        };
+
+        let mut child = Command::new(&self.path)
+            .env("PACK_PREFERENCES_SOURCE", &source.path)
+            .env("PACK_PREFERENCES_TARGET", &target.path)
+            .env("PACK_PREFERENCES_FILE_TYPE", file_type)
+            .env("PACK_PREFERENCES_TARGET_ID", target_id)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to start plugin {}", self.name))?;
+
+        // Feed stdin from another thread so a plugin that writes before it has read
+        // everything cannot deadlock against a full pipe
+        let mut stdin = child.stdin.take().context("Plugin has no stdin")?;
+        let input = data.to_vec();
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            let status = child
+                .try_wait()
+                .with_context(|| format!("Plugin {} did not finish", self.name))?;
+            if let Some(status) = status {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                bail!(
+                    "Plugin {} was stopped after running for {} s",
+                    self.name,
+                    timeout.as_secs()
+                );
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        // A plugin may exit without reading its input, closing the pipe early
+        let _ = writer.join();
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        if !status.success() {
+            bail!(
+                "Plugin {} failed ({}): {}",
+                self.name,
+                status,
+                String::from_utf8_lossy(&stderr).trim()
+            );
+        }
+        if stdout.is_empty() {
+            bail!("Plugin {} produced no output", self.name);
+        }
+        // Client settings must still read back; launcher files are JSON
+        if target.file_type != FileType::Launcher {
+            marshal::decode(&stdout).with_context(|| {
+                format!(
+                    "Plugin {} produced output that is not a settings file",
+                    self.name
+                )
+            })?;
+        }
+        Ok(stdout)
+    }
+}
+
+/// Reads a child's output pipe to the end on another thread, so neither pipe can
+/// fill up and stall the child while the other is being waited on.
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// Passes the source file through each plugin in turn and returns what should be
+/// written to `target`.
+pub fn apply(
+    plugins: &[Plugin],
+    source: &CharacterFile,
+    target: &CharacterFile,
+) -> Result<Vec<u8>> {
+    let mut data = fs::read(&source.path)
+        .with_context(|| format!("Failed to read {}", source.path.display()))?;
+    for plugin in plugins {
+        data = plugin.transform(&data, source, target)?;
+    }
+    Ok(data)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::discovery;
+    use crate::settings;
+    use std::collections::HashMap;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    fn write_plugin(dir: &Path, name: &str, script: &str) -> Result<()> {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", script))?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_and_apply_plugins() -> Result<()> {
+        let dir = tempdir()?;
+        let plugins_dir = dir.path().join("plugins");
+        fs::create_dir(&plugins_dir)?;
+        write_plugin(&plugins_dir, "large", "sed s/small/large/")?;
+        write_plugin(
+            &plugins_dir,
+            "only-2",
+            "[ \"$PACK_PREFERENCES_TARGET_ID\" = 2 ] && cat",
+        )?;
+        write_plugin(&plugins_dir, "broken", "echo oops >&2; exit 3")?;
+        write_plugin(&plugins_dir, "garbage", "cat; printf trailing")?;
+        fs::write(plugins_dir.join("README.txt"), "not a plugin")?;
+
+        let available = discover_plugins(&plugins_dir);
+        let names: Vec<&str> = available.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["broken", "garbage", "large", "only-2"]);
+        assert!(select(&available, &["missing".to_string()]).is_err());
+
+        let settings = |font: &[u8]| {
+            marshal::encode(&marshal::Value::Dict(vec![(
+                marshal::Value::Bytes(b"font".to_vec()),
+                marshal::Value::Bytes(font.to_vec()),
+            )]))
+        };
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        fs::write(profile.join("core_char_1.dat"), settings(b"small"))?;
+        fs::write(profile.join("core_char_2.dat"), "")?;
+        let source = discovery::settings_file(profile.join("core_char_1.dat")).unwrap();
+        let target = discovery::settings_file(profile.join("core_char_2.dat")).unwrap();
+
+        let chain = select(&available, &["large".to_string(), "only-2".to_string()])?;
+        assert_eq!(apply(&chain, &source, &target)?, settings(b"large"));
+
+        let broken = select(&available, &["broken".to_string()])?;
+        let err = apply(&broken, &source, &target).unwrap_err();
+        assert!(err.to_string().contains("oops"));
+
+        let garbage = select(&available, &["garbage".to_string()])?;
+        let err = apply(&garbage, &source, &target).unwrap_err();
+        assert!(err.to_string().contains("not a settings file"));
+
+        let sync = settings::sync_settings_transformed(
+            &source,
+            &[&target],
+            false,
+            &chain,
+            None,
+            |_, _| {},
+        )?;
+        assert!(sync.results[0].success);
+        assert_eq!(fs::read(&target.path)?, settings(b"large"));
+        let verification = settings::verify_transformed_sync(&sync, &HashMap::new());
+        assert!(verification[0].matches_source);
+
+        let failed = settings::sync_settings_transformed(
+            &source,
+            &[&target],
+            false,
+            &broken,
+            None,
+            |_, _| {},
+        )?;
+        assert!(!failed.results[0].success);
+        assert_eq!(fs::read(&target.path)?, settings(b"large"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_plugin_timeout() -> Result<()> {
+        let dir = tempdir()?;
+        write_plugin(dir.path(), "hang", "exec sleep 30")?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        fs::write(profile.join("core_char_1.dat"), "")?;
+        let file = discovery::settings_file(profile.join("core_char_1.dat")).unwrap();
+        let plugin = discover_plugins(dir.path()).remove(0);
+
+        let started = Instant::now();
+        let err = plugin
+            .transform_within(b"x", &file, &file, Duration::from_millis(200))
+            .unwrap_err();
+        assert!(err.to_string().contains("was stopped"));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+//! Pre-flight checks before a sync writes anything: no process holds a target file
+//! open, every target profile can be written to, and the disk has room for the
+//! backups and the new files. Shown as a checklist in the sync confirmation, and
+//! enforced by [`guard`] where there is no confirmation to show it in.
+
+use anyhow::{bail, Result};
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use crate::discovery;
+use crate::elevate;
+use crate::process;
+use crate::troubleshoot::Outcome;
+
+/// Left free on top of what the sync needs, so a disk is never filled completely
+const SPACE_MARGIN: u64 = 1 << 20;
+
+/// Title of the check that every target profile can be written to
+const WRITABLE: &str = "Target profiles are writable";
+
+/// Open files and failures listed by name before the rest are summed up
+const MAX_LISTED: usize = 5;
+
+/// Result of one pre-flight check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub title: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn passed(&self) -> bool {
+        self.outcome != Outcome::Failed
+    }
+}
+
+/// Whether the only failures are profiles that can't be written to, which an
+/// elevated sync can get past; every other check still has to pass.
+pub fn only_unwritable(checks: &[Check]) -> bool {
+    let mut failed = checks.iter().filter(|c| !c.passed()).peekable();
+    failed.peek().is_some() && failed.all(|c| c.title == WRITABLE)
+}
+
+/// Runs every check for a sync of `source` over `targets`; `backup` says whether
+/// the target profiles are backed up first.
+pub fn run(source: &Path, targets: &[PathBuf], backup: bool) -> Vec<Check> {
+    let mut dirs: Vec<&Path> = targets.iter().filter_map(|t| t.parent()).collect();
+    dirs.sort();
+    dirs.dedup();
+    vec![
+        check_open_files(targets),
+        check_writable(&dirs),
+        check_space(source, targets, &dirs, backup),
+    ]
+}
+
+/// Refuses a sync the confirmation would not let through: one into the prefix of
+/// a running client, one of `running`, which writes its settings back when it
+/// exits, or one that fails a check.
+pub fn guard(source: &Path, targets: &[PathBuf], running: &[PathBuf]) -> Result<()> {
+    let at_risk = process::files_in_prefixes(targets, running);
+    if !at_risk.is_empty() {
+        bail!(
+            "An EVE client is running in the prefix of {} target(s); it would undo the \
+             sync when it exits",
+            at_risk.len()
+        );
+    }
+    if let Some(failed) = run(source, targets, true).into_iter().find(|c| !c.passed()) {
+        bail!(
+            "Pre-flight check failed: {}\n{}",
+            failed.title,
+            failed.detail
+        );
+    }
+    Ok(())
+}
+
+fn check_open_files(targets: &[PathBuf]) -> Check {
+    let title = "No target file is open";
+    if !cfg!(target_os = "linux") {
+        return Check {
+            title,
+            outcome: Outcome::Skipped,
+            detail: "Open files can only be checked on Linux".to_string(),
+        };
+    }
+
+    let open = process::processes_with_open(targets);
+    if open.is_empty() {
+        return Check {
+            title,
+            outcome: Outcome::Passed,
+            detail: format!("No process has any of the {} target(s) open", targets.len()),
+        };
+    }
+    let mut lines: Vec<String> = open
+        .iter()
+        .take(MAX_LISTED)
+        .map(|o| {
+            format!(
+                "{} (pid {}) has {} open",
+                o.command,
+                o.pid,
+                o.path.file_name().unwrap_or_default().to_string_lossy()
+            )
+        })
+        .collect();
+    if open.len() > MAX_LISTED {
+        lines.push(format!("and {} more", open.len() - MAX_LISTED));
+    }
+    Check {
+        title,
+        outcome: Outcome::Failed,
+        detail: lines.join("\n"),
+    }
+}
+
+fn check_writable(dirs: &[&Path]) -> Check {
+    let failures: Vec<String> = dirs
+        .iter()
+        .filter_map(|dir| {
+            let probe = dir.join(format!(
+                ".pack-preferences-preflight-{}",
+                std::process::id()
+            ));
+            let result = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&probe)
+                .and_then(|_| fs::remove_file(&probe));
+            result.err().map(|e| {
+                let label = discovery::profile_label(dir);
+                match elevate::owner_of(dir).filter(|&o| Some(o) != elevate::current_uid()) {
+                    Some(owner) => {
+                        format!("{}: owned by another user (uid {}); {}", label, owner, e)
+                    }
+                    None => format!("{}: {}", label, e),
+                }
+            })
+        })
+        .collect();
+    Check {
+        title: WRITABLE,
+        outcome: if failures.is_empty() {
+            Outcome::Passed
+        } else {
+            Outcome::Failed
+        },
+        detail: if failures.is_empty() {
+            format!("Wrote a test file in {} profile(s)", dirs.len())
+        } else {
+            failures.join("\n")
+        },
+    }
+}
+
+fn check_space(source: &Path, targets: &[PathBuf], dirs: &[&Path], backup: bool) -> Check {
+    let title = "Enough free disk space";
+    let source_len = fs::metadata(source).map(|m| m.len()).unwrap_or_default();
+    let mut needed = source_len * targets.len() as u64 + SPACE_MARGIN;
+    if backup {
+        needed += dirs.iter().map(|dir| dir_size(dir)).sum::<u64>();
+    }
+
+    let mut short = Vec::new();
+    for dir in dirs {
+        let Some(available) = process::available_space(dir) else {
+            return Check {
+                title,
+                outcome: Outcome::Skipped,
+                detail: format!(
+                    "Free space in {} could not be read",
+                    discovery::profile_label(dir)
+                ),
+            };
+        };
+        if available < needed {
+            short.push(format!(
+                "{}: {} free, {} needed",
+                discovery::profile_label(dir),
+                format_size(available),
+                format_size(needed)
+            ));
+        }
+    }
+    Check {
+        title,
+        outcome: if short.is_empty() {
+            Outcome::Passed
+        } else {
+            Outcome::Failed
+        },
+        detail: if short.is_empty() {
+            format!("About {} needed", format_size(needed))
+        } else {
+            short.join("\n")
+        },
+    }
+}
+
+/// Total size of the files under `dir`
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1 << 30 => format!("{:.1} GiB", b as f64 / (1u64 << 30) as f64),
+        b if b >= 1 << 20 => format!("{:.1} MiB", b as f64 / (1u64 << 20) as f64),
+        b => format!("{} KiB", b.div_ceil(1 << 10)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_preflight() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        let source = settings_dir.join("core_char_1.dat");
+        let target = settings_dir.join("core_char_2.dat");
+        fs::write(&source, "new")?;
+        fs::write(&target, "old")?;
+        let targets = vec![target.clone()];
+
+        let checks = run(&source, &targets, true);
+        assert_eq!(checks.len(), 3);
+        assert!(checks.iter().all(Check::passed), "{:?}", checks);
+        assert!(!only_unwritable(&checks));
+        assert_eq!(fs::read_dir(&settings_dir)?.count(), 2);
+
+        // Without a confirmation, a client running in the prefix refuses the sync
+        guard(&source, &targets, &[])?;
+        assert!(guard(&source, &targets, &[dir.path().to_path_buf()]).is_err());
+
+        // Holding a target open fails the first check
+        #[cfg(target_os = "linux")]
+        {
+            let _open = fs::File::open(&target)?;
+            let check = &run(&source, &targets, true)[0];
+            assert_eq!(check.outcome, Outcome::Failed);
+            assert!(check.detail.contains("core_char_2.dat"));
+        }
+
+        let failed = |title| Check {
+            title,
+            outcome: Outcome::Failed,
+            detail: String::new(),
+        };
+        let mut checks = checks;
+        checks[1] = failed(WRITABLE);
+        assert!(only_unwritable(&checks));
+        checks[0] = failed("No target file is open");
+        assert!(!only_unwritable(&checks));
+
+        assert_eq!(format_size(1536), "2 KiB");
+        assert_eq!(format_size(3 << 20), "3.0 MiB");
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::discovery::{self, CharacterFile};
+use crate::journal;
+use crate::plugins;
+use crate::process;
+use crate::settings::{self, BackupOptions, BackupTrigger, SyncResult};
+
+/// A saved source and set of targets that can be synced again in one step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncPreset {
+    pub name: String,
+    pub source: PathBuf,
+    pub targets: Vec<PathBuf>,
+
+    /// When to run the preset unattended, if at all
+    #[serde(default)]
+    pub schedule: Option<Schedule>,
+
+    /// When the schedule last fired or was switched on, RFC 3339
+    #[serde(default)]
+    pub last_run: Option<String>,
+
+    /// Names of the plugins the source passes through, in order
+    #[serde(default)]
+    pub plugins: Vec<String>,
+}
+
+/// Weekly or daily time of day at which a preset runs.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Day of the week; `None` runs every day
+    #[serde(default)]
+    pub weekday: Option<Weekday>,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            weekday: Some(Weekday::Sun),
+            hour: 12,
+            minute: 0,
+        }
+    }
+}
+
+impl Schedule {
+    pub fn describe(&self) -> String {
+        let day = match self.weekday {
+            Some(day) => format!("every {}", weekday_name(day)),
+            None => "daily".to_string(),
+        };
+        format!("{} at {:02}:{:02}", day, self.hour, self.minute)
+    }
+
+    /// Most recent scheduled time at or before `now`.
+    pub fn last_occurrence(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let time =
+            NaiveTime::from_hms_opt(self.hour.min(23), self.minute.min(59), 0).unwrap_or_default();
+        let mut candidate = now.date().and_time(time);
+        if candidate > now {
+            candidate -= Duration::days(1);
+        }
+        if let Some(day) = self.weekday {
+            while candidate.weekday() != day {
+                candidate -= Duration::days(1);
+            }
+        }
+        candidate
+    }
+
+    /// Returns true if an occurrence has passed since `last_run`. A preset that has
+    /// never run is not due until its first occurrence after it was scheduled.
+    pub fn is_due(&self, last_run: Option<NaiveDateTime>, now: NaiveDateTime) -> bool {
+        last_run.is_some_and(|last| self.last_occurrence(now) > last)
+    }
+}
+
+pub fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+impl SyncPreset {
+    /// Parsed `last_run`, in local time.
+    pub fn last_run_time(&self) -> Option<NaiveDateTime> {
+        let last = DateTime::parse_from_rfc3339(self.last_run.as_deref()?).ok()?;
+        Some(last.with_timezone(&Local).naive_local())
+    }
+
+    /// Returns true if the preset is scheduled and an occurrence is pending.
+    pub fn is_due(&self, now: NaiveDateTime) -> bool {
+        self.schedule
+            .is_some_and(|schedule| schedule.is_due(self.last_run_time(), now))
+    }
+
+    /// Marks the schedule as having fired now; nothing earlier is still due.
+    pub fn mark_run(&mut self) {
+        self.last_run = Some(Local::now().to_rfc3339());
+    }
+
+    /// Checks that the preset still applies to files on disk and resolves them.
+    /// Nothing is written.
+    pub fn resolve(&self) -> Result<(CharacterFile, Vec<CharacterFile>)> {
+        let source = discovery::settings_file(self.source.clone())
+            .with_context(|| format!("Source is not a settings file: {}", self.source.display()))?;
+        if !source.path.exists() {
+            bail!("Source no longer exists: {}", source.path.display());
+        }
+
+        let mut targets = Vec::new();
+        for path in &self.targets {
+            let target = discovery::settings_file(path.clone())
+                .with_context(|| format!("Target is not a settings file: {}", path.display()))?;
+            if !target.path.exists() {
+                bail!("Target no longer exists: {}", path.display());
+            }
+            if target.file_type != source.file_type {
+                bail!("Target is a different file type: {}", path.display());
+            }
+            if target.path == source.path {
+                bail!("Target is the source: {}", path.display());
+            }
+            targets.push(target);
+        }
+        if targets.is_empty() {
+            bail!("Preset has no targets");
+        }
+        Ok((source, targets))
+    }
+}
+
+/// Outcome of an unattended preset run
+#[derive(Debug)]
+pub enum PresetRun {
+    /// An EVE client was running, so nothing was touched; try again later
+    Deferred,
+    Synced {
+        backups: Vec<PathBuf>,
+        results: Vec<SyncResult>,
+    },
+}
+
+/// Runs a preset unattended: defers while any EVE client is running, performs a dry
+/// run first and only writes if every target passes, backing up each profile before
+/// with the backups tagged `trigger` and named and pruned by `backup_options`.
+pub fn run_preset(
+    preset: &SyncPreset,
+    trigger: BackupTrigger,
+    backup_options: &BackupOptions,
+) -> Result<PresetRun> {
+    if !process::detect_eve_prefixes()?.is_empty() {
+        return Ok(PresetRun::Deferred);
+    }
+
+    let (source, targets) = preset.resolve()?;
+    let targets: Vec<&CharacterFile> = targets.iter().collect();
+    let plugins = if preset.plugins.is_empty() {
+        Vec::new()
+    } else {
+        plugins::select(
+            &plugins::discover_plugins(&plugins::plugins_dir()?),
+            &preset.plugins,
+        )?
+    };
+
+    let dry_run =
+        settings::sync_settings_transformed(&source, &targets, true, &plugins, None, |_, _| {})?
+            .results;
+    if let Some(failed) = dry_run.iter().find(|r| !r.success) {
+        bail!("Dry run failed: {}", failed.message);
+    }
+
+    let synced = journal::sync(
+        &source,
+        &targets,
+        &plugins,
+        trigger,
+        backup_options,
+        |_, _, _| {},
+        |_, _| {},
+    )?;
+    let mut backups: Vec<PathBuf> = synced.backups.into_values().collect();
+    backups.sort();
+    Ok(PresetRun::Synced {
+        backups,
+        results: synced.sync.results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-09-01 was a Sunday
+        NaiveDate::from_ymd_opt(2024, 9, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_weekly_schedule() {
+        let schedule = Schedule::default();
+        assert_eq!(schedule.last_occurrence(at(4, 9, 0)), at(1, 12, 0));
+        assert_eq!(schedule.last_occurrence(at(8, 12, 0)), at(8, 12, 0));
+        assert_eq!(schedule.last_occurrence(at(8, 11, 59)), at(1, 12, 0));
+
+        assert!(!schedule.is_due(None, at(8, 13, 0)));
+        assert!(schedule.is_due(Some(at(2, 0, 0)), at(8, 13, 0)));
+        assert!(!schedule.is_due(Some(at(8, 12, 30)), at(8, 13, 0)));
+    }
+
+    #[test]
+    fn test_daily_schedule() {
+        let schedule = Schedule {
+            weekday: None,
+            hour: 6,
+            minute: 30,
+        };
+        assert_eq!(schedule.last_occurrence(at(4, 6, 0)), at(3, 6, 30));
+        assert!(schedule.is_due(Some(at(3, 7, 0)), at(4, 6, 30)));
+        assert_eq!(schedule.describe(), "daily at 06:30");
+    }
+
+    #[test]
+    fn test_preset_toml_round_trip() -> Result<()> {
+        let preset = SyncPreset {
+            name: "weekly push".to_string(),
+            source: PathBuf::from("/eve/settings_Default/core_char_1.dat"),
+            targets: vec![PathBuf::from("/eve/settings_Default/core_char_2.dat")],
+            schedule: Some(Schedule::default()),
+            last_run: None,
+            plugins: vec!["strip-passwords".to_string()],
+        };
+        let text = toml::to_string(&preset)?;
+        assert_eq!(toml::from_str::<SyncPreset>(&text)?, preset);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_rejects_mismatched_targets() -> Result<()> {
+        let dir = tempdir()?;
+        for name in ["core_char_1.dat", "core_char_2.dat", "core_user_3.dat"] {
+            fs::write(dir.path().join(name), b"x")?;
+        }
+        let mut preset = SyncPreset {
+            name: "weekly push".to_string(),
+            source: dir.path().join("core_char_1.dat"),
+            targets: vec![dir.path().join("core_char_2.dat")],
+            schedule: None,
+            last_run: None,
+            plugins: Vec::new(),
+        };
+        assert_eq!(preset.resolve()?.1.len(), 1);
+
+        preset.targets.push(dir.path().join("core_user_3.dat"));
+        assert!(preset.resolve().is_err());
+
+        preset.targets = vec![dir.path().join("core_char_9.dat")];
+        assert!(preset.resolve().is_err());
+        Ok(())
+    }
+}
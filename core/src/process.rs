@@ -0,0 +1,1357 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::{self, Server};
+
+/// Known install layouts: EVE settings base directories relative to a Wine user's
+/// home. New launcher paths or renamed CCP directories only need a new entry here.
+const EVE_BASE_LAYOUTS: &[&[&str]] = &[
+    &["AppData", "Local", "CCP", "EVE"],
+    // Older Wine prefixes expose the XP-style path instead of AppData
+    &["Local Settings", "Application Data", "CCP", "EVE"],
+];
+
+/// Where the Mac client keeps its settings, under its `p_drive`
+const MAC_EVE_BASE_LAYOUTS: &[&[&str]] = &[&["User", "My Documents", "EVE"]];
+
+/// The Mac client's stand-in for a Windows drive, under Application Support
+const MAC_CLIENT_DRIVE: &[&str] = &["EVE Online", "p_drive"];
+
+/// Wine user Proton runs the client as. Plain Wine, Lutris and Bottles prefixes
+/// name the user after the desktop login instead.
+pub const STEAM_WINE_USER: &str = "steamuser";
+
+/// Windows' shared profile, which never holds EVE settings
+const PUBLIC_WINE_USER: &str = "Public";
+
+/// Launcher settings locations relative to the Wine user's AppData directory.
+const LAUNCHER_SUBDIRS: &[&[&str]] = &[
+    &["Roaming", "EVE Online", "accounts"],
+    &["Local", "CCP", "EVE", "launcher", "accounts"],
+];
+
+/// Filesystem types that reach storage over the network. Any `fuse.*` filesystem
+/// counts too, since sshfs, rclone and the like sit behind FUSE; `fuseblk` is a
+/// local disk and doesn't match.
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "davfs",
+    "fuse",
+];
+
+/// Steam app ID of EVE Online, which names its Proton prefix under `compatdata`
+pub const EVE_STEAM_APP_ID: u32 = 8500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedPrefix {
+    pub path: PathBuf,
+    pub source: PrefixSource,
+}
+
+/// Where a prefix was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrefixSource {
+    /// Command line of a running client
+    Process,
+    /// Command line of a running launcher, which is often up before any client
+    Launcher,
+    /// Lutris game configuration, named by its file stem
+    Lutris(String),
+    /// Bottles bottle, by name
+    Bottles(String),
+    /// CodeWeavers CrossOver bottle, by name
+    CrossOver(String),
+    /// A well-known prefix location that holds EVE settings
+    Common,
+}
+
+impl DetectedPrefix {
+    /// Short description for prefix pickers
+    pub fn label(&self) -> String {
+        match &self.source {
+            PrefixSource::Process => format!("Running: {}", self.path.display()),
+            PrefixSource::Launcher => format!("Launcher: {}", self.path.display()),
+            PrefixSource::Lutris(game) => format!("Lutris ({}): {}", game, self.path.display()),
+            PrefixSource::Bottles(bottle) => {
+                format!("Bottles ({}): {}", bottle, self.path.display())
+            }
+            PrefixSource::CrossOver(bottle) => {
+                format!("CrossOver ({}): {}", bottle, self.path.display())
+            }
+            PrefixSource::Common => format!("Found: {}", self.path.display()),
+        }
+    }
+}
+
+/// On native Windows and macOS there is no Wine prefix: the user's own home, or
+/// the Mac client's `p_drive`, holds the settings and stands in for one. None on
+/// Linux.
+pub fn native_root() -> Option<PathBuf> {
+    if cfg!(windows) {
+        dirs::home_dir()
+    } else if cfg!(target_os = "macos") {
+        dirs::data_dir().map(|dir| MAC_CLIENT_DRIVE.iter().fold(dir, |p, part| p.join(part)))
+    } else {
+        None
+    }
+}
+
+/// Settings layouts to look for under each home.
+fn base_layouts() -> &'static [&'static [&'static str]] {
+    if cfg!(target_os = "macos") {
+        MAC_EVE_BASE_LAYOUTS
+    } else {
+        EVE_BASE_LAYOUTS
+    }
+}
+
+/// Scans /proc for running EVE Online processes and extracts Wine prefixes. On
+/// native Windows and macOS a running client means the user's own settings.
+pub fn detect_eve_prefixes() -> Result<Vec<DetectedPrefix>> {
+    if let Some(root) = native_root() {
+        let running = (native_eve_processes() > 0).then_some(DetectedPrefix {
+            path: root,
+            source: PrefixSource::Process,
+        });
+        return Ok(running.into_iter().collect());
+    }
+    proc_prefixes(is_eve_cmdline, PrefixSource::Process)
+}
+
+/// Scans /proc for running EVE launchers and extracts their Wine prefixes, so the
+/// prefix is known before a client is started. Natively the launcher adds nothing
+/// to [`native_root`].
+pub fn detect_launcher_prefixes() -> Vec<DetectedPrefix> {
+    if native_root().is_some() {
+        return Vec::new();
+    }
+    proc_prefixes(is_launcher_cmdline, PrefixSource::Launcher).unwrap_or_default()
+}
+
+/// The Wine prefixes of the processes whose command line `matches`, once each.
+fn proc_prefixes(matches: fn(&str) -> bool, source: PrefixSource) -> Result<Vec<DetectedPrefix>> {
+    let mut prefixes = Vec::new();
+
+    let proc_dir = fs::read_dir("/proc")?;
+
+    for entry in proc_dir.flatten() {
+        let file_name = entry.file_name();
+        let name = file_name.to_string_lossy();
+
+        // Skip non-numeric entries
+        let pid: u32 = match name.parse() {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+
+        // Try to read cmdline
+        let cmdline_path = entry.path().join("cmdline");
+        if let Ok(cmdline) = fs::read(&cmdline_path) {
+            // cmdline is null-byte delimited
+            let cmdline_str = String::from_utf8_lossy(&cmdline);
+
+            if matches(&cmdline_str) {
+                // Extract prefix: everything up to and including "drive_c"
+                if let Some(prefix) = extract_prefix(&cmdline_str) {
+                    prefixes.push(DetectedPrefix {
+                        path: host_path(pid, prefix),
+                        source: source.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Deduplicate by path
+    prefixes.sort_by(|a, b| a.path.cmp(&b.path));
+    prefixes.dedup_by(|a, b| a.path == b.path);
+
+    Ok(prefixes)
+}
+
+/// Counts running EVE client processes, whether or not their prefix can be found.
+pub fn count_eve_processes() -> usize {
+    if native_root().is_some() {
+        return native_eve_processes();
+    }
+    let Ok(proc_dir) = fs::read_dir("/proc") else {
+        return 0;
+    };
+    proc_dir
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|e| fs::read(e.path().join("cmdline")).ok())
+        .filter(|cmdline| is_eve_cmdline(&String::from_utf8_lossy(cmdline)))
+        .count()
+}
+
+/// The `files` inside any of `prefixes`, such as the targets of a sync that a
+/// running client would overwrite on exit. Symlinked paths are resolved first, so
+/// a prefix reached through `~/.steam/steam` still matches.
+pub fn files_in_prefixes(files: &[PathBuf], prefixes: &[PathBuf]) -> Vec<PathBuf> {
+    let resolve = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let prefixes: Vec<PathBuf> = prefixes.iter().map(|p| resolve(p)).collect();
+    files
+        .iter()
+        .filter(|file| {
+            let file = resolve(file);
+            prefixes.iter().any(|prefix| file.starts_with(prefix))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Looks for eve-online.exe or exefile.exe (case-insensitive).
+fn is_eve_cmdline(cmdline: &str) -> bool {
+    let lower = cmdline.to_lowercase();
+    lower.contains("eve-online.exe") || lower.contains("exefile.exe")
+}
+
+/// Looks for evelauncher.exe (case-insensitive).
+fn is_launcher_cmdline(cmdline: &str) -> bool {
+    cmdline.to_lowercase().contains("evelauncher.exe")
+}
+
+/// Counts EVE clients in the Windows or macOS process list.
+fn native_eve_processes() -> usize {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // Keeps tasklist from flashing a console window over the GUI
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        std::process::Command::new("tasklist")
+            .args(["/FO", "CSV", "/NH"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map(|output| count_eve_tasks(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or(0)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("ps")
+            .args(["-axo", "comm="])
+            .output()
+            .map(|output| count_eve_commands(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or(0)
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        0
+    }
+}
+
+/// A file some process holds open
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenFile {
+    pub path: PathBuf,
+    pub pid: u32,
+    /// The process's command name, as in `/proc/<pid>/comm`
+    pub command: String,
+}
+
+/// Processes holding any of `files` open, found through the `/proc/<pid>/fd`
+/// links. Processes of other users can't be read and are skipped. Only Linux has
+/// `/proc` laid out this way; elsewhere nothing is found.
+pub fn processes_with_open(files: &[PathBuf]) -> Vec<OpenFile> {
+    let wanted: Vec<(PathBuf, &PathBuf)> = files
+        .iter()
+        .filter_map(|f| Some((fs::canonicalize(f).ok()?, f)))
+        .collect();
+    let mut open = Vec::new();
+    let Ok(procs) = fs::read_dir("/proc") else {
+        return open;
+    };
+    for entry in procs.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|n| n.parse().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            if let Some((_, file)) = wanted.iter().find(|(canonical, _)| *canonical == target) {
+                let command = fs::read_to_string(entry.path().join("comm"))
+                    .map(|c| c.trim().to_string())
+                    .unwrap_or_default();
+                open.push(OpenFile {
+                    path: (*file).clone(),
+                    pid,
+                    command,
+                });
+            }
+        }
+    }
+    open.sort_by_key(|o| o.pid);
+    open.dedup();
+    open
+}
+
+/// Bytes free to this user on the filesystem holding `dir`, as `df` reports it.
+/// None on Windows or when `df` can't be run.
+pub fn available_space(dir: &Path) -> Option<u64> {
+    if cfg!(windows) {
+        return None;
+    }
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(dir)
+        .output()
+        .ok()?;
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Type of the network or FUSE filesystem holding `dir`, such as `nfs4` or
+/// `fuse.sshfs`, or None when it is local. Only Linux is checked.
+pub fn network_filesystem(dir: &Path) -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    network_fs_in_mounts(&mounts, &dir)
+}
+
+/// Finds the mount holding `path` in `/proc/mounts` contents and returns its
+/// type if it is a network filesystem.
+fn network_fs_in_mounts(mounts: &str, path: &Path) -> Option<String> {
+    let (_, fs_type) = mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = unescape_mount_field(fields.nth(1)?);
+            Some((PathBuf::from(mount_point), fields.next()?))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.components().count())?;
+
+    let network = NETWORK_FILESYSTEMS.contains(&fs_type) || fs_type.starts_with("fuse.");
+    network.then(|| fs_type.to_string())
+}
+
+/// Reads the Available column of POSIX `df -Pk` output, given in KiB.
+fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    // The filesystem name may contain spaces, so count columns from the end
+    let columns: Vec<&str> = line.split_whitespace().collect();
+    let available: u64 = columns.get(columns.len().checked_sub(3)?)?.parse().ok()?;
+    Some(available * 1024)
+}
+
+/// Counts EVE clients in `ps -axo comm=` output, one executable path per line.
+/// The Mac client runs as `exefile`, without the Windows extension.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn count_eve_commands(output: &str) -> usize {
+    output
+        .lines()
+        .filter(|command| {
+            let name = Path::new(command.trim()).file_name().unwrap_or_default();
+            is_eve_cmdline(command) || name.eq_ignore_ascii_case("exefile")
+        })
+        .count()
+}
+
+/// Counts EVE clients in `tasklist /FO CSV /NH` output, whose first column is the
+/// quoted image name.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn count_eve_tasks(output: &str) -> usize {
+    output
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .filter(|image| is_eve_cmdline(image.trim_matches('"')))
+        .count()
+}
+
+/// The parts of a Lutris game configuration that locate a Wine install
+#[derive(Debug, Default, Deserialize)]
+struct LutrisConfig {
+    #[serde(default)]
+    game: LutrisGame,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LutrisGame {
+    #[serde(default)]
+    exe: Option<String>,
+    #[serde(default)]
+    prefix: Option<String>,
+}
+
+/// Finds EVE installs in Lutris game configurations, whether or not the game is
+/// running. Covers the classic and current config locations and the Flatpak's.
+pub fn lutris_prefixes() -> Vec<DetectedPrefix> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    lutris_prefixes_in(&[
+        home.join(".config/lutris/games"),
+        home.join(".local/share/lutris/games"),
+        home.join(".var/app/net.lutris.Lutris/config/lutris/games"),
+        home.join(".var/app/net.lutris.Lutris/data/lutris/games"),
+    ])
+}
+
+/// Returns the EVE prefixes configured in the given Lutris game directories that
+/// exist, once each. A game is EVE when its executable is the client or launcher,
+/// or Lutris named it after EVE when installing it.
+fn lutris_prefixes_in(game_dirs: &[PathBuf]) -> Vec<DetectedPrefix> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let expand = |path: &str| match path.strip_prefix("~/") {
+        Some(rest) => home.join(rest),
+        None => PathBuf::from(path),
+    };
+
+    let mut seen = Vec::new();
+    let mut prefixes = Vec::new();
+    for dir in game_dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.extension()
+                    .is_some_and(|ext| ext == "yml" || ext == "yaml")
+            })
+            .collect();
+        files.sort();
+        for file in files {
+            let Some(config) = fs::read_to_string(&file)
+                .ok()
+                .and_then(|text| serde_yaml::from_str::<LutrisConfig>(&text).ok())
+            else {
+                continue;
+            };
+            let stem = file
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let exe = config.game.exe.as_deref().unwrap_or_default();
+            let is_eve = is_eve_cmdline(exe)
+                || exe.to_lowercase().contains("evelauncher")
+                || stem.to_lowercase().starts_with("eve-online");
+            if !is_eve {
+                continue;
+            }
+
+            let prefix = match config.game.prefix.as_deref() {
+                Some(prefix) => {
+                    let prefix = expand(prefix);
+                    if prefix.ends_with("drive_c") {
+                        prefix
+                    } else {
+                        prefix.join("drive_c")
+                    }
+                }
+                None => match extract_prefix(&expand(exe).to_string_lossy()) {
+                    Some(prefix) => prefix,
+                    None => continue,
+                },
+            };
+            if !prefix.is_dir() {
+                continue;
+            }
+            let canonical = fs::canonicalize(&prefix).unwrap_or_else(|_| prefix.clone());
+            if !seen.contains(&canonical) {
+                seen.push(canonical);
+                prefixes.push(DetectedPrefix {
+                    path: prefix,
+                    source: PrefixSource::Lutris(stem),
+                });
+            }
+        }
+    }
+    prefixes
+}
+
+/// Finds bottles with EVE installed, in the native and Flatpak Bottles data
+/// directories.
+pub fn bottles_prefixes() -> Vec<DetectedPrefix> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    bottles_prefixes_in(
+        &[
+            home.join(".local/share/bottles/bottles"),
+            home.join(".var/app/com.usebottles.bottles/data/bottles/bottles"),
+        ],
+        PrefixSource::Bottles,
+    )
+}
+
+/// Finds CodeWeavers CrossOver bottles with EVE installed, in `~/.cxoffice`.
+pub fn crossover_prefixes() -> Vec<DetectedPrefix> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    bottles_prefixes_in(&[home.join(".cxoffice")], PrefixSource::CrossOver)
+}
+
+/// Returns the `drive_c` of every bottle under the given roots where some Wine
+/// user has EVE or launcher settings, with `source` naming the bottle. Bottles and
+/// CrossOver name the Wine user after the desktop user rather than `steamuser`,
+/// so every user directory is checked.
+fn bottles_prefixes_in(
+    roots: &[PathBuf],
+    source: fn(String) -> PrefixSource,
+) -> Vec<DetectedPrefix> {
+    let mut prefixes = Vec::new();
+    for root in roots {
+        let mut bottles = read_subdirs(root);
+        bottles.sort();
+        for bottle in bottles {
+            let prefix = bottle.join("drive_c");
+            let has_eve = read_subdirs(&prefix.join("users")).iter().any(|user| {
+                user.join("AppData/Local/CCP/EVE").is_dir()
+                    || user.join("AppData/Roaming/EVE Online").is_dir()
+            });
+            if has_eve {
+                let name = bottle
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                prefixes.push(DetectedPrefix {
+                    path: prefix,
+                    source: source(name),
+                });
+            }
+        }
+    }
+    prefixes
+}
+
+/// EVE installs known to Lutris, Bottles or CrossOver, running or not, once each.
+pub fn installed_prefixes() -> Vec<DetectedPrefix> {
+    let mut prefixes = lutris_prefixes();
+    for bottle in bottles_prefixes().into_iter().chain(crossover_prefixes()) {
+        if !prefixes.iter().any(|p| p.path == bottle.path) {
+            prefixes.push(bottle);
+        }
+    }
+    prefixes
+}
+
+/// Updates a detected-prefix list with what a process scan saw. Prefixes of running
+/// clients and launchers (`running`) replace the previous ones at the top, taking
+/// the place of any other entry for the same prefix. An install from `installed`
+/// that has no entry, e.g. because its client just stopped, goes back at the end.
+pub fn merge_running(
+    detected: &mut Vec<DetectedPrefix>,
+    running: Vec<DetectedPrefix>,
+    installed: Vec<DetectedPrefix>,
+) {
+    detected.retain(|d| {
+        !matches!(d.source, PrefixSource::Process | PrefixSource::Launcher)
+            && !running.iter().any(|r| r.path == d.path)
+    });
+    detected.splice(0..0, running);
+    for install in installed {
+        if !detected.iter().any(|d| d.path == install.path) {
+            detected.push(install);
+        }
+    }
+}
+
+/// Probes the places EVE usually ends up when nothing says where it is: Steam
+/// libraries, prefixes under `~/Games` (Lutris' default install folder, e.g.
+/// `~/Games/eve-online`) and the default `~/.wine`. Only prefixes that already
+/// hold settings profiles are returned.
+pub fn common_prefixes() -> Vec<DetectedPrefix> {
+    let mut candidates = steam_library_prefixes();
+    if let Some(home) = dirs::home_dir() {
+        let mut games = read_subdirs(&home.join("Games"));
+        games.sort();
+        candidates.extend(games.into_iter().map(|game| game.join("drive_c")));
+        candidates.push(home.join(".wine/drive_c"));
+    }
+    probe_prefixes(&candidates)
+}
+
+/// Returns the candidate prefixes with at least one settings profile, once each.
+fn probe_prefixes(candidates: &[PathBuf]) -> Vec<DetectedPrefix> {
+    let mut seen = Vec::new();
+    let mut prefixes = Vec::new();
+    for prefix in candidates {
+        if !find_profile_dirs(prefix).is_ok_and(|dirs| !dirs.is_empty()) {
+            continue;
+        }
+        let canonical = fs::canonicalize(prefix).unwrap_or_else(|_| prefix.clone());
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            prefixes.push(DetectedPrefix {
+                path: prefix.clone(),
+                source: PrefixSource::Common,
+            });
+        }
+    }
+    prefixes
+}
+
+/// Returns true when this app runs inside a Flatpak sandbox.
+pub fn in_flatpak() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+/// Extracts the Wine prefix from a command line containing drive_c.
+fn extract_prefix(cmdline: &str) -> Option<PathBuf> {
+    // Split on null bytes to get arguments
+    for arg in cmdline.split('\0') {
+        let lower = arg.to_lowercase();
+        if let Some(pos) = lower.find("drive_c") {
+            // Include "drive_c" in the prefix
+            let prefix = &arg[..pos + 7];
+            return Some(PathBuf::from(prefix));
+        }
+    }
+    None
+}
+
+/// One line of `/proc/<pid>/mountinfo`
+#[derive(Debug, Clone, PartialEq)]
+struct Mount {
+    /// `major:minor` of the mounted filesystem
+    device: String,
+    /// Directory within the filesystem that is mounted
+    root: PathBuf,
+    /// Where it is mounted
+    mount_point: PathBuf,
+}
+
+/// Translates a path seen by process `pid` to the same file as seen by this process.
+///
+/// Steam's container runtime (pressure-vessel, also used under gamescope on the Deck)
+/// runs the client in its own mount namespace, so the prefix in its command line can
+/// be a container path. When the namespaces differ, the path is mapped through both
+/// mount tables; failing that, through `/proc/<pid>/root`, which only resolves while
+/// the process runs. Paths from the same namespace are returned unchanged.
+fn host_path(pid: u32, path: PathBuf) -> PathBuf {
+    let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+    let same_namespace = match (
+        fs::read_link(proc_dir.join("ns/mnt")),
+        fs::read_link("/proc/self/ns/mnt"),
+    ) {
+        (Ok(theirs), Ok(ours)) => theirs == ours,
+        // Can't tell; assume the common case
+        _ => true,
+    };
+    if same_namespace {
+        return path;
+    }
+
+    let container = fs::read_to_string(proc_dir.join("mountinfo")).unwrap_or_default();
+    let host = fs::read_to_string("/proc/self/mountinfo").unwrap_or_default();
+    if let Some(translated) =
+        translate_mount_path(&path, &parse_mountinfo(&container), &parse_mountinfo(&host))
+    {
+        if translated.is_dir() {
+            return translated;
+        }
+    }
+
+    let through_root = proc_dir
+        .join("root")
+        .join(path.strip_prefix("/").unwrap_or(&path));
+    if through_root.is_dir() {
+        return through_root;
+    }
+    path
+}
+
+fn parse_mountinfo(contents: &str) -> Vec<Mount> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(' ').collect();
+            Some(Mount {
+                device: fields.get(2)?.to_string(),
+                root: PathBuf::from(unescape_mount_field(fields.get(3)?)),
+                mount_point: PathBuf::from(unescape_mount_field(fields.get(4)?)),
+            })
+        })
+        .collect()
+}
+
+/// Decodes the `\ooo` octal escapes mountinfo uses for spaces, tabs and backslashes.
+pub(crate) fn unescape_mount_field(field: &str) -> String {
+    let mut out = String::new();
+    let mut rest = field;
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        let escape = rest.get(pos + 1..pos + 4);
+        match escape.and_then(|e| u8::from_str_radix(e, 8).ok()) {
+            Some(byte) => {
+                out.push(byte as char);
+                rest = &rest[pos + 4..];
+            }
+            None => {
+                out.push('\\');
+                rest = &rest[pos + 1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Maps `path` from one mount table to another: find the container mount holding it,
+/// work out where that is inside the filesystem, then find a host mount of the same
+/// filesystem that exposes that directory.
+fn translate_mount_path(path: &Path, container: &[Mount], host: &[Mount]) -> Option<PathBuf> {
+    let mount = container
+        .iter()
+        .filter(|m| path.starts_with(&m.mount_point))
+        .max_by_key(|m| m.mount_point.components().count())?;
+    let in_fs = mount.root.join(path.strip_prefix(&mount.mount_point).ok()?);
+
+    let host_mount = host
+        .iter()
+        .filter(|m| m.device == mount.device && in_fs.starts_with(&m.root))
+        .max_by_key(|m| m.root.components().count())?;
+    Some(
+        host_mount
+            .mount_point
+            .join(in_fs.strip_prefix(&host_mount.root).ok()?),
+    )
+}
+
+/// Returns true when running on a Steam Deck: Steam sets `SteamDeck=1` in game
+/// mode, and the DMI board names the Deck models on the desktop side.
+pub fn is_steam_deck() -> bool {
+    if std::env::var("SteamDeck").is_ok_and(|v| v == "1") {
+        return true;
+    }
+    let dmi = |name: &str| {
+        fs::read_to_string(Path::new("/sys/devices/virtual/dmi/id").join(name))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    dmi("board_vendor") == "Valve" && matches!(dmi("board_name").as_str(), "Jupiter" | "Galileo")
+}
+
+/// Finds EVE's Proton prefix in the usual Steam library locations, including
+/// SD cards and other removable drives mounted under `/run/media`.
+pub fn steam_library_prefixes() -> Vec<PathBuf> {
+    let mut libraries = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        libraries.push(home.join(".local/share/Steam"));
+        libraries.push(home.join(".steam/steam"));
+        libraries.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+    }
+    // Older SteamOS mounts the card at /run/media/<device>, newer at /run/media/<user>/<label>
+    for media in read_subdirs(Path::new("/run/media")) {
+        libraries.extend(read_subdirs(&media));
+        libraries.push(media);
+    }
+    library_prefixes(&libraries)
+}
+
+fn read_subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the EVE prefixes that exist in the given Steam library roots, once each.
+fn library_prefixes(libraries: &[PathBuf]) -> Vec<PathBuf> {
+    let mut seen = Vec::new();
+    let mut prefixes = Vec::new();
+    for library in libraries {
+        let prefix = library
+            .join("steamapps/compatdata")
+            .join(EVE_STEAM_APP_ID.to_string())
+            .join("pfx/drive_c");
+        if !prefix.is_dir() {
+            continue;
+        }
+        let canonical = fs::canonicalize(&prefix).unwrap_or_else(|_| prefix.clone());
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            prefixes.push(prefix);
+        }
+    }
+    prefixes
+}
+
+/// Home directories of the Wine users in a prefix, `steamuser` first and the rest
+/// by name. The shared `Public` profile is left out.
+pub fn wine_users(prefix: &Path) -> Vec<PathBuf> {
+    let mut users: Vec<PathBuf> = read_subdirs(&prefix.join("users"))
+        .into_iter()
+        .filter(|u| !u.ends_with(PUBLIC_WINE_USER))
+        .collect();
+    users.sort_by_key(|u| (!u.ends_with(STEAM_WINE_USER), u.clone()));
+    users
+}
+
+/// Home directories to look for settings under: each Wine user's, or on native
+/// Windows and macOS the [`native_root`] itself.
+fn user_homes(prefix: &Path) -> Vec<PathBuf> {
+    if native_root().is_some() {
+        vec![prefix.to_path_buf()]
+    } else {
+        wine_users(prefix)
+    }
+}
+
+/// EVE base directories under every Wine user of the prefix, with the user's name,
+/// in [`wine_users`] order.
+pub fn eve_dirs_by_user(prefix: &Path) -> Vec<(String, PathBuf)> {
+    let mut dirs = Vec::new();
+    for user in user_homes(prefix) {
+        let name = user
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for layout in base_layouts() {
+            let base = layout.iter().fold(user.clone(), |p, part| p.join(part));
+            if base.is_dir() {
+                dirs.push((name.clone(), base));
+            }
+        }
+    }
+    dirs
+}
+
+/// Resolves the existing EVE base directories in a prefix using `EVE_BASE_LAYOUTS`
+/// under each Wine user. Layouts that resolve to the same directory (e.g. via
+/// symlinks) are returned once.
+pub fn find_eve_base_dirs(prefix: &Path) -> Vec<PathBuf> {
+    let mut seen = Vec::new();
+    let mut bases = Vec::new();
+
+    for (_, base) in eve_dirs_by_user(prefix) {
+        let canonical = fs::canonicalize(&base).unwrap_or_else(|_| base.clone());
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            bases.push(base);
+        }
+    }
+
+    bases
+}
+
+/// EVE base directory a client would create in a fresh prefix (the current layout),
+/// under `steamuser` or, in a plain Wine prefix without one, its first user.
+pub fn default_eve_base_dir(prefix: &Path) -> PathBuf {
+    let user = user_homes(prefix)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| prefix.join("users").join(STEAM_WINE_USER));
+    base_layouts()[0].iter().fold(user, |p, part| p.join(part))
+}
+
+/// Finds every EVE settings profile within a Wine prefix: Tranquility's before
+/// the test servers', and `settings_Default` before the rest, so the first is the
+/// profile the live client uses unless told otherwise.
+pub fn find_settings_dirs(prefix: &Path) -> Result<Vec<PathBuf>> {
+    let mut settings_dirs = find_profile_dirs(prefix)?;
+    settings_dirs.sort_by_key(|dir| {
+        (
+            discovery::server_for(dir) != Some(Server::Tranquility),
+            dir.file_name() != Some(DEFAULT_PROFILE.as_ref()),
+        )
+    });
+    Ok(settings_dirs)
+}
+
+/// Finds EVE launcher per-account settings directories within a Wine prefix.
+pub fn find_launcher_dirs(prefix: &Path) -> Vec<PathBuf> {
+    user_homes(prefix)
+        .into_iter()
+        .flat_map(|user| {
+            let app_data = user.join("AppData");
+            LAUNCHER_SUBDIRS
+                .iter()
+                .map(move |parts| parts.iter().fold(app_data.clone(), |p, part| p.join(part)))
+        })
+        .filter(|p| p.is_dir())
+        .collect()
+}
+
+/// How many levels below the prefix [`search_settings_dirs`] looks
+const SETTINGS_SEARCH_DEPTH: usize = 12;
+
+/// Directories [`search_settings_dirs`] reads before giving up
+const SETTINGS_SEARCH_LIMIT: usize = 50_000;
+
+/// Searches the whole prefix for `settings_*` profiles holding character files,
+/// for installs outside the known layouts such as custom Wine setups or relocated
+/// and symlinked directories. Symlinks are followed, each directory is read once,
+/// and the search is bounded in depth and in directories read.
+pub fn search_settings_dirs(prefix: &Path) -> Vec<PathBuf> {
+    search_settings_dirs_in(prefix, SETTINGS_SEARCH_DEPTH, SETTINGS_SEARCH_LIMIT)
+}
+
+fn search_settings_dirs_in(root: &Path, max_depth: usize, limit: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([(root.to_path_buf(), 0)]);
+    let mut read = 0;
+    while let Some((dir, depth)) = queue.pop_front() {
+        if read == limit {
+            break;
+        }
+        let canonical = fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+        if !seen.insert(canonical) {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        read += 1;
+
+        let mut subdirs = Vec::new();
+        let mut has_character_file = false;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                subdirs.push(path);
+            } else if name.starts_with("core_char_") && name.ends_with(".dat") {
+                has_character_file = true;
+            }
+        }
+
+        let is_profile = dir
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with("settings_"))
+            && !discovery::is_backup_dir(&dir);
+        if is_profile && has_character_file {
+            found.push(dir);
+        } else if depth < max_depth {
+            subdirs.sort();
+            queue.extend(subdirs.into_iter().map(|sub| (sub, depth + 1)));
+        }
+    }
+    found.sort();
+    found
+}
+
+/// The profile the client uses unless one is chosen in its settings
+pub const DEFAULT_PROFILE: &str = "settings_Default";
+
+/// Finds every `settings_*` profile directory within a Wine prefix, across all installs.
+pub fn find_profile_dirs(prefix: &Path) -> Result<Vec<PathBuf>> {
+    let mut profile_dirs = Vec::new();
+
+    for eve_base in find_eve_base_dirs(prefix) {
+        for install in fs::read_dir(&eve_base)? {
+            let install_path = install?.path();
+            if install_path.is_dir() {
+                profile_dirs.extend(profiles_in(&install_path)?);
+            }
+        }
+    }
+
+    profile_dirs.sort();
+    Ok(profile_dirs)
+}
+
+/// The profiles next to `settings_dir`, itself included, for a profile that was
+/// browsed to or searched for rather than found through its prefix.
+pub fn sibling_profile_dirs(settings_dir: &Path) -> Result<Vec<PathBuf>> {
+    let Some(install) = settings_dir.parent() else {
+        return Ok(Vec::new());
+    };
+    let mut profile_dirs = profiles_in(install)?;
+    profile_dirs.sort();
+    Ok(profile_dirs)
+}
+
+/// The `settings_*` directories directly inside an install, backups left out.
+fn profiles_in(install: &Path) -> Result<Vec<PathBuf>> {
+    let mut profile_dirs = Vec::new();
+    for entry in fs::read_dir(install)? {
+        let path = entry?.path();
+        let is_profile = path
+            .file_name()
+            .map(|n| n.to_string_lossy().starts_with("settings_"))
+            .unwrap_or(false);
+
+        if is_profile && path.is_dir() && !discovery::is_backup_dir(&path) {
+            profile_dirs.push(path);
+        }
+    }
+    Ok(profile_dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_prefix() {
+        let cmdline = "/home/user/Games/Eve/drive_c/eve/exefile.exe\0--arg";
+        let prefix = extract_prefix(cmdline);
+        assert_eq!(prefix, Some(PathBuf::from("/home/user/Games/Eve/drive_c")));
+    }
+
+    #[test]
+    fn test_extract_prefix_mixed_case() {
+        let cmdline = "/home/user/Games/Eve/Drive_C/eve/exefile.exe";
+        let prefix = extract_prefix(cmdline);
+        // Should still work with mixed case
+        assert!(prefix.is_some());
+    }
+
+    #[test]
+    fn test_extract_prefix_no_match() {
+        let cmdline = "/usr/bin/firefox";
+        let prefix = extract_prefix(cmdline);
+        assert_eq!(prefix, None);
+    }
+
+    #[test]
+    fn test_launcher_cmdline() {
+        let launcher = "/home/me/Games/eve/drive_c/EVE/Launcher/EveLauncher.exe\0/noqt";
+        assert!(is_launcher_cmdline(launcher) && !is_eve_cmdline(launcher));
+        let client = "/home/me/Games/eve/drive_c/EVE/SharedCache/tq/bin64/exefile.exe";
+        assert!(is_eve_cmdline(client) && !is_launcher_cmdline(client));
+        assert_eq!(
+            extract_prefix(launcher),
+            Some(PathBuf::from("/home/me/Games/eve/drive_c"))
+        );
+    }
+
+    #[test]
+    fn test_count_eve_tasks() {
+        let output = "\"explorer.exe\",\"4120\",\"Console\",\"1\",\"98,304 K\"\r\n\
+                      \"exefile.exe\",\"9312\",\"Console\",\"1\",\"2,411,008 K\"\r\n\
+                      \"ExeFile.exe\",\"9876\",\"Console\",\"1\",\"1,870,112 K\"\r\n";
+        assert_eq!(count_eve_tasks(output), 2);
+        assert_eq!(count_eve_tasks(""), 0);
+    }
+
+    #[test]
+    fn test_files_in_prefixes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let running = dir.path().join("running/drive_c");
+        let idle = dir.path().join("idle/drive_c");
+        fs::create_dir_all(&running)?;
+        fs::create_dir_all(&idle)?;
+        let in_running = running.join("core_char_1.dat");
+        let in_idle = idle.join("core_char_2.dat");
+        fs::write(&in_running, "")?;
+        fs::write(&in_idle, "")?;
+
+        let files = vec![in_running.clone(), in_idle];
+        assert_eq!(
+            files_in_prefixes(&files, std::slice::from_ref(&running)),
+            vec![in_running]
+        );
+        assert!(files_in_prefixes(&files, &[]).is_empty());
+
+        #[cfg(unix)]
+        {
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&running, &link)?;
+            assert_eq!(files_in_prefixes(&files, &[link]).len(), 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_df_available() {
+        let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/nvme0n1p2   479151816 301234568 153517884      67% /home\n";
+        assert_eq!(parse_df_available(output), Some(153517884 * 1024));
+        assert_eq!(parse_df_available("Filesystem\n"), None);
+        assert_eq!(parse_df_available(""), None);
+    }
+
+    #[test]
+    fn test_network_fs_in_mounts() {
+        let mounts = "/dev/nvme0n1p2 / ext4 rw,relatime 0 0\n\
+                      nas:/export/games /mnt/games nfs4 rw,relatime 0 0\n\
+                      /dev/sda1 /mnt/games/local ext4 rw 0 0\n\
+                      user@host:/eve /home/pilot/remote\\040eve fuse.sshfs rw 0 0\n\
+                      /dev/sdb1 /mnt/windows fuseblk rw 0 0\n";
+        let find = |path: &str| network_fs_in_mounts(mounts, Path::new(path));
+
+        assert_eq!(find("/mnt/games/pfx/drive_c").as_deref(), Some("nfs4"));
+        assert_eq!(find("/mnt/games/local/pfx"), None);
+        assert_eq!(
+            find("/home/pilot/remote eve/settings").as_deref(),
+            Some("fuse.sshfs")
+        );
+        assert_eq!(find("/mnt/windows/EVE"), None);
+        assert_eq!(find("/home/pilot/.wine"), None);
+        assert_eq!(find("/mnt/gamesx"), None);
+    }
+
+    #[test]
+    fn test_merge_running() {
+        let prefix = |path: &str, source: PrefixSource| DetectedPrefix {
+            path: PathBuf::from(path),
+            source,
+        };
+        let lutris = prefix("/games/eve", PrefixSource::Lutris("eve".into()));
+        let common = prefix("/home/u/.wine/drive_c", PrefixSource::Common);
+        let mut detected = vec![lutris.clone(), common.clone()];
+
+        let running = prefix("/games/eve", PrefixSource::Process);
+        merge_running(&mut detected, vec![running.clone()], vec![lutris.clone()]);
+        assert_eq!(detected, [running, common.clone()]);
+
+        // The client stops: the install comes back instead of vanishing
+        merge_running(&mut detected, Vec::new(), vec![lutris.clone()]);
+        assert_eq!(detected, [common, lutris]);
+    }
+
+    #[test]
+    fn test_count_eve_commands() {
+        let output = "/sbin/launchd\n\
+                      /Applications/EVE Online.app/Contents/Resources/build/bin64/exefile\n\
+                      /Applications/EVE Launcher.app/Contents/MacOS/evelauncher\n\
+                      /usr/libexec/exefiled\n";
+        assert_eq!(count_eve_commands(output), 1);
+    }
+
+    #[test]
+    fn test_translate_container_path() {
+        let container = parse_mountinfo(
+            "1 0 0:30 / / rw - overlay overlay rw\n\
+             2 1 259:2 /home/deck /home/deck rw - ext4 /dev/nvme0n1p8 rw\n\
+             3 1 259:2 /home/deck/.local/share/Steam/steamapps/compatdata/8500/pfx \
+             /run/pfx rw - ext4 /dev/nvme0n1p8 rw",
+        );
+        let host = parse_mountinfo(
+            "20 1 259:2 / / rw - ext4 /dev/nvme0n1p8 rw\n\
+             21 20 259:1 / /boot rw - vfat /dev/nvme0n1p1 rw",
+        );
+
+        assert_eq!(
+            translate_mount_path(Path::new("/run/pfx/drive_c"), &container, &host),
+            Some(PathBuf::from(
+                "/home/deck/.local/share/Steam/steamapps/compatdata/8500/pfx/drive_c"
+            ))
+        );
+        // Files that only exist inside the container's own root don't translate
+        assert_eq!(
+            translate_mount_path(Path::new("/usr/lib/wine"), &container, &host),
+            None
+        );
+    }
+
+    #[test]
+    fn test_library_prefixes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let internal = dir.path().join("Steam");
+        let card = dir.path().join("media/deck/SD");
+        let prefix = card.join("steamapps/compatdata/8500/pfx/drive_c");
+        fs::create_dir_all(&prefix)?;
+        fs::create_dir_all(internal.join("steamapps/compatdata/440/pfx/drive_c"))?;
+
+        assert_eq!(
+            library_prefixes(&[internal, card.clone(), card]),
+            vec![prefix]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lutris_prefixes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let games = dir.path().join("games");
+        fs::create_dir(&games)?;
+        let eve = dir.path().join("eve");
+        let other = dir.path().join("other");
+        fs::create_dir_all(eve.join("drive_c"))?;
+        fs::create_dir_all(other.join("drive_c"))?;
+        fs::write(
+            games.join("eve-online-1700000000.yml"),
+            format!(
+                "game:\n  exe: {0}/drive_c/EVE/eve-online.exe\n  prefix: {0}\nwine:\n  version: lutris-7.2\n",
+                eve.display()
+            ),
+        )?;
+        // Same install under another name, found through the executable alone
+        fs::write(
+            games.join("my-eve.yml"),
+            format!(
+                "game:\n  exe: {}/drive_c/users/me/AppData/Local/eve-online/evelauncher.exe\n",
+                eve.display()
+            ),
+        )?;
+        fs::write(
+            games.join("another-game-1.yml"),
+            format!(
+                "game:\n  exe: {0}/drive_c/game.exe\n  prefix: {0}\n",
+                other.display()
+            ),
+        )?;
+        fs::write(games.join("broken.yml"), "game: [")?;
+
+        let found = lutris_prefixes_in(&[games, dir.path().join("missing")]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, eve.join("drive_c"));
+        assert_eq!(
+            found[0].source,
+            PrefixSource::Lutris("eve-online-1700000000".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_bottles_prefixes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bottles = dir.path().join("bottles");
+        fs::create_dir_all(bottles.join("EVE/drive_c/users/me/AppData/Local/CCP/EVE"))?;
+        fs::create_dir_all(bottles.join("Games/drive_c/users/me/AppData/Local"))?;
+
+        let found = bottles_prefixes_in(
+            &[bottles.clone(), dir.path().join("flatpak")],
+            PrefixSource::Bottles,
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, bottles.join("EVE/drive_c"));
+        assert_eq!(found[0].source, PrefixSource::Bottles("EVE".to_string()));
+
+        // CrossOver keeps its bottles the same way, in ~/.cxoffice
+        let cxoffice = dir.path().join(".cxoffice");
+        fs::create_dir_all(
+            cxoffice.join("EVE Online/drive_c/users/me/AppData/Roaming/EVE Online"),
+        )?;
+        let found = bottles_prefixes_in(std::slice::from_ref(&cxoffice), PrefixSource::CrossOver);
+        assert_eq!(found[0].path, cxoffice.join("EVE Online/drive_c"));
+        assert_eq!(
+            found[0].label(),
+            format!("CrossOver (EVE Online): {}", found[0].path.display())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unescape_mount_field() {
+        assert_eq!(
+            unescape_mount_field("/home/u/My\\040Games"),
+            "/home/u/My Games"
+        );
+        assert_eq!(unescape_mount_field("/a\\b"), "/a\\b");
+    }
+
+    #[test]
+    fn test_find_eve_base_dirs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert!(find_eve_base_dirs(dir.path()).is_empty());
+
+        let xp_style = dir
+            .path()
+            .join("users/steamuser/Local Settings/Application Data/CCP/EVE");
+        fs::create_dir_all(&xp_style)?;
+        assert_eq!(find_eve_base_dirs(dir.path()), vec![xp_style.clone()]);
+
+        // Plain Wine prefixes use the login name; steamuser still comes first
+        let login = dir.path().join("users/alice/AppData/Local/CCP/EVE");
+        fs::create_dir_all(&login)?;
+        fs::create_dir_all(dir.path().join("users/Public/AppData/Local/CCP/EVE"))?;
+        assert_eq!(find_eve_base_dirs(dir.path()), vec![xp_style, login]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_probe_prefixes() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let lutris = dir.path().join("Games/eve-online/drive_c");
+        let empty = dir.path().join(".wine/drive_c");
+        fs::create_dir_all(
+            lutris.join("users/me/AppData/Local/CCP/EVE/c_eve_tq_tranquility/settings_Default"),
+        )?;
+        fs::create_dir_all(empty.join("users/me"))?;
+
+        let found = probe_prefixes(&[
+            lutris.clone(),
+            empty,
+            dir.path().join("missing"),
+            lutris.clone(),
+        ]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, lutris);
+        assert_eq!(found[0].source, PrefixSource::Common);
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_settings_dirs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let custom = dir.path().join("games/eve/cache/settings_Default");
+        fs::create_dir_all(&custom)?;
+        fs::write(custom.join("core_char_90000001.dat"), "x")?;
+        let backup = custom.with_file_name("settings_Default_backup_20260301_120000Z");
+        fs::create_dir_all(&backup)?;
+        fs::write(backup.join("core_char_90000001.dat"), "x")?;
+        fs::create_dir_all(dir.path().join("games/eve/cache/settings_Empty"))?;
+
+        assert_eq!(search_settings_dirs(dir.path()), vec![custom.clone()]);
+        // Too shallow to reach it
+        assert!(search_settings_dirs_in(dir.path(), 3, 100).is_empty());
+
+        // A symlink loop is read once
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path(), dir.path().join("games/loop"))?;
+            assert_eq!(search_settings_dirs(dir.path()), vec![custom]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_profile_dirs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let install = dir
+            .path()
+            .join("users/steamuser/AppData/Local/CCP/EVE/c_eve_tq_tranquility");
+        fs::create_dir_all(install.join("settings_Default"))?;
+        fs::create_dir_all(install.join("settings_PvP"))?;
+        fs::create_dir_all(install.join("settings_Alpha"))?;
+        fs::create_dir_all(install.join("settings_Default_backup_20240101_120000"))?;
+        fs::create_dir_all(install.join("cache"))?;
+
+        let dirs = find_profile_dirs(dir.path())?;
+        assert_eq!(
+            dirs,
+            vec![
+                install.join("settings_Alpha"),
+                install.join("settings_Default"),
+                install.join("settings_PvP")
+            ]
+        );
+        assert_eq!(sibling_profile_dirs(&install.join("settings_PvP"))?, dirs);
+
+        // The test server sorts first by name, but Tranquility's profiles come first
+        let sisi = install.with_file_name("c_eve_sisi_singularity");
+        fs::create_dir_all(sisi.join("settings_Default"))?;
+        assert_eq!(
+            find_settings_dirs(dir.path())?,
+            vec![
+                install.join("settings_Default"),
+                install.join("settings_Alpha"),
+                install.join("settings_PvP"),
+                sisi.join("settings_Default")
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+//! Read-only HTML report of the character roster, when each file was last synced,
+//! how each profile's backups stand, how long operations have been taking and, if a
+//! baseline is loaded, how every file measures up to it. The page is a single
+//! static file with inline styles, so it can be dropped into a wiki or opened
+//! straight from disk.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::baseline::{Baseline, ComplianceReport};
+use crate::config::TimeDisplay;
+use crate::discovery::{self, CharacterFile, FileType};
+use crate::settings;
+use crate::state::{self, SyncRecord, TimingRecord};
+
+/// Profiles whose newest backup is older than this are marked stale
+const STALE_BACKUP_DAYS: i64 = 7;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:2em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+th{background:#eee}.bad{color:#b00}.good{color:#070}.dim{color:#777}";
+
+/// What goes into a report
+pub struct ReportData<'a> {
+    pub files: &'a [CharacterFile],
+    /// Sync history, oldest first
+    pub history: &'a [SyncRecord],
+    /// Recorded operation timings, oldest first
+    pub timings: &'a [TimingRecord],
+    pub compliance: Option<(&'a Baseline, &'a ComplianceReport)>,
+    pub time_display: TimeDisplay,
+}
+
+/// Renders the report, naming files with `name_of`.
+pub fn render_html(data: &ReportData, name_of: impl Fn(&CharacterFile) -> String) -> String {
+    let now = Local::now();
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+         <title>EVE settings report</title><style>{}</style></head><body>\n\
+         <h1>EVE settings report</h1>\n<p class=\"dim\">Generated {}</p>\n",
+        STYLE,
+        data.time_display.format(&now)
+    );
+
+    html.push_str(
+        "<h2>Characters</h2>\n<table><tr><th>Name</th><th>ID</th><th>Type</th>\
+         <th>Profile</th><th>Modified</th><th>Last synced</th></tr>\n",
+    );
+    for file in data.files {
+        let modified = file
+            .path
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .map(|t| data.time_display.format(&DateTime::<Local>::from(t)));
+        let synced = last_synced(data.history, file, data.time_display);
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>{}</tr>",
+            escape(&name_of(file)),
+            file.character_id,
+            kind(file),
+            escape(&discovery::profile_label(file.settings_dir())),
+            modified.as_deref().unwrap_or("-"),
+            match synced {
+                Some(time) => format!("<td>{}</td>", escape(&time)),
+                None => "<td class=\"dim\">never</td>".to_string(),
+            }
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str(
+        "<h2>Backups</h2>\n<table><tr><th>Profile</th><th>Backups</th><th>Newest</th></tr>\n",
+    );
+    let dirs: BTreeSet<&Path> = data.files.iter().map(|f| f.settings_dir()).collect();
+    for dir in dirs {
+        let backups = settings::list_backups(dir).unwrap_or_default();
+        let newest = backups.first().and_then(|b| settings::backup_created(b));
+        let cell = match newest {
+            Some(time) if (now - time).num_days() >= STALE_BACKUP_DAYS => {
+                format!(
+                    "<td class=\"bad\">{} (stale)</td>",
+                    data.time_display.format(&time)
+                )
+            }
+            Some(time) => format!(
+                "<td class=\"good\">{}</td>",
+                data.time_display.format(&time)
+            ),
+            None => "<td class=\"bad\">none</td>".to_string(),
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td title=\"{}\">{}</td><td>{}</td>{}</tr>",
+            escape(&dir.display().to_string()),
+            escape(&discovery::profile_label(dir)),
+            backups.len(),
+            cell
+        );
+    }
+    html.push_str("</table>\n");
+
+    let timings = state::summarize_timings(data.timings);
+    if !timings.is_empty() {
+        html.push_str(
+            "<h2>Timings</h2>\n<table><tr><th>Operation</th><th>Runs</th><th>Average</th>\
+             <th>Per file</th><th>Last</th></tr>\n",
+        );
+        for summary in timings {
+            let last = DateTime::parse_from_rfc3339(&summary.last.timestamp)
+                .map(|t| data.time_display.format(&t.with_timezone(&Local)))
+                .unwrap_or_else(|_| summary.last.timestamp.clone());
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{} ({})</td></tr>",
+                summary.operation.name(),
+                summary.runs,
+                state::format_millis(summary.average_millis),
+                summary
+                    .per_file_millis
+                    .map(|ms| format!("{:.1} ms", ms))
+                    .unwrap_or_else(|| "-".to_string()),
+                escape(&last),
+                state::format_millis(summary.last.millis as f64)
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if let Some((baseline, report)) = data.compliance {
+        let compliant = report
+            .characters
+            .iter()
+            .filter(|c| c.is_compliant())
+            .count();
+        let _ = write!(
+            html,
+            "<h2>Compliance: {}</h2>\n<p>{} of {} file(s) compliant</p>\n\
+             <table><tr><th>Name</th><th>Passed</th><th>Issues</th></tr>\n",
+            escape(&baseline.name),
+            compliant,
+            report.characters.len()
+        );
+        for c in &report.characters {
+            let issues: Vec<String> = c
+                .failing
+                .iter()
+                .map(|f| format!("{} is {}", f.rule, f.value))
+                .chain(c.missing.iter().map(|key| format!("{} is missing", key)))
+                .map(|issue| escape(&issue))
+                .collect();
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"{}\">{}/{}</td><td>{}</td></tr>",
+                escape(&name_of(&c.file)),
+                if c.is_compliant() { "good" } else { "bad" },
+                c.passed(),
+                c.checked,
+                issues.join("<br>")
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// Renders the report and writes it to `dest`.
+pub fn export_html(
+    data: &ReportData,
+    name_of: impl Fn(&CharacterFile) -> String,
+    dest: &Path,
+) -> Result<()> {
+    fs::write(dest, render_html(data, name_of))
+        .with_context(|| format!("Failed to write {}", dest.display()))
+}
+
+/// Time of the newest real sync that wrote to or read from `file`.
+fn last_synced(
+    history: &[SyncRecord],
+    file: &CharacterFile,
+    time_display: TimeDisplay,
+) -> Option<String> {
+    history
+        .iter()
+        .rev()
+        .filter(|r| !r.dry_run && r.succeeded > 0)
+        .find(|r| r.source == file.path || r.targets.contains(&file.path))
+        .map(|r| time_display.format_rfc3339(&r.timestamp))
+}
+
+fn kind(file: &CharacterFile) -> &'static str {
+    match file.file_type {
+        FileType::Character => "Character",
+        FileType::User => "Account",
+        FileType::Launcher => "Launcher",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_render_html() -> Result<()> {
+        let dir = tempdir()?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        let path = profile.join("core_char_90000001.dat");
+        fs::write(&path, b"x")?;
+        let file = discovery::settings_file(path.clone()).unwrap();
+        let history = vec![SyncRecord {
+            timestamp: "2026-03-01T12:30:00+00:00".to_string(),
+            source: dir.path().join("other.dat"),
+            targets: vec![path],
+            dry_run: false,
+            succeeded: 1,
+        }];
+        let timings = vec![TimingRecord {
+            timestamp: "2026-03-01T12:31:00+00:00".to_string(),
+            operation: state::TimedOperation::Sync,
+            files: 4,
+            millis: 2000,
+        }];
+
+        let html = render_html(
+            &ReportData {
+                files: std::slice::from_ref(&file),
+                history: &history,
+                timings: &timings,
+                compliance: None,
+                time_display: TimeDisplay::Utc,
+            },
+            |_| "<Main> & Co".to_string(),
+        );
+        assert!(html.contains("&lt;Main&gt; &amp; Co"));
+        assert!(html.contains("90000001"));
+        assert!(html.contains("2026-03-01 12:30 UTC"));
+        assert!(html.contains("<td class=\"bad\">none</td>"));
+        assert!(html.contains("<td>Sync</td><td>1</td><td>2.00 s</td><td>500.0 ms</td>"));
+        assert!(!html.contains("Compliance"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_backups_list_each_profile_once() -> Result<()> {
+        let dir = tempdir()?;
+        let mut files = Vec::new();
+        // Files of two profiles, interleaved as a sort by name can leave them
+        for (profile, id) in [("settings_A", 1), ("settings_B", 2), ("settings_A", 3)] {
+            let profile = dir.path().join(profile);
+            fs::create_dir_all(&profile)?;
+            let path = profile.join(format!("core_char_9000000{}.dat", id));
+            fs::write(&path, b"x")?;
+            files.push(discovery::settings_file(path).unwrap());
+        }
+
+        let html = render_html(
+            &ReportData {
+                files: &files,
+                history: &[],
+                timings: &[],
+                compliance: None,
+                time_display: TimeDisplay::Utc,
+            },
+            |_| String::new(),
+        );
+        let backups = html.split("<h2>Backups</h2>").nth(1).unwrap_or_default();
+        let rows = |profile: &str| backups.matches(&format!(">{} (", profile)).count();
+        assert_eq!(rows("settings_A"), 1);
+        assert_eq!(rows("settings_B"), 1);
+        Ok(())
+    }
+}
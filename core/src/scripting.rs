@@ -0,0 +1,333 @@
+//! Rhai scripts for automating workflows the app has no button for. Scripts see a
+//! small slice of the core API:
+//! - `prefixes()`: detected Wine prefixes
+//! - `profiles(prefix)`: `settings_*` directories of a prefix
+//! - `characters(profile)`: settings files of a profile, as maps with `path`, `id`,
+//!   `kind` (`"character"`, `"user"` or `"launcher"`) and `is_default`
+//! - `backup(profile)`: backs a profile up and returns the backup's path
+//! - `sync_files(source, targets, dry_run)`: copies one file over others as the
+//!   app's sync does, journaled and backing up their profiles first, and returns a
+//!   map per target with `target`, `success` and `message`. It fails without
+//!   writing anything if an EVE client is running in a target's prefix or a
+//!   pre-flight check fails
+//!
+//! `print` and `debug` output is collected and shown when the script finishes.
+//! Backups and syncs are written to the audit log like any other change.
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::settings::BackupOptions;
+
+/// Extension of script files in the scripts folder
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// What a script printed and what it evaluated to, or why it failed
+#[derive(Debug, Clone)]
+pub struct ScriptRun {
+    pub output: Vec<String>,
+    pub result: Result<String, String>,
+}
+
+/// Folder scripts are listed from, next to the config file.
+pub fn scripts_dir() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("scripts"))
+}
+
+/// `.rhai` files in `dir`, sorted by name. A missing folder has no scripts.
+pub fn discover_scripts(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut scripts: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+/// Runs a script file. Backups it takes are named and pruned by `backup_options`.
+pub fn run_script_file(path: &Path, backup_options: &BackupOptions) -> ScriptRun {
+    match fs::read_to_string(path) {
+        Ok(script) => run_script(&script, backup_options),
+        Err(e) => ScriptRun {
+            output: Vec::new(),
+            result: Err(format!("Failed to read {}: {}", path.display(), e)),
+        },
+    }
+}
+
+#[cfg(feature = "scripting")]
+pub use engine::run_script;
+
+/// Runs a script. Without the `scripting` feature every run fails with an
+/// explanation.
+#[cfg(not(feature = "scripting"))]
+pub fn run_script(_script: &str, _backup_options: &BackupOptions) -> ScriptRun {
+    ScriptRun {
+        output: Vec::new(),
+        result: Err("This build has no scripting support".to_string()),
+    }
+}
+
+#[cfg(feature = "scripting")]
+mod engine {
+    use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    use super::ScriptRun;
+    use crate::discovery::{self, CharacterFile, FileType, IgnoreRules};
+    use crate::journal;
+    use crate::preflight;
+    use crate::process;
+    use crate::settings::{self, BackupOptions, BackupTrigger};
+    use crate::state::{self, AuditEntry};
+
+    /// Enough for any sensible script, while stopping a runaway loop
+    const MAX_OPERATIONS: u64 = 10_000_000;
+
+    type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+    fn script_error(e: impl std::fmt::Display) -> Box<EvalAltResult> {
+        e.to_string().into()
+    }
+
+    fn audit(action: &str, paths: &[PathBuf]) -> ScriptResult<()> {
+        state::record_audit(&AuditEntry::new(action, paths)).map_err(script_error)
+    }
+
+    fn path_strings(paths: impl IntoIterator<Item = PathBuf>) -> Array {
+        paths
+            .into_iter()
+            .map(|p| Dynamic::from(p.to_string_lossy().to_string()))
+            .collect()
+    }
+
+    fn prefixes() -> ScriptResult<Array> {
+        let mut found: Vec<PathBuf> = process::detect_eve_prefixes()
+            .map_err(script_error)?
+            .into_iter()
+            .map(|p| p.path)
+            .collect();
+        for prefix in process::steam_library_prefixes() {
+            if !found.contains(&prefix) {
+                found.push(prefix);
+            }
+        }
+        Ok(path_strings(found))
+    }
+
+    fn profiles(prefix: &str) -> ScriptResult<Array> {
+        let dirs = process::find_profile_dirs(Path::new(prefix)).map_err(script_error)?;
+        Ok(path_strings(dirs))
+    }
+
+    fn file_map(file: CharacterFile) -> Dynamic {
+        let mut map = Map::new();
+        map.insert(
+            "path".into(),
+            file.path.to_string_lossy().to_string().into(),
+        );
+        map.insert("id".into(), (file.character_id as i64).into());
+        let kind = match file.file_type {
+            FileType::Character => "character",
+            FileType::User => "user",
+            FileType::Launcher => "launcher",
+        };
+        map.insert("kind".into(), kind.into());
+        map.insert("is_default".into(), file.is_default.into());
+        map.into()
+    }
+
+    fn characters(profile: &str) -> ScriptResult<Array> {
+        let files =
+            discovery::discover_character_files(Path::new(profile), &IgnoreRules::default())
+                .map_err(script_error)?;
+        Ok(files.into_iter().map(file_map).collect())
+    }
+
+    fn backup(profile: &str, options: &BackupOptions) -> ScriptResult<String> {
+        let backup = settings::create_backup(Path::new(profile), BackupTrigger::Manual, options)
+            .map_err(script_error)?;
+        audit("Script backup", std::slice::from_ref(&backup))?;
+        Ok(backup.to_string_lossy().to_string())
+    }
+
+    fn settings_file(path: &str) -> ScriptResult<CharacterFile> {
+        discovery::settings_file(PathBuf::from(path))
+            .filter(|f| f.path.exists())
+            .ok_or_else(|| script_error(format!("Not a settings file: {}", path)))
+    }
+
+    fn sync_files(
+        source: &str,
+        targets: Array,
+        dry_run: bool,
+        options: &BackupOptions,
+    ) -> ScriptResult<Array> {
+        let source = settings_file(source)?;
+        let targets = targets
+            .into_iter()
+            .map(|t| {
+                let path = t
+                    .into_string()
+                    .map_err(|kind| script_error(format!("Target is a {}, not a path", kind)))?;
+                let target = settings_file(&path)?;
+                if target.file_type != source.file_type {
+                    return Err(script_error(format!(
+                        "Target is a different file type: {}",
+                        path
+                    )));
+                }
+                Ok(target)
+            })
+            .collect::<ScriptResult<Vec<CharacterFile>>>()?;
+        let targets: Vec<&CharacterFile> = targets.iter().collect();
+
+        let results = if dry_run {
+            settings::sync_settings(&source, &targets, true).map_err(script_error)?
+        } else {
+            // There is no confirmation to warn in, so what it would warn about refuses
+            let paths: Vec<PathBuf> = targets.iter().map(|t| t.path.clone()).collect();
+            let running: Vec<PathBuf> = process::detect_eve_prefixes()
+                .map_err(script_error)?
+                .into_iter()
+                .map(|p| p.path)
+                .collect();
+            preflight::guard(&source.path, &paths, &running).map_err(script_error)?;
+
+            let mut backups = Vec::new();
+            let synced = journal::sync(
+                &source,
+                &targets,
+                &[],
+                BackupTrigger::PreSync,
+                options,
+                |_, backup, _| backups.push(backup.to_path_buf()),
+                |_, _| {},
+            );
+            for backup in backups {
+                audit("Script backup", &[backup])?;
+            }
+            synced
+                .map_err(|e| script_error(format!("{:#}", e)))?
+                .sync
+                .results
+        };
+        if !dry_run {
+            let written: Vec<PathBuf> = std::iter::once(source.path.clone())
+                .chain(
+                    results
+                        .iter()
+                        .filter(|r| r.success)
+                        .map(|r| r.target_file.clone()),
+                )
+                .collect();
+            audit("Script sync", &written)?;
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|r| {
+                let mut map = Map::new();
+                map.insert(
+                    "target".into(),
+                    r.target_file.to_string_lossy().to_string().into(),
+                );
+                map.insert("success".into(), r.success.into());
+                map.insert("message".into(), r.message.into());
+                Dynamic::from(map)
+            })
+            .collect())
+    }
+
+    /// Runs a script against the core API, naming and pruning the backups it takes
+    /// by `backup_options`.
+    pub fn run_script(script: &str, backup_options: &BackupOptions) -> ScriptRun {
+        let output = Arc::new(Mutex::new(Vec::new()));
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let printed = Arc::clone(&output);
+        engine.on_print(move |line| printed.lock().unwrap().push(line.to_string()));
+        let debugged = Arc::clone(&output);
+        engine.on_debug(move |line, _, _| debugged.lock().unwrap().push(line.to_string()));
+
+        let backup_with = backup_options.clone();
+        let sync_with = backup_options.clone();
+        engine
+            .register_fn("prefixes", prefixes)
+            .register_fn("profiles", profiles)
+            .register_fn("characters", characters)
+            .register_fn("backup", move |profile: &str| backup(profile, &backup_with))
+            .register_fn(
+                "sync_files",
+                move |source: &str, targets: Array, dry_run: bool| {
+                    sync_files(source, targets, dry_run, &sync_with)
+                },
+            );
+
+        let result = engine
+            .eval::<Dynamic>(script)
+            .map(|value| {
+                if value.is_unit() {
+                    String::new()
+                } else {
+                    value.to_string()
+                }
+            })
+            .map_err(|e| e.to_string());
+
+        let output = output.lock().unwrap().clone();
+        ScriptRun { output, result }
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_run_script() -> Result<()> {
+        let dir = tempdir()?;
+        let profile = dir.path().join("settings_Default");
+        fs::create_dir(&profile)?;
+        fs::write(profile.join("core_char_1.dat"), "main")?;
+        fs::write(profile.join("core_char_2.dat"), "alt")?;
+        fs::write(profile.join("core_user_3.dat"), "account")?;
+
+        let script = format!(
+            r#"
+            let files = characters("{}");
+            let chars = files.filter(|f| f.kind == "character");
+            print(`${{chars.len()}} characters`);
+            let results = sync_files(chars[0].path, [chars[1].path], true);
+            results[0].message
+            "#,
+            profile.display()
+        );
+        let run = run_script(&script, &BackupOptions::default());
+        assert_eq!(run.output, vec!["2 characters"]);
+        assert_eq!(run.result, Ok("Would copy".to_string()));
+        assert_eq!(fs::read_to_string(profile.join("core_char_2.dat"))?, "alt");
+
+        let mismatched = format!(
+            r#"sync_files("{0}/core_char_1.dat", ["{0}/core_user_3.dat"], true)"#,
+            profile.display()
+        );
+        assert!(run_script(&mismatched, &BackupOptions::default())
+            .result
+            .is_err_and(|e| e.contains("different file type")));
+        assert!(run_script("loop {}", &BackupOptions::default())
+            .result
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,1640 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::discovery::{self, CharacterFile, IgnoreRules, BACKUP_INFIX, BACKUP_MARKER};
+use crate::inspect::{self, TreeDiff};
+use crate::journal::{Step, SyncJournal};
+use crate::marshal;
+use crate::plugins::{self, Plugin};
+use crate::process;
+use crate::state::{self, AuditEntry};
+
+/// Result of a sync operation.
+#[derive(Debug, Clone)]
+pub struct SyncResult {
+    pub target_file: PathBuf,
+    pub success: bool,
+    pub message: String,
+}
+
+/// Layout of the time in backup and other generated file names. Older backups were
+/// named in local time, without the trailing `Z`.
+const NAME_STAMP_FORMAT: &str = "%Y%m%d_%H%M%SZ";
+
+/// The current time for a file or folder name. UTC, so names sort in the order
+/// they were made even across a time zone change.
+pub fn name_stamp() -> String {
+    Utc::now().format(NAME_STAMP_FORMAT).to_string()
+}
+
+/// How backups have always been named: `settings_Default_backup_20260301_120000Z`
+pub const DEFAULT_BACKUP_NAME_FORMAT: &str = "{profile}_backup_{date}_{time}Z";
+
+/// Tokens a backup name format can use
+pub const BACKUP_NAME_TOKENS: [&str; 5] = ["{profile}", "{date}", "{time}", "{trigger}", "{note}"];
+
+/// How new backups are named and how many are kept, as set in the config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackupOptions {
+    /// An empty or invalid format falls back to [`DEFAULT_BACKUP_NAME_FORMAT`]
+    pub name_format: String,
+    /// Applied after every backup
+    pub retention: BackupRetention,
+}
+
+impl BackupOptions {
+    fn name_format(&self) -> &str {
+        if validate_backup_name_format(&self.name_format).is_ok() {
+            &self.name_format
+        } else {
+            DEFAULT_BACKUP_NAME_FORMAT
+        }
+    }
+}
+
+/// Checks that `format` only uses known tokens and makes a single folder name.
+pub fn validate_backup_name_format(format: &str) -> Result<()> {
+    if format.trim().is_empty() {
+        bail!("The backup name format is empty");
+    }
+    if format.contains(['/', '\\']) {
+        bail!("A backup name can't contain / or \\");
+    }
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .map(|end| start + end + 1)
+            .context("A { in the backup name format is never closed")?;
+        let token = &rest[start..end];
+        if !BACKUP_NAME_TOKENS.contains(&token) {
+            bail!(
+                "Unknown token {}; use {}",
+                token,
+                BACKUP_NAME_TOKENS.join(", ")
+            );
+        }
+        rest = &rest[end..];
+    }
+    Ok(())
+}
+
+/// What caused a backup to be made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupTrigger {
+    /// Asked for by the user or a script
+    Manual,
+    /// Taken automatically before a sync or any other change the app makes
+    PreSync,
+    /// Taken automatically before a restore overwrites the profile
+    PreRestore,
+    /// Taken before a preset's scheduled run
+    Scheduled,
+}
+
+impl BackupTrigger {
+    /// Every trigger, in the order the backup filter and retention settings list them
+    pub const ALL: [BackupTrigger; 4] = [
+        BackupTrigger::Manual,
+        BackupTrigger::PreSync,
+        BackupTrigger::PreRestore,
+        BackupTrigger::Scheduled,
+    ];
+
+    /// Name shown in the backup filter and retention settings
+    pub fn name(self) -> &'static str {
+        match self {
+            BackupTrigger::Manual => "Manual",
+            BackupTrigger::PreSync => "Pre-sync",
+            BackupTrigger::PreRestore => "Pre-restore",
+            BackupTrigger::Scheduled => "Scheduled",
+        }
+    }
+
+    /// The trigger as it appears in backup names
+    pub fn tag(self) -> &'static str {
+        match self {
+            BackupTrigger::Manual => "manual",
+            BackupTrigger::PreSync => "pre-sync",
+            BackupTrigger::PreRestore => "pre-restore",
+            BackupTrigger::Scheduled => "scheduled",
+        }
+    }
+}
+
+/// A backup's name for a profile `profile` made at `time`. Characters in the note
+/// that don't belong in a file name become dashes; without a note, `{note}` is
+/// left empty.
+pub fn format_backup_name(
+    format: &str,
+    profile: &str,
+    time: DateTime<Utc>,
+    trigger: BackupTrigger,
+    note: Option<&str>,
+) -> String {
+    let note: String = note
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || "-_.".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format
+        .replace("{date}", &time.format("%Y%m%d").to_string())
+        .replace("{time}", &time.format("%H%M%S").to_string())
+        .replace("{trigger}", trigger.tag())
+        .replace("{note}", &note)
+        .replace("{profile}", profile)
+}
+
+/// Contents of the marker written into every backup
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupMarker {
+    /// RFC 3339
+    created: String,
+    /// Name of the settings directory the backup was taken of
+    profile: String,
+    /// None for backups made before triggers were recorded
+    #[serde(default)]
+    trigger: Option<BackupTrigger>,
+    /// What the user wrote when taking the backup by hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+impl BackupMarker {
+    /// Reads a backup's marker. Markers written before backup names could be
+    /// chosen hold only the time.
+    fn read(backup: &Path) -> Option<Self> {
+        let text = fs::read_to_string(backup.join(BACKUP_MARKER)).ok()?;
+        serde_json::from_str(&text).ok().or_else(|| {
+            Some(Self {
+                created: text.trim().to_string(),
+                profile: String::new(),
+                trigger: None,
+                note: None,
+            })
+        })
+    }
+}
+
+/// What made a backup, if it was recorded.
+pub fn backup_trigger(backup: &Path) -> Option<BackupTrigger> {
+    BackupMarker::read(backup)?.trigger
+}
+
+/// The note a backup was taken with, if any.
+pub fn backup_note(backup: &Path) -> Option<String> {
+    BackupMarker::read(backup)?.note
+}
+
+/// How many backups with each trigger to keep per profile, newest first.
+/// Triggers left out keep every backup.
+pub type BackupRetention = BTreeMap<BackupTrigger, usize>;
+
+/// Deletes the oldest backups of `settings_dir` beyond what `retention` keeps for
+/// their trigger; the newest of each trigger is always kept. Backups without a
+/// recorded trigger are never deleted. Returns the backups deleted.
+pub fn prune_backups(settings_dir: &Path, retention: &BackupRetention) -> Result<Vec<PathBuf>> {
+    let mut seen: HashMap<BackupTrigger, usize> = HashMap::new();
+    let mut removed = Vec::new();
+    for backup in list_backups(settings_dir)? {
+        let Some(trigger) = backup_trigger(&backup) else {
+            continue;
+        };
+        let Some(&keep) = retention.get(&trigger) else {
+            continue;
+        };
+        let count = seen.entry(trigger).or_default();
+        *count += 1;
+        if *count > keep.max(1) {
+            fs::remove_dir_all(&backup)
+                .with_context(|| format!("Failed to remove {}", backup.display()))?;
+            removed.push(backup);
+        }
+    }
+    Ok(removed)
+}
+
+/// Appended to a settings directory's name, hidden, for its lock file. The lock
+/// sits next to the directory so backups and restores never copy or delete it.
+const LOCK_SUFFIX: &str = ".pack-preferences.lock";
+
+/// A lock whose holder cannot be checked is taken over after this long
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Advisory lock on a settings directory, held while it is being changed so the
+/// GUI, a command-line run and other instances never interleave backups and
+/// syncs. Released when dropped.
+#[derive(Debug)]
+pub struct DirLock {
+    path: PathBuf,
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub(crate) fn lock_path(settings_dir: &Path) -> Result<PathBuf> {
+    let parent = settings_dir
+        .parent()
+        .context("Settings directory has no parent")?;
+    let name = settings_dir
+        .file_name()
+        .context("Settings directory has no name")?
+        .to_string_lossy();
+    Ok(parent.join(format!(".{}{}", name, LOCK_SUFFIX)))
+}
+
+/// Whether a lock left by process `pid` at `written` can be taken over: its
+/// process has exited, or, where that cannot be checked, it is old.
+pub(crate) fn lock_is_stale(pid: Option<u32>, written: Option<SystemTime>) -> bool {
+    let proc = Path::new("/proc");
+    if let Some(pid) = pid.filter(|_| proc.is_dir()) {
+        return !proc.join(pid.to_string()).exists();
+    }
+    written
+        .and_then(|t| t.elapsed().ok())
+        .is_none_or(|age| age > STALE_LOCK_AGE)
+}
+
+/// What a lock file taken now holds: this process's ID and the time.
+pub(crate) fn lock_contents() -> String {
+    format!("{}\n{}\n", std::process::id(), Local::now().to_rfc3339())
+}
+
+/// Locks `settings_dir`, taking over a lock its holder left behind. Fails if
+/// another operation, in this process or another, holds it.
+pub fn lock_dir(settings_dir: &Path) -> Result<DirLock> {
+    let path = lock_path(settings_dir)?;
+    for _ in 0..2 {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                let _ = file.write_all(lock_contents().as_bytes());
+                return Ok(DirLock { path });
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let contents = fs::read_to_string(&path).unwrap_or_default();
+                let mut lines = contents.lines();
+                let pid = lines.next().and_then(|l| l.trim().parse().ok());
+                let since = lines.next().unwrap_or("an unknown time").trim().to_string();
+                let written = path.metadata().and_then(|m| m.modified()).ok();
+                if !lock_is_stale(pid, written) {
+                    let holder = match pid {
+                        Some(pid) if pid == std::process::id() => "another operation".to_string(),
+                        Some(pid) => format!("Pack Preferences (process {})", pid),
+                        None => "another program".to_string(),
+                    };
+                    bail!(
+                        "{} is in use by {} since {}; try again when it finishes",
+                        settings_dir.display(),
+                        holder,
+                        since
+                    );
+                }
+                let _ = fs::remove_file(&path);
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to create {}", path.display()))
+            }
+        }
+    }
+    bail!("Could not lock {}", settings_dir.display())
+}
+
+/// Locks every directory in `dirs`, in order. All are released if any is busy.
+pub(crate) fn lock_dirs<'a>(dirs: impl IntoIterator<Item = &'a Path>) -> Result<Vec<DirLock>> {
+    let dirs: BTreeSet<&Path> = dirs.into_iter().collect();
+    dirs.into_iter().map(lock_dir).collect()
+}
+
+/// Returns the path a backup created now would be written to, named by the
+/// format in `options`. Backups sit next to the directory they were taken of.
+pub fn next_backup_path(
+    settings_dir: &Path,
+    trigger: BackupTrigger,
+    note: Option<&str>,
+    options: &BackupOptions,
+) -> Result<PathBuf> {
+    let parent = settings_dir
+        .parent()
+        .context("Settings directory has no parent")?;
+
+    let dir_name = settings_dir
+        .file_name()
+        .context("Settings directory has no name")?
+        .to_string_lossy();
+
+    let backup_name =
+        format_backup_name(options.name_format(), &dir_name, Utc::now(), trigger, note);
+    if matches!(backup_name.trim(), "" | "." | "..") {
+        bail!("The backup name format gives an empty name");
+    }
+    let mut backup_path = parent.join(&backup_name);
+
+    // Two backups within the same second (e.g. restore's safety backup) must not collide
+    let mut suffix = 2;
+    while backup_path.exists() {
+        backup_path = parent.join(format!("{}_{}", backup_name, suffix));
+        suffix += 1;
+    }
+
+    Ok(backup_path)
+}
+
+/// Post-sync verification of a single target file.
+#[derive(Debug, Clone)]
+pub struct VerificationEntry {
+    pub target_file: PathBuf,
+    /// SHA-256 of the target after the sync, or None if it could not be read
+    pub hash: Option<String>,
+    pub matches_source: bool,
+    /// Copy of the target's previous contents inside the pre-sync backup
+    pub backup_file: Option<PathBuf>,
+}
+
+/// Returns the hex-encoded SHA-256 hash of a file.
+pub fn file_hash(path: &Path) -> Result<String> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    Ok(hex_digest(&data))
+}
+
+fn hex_digest(data: &[u8]) -> String {
+    let digest = Sha256::digest(data);
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Re-reads every successfully synced target and compares it against the source.
+/// `backups` maps each settings directory to the backup taken before the sync.
+pub fn verify_sync(
+    source: &Path,
+    results: &[SyncResult],
+    backups: &HashMap<PathBuf, PathBuf>,
+) -> Vec<VerificationEntry> {
+    let source_hash = file_hash(source).ok();
+    verify_targets(results, backups, |_| source_hash.clone())
+}
+
+/// Like [`verify_sync`] for a sync that ran plugins: each target is compared against
+/// the hash of what the plugins produced for it rather than the source.
+pub fn verify_transformed_sync(
+    sync: &TransformedSync,
+    backups: &HashMap<PathBuf, PathBuf>,
+) -> Vec<VerificationEntry> {
+    verify_targets(&sync.results, backups, |target| {
+        sync.written.get(target).cloned()
+    })
+}
+
+fn verify_targets(
+    results: &[SyncResult],
+    backups: &HashMap<PathBuf, PathBuf>,
+    expected_hash: impl Fn(&Path) -> Option<String>,
+) -> Vec<VerificationEntry> {
+    results
+        .iter()
+        .filter(|r| r.success)
+        .map(|r| {
+            let source_hash = expected_hash(&r.target_file);
+            let hash = file_hash(&r.target_file).ok();
+            let backup_file = r
+                .target_file
+                .parent()
+                .and_then(|dir| backups.get(dir))
+                .zip(r.target_file.file_name())
+                .map(|(dir, name)| dir.join(name))
+                .filter(|p| p.exists());
+
+            VerificationEntry {
+                target_file: r.target_file.clone(),
+                matches_source: hash.is_some() && hash == source_hash,
+                hash,
+                backup_file,
+            }
+        })
+        .collect()
+}
+
+/// Formats verification entries as a plain-text sync report.
+pub fn format_sync_report(source: &Path, entries: &[VerificationEntry]) -> String {
+    let mut report = format!(
+        "Pack Preferences sync report - {}\nSource: {}\n\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        source.display()
+    );
+
+    for entry in entries {
+        report.push_str(&format!(
+            "{}\n  hash: {}\n  matches source: {}\n  previous version: {}\n",
+            entry.target_file.display(),
+            entry.hash.as_deref().unwrap_or("unreadable"),
+            if entry.matches_source { "yes" } else { "NO" },
+            entry
+                .backup_file
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "none".to_string())
+        ));
+    }
+
+    report
+}
+
+/// Creates a backup of the settings directory.
+pub fn create_backup(
+    settings_dir: &Path,
+    trigger: BackupTrigger,
+    options: &BackupOptions,
+) -> Result<PathBuf> {
+    create_noted_backup(settings_dir, trigger, None, options)
+}
+
+/// [`create_backup`] with a note saying why it was taken, kept in its marker and
+/// available to the name format as `{note}`. A blank note counts as none.
+pub fn create_noted_backup(
+    settings_dir: &Path,
+    trigger: BackupTrigger,
+    note: Option<&str>,
+    options: &BackupOptions,
+) -> Result<PathBuf> {
+    let _lock = lock_dir(settings_dir)?;
+    backup_locked(settings_dir, trigger, note, options)
+}
+
+/// Contents of the marker for a backup of `settings_dir` taken now.
+pub(crate) fn backup_marker(
+    settings_dir: &Path,
+    trigger: BackupTrigger,
+    note: Option<&str>,
+) -> Result<String> {
+    let marker = BackupMarker {
+        created: Local::now().to_rfc3339(),
+        profile: settings_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        trigger: Some(trigger),
+        note: note.map(str::to_string),
+    };
+    serde_json::to_string(&marker).context("Failed to serialize backup marker")
+}
+
+/// [`create_noted_backup`] for a caller that holds the directory's lock.
+pub(crate) fn backup_locked(
+    settings_dir: &Path,
+    trigger: BackupTrigger,
+    note: Option<&str>,
+    options: &BackupOptions,
+) -> Result<PathBuf> {
+    let note = note.map(str::trim).filter(|n| !n.is_empty());
+    let backup_path = next_backup_path(settings_dir, trigger, note, options)?;
+
+    copy_dir_recursive(settings_dir, &backup_path)?;
+
+    // Mark the copy so discovery never mistakes it for a live profile, and so it
+    // is found whatever it was named
+    fs::write(
+        backup_path.join(BACKUP_MARKER),
+        backup_marker(settings_dir, trigger, note)?,
+    )
+    .context("Failed to write backup marker")?;
+
+    // The new backup is in place, so failing to tidy older ones is no reason to
+    // fail the operation it protects
+    if !options.retention.is_empty() {
+        if let Ok(removed) = prune_backups(settings_dir, &options.retention) {
+            if !removed.is_empty() {
+                let _ = state::record_audit(&AuditEntry::new("Prune backups", &removed));
+            }
+        }
+    }
+
+    Ok(backup_path)
+}
+
+/// When a backup was made: the time in its marker, or failing that the one in
+/// its name.
+pub fn backup_created(backup: &Path) -> Option<DateTime<Local>> {
+    if let Some(time) =
+        BackupMarker::read(backup).and_then(|m| DateTime::parse_from_rfc3339(&m.created).ok())
+    {
+        return Some(time.with_timezone(&Local));
+    }
+    let name = backup.file_name()?.to_string_lossy();
+    let stamp = name.split(BACKUP_INFIX).nth(1)?;
+    let naive = NaiveDateTime::parse_from_str(stamp.get(..15)?, "%Y%m%d_%H%M%S").ok()?;
+    if stamp[15..].starts_with('Z') {
+        Some(Utc.from_utc_datetime(&naive).with_timezone(&Local))
+    } else {
+        Local.from_local_datetime(&naive).earliest()
+    }
+}
+
+/// Copies a directory recursively.
+pub(crate) fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+    copy_tree(src, dst, on_network_fs(dst.parent().unwrap_or(dst)))
+}
+
+/// [`copy_dir_recursive`] with the network filesystem check already made.
+fn copy_tree(src: &Path, dst: &Path, network: bool) -> Result<()> {
+    fs::create_dir_all(dst)?;
+
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+
+        if src_path.is_dir() {
+            copy_tree(&src_path, &dst_path, network)?;
+        } else {
+            copy_contents(&src_path, &dst_path, network)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of files under `dir`, counting into subdirectories.
+pub fn count_files(dir: &Path) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            if entry.path().is_dir() {
+                count_files(&entry.path())
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+/// Lists available backups for a settings directory.
+pub fn list_backups(settings_dir: &Path) -> Result<Vec<PathBuf>> {
+    let parent = settings_dir
+        .parent()
+        .context("Settings directory has no parent")?;
+
+    let dir_name = settings_dir
+        .file_name()
+        .context("Settings directory has no name")?
+        .to_string_lossy();
+
+    let backup_prefix = format!("{}{}", dir_name, BACKUP_INFIX);
+
+    let mut backups = Vec::new();
+
+    for entry in fs::read_dir(parent)? {
+        let entry = entry?;
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        // Backups in the default format are known by name; others by their marker
+        let name = entry.file_name().to_string_lossy().to_string();
+        let marked = || BackupMarker::read(&path).is_some_and(|m| m.profile == dir_name);
+        if name.starts_with(&backup_prefix) || marked() {
+            backups.push(path);
+        }
+    }
+
+    // Newest first; a custom name need not sort by time
+    backups.sort_by_cached_key(|b| std::cmp::Reverse((backup_created(b), b.clone())));
+
+    Ok(backups)
+}
+
+/// Returns the newest backup worth restoring if the settings directory looks reset:
+/// it holds no character or account files of its own (only defaults, or nothing),
+/// while a backup of it does. This is what a recreated Proton prefix or a client
+/// that lost its settings leaves behind.
+pub fn find_reset_backup(settings_dir: &Path) -> Result<Option<PathBuf>> {
+    let has_own_files = |files: Vec<CharacterFile>| files.iter().any(|f| !f.is_default);
+
+    if has_own_files(discovery::discover_character_files(
+        settings_dir,
+        &IgnoreRules::default(),
+    )?) {
+        return Ok(None);
+    }
+
+    for backup in list_backups(settings_dir)? {
+        if has_own_files(discovery::discover_backup_files(&backup)?) {
+            return Ok(Some(backup));
+        }
+    }
+    Ok(None)
+}
+
+/// Restores a backup to the settings directory, backing it up first. Both steps
+/// go into `journal` before they are taken.
+pub fn restore_backup(
+    backup_path: &Path,
+    settings_dir: &Path,
+    journal: &mut SyncJournal,
+) -> Result<()> {
+    let _lock = lock_dir(settings_dir)?;
+
+    // First, create a backup of current state
+    journal.backup_locked(settings_dir, BackupTrigger::PreRestore)?;
+
+    journal.touch(settings_dir)?;
+    restore_contents(backup_path, settings_dir)
+}
+
+/// Replaces everything in `settings_dir` with the contents of `backup_path`, for a
+/// caller that holds the directory's lock.
+pub(crate) fn restore_contents(backup_path: &Path, settings_dir: &Path) -> Result<()> {
+    // Remove current settings directory contents
+    for entry in fs::read_dir(settings_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    // Copy backup contents to settings directory
+    let network = on_network_fs(settings_dir);
+    for entry in fs::read_dir(backup_path)? {
+        let entry = entry?;
+        if entry.file_name() == BACKUP_MARKER {
+            continue;
+        }
+        let src_path = entry.path();
+        let dst_path = settings_dir.join(entry.file_name());
+
+        if src_path.is_dir() {
+            copy_tree(&src_path, &dst_path, network)?;
+        } else {
+            copy_contents(&src_path, &dst_path, network)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// How a file in a backup compares with the live file it would replace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupComparison {
+    pub backup_size: u64,
+    /// None if the profile no longer has this file
+    pub live_size: Option<u64>,
+    /// Offset of the first differing byte, or None if the files are identical
+    pub first_difference: Option<u64>,
+    /// What restoring would change, key by key: added keys are in the backup only.
+    /// None if either file could not be decoded.
+    pub keys: Option<TreeDiff>,
+}
+
+/// Compares a file inside a backup with the same file in the live profile.
+pub fn compare_with_live(backup_file: &Path, settings_dir: &Path) -> Result<BackupComparison> {
+    let backup =
+        fs::read(backup_file).with_context(|| format!("Failed to read {:?}", backup_file))?;
+    let name = backup_file.file_name().context("Backup file has no name")?;
+    let live = fs::read(settings_dir.join(name)).ok();
+
+    let first_difference = match &live {
+        None => Some(0),
+        Some(live) => backup
+            .iter()
+            .zip(live)
+            .position(|(a, b)| a != b)
+            .or((backup.len() != live.len()).then(|| backup.len().min(live.len())))
+            .map(|offset| offset as u64),
+    };
+
+    let keys = match (
+        marshal::decode(&backup),
+        live.as_deref().map(marshal::decode),
+    ) {
+        (Ok(backup_tree), Some(Ok(live_tree))) => Some(inspect::diff(&live_tree, &backup_tree)),
+        _ => None,
+    };
+
+    Ok(BackupComparison {
+        backup_size: backup.len() as u64,
+        live_size: live.map(|l| l.len() as u64),
+        first_difference,
+        keys,
+    })
+}
+
+/// Restores a single file from a backup into the live profile, backing the profile
+/// up first. Both steps go into `journal`. Returns the path of that safety backup.
+pub fn restore_backup_file(
+    backup_file: &Path,
+    settings_dir: &Path,
+    journal: &mut SyncJournal,
+) -> Result<PathBuf> {
+    let name = backup_file.file_name().context("Backup file has no name")?;
+    let _lock = lock_dir(settings_dir)?;
+    let safety_backup = journal.backup_locked(settings_dir, BackupTrigger::PreRestore)?;
+    copy_file_atomic(backup_file, &settings_dir.join(name), Some(journal))?;
+    Ok(safety_backup)
+}
+
+/// Syncs settings from a source character to target characters.
+/// If dry_run is true, returns what would be done without modifying files.
+pub fn sync_settings(
+    source: &CharacterFile,
+    targets: &[&CharacterFile],
+    dry_run: bool,
+) -> Result<Vec<SyncResult>> {
+    sync_settings_with_progress(source, targets, dry_run, None, |_, _| {})
+}
+
+/// Like [`sync_settings`], calling `progress(done, total)` before each target and once
+/// more when all are done. Each target's staging and rename go into `journal` first.
+pub fn sync_settings_with_progress(
+    source: &CharacterFile,
+    targets: &[&CharacterFile],
+    dry_run: bool,
+    mut journal: Option<&mut SyncJournal>,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<Vec<SyncResult>> {
+    let _locks = if dry_run {
+        Vec::new()
+    } else {
+        lock_dirs(targets.iter().map(|t| t.settings_dir()))?
+    };
+    let network = if dry_run {
+        HashMap::new()
+    } else {
+        network_dirs(targets)
+    };
+    let mut results = Vec::new();
+
+    for (done, target) in targets.iter().enumerate() {
+        progress(done, targets.len());
+        // Only sync matching file types (char to char, user to user)
+        if source.file_type != target.file_type {
+            continue;
+        }
+
+        let result = if dry_run {
+            SyncResult {
+                target_file: target.path.clone(),
+                success: true,
+                message: "Would copy".to_string(),
+            }
+        } else {
+            let copied = replace_file(&target.path, journal.as_deref_mut(), |tmp_path| {
+                copy_contents(&source.path, tmp_path, network[target.settings_dir()])
+                    .with_context(|| format!("Failed to copy to temp file: {:?}", tmp_path))?;
+                Ok(())
+            });
+            match copied {
+                Ok(()) => SyncResult {
+                    target_file: target.path.clone(),
+                    success: true,
+                    message: "Copied successfully".to_string(),
+                },
+                Err(e) => SyncResult {
+                    target_file: target.path.clone(),
+                    success: false,
+                    message: format!("Failed: {}", e),
+                },
+            }
+        };
+
+        results.push(result);
+    }
+    progress(targets.len(), targets.len());
+
+    Ok(results)
+}
+
+/// Result of a sync that passed the source through plugins on its way to each target
+#[derive(Debug, Default)]
+pub struct TransformedSync {
+    pub results: Vec<SyncResult>,
+    /// SHA-256 of what was written to each target
+    pub written: HashMap<PathBuf, String>,
+}
+
+/// Like [`sync_settings_with_progress`], running `plugins` in order on the source's
+/// contents for every target and writing their output instead. Plugins also run on
+/// a dry run, so a failing plugin shows up before anything is written. Without
+/// plugins this is a plain sync.
+pub fn sync_settings_transformed(
+    source: &CharacterFile,
+    targets: &[&CharacterFile],
+    dry_run: bool,
+    plugins: &[Plugin],
+    mut journal: Option<&mut SyncJournal>,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<TransformedSync> {
+    if plugins.is_empty() {
+        return Ok(TransformedSync {
+            results: sync_settings_with_progress(source, targets, dry_run, journal, progress)?,
+            written: HashMap::new(),
+        });
+    }
+
+    let _locks = if dry_run {
+        Vec::new()
+    } else {
+        lock_dirs(targets.iter().map(|t| t.settings_dir()))?
+    };
+    let network = if dry_run {
+        HashMap::new()
+    } else {
+        network_dirs(targets)
+    };
+    let mut sync = TransformedSync::default();
+    for (done, target) in targets.iter().enumerate() {
+        progress(done, targets.len());
+        if source.file_type != target.file_type {
+            continue;
+        }
+
+        let outcome = plugins::apply(plugins, source, target).and_then(|data| {
+            if !dry_run {
+                replace_file(&target.path, journal.as_deref_mut(), |tmp_path| {
+                    write_contents(tmp_path, &data, network[target.settings_dir()])
+                        .with_context(|| format!("Failed to write temp file: {:?}", tmp_path))
+                })?;
+                sync.written.insert(target.path.clone(), hex_digest(&data));
+            }
+            Ok(())
+        });
+        sync.results.push(match outcome {
+            Ok(()) if dry_run => SyncResult {
+                target_file: target.path.clone(),
+                success: true,
+                message: format!("Would copy through {} plugin(s)", plugins.len()),
+            },
+            Ok(()) => SyncResult {
+                target_file: target.path.clone(),
+                success: true,
+                message: format!("Copied through {} plugin(s)", plugins.len()),
+            },
+            Err(e) => SyncResult {
+                target_file: target.path.clone(),
+                success: false,
+                message: format!("Failed: {:#}", e),
+            },
+        });
+    }
+    progress(targets.len(), targets.len());
+
+    Ok(sync)
+}
+
+/// Copies a settings file to a new ID in the same profile, e.g. for an alt that has
+/// not logged in yet. A file the ID already has is never overwritten. The new file
+/// goes into `journal` before it is written.
+pub fn duplicate_as(
+    source: &CharacterFile,
+    new_id: u64,
+    dry_run: bool,
+    journal: Option<&mut SyncJournal>,
+) -> Result<SyncResult> {
+    let name = discovery::settings_file_name(source.file_type, new_id)
+        .context("Launcher files can't be duplicated")?;
+    let target_file = source.settings_dir().join(name);
+    if target_file.exists() {
+        bail!("{} already exists", target_file.display());
+    }
+
+    if dry_run {
+        return Ok(SyncResult {
+            target_file,
+            success: true,
+            message: "Would create".to_string(),
+        });
+    }
+
+    let _lock = lock_dir(source.settings_dir())?;
+    copy_file_atomic(&source.path, &target_file, journal)?;
+    Ok(SyncResult {
+        target_file,
+        success: true,
+        message: "Created".to_string(),
+    })
+}
+
+/// [`duplicate_as`] for each of `ids`, e.g. a fresh batch of alts, carrying on
+/// past IDs that fail.
+pub fn duplicate_for_ids(
+    source: &CharacterFile,
+    ids: &[u64],
+    dry_run: bool,
+    mut journal: Option<&mut SyncJournal>,
+) -> Vec<SyncResult> {
+    ids.iter()
+        .map(|&id| {
+            duplicate_as(source, id, dry_run, journal.as_deref_mut()).unwrap_or_else(|e| {
+                SyncResult {
+                    target_file: discovery::settings_file_name(source.file_type, id)
+                        .map(|name| source.settings_dir().join(name))
+                        .unwrap_or_default(),
+                    success: false,
+                    message: format!("Failed: {:#}", e),
+                }
+            })
+        })
+        .collect()
+}
+
+/// Reads a pasted list of IDs separated by whitespace, commas or semicolons, in
+/// order and once each.
+pub fn parse_id_list(text: &str) -> Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for token in text
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|t| !t.is_empty())
+    {
+        match token.parse::<u64>() {
+            Ok(id) if id > 0 => {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            _ => bail!("'{}' is not an ID", token),
+        }
+    }
+    Ok(ids)
+}
+
+/// Copies a settings file into another profile directory under the same filename,
+/// creating it there if the character has no settings in that profile yet. The
+/// target goes into `journal` before it is written.
+pub fn copy_to_profile(
+    source: &CharacterFile,
+    target_dir: &Path,
+    dry_run: bool,
+    journal: Option<&mut SyncJournal>,
+) -> Result<SyncResult> {
+    let file_name = source.path.file_name().context("Source file has no name")?;
+    let target_file = target_dir.join(file_name);
+
+    if source.settings_dir() == target_dir {
+        anyhow::bail!("Source and target profile are the same");
+    }
+
+    let existed = target_file.exists();
+
+    if dry_run {
+        let message = if existed {
+            "Would overwrite"
+        } else {
+            "Would create"
+        };
+        return Ok(SyncResult {
+            target_file,
+            success: true,
+            message: message.to_string(),
+        });
+    }
+
+    let _lock = lock_dir(target_dir)?;
+    Ok(
+        match copy_file_atomic(&source.path, &target_file, journal) {
+            Ok(()) => SyncResult {
+                target_file,
+                success: true,
+                message: if existed { "Overwrote" } else { "Created" }.to_string(),
+            },
+            Err(e) => SyncResult {
+                target_file,
+                success: false,
+                message: format!("Failed: {}", e),
+            },
+        },
+    )
+}
+
+/// Deletes a single settings file after backing up the directory that contains it.
+/// Both steps go into `journal`. Returns the backup path.
+pub fn delete_settings_file(file: &Path, journal: &mut SyncJournal) -> Result<PathBuf> {
+    let settings_dir = file.parent().context("Settings file has no parent")?;
+    let _lock = lock_dir(settings_dir)?;
+    let backup_path = journal.backup_locked(settings_dir, BackupTrigger::PreSync)?;
+
+    journal.touch(file)?;
+    fs::remove_file(file).with_context(|| format!("Failed to remove {:?}", file))?;
+
+    Ok(backup_path)
+}
+
+/// Whether `dir` is on a network filesystem. Looking means reading the mount
+/// table, so operations ask once per destination directory and pass the answer to
+/// [`copy_contents`] and [`write_contents`].
+pub(crate) fn on_network_fs(dir: &Path) -> bool {
+    process::network_filesystem(dir).is_some()
+}
+
+/// [`on_network_fs`] for each directory holding one of `targets`, once each.
+fn network_dirs<'a>(targets: &[&'a CharacterFile]) -> HashMap<&'a Path, bool> {
+    let mut dirs = HashMap::new();
+    for target in targets {
+        let dir = target.settings_dir();
+        dirs.entry(dir).or_insert_with(|| on_network_fs(dir));
+    }
+    dirs
+}
+
+/// Copies `src` to `dst`. When `dst` is on a network filesystem (`network`) the
+/// bytes are read and written out rather than passed to `fs::copy`, which may ask
+/// the server to clone the file, and the copy is flushed before returning so a
+/// rename that follows never swaps in a file the server doesn't fully have yet.
+pub(crate) fn copy_contents(src: &Path, dst: &Path, network: bool) -> std::io::Result<u64> {
+    if !network {
+        return fs::copy(src, dst);
+    }
+
+    let data = fs::read(src)?;
+    write_contents(dst, &data, network)?;
+    Ok(data.len() as u64)
+}
+
+/// Writes `data` to `dst`, flushing it to the server first on a network
+/// filesystem, as [`copy_contents`] does.
+pub(crate) fn write_contents(dst: &Path, data: &[u8], network: bool) -> std::io::Result<()> {
+    if !network {
+        return fs::write(dst, data);
+    }
+
+    let mut file = fs::File::create(dst)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Copies a file atomically using a temporary file and rename, recording both in
+/// `journal` first.
+pub(crate) fn copy_file_atomic(
+    src: &Path,
+    dst: &Path,
+    journal: Option<&mut SyncJournal>,
+) -> Result<()> {
+    let network = on_network_fs(dst.parent().unwrap_or(Path::new(".")));
+    replace_file(dst, journal, |tmp_path| {
+        copy_contents(src, tmp_path, network)
+            .with_context(|| format!("Failed to copy to temp file: {:?}", tmp_path))?;
+        Ok(())
+    })
+}
+
+/// Replaces `dst` atomically: `stage` writes the new contents to the temporary
+/// file, which is then renamed over it. Both steps are recorded in `journal`
+/// before they are taken.
+fn replace_file(
+    dst: &Path,
+    mut journal: Option<&mut SyncJournal>,
+    stage: impl FnOnce(&Path) -> Result<()>,
+) -> Result<()> {
+    let tmp_path = dst.with_extension("tmp");
+    if let Some(journal) = journal.as_deref_mut() {
+        journal.touch(dst)?;
+    }
+    stage(&tmp_path)?;
+
+    if let Some(journal) = journal {
+        journal.record(&Step::Renamed {
+            target: dst.to_path_buf(),
+        })?;
+    }
+    fs::rename(&tmp_path, dst)
+        .with_context(|| format!("Failed to rename temp file to: {:?}", dst))?;
+    Ok(())
+}
+
+/// Writes `data` to `dst` the same way [`copy_file_atomic`] copies a file.
+pub(crate) fn write_file_atomic(
+    data: &[u8],
+    dst: &Path,
+    journal: Option<&mut SyncJournal>,
+) -> Result<()> {
+    let network = on_network_fs(dst.parent().unwrap_or(Path::new(".")));
+    replace_file(dst, journal, |tmp_path| {
+        write_contents(tmp_path, data, network)
+            .with_context(|| format!("Failed to write temp file: {:?}", tmp_path))
+    })
+}
+
+/// Finds temporary files left behind by an interrupted `copy_file_atomic`.
+pub fn find_stale_temp_files(settings_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut stale = Vec::new();
+
+    if !settings_dir.exists() {
+        return Ok(stale);
+    }
+
+    for entry in fs::read_dir(settings_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if path.is_file()
+            && (name.starts_with("core_char_") || name.starts_with("core_user_"))
+            && name.ends_with(".tmp")
+        {
+            stale.push(path);
+        }
+    }
+
+    stale.sort();
+    Ok(stale)
+}
+
+/// Deletes the given temporary files, returning how many were removed.
+pub fn remove_temp_files(files: &[PathBuf]) -> Result<usize> {
+    // A running sync in another instance could still be writing one
+    let _locks = lock_dirs(files.iter().filter_map(|f| f.parent()))?;
+    let mut removed = 0;
+    for path in files {
+        fs::remove_file(path).with_context(|| format!("Failed to remove {:?}", path))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_copy_file_atomic() -> Result<()> {
+        let dir = tempdir()?;
+        let src = dir.path().join("source.txt");
+        let dst = dir.path().join("dest.txt");
+
+        let mut file = File::create(&src)?;
+        file.write_all(b"test content")?;
+
+        copy_file_atomic(&src, &dst, None)?;
+
+        let content = fs::read_to_string(&dst)?;
+        assert_eq!(content, "test content");
+
+        // Temp file should not exist
+        assert!(!dir.path().join("dest.tmp").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dir_lock() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+
+        let lock = lock_dir(&settings_dir)?;
+        assert!(lock_dir(&settings_dir).is_err());
+        assert!(create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default()
+        )
+        .is_err());
+        drop(lock);
+        assert!(!lock_path(&settings_dir)?.exists());
+        create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default(),
+        )?;
+
+        // Left behind by a process that has since exited
+        fs::write(lock_path(&settings_dir)?, format!("{}\n", u32::MAX))?;
+        lock_dir(&settings_dir)?;
+        assert!(!lock_path(&settings_dir)?.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_marker_not_restored() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        fs::write(settings_dir.join("core_char_1.dat"), b"original")?;
+
+        let backup = create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default(),
+        )?;
+        assert!(backup.join(BACKUP_MARKER).exists());
+
+        fs::write(settings_dir.join("core_char_1.dat"), b"changed")?;
+        restore_backup(&backup, &settings_dir, &mut journal::scratch(dir.path()))?;
+
+        assert_eq!(fs::read(settings_dir.join("core_char_1.dat"))?, b"original");
+        assert!(!settings_dir.join(BACKUP_MARKER).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_created() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        let backup = create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default(),
+        )?;
+        assert!(backup.to_string_lossy().ends_with('Z'));
+        assert!(backup_created(&backup).is_some());
+
+        // Without a marker the name is read: UTC if it ends in Z, local otherwise
+        let utc = dir.path().join("settings_Default_backup_20260301_120000Z");
+        let local = dir.path().join("settings_Default_backup_20260301_120000_2");
+        let expected = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(backup_created(&utc), Some(expected.with_timezone(&Local)));
+        assert_eq!(
+            backup_created(&local).map(|t| t.naive_local()),
+            Some(expected.naive_utc())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_name_format() -> Result<()> {
+        let time = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            format_backup_name(
+                DEFAULT_BACKUP_NAME_FORMAT,
+                "settings_Default",
+                time,
+                BackupTrigger::PreSync,
+                None
+            ),
+            "settings_Default_backup_20260301_120000Z"
+        );
+        assert_eq!(
+            format_backup_name(
+                "{date}-{profile}-{trigger}-{note}",
+                "settings_Default",
+                time,
+                BackupTrigger::Manual,
+                Some("before patch/1")
+            ),
+            "20260301-settings_Default-manual-before-patch-1"
+        );
+        assert!(validate_backup_name_format("{date}_{profile}").is_ok());
+        assert!(validate_backup_name_format("{day}").is_err());
+        assert!(validate_backup_name_format("{date").is_err());
+        assert!(validate_backup_name_format("backups/{date}").is_err());
+        assert!(validate_backup_name_format(" ").is_err());
+
+        // Custom names are found by their marker
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        let default = create_noted_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            Some(" trying "),
+            &BackupOptions::default(),
+        )?;
+        assert_eq!(backup_note(&default).as_deref(), Some("trying"));
+        let custom = dir.path().join("nightly-20990101");
+        fs::create_dir(&custom)?;
+        fs::write(
+            custom.join(BACKUP_MARKER),
+            r#"{"created": "2099-01-01T00:00:00Z", "profile": "settings_Default"}"#,
+        )?;
+        let other = dir.path().join("nightly-other");
+        fs::create_dir(&other)?;
+        fs::write(
+            other.join(BACKUP_MARKER),
+            r#"{"created": "2099-01-01T00:00:00Z", "profile": "settings_Other"}"#,
+        )?;
+        assert_eq!(list_backups(&settings_dir)?, vec![custom, default]);
+
+        let options = BackupOptions {
+            name_format: "{profile}-{trigger}".to_string(),
+            ..BackupOptions::default()
+        };
+        let named = create_backup(&settings_dir, BackupTrigger::Manual, &options)?;
+        assert_eq!(named, dir.path().join("settings_Default-manual"));
+        let invalid = BackupOptions {
+            name_format: "{day}".to_string(),
+            ..BackupOptions::default()
+        };
+        let fallback = next_backup_path(&settings_dir, BackupTrigger::Manual, None, &invalid)?;
+        assert!(fallback
+            .to_string_lossy()
+            .contains("settings_Default_backup_"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_backups() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        let untagged = dir.path().join("settings_Default_backup_20200101_000000Z");
+        fs::create_dir(&untagged)?;
+        fs::write(untagged.join(BACKUP_MARKER), "2020-01-01T00:00:00Z")?;
+        let manual = create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default(),
+        )?;
+        let synced: Vec<PathBuf> = (0..3)
+            .map(|_| {
+                create_backup(
+                    &settings_dir,
+                    BackupTrigger::PreSync,
+                    &BackupOptions::default(),
+                )
+            })
+            .collect::<Result<_>>()?;
+        assert_eq!(backup_trigger(&manual), Some(BackupTrigger::Manual));
+        assert_eq!(backup_trigger(&untagged), None);
+
+        let retention =
+            BackupRetention::from([(BackupTrigger::PreSync, 2), (BackupTrigger::Manual, 0)]);
+        assert_eq!(
+            prune_backups(&settings_dir, &retention)?,
+            vec![synced[0].clone()]
+        );
+        assert_eq!(
+            list_backups(&settings_dir)?,
+            vec![synced[2].clone(), synced[1].clone(), manual, untagged]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_reset_backup() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        fs::write(settings_dir.join("core_char_1.dat"), b"main")?;
+        assert_eq!(find_reset_backup(&settings_dir)?, None);
+
+        let backup = create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default(),
+        )?;
+        assert_eq!(find_reset_backup(&settings_dir)?, None);
+
+        // The client recreates only the defaults after a reset
+        fs::remove_file(settings_dir.join("core_char_1.dat"))?;
+        fs::write(settings_dir.join("core_char__.dat"), b"stock")?;
+        assert_eq!(find_reset_backup(&settings_dir)?, Some(backup));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_decoded_backup_file() -> Result<()> {
+        use crate::marshal::{self, Value};
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        let entry = |k: &str, v: i64| (Value::Bytes(k.as_bytes().to_vec()), Value::Int(v));
+        let live_file = settings_dir.join("core_char_1.dat");
+        fs::write(
+            &live_file,
+            marshal::encode(&Value::Dict(vec![entry("zoom", 1), entry("volume", 5)])),
+        )?;
+        let backup = create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default(),
+        )?;
+        fs::write(
+            &live_file,
+            marshal::encode(&Value::Dict(vec![entry("zoom", 2), entry("theme", 1)])),
+        )?;
+
+        let keys = compare_with_live(&backup.join("core_char_1.dat"), &settings_dir)?
+            .keys
+            .context("both files decode")?;
+        let text =
+            |paths: &[inspect::KeyPath]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert_eq!(text(&keys.added), ["volume"]);
+        assert_eq!(text(&keys.removed), ["theme"]);
+        assert_eq!(text(&keys.changed), ["zoom"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_single_file_from_backup() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        fs::write(settings_dir.join("core_char_1.dat"), b"version one")?;
+        fs::write(settings_dir.join("core_char_2.dat"), b"other")?;
+
+        let backup = create_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            &BackupOptions::default(),
+        )?;
+        let backup_file = backup.join("core_char_1.dat");
+        assert_eq!(
+            compare_with_live(&backup_file, &settings_dir)?.first_difference,
+            None
+        );
+
+        fs::write(settings_dir.join("core_char_1.dat"), b"version two!")?;
+        fs::write(settings_dir.join("core_char_2.dat"), b"other, changed")?;
+        let comparison = compare_with_live(&backup_file, &settings_dir)?;
+        assert_eq!(comparison.first_difference, Some(8));
+        assert_eq!(comparison.live_size, Some(12));
+        assert_eq!(comparison.keys, None);
+
+        let safety = restore_backup_file(
+            &backup_file,
+            &settings_dir,
+            &mut journal::scratch(dir.path()),
+        )?;
+        assert_eq!(
+            fs::read(settings_dir.join("core_char_1.dat"))?,
+            b"version one"
+        );
+        assert_eq!(
+            fs::read(settings_dir.join("core_char_2.dat"))?,
+            b"other, changed"
+        );
+        assert_eq!(fs::read(safety.join("core_char_1.dat"))?, b"version two!");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_settings_file() -> Result<()> {
+        let dir = tempdir()?;
+        let settings_dir = dir.path().join("settings_Default");
+        fs::create_dir(&settings_dir)?;
+        let file = settings_dir.join("core_char_1.dat");
+        fs::write(&file, b"doomheim")?;
+
+        let backup = delete_settings_file(&file, &mut journal::scratch(dir.path()))?;
+
+        assert!(!file.exists());
+        assert_eq!(fs::read(backup.join("core_char_1.dat"))?, b"doomheim");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_stale_temp_files() -> Result<()> {
+        let dir = tempdir()?;
+        fs::write(dir.path().join("core_char_1.dat"), b"")?;
+        fs::write(dir.path().join("core_char_2.tmp"), b"")?;
+        fs::write(dir.path().join("core_user_3.tmp"), b"")?;
+        fs::write(dir.path().join("other.tmp"), b"")?;
+
+        let stale = find_stale_temp_files(dir.path())?;
+        assert_eq!(
+            stale,
+            vec![
+                dir.path().join("core_char_2.tmp"),
+                dir.path().join("core_user_3.tmp")
+            ]
+        );
+
+        assert_eq!(remove_temp_files(&stale)?, 2);
+        assert!(find_stale_temp_files(dir.path())?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_to_profile() -> Result<()> {
+        use crate::discovery::FileType;
+
+        let dir = tempdir()?;
+        let default = dir.path().join("settings_Default");
+        let pvp = dir.path().join("settings_PvP");
+        fs::create_dir(&default)?;
+        fs::create_dir(&pvp)?;
+        fs::write(default.join("core_char_1.dat"), b"main")?;
+
+        let source = CharacterFile {
+            path: default.join("core_char_1.dat"),
+            character_id: 1,
+            file_type: FileType::Character,
+            is_default: false,
+        };
+
+        let result = copy_to_profile(&source, &pvp, true, None)?;
+        assert_eq!(result.message, "Would create");
+        assert!(!pvp.join("core_char_1.dat").exists());
+
+        let mut journal = journal::scratch(dir.path());
+        let result = copy_to_profile(&source, &pvp, false, Some(&mut journal))?;
+        assert!(result.success);
+        assert_eq!(fs::read(pvp.join("core_char_1.dat"))?, b"main");
+
+        assert!(copy_to_profile(&source, &default, false, Some(&mut journal)).is_err());
+
+        // A new alt gets a copy under its own ID; an existing file is left alone
+        let result = duplicate_as(&source, 2, true, None)?;
+        assert_eq!(result.target_file, default.join("core_char_2.dat"));
+        assert!(!result.target_file.exists());
+        duplicate_as(&source, 2, false, Some(&mut journal))?;
+        assert_eq!(fs::read(default.join("core_char_2.dat"))?, b"main");
+        assert!(duplicate_as(&source, 2, false, Some(&mut journal)).is_err());
+
+        // Several at once, going on past an ID that already has settings
+        assert_eq!(parse_id_list("3, 4\n3;2")?, vec![3, 4, 2]);
+        assert!(parse_id_list("3 Alt").is_err());
+        let results = duplicate_for_ids(&source, &[3, 4, 2], false, Some(&mut journal));
+        let created: Vec<bool> = results.iter().map(|r| r.success).collect();
+        assert_eq!(created, vec![true, true, false]);
+        assert!(default.join("core_char_4.dat").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sync_settings_progress() -> Result<()> {
+        use crate::discovery::FileType;
+
+        let dir = tempdir()?;
+        let file = |id: u64| CharacterFile {
+            path: dir.path().join(format!("core_char_{}.dat", id)),
+            character_id: id,
+            file_type: FileType::Character,
+            is_default: false,
+        };
+        let (source, a, b) = (file(1), file(2), file(3));
+
+        let mut calls = Vec::new();
+        let results =
+            sync_settings_with_progress(&source, &[&a, &b], true, None, |done, total| {
+                calls.push((done, total))
+            })?;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(calls, vec![(0, 2), (1, 2), (2, 2)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_sync() -> Result<()> {
+        let dir = tempdir()?;
+        let src = dir.path().join("core_char_1.dat");
+        let good = dir.path().join("core_char_2.dat");
+        let bad = dir.path().join("core_char_3.dat");
+        fs::write(&src, b"source")?;
+        fs::write(&good, b"source")?;
+        fs::write(&bad, b"stale")?;
+
+        let backup_dir = dir.path().join("backup");
+        fs::create_dir(&backup_dir)?;
+        fs::write(backup_dir.join("core_char_2.dat"), b"old")?;
+
+        let results: Vec<SyncResult> = [&good, &bad]
+            .iter()
+            .map(|p| SyncResult {
+                target_file: p.to_path_buf(),
+                success: true,
+                message: String::new(),
+            })
+            .collect();
+
+        let backups = HashMap::from([(dir.path().to_path_buf(), backup_dir.clone())]);
+        let entries = verify_sync(&src, &results, &backups);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].matches_source);
+        assert_eq!(
+            entries[0].backup_file,
+            Some(backup_dir.join("core_char_2.dat"))
+        );
+        assert!(!entries[1].matches_source);
+        assert_eq!(entries[1].backup_file, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,618 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::settings;
+
+/// Oldest names are evicted beyond this many cached entries
+const MAX_CACHED_NAMES: usize = 5000;
+
+/// The app log is rotated to `app.log.1` once it grows past this size
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Only the most recent sync records are kept
+const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// Only the most recent timing records are kept
+const MAX_TIMING_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedName {
+    name: String,
+    /// Unix timestamp of the last time ESI returned this name
+    updated: i64,
+    /// ESI reported the character as nonexistent
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// Character ID to name cache stored in the state directory.
+#[derive(Debug, Default)]
+pub struct NameCache {
+    entries: HashMap<u64, CachedName>,
+}
+
+impl NameCache {
+    fn path() -> Result<PathBuf> {
+        Ok(Config::state_dir()?.join("name_cache.json"))
+    }
+
+    pub fn load() -> Self {
+        let entries = Self::path()
+            .and_then(|p| Ok(fs::read_to_string(p)?))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { entries }
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        self.prune();
+        let json = serde_json::to_string(&self.entries).context("Failed to serialize names")?;
+        fs::write(Self::path()?, json).context("Failed to write name cache")?;
+        Ok(())
+    }
+
+    /// Returns the cached name; deleted characters have none.
+    pub fn get(&self, character_id: u64) -> Option<&str> {
+        self.entries
+            .get(&character_id)
+            .filter(|e| !e.deleted)
+            .map(|e| e.name.as_str())
+    }
+
+    pub fn is_deleted(&self, character_id: u64) -> bool {
+        self.entries.get(&character_id).is_some_and(|e| e.deleted)
+    }
+
+    /// Records that ESI no longer knows this character, so it isn't looked up again.
+    pub fn mark_deleted(&mut self, character_id: u64) {
+        self.entries.insert(
+            character_id,
+            CachedName {
+                name: String::new(),
+                updated: Local::now().timestamp(),
+                deleted: true,
+            },
+        );
+    }
+
+    pub fn contains(&self, character_id: u64) -> bool {
+        self.entries.contains_key(&character_id)
+    }
+
+    /// Every cached name by character ID, leaving out deleted characters.
+    pub fn to_map(&self) -> HashMap<u64, String> {
+        self.entries
+            .iter()
+            .filter(|(_, e)| !e.deleted)
+            .map(|(id, e)| (*id, e.name.clone()))
+            .collect()
+    }
+
+    /// Replaces the cached names with imported ones. Characters known to be deleted
+    /// stay marked so they aren't looked up again.
+    pub fn replace_names(&mut self, names: HashMap<u64, String>) {
+        self.entries.retain(|_, e| e.deleted);
+        for (id, name) in names {
+            self.insert(id, name);
+        }
+    }
+
+    /// Every cached name, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .values()
+            .filter(|e| !e.deleted)
+            .map(|e| e.name.as_str())
+    }
+
+    pub fn insert(&mut self, character_id: u64, name: String) {
+        self.entries.insert(
+            character_id,
+            CachedName {
+                name,
+                updated: Local::now().timestamp(),
+                deleted: false,
+            },
+        );
+    }
+
+    /// IDs of names last refreshed before the Unix timestamp `cutoff`, oldest first.
+    /// Deleted characters are left out; they are not looked up again.
+    pub fn stale_ids(&self, cutoff: i64) -> Vec<u64> {
+        let mut stale: Vec<(i64, u64)> = self
+            .entries
+            .iter()
+            .filter(|(_, e)| !e.deleted && e.updated < cutoff)
+            .map(|(id, e)| (e.updated, *id))
+            .collect();
+        stale.sort_unstable();
+        stale.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Drops the least recently refreshed entries beyond `MAX_CACHED_NAMES`.
+    fn prune(&mut self) {
+        if self.entries.len() <= MAX_CACHED_NAMES {
+            return;
+        }
+
+        let mut by_age: Vec<(u64, i64)> = self
+            .entries
+            .iter()
+            .map(|(id, e)| (*id, e.updated))
+            .collect();
+        by_age.sort_by_key(|(_, updated)| *updated);
+
+        let excess = self.entries.len() - MAX_CACHED_NAMES;
+        for (id, _) in by_age.into_iter().take(excess) {
+            self.entries.remove(&id);
+        }
+    }
+}
+
+/// Appends timestamped lines to the persistent app log, rotating it when too large.
+pub fn append_log(lines: &[String]) -> Result<()> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let dir = Config::state_dir()?;
+    let path = dir.join("app.log");
+
+    if fs::metadata(&path).map(|m| m.len()).unwrap_or(0) > MAX_LOG_BYTES {
+        fs::rename(&path, dir.join("app.log.1")).context("Failed to rotate log")?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open log file")?;
+    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
+    for line in lines {
+        writeln!(file, "{} {}", timestamp, line).context("Failed to write log")?;
+    }
+
+    Ok(())
+}
+
+/// One completed sync, as recorded in the history file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncRecord {
+    pub timestamp: String,
+    pub source: PathBuf,
+    pub targets: Vec<PathBuf>,
+    pub dry_run: bool,
+    pub succeeded: usize,
+}
+
+/// Appends a sync record to `history.jsonl`, keeping only the newest entries.
+pub fn record_sync(record: &SyncRecord) -> Result<()> {
+    let path = Config::state_dir()?.join("history.jsonl");
+
+    let mut lines: Vec<String> = fs::read_to_string(&path)
+        .map(|h| h.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+    lines.push(serde_json::to_string(record).context("Failed to serialize sync record")?);
+
+    let start = lines.len().saturating_sub(MAX_HISTORY_ENTRIES);
+    fs::write(&path, lines[start..].join("\n") + "\n").context("Failed to write history")?;
+
+    Ok(())
+}
+
+/// Reads the sync history, oldest first. Damaged lines are skipped.
+pub fn read_history() -> Result<Vec<SyncRecord>> {
+    let path = Config::state_dir()?.join("history.jsonl");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(&path).context("Failed to read history")?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Operations whose duration is recorded in the timings file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimedOperation {
+    Scan,
+    Backup,
+    Sync,
+}
+
+impl TimedOperation {
+    pub const ALL: [TimedOperation; 3] = [Self::Scan, Self::Backup, Self::Sync];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Scan => "Scan",
+            Self::Backup => "Backup",
+            Self::Sync => "Sync",
+        }
+    }
+}
+
+/// How long one scan, backup or sync took.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimingRecord {
+    pub timestamp: String,
+    pub operation: TimedOperation,
+    /// Settings files the operation read or wrote
+    pub files: usize,
+    pub millis: u64,
+}
+
+impl TimingRecord {
+    pub fn new(operation: TimedOperation, files: usize, elapsed: Duration) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            operation,
+            files,
+            millis: elapsed.as_millis().try_into().unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Average time per file; None when the operation touched no files.
+    pub fn per_file_millis(&self) -> Option<f64> {
+        (self.files > 0).then(|| self.millis as f64 / self.files as f64)
+    }
+
+    /// A log line such as "Sync of 12 file(s) took 1.23 s (102.5 ms per file)".
+    pub fn describe(&self) -> String {
+        let mut line = format!(
+            "{} of {} file(s) took {}",
+            self.operation.name(),
+            self.files,
+            format_millis(self.millis as f64)
+        );
+        if let Some(per_file) = self.per_file_millis() {
+            line.push_str(&format!(" ({:.1} ms per file)", per_file));
+        }
+        line
+    }
+}
+
+/// Formats a duration in milliseconds, switching to seconds from one second up.
+pub fn format_millis(millis: f64) -> String {
+    if millis >= 1000.0 {
+        format!("{:.2} s", millis / 1000.0)
+    } else {
+        format!("{:.0} ms", millis)
+    }
+}
+
+fn timings_path() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join("timings.jsonl"))
+}
+
+/// Appends a timing record to `timings.jsonl`, keeping only the newest entries.
+pub fn record_timing(record: &TimingRecord) -> Result<()> {
+    append_timing(&timings_path()?, record)
+}
+
+/// Reads the recorded timings, oldest first. Damaged lines are skipped.
+pub fn read_timings() -> Result<Vec<TimingRecord>> {
+    read_timings_from(&timings_path()?)
+}
+
+fn append_timing(path: &Path, record: &TimingRecord) -> Result<()> {
+    let mut lines: Vec<String> = fs::read_to_string(path)
+        .map(|t| t.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+    lines.push(serde_json::to_string(record).context("Failed to serialize timing")?);
+
+    let start = lines.len().saturating_sub(MAX_TIMING_ENTRIES);
+    fs::write(path, lines[start..].join("\n") + "\n").context("Failed to write timings")?;
+
+    Ok(())
+}
+
+fn read_timings_from(path: &Path) -> Result<Vec<TimingRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).context("Failed to read timings")?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Aggregated timings of one operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingSummary {
+    pub operation: TimedOperation,
+    pub runs: usize,
+    pub average_millis: f64,
+    /// Total time over total files; None when no run touched a file
+    pub per_file_millis: Option<f64>,
+    pub last: TimingRecord,
+}
+
+/// Summarizes the records per operation, in `TimedOperation::ALL` order.
+/// Operations without records are left out.
+pub fn summarize_timings(records: &[TimingRecord]) -> Vec<TimingSummary> {
+    TimedOperation::ALL
+        .iter()
+        .filter_map(|&operation| {
+            let runs: Vec<&TimingRecord> = records
+                .iter()
+                .filter(|r| r.operation == operation)
+                .collect();
+            let last = (*runs.last()?).clone();
+            let total: u64 = runs.iter().map(|r| r.millis).sum();
+            let files: usize = runs.iter().map(|r| r.files).sum();
+            Some(TimingSummary {
+                operation,
+                runs: runs.len(),
+                average_millis: total as f64 / runs.len() as f64,
+                per_file_millis: (files > 0).then(|| total as f64 / files as f64),
+                last,
+            })
+        })
+        .collect()
+}
+
+/// One mutating operation in the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    /// OS account that ran the operation
+    pub user: String,
+    pub action: String,
+    pub files: Vec<AuditedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditedFile {
+    pub path: PathBuf,
+    /// SHA-256 after the operation; None for directories and removed files
+    pub hash: Option<String>,
+}
+
+impl AuditEntry {
+    /// Builds an entry for `action`, hashing each file as it is now.
+    pub fn new(action: impl Into<String>, paths: &[PathBuf]) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            user: current_user(),
+            action: action.into(),
+            files: paths
+                .iter()
+                .map(|path| AuditedFile {
+                    path: path.clone(),
+                    hash: settings::file_hash(path).ok(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Name of the OS account running the app.
+pub fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn audit_path() -> Result<PathBuf> {
+    Ok(Config::state_dir()?.join("audit.jsonl"))
+}
+
+/// Appends an entry to the audit log. Unlike the app log and sync history, the
+/// audit log is never rotated or truncated.
+pub fn record_audit(entry: &AuditEntry) -> Result<()> {
+    append_audit(&audit_path()?, entry)
+}
+
+/// Reads the whole audit log, oldest first, with the number of damaged lines skipped.
+pub fn read_audit() -> Result<(Vec<AuditEntry>, usize)> {
+    read_audit_from(&audit_path()?)
+}
+
+fn append_audit(path: &Path, entry: &AuditEntry) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .context("Failed to open audit log")?;
+    let line = serde_json::to_string(entry).context("Failed to serialize audit entry")?;
+    writeln!(file, "{}", line).context("Failed to write audit log")?;
+    Ok(())
+}
+
+fn read_audit_from(path: &Path) -> Result<(Vec<AuditEntry>, usize)> {
+    if !path.exists() {
+        return Ok((Vec::new(), 0));
+    }
+
+    let contents = fs::read_to_string(path).context("Failed to read audit log")?;
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => skipped += 1,
+        }
+    }
+    Ok((entries, skipped))
+}
+
+/// Formats audit entries as plain text for export, one file per line.
+pub fn format_audit(entries: &[AuditEntry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(&format!(
+            "{}  {}  {}\n",
+            entry.timestamp, entry.user, entry.action
+        ));
+        for file in &entry.files {
+            out.push_str(&format!(
+                "    {}  {}\n",
+                file.hash.as_deref().unwrap_or("-"),
+                file.path.display()
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_cache_prunes_oldest() {
+        let mut cache = NameCache::default();
+        for id in 0..(MAX_CACHED_NAMES as u64 + 10) {
+            cache.entries.insert(
+                id,
+                CachedName {
+                    name: format!("Pilot {}", id),
+                    updated: id as i64,
+                    deleted: false,
+                },
+            );
+        }
+
+        cache.prune();
+
+        assert_eq!(cache.entries.len(), MAX_CACHED_NAMES);
+        assert!(!cache.contains(0));
+        assert!(!cache.contains(9));
+        assert_eq!(cache.get(10), Some("Pilot 10"));
+    }
+
+    #[test]
+    fn test_name_cache_deleted() {
+        let mut cache = NameCache::default();
+        cache.insert(1, "Pilot".to_string());
+        cache.mark_deleted(1);
+
+        assert!(cache.contains(1));
+        assert!(cache.is_deleted(1));
+        assert_eq!(cache.get(1), None);
+    }
+
+    #[test]
+    fn test_name_cache_stale_ids() {
+        let mut cache = NameCache::default();
+        for (id, updated) in [(1, 300), (2, 100), (3, 200), (4, 900)] {
+            cache.entries.insert(
+                id,
+                CachedName {
+                    name: format!("Pilot {}", id),
+                    updated,
+                    deleted: false,
+                },
+            );
+        }
+        cache.entries.get_mut(&3).unwrap().deleted = true;
+
+        assert_eq!(cache.stale_ids(500), vec![2, 1]);
+        assert!(cache.stale_ids(50).is_empty());
+    }
+
+    #[test]
+    fn test_name_cache_replace_names() {
+        let mut cache = NameCache::default();
+        cache.insert(1, "Alpha".to_string());
+        cache.insert(2, "Bravo".to_string());
+        cache.mark_deleted(3);
+
+        cache.replace_names(HashMap::from([
+            (2, "Bravo II".to_string()),
+            (4, "Delta".to_string()),
+        ]));
+
+        assert_eq!(
+            cache.to_map(),
+            HashMap::from([(2, "Bravo II".to_string()), (4, "Delta".to_string())])
+        );
+        assert!(cache.is_deleted(3));
+    }
+
+    #[test]
+    fn test_audit_log_appends() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let log = dir.path().join("audit.jsonl");
+        let file = dir.path().join("core_char_1.dat");
+        fs::write(&file, b"settings")?;
+
+        let first = AuditEntry::new("Sync", std::slice::from_ref(&file));
+        let second = AuditEntry::new("Delete settings file", &[dir.path().join("gone.dat")]);
+        append_audit(&log, &first)?;
+        append_audit(&log, &second)?;
+
+        let (entries, skipped) = read_audit_from(&log)?;
+        assert_eq!(entries, vec![first, second]);
+        assert_eq!(skipped, 0);
+        assert_eq!(
+            entries[0].files[0].hash.as_deref(),
+            Some(settings::file_hash(&file)?.as_str())
+        );
+        assert_eq!(entries[1].files[0].hash, None);
+        assert!(format_audit(&entries).contains("Delete settings file"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_log_skips_damaged_lines() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let log = dir.path().join("audit.jsonl");
+        let first = AuditEntry::new("Sync", &[]);
+        let second = AuditEntry::new("Restore backup", &[]);
+        append_audit(&log, &first)?;
+        OpenOptions::new()
+            .append(true)
+            .open(&log)?
+            .write_all(b"{\"timestamp\": \"2024-01-\n")?;
+        append_audit(&log, &second)?;
+
+        assert_eq!(read_audit_from(&log)?, (vec![first, second], 1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_timings() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("timings.jsonl");
+        let scan = TimingRecord::new(TimedOperation::Scan, 0, Duration::from_millis(40));
+        let first = TimingRecord::new(TimedOperation::Sync, 10, Duration::from_millis(1000));
+        let second = TimingRecord::new(TimedOperation::Sync, 30, Duration::from_millis(3000));
+        for record in [&scan, &first, &second] {
+            append_timing(&path, record)?;
+        }
+
+        let records = read_timings_from(&path)?;
+        assert_eq!(records, vec![scan.clone(), first.clone(), second.clone()]);
+        assert_eq!(scan.describe(), "Scan of 0 file(s) took 40 ms");
+        assert_eq!(
+            second.describe(),
+            "Sync of 30 file(s) took 3.00 s (100.0 ms per file)"
+        );
+
+        let summary = summarize_timings(&records);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].operation, TimedOperation::Scan);
+        assert_eq!(summary[0].per_file_millis, None);
+        assert_eq!(summary[1].runs, 2);
+        assert_eq!(summary[1].average_millis, 2000.0);
+        assert_eq!(summary[1].per_file_millis, Some(100.0));
+        assert_eq!(summary[1].last, second);
+
+        Ok(())
+    }
+}
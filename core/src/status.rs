@@ -0,0 +1,218 @@
+//! Optional status endpoint for monitoring. While enabled, the app serves a JSON
+//! report on `http://127.0.0.1:<port>/status` with its health, when it last synced
+//! and backed up, and where each scheduled preset stands, so an external monitor
+//! can alert when unattended backups stop happening. Only localhost is bound.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Port offered when the endpoint is first switched on
+pub const DEFAULT_STATUS_PORT: u16 = 47_809;
+
+/// How often the server thread checks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long a client gets to send its request line
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Report shared between the app, which keeps it current, and the server thread
+pub type SharedStatus = Arc<Mutex<StatusReport>>;
+
+/// What the endpoint reports. Times are RFC 3339.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StatusReport {
+    /// False while any scheduled preset's last attempt failed
+    pub healthy: bool,
+    pub started: String,
+    /// Last sync that wrote files, manual or scheduled, since the app started
+    pub last_sync: Option<String>,
+    /// Last backup created since the app started
+    pub last_backup: Option<String>,
+    pub presets: Vec<PresetStatus>,
+}
+
+/// Where a scheduled preset stands
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresetStatus {
+    pub name: String,
+    /// e.g. "daily at 03:00"
+    pub schedule: String,
+    pub last_run: Option<String>,
+    pub state: PresetState,
+    /// Why the last attempt failed, if it did
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetState {
+    /// Waiting for its next occurrence
+    Idle,
+    /// An occurrence is pending and will run at the next check
+    Due,
+    Running,
+    /// Held back until the EVE clients close
+    Deferred,
+}
+
+impl StatusReport {
+    pub fn new(started: String) -> Self {
+        Self {
+            healthy: true,
+            started,
+            ..Self::default()
+        }
+    }
+
+    /// Replaces the preset list and recomputes health from it.
+    pub fn set_presets(&mut self, presets: Vec<PresetStatus>) {
+        self.healthy = presets.iter().all(|p| p.error.is_none());
+        self.presets = presets;
+    }
+}
+
+/// A running status endpoint; dropping it stops the server.
+pub struct StatusServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StatusServer {
+    /// Serves `report` on localhost. Port 0 picks a free port; see [`Self::addr`].
+    pub fn start(port: u16, report: SharedStatus) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .with_context(|| format!("Failed to listen on port {}", port))?;
+        let addr = listener.local_addr()?;
+        // Polled rather than blocking, so dropping the server can end the thread
+        listener
+            .set_nonblocking(true)
+            .context("Failed to configure status listener")?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let _ = respond(stream, &report);
+                    }
+                    // Nothing waiting, or a failed accept; either way try again shortly
+                    Err(_) => thread::sleep(POLL_INTERVAL),
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/status", self.addr)
+    }
+}
+
+impl Drop for StatusServer {
+    /// Waits for the server thread so the port is free again once this returns.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Answers one request. Only `GET /status` (or `/`) is served; the connection is
+/// closed after each response.
+fn respond(stream: TcpStream, report: &SharedStatus) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+
+    let (status, body) = match (method, path) {
+        ("GET", "/" | "/status") => {
+            let report = report
+                .lock()
+                .map_err(|_| anyhow::anyhow!("Status poisoned"))?;
+            ("200 OK", serde_json::to_string_pretty(&*report)?)
+        }
+        ("GET", _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            r#"{"error":"method not allowed"}"#.to_string(),
+        ),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn get(addr: SocketAddr, request: &str) -> Result<String> {
+        let mut stream = TcpStream::connect(addr)?;
+        stream.write_all(request.as_bytes())?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    }
+
+    #[test]
+    fn test_status_endpoint() -> Result<()> {
+        let report = Arc::new(Mutex::new(StatusReport::new("started".to_string())));
+        report.lock().unwrap().set_presets(vec![PresetStatus {
+            name: "Nightly".to_string(),
+            schedule: "daily at 03:00".to_string(),
+            last_run: None,
+            state: PresetState::Deferred,
+            error: Some("Source no longer exists".to_string()),
+        }]);
+
+        let server = StatusServer::start(0, Arc::clone(&report))?;
+        let response = get(
+            server.addr(),
+            "GET /status HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        )?;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap_or("");
+        let json: serde_json::Value = serde_json::from_str(body)?;
+        assert_eq!(json["healthy"], false);
+        assert_eq!(json["presets"][0]["state"], "deferred");
+        assert!(json["last_backup"].is_null());
+
+        let missing = get(server.addr(), "GET /metrics HTTP/1.1\r\n\r\n")?;
+        assert!(missing.starts_with("HTTP/1.1 404"));
+        let post = get(server.addr(), "POST /status HTTP/1.1\r\n\r\n")?;
+        assert!(post.starts_with("HTTP/1.1 405"));
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use std::path::{Path, PathBuf};
+
+use crate::process;
+
+/// Flatpak's documentation on granting an app access to the host
+pub const FLATPAK_HELP_URL: &str = "https://docs.flatpak.org/en/latest/sandbox-permissions.html";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    /// Not applicable, e.g. no prefix to inspect yet
+    Skipped,
+}
+
+/// Something the user can do about a failed check, offered as a button.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    /// Start the client, then scan again
+    Rescan,
+    /// Choose the prefix by hand
+    BrowsePrefix,
+    /// Use a prefix with EVE settings found on disk
+    UsePrefix(PathBuf),
+    OpenUrl(&'static str),
+}
+
+/// Result of one troubleshooting check
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub title: &'static str,
+    pub outcome: Outcome,
+    pub detail: String,
+    pub fixes: Vec<Fix>,
+}
+
+/// Works through the common reasons a scan finds no prefix or no settings directory:
+/// no client running, a launcher that hides the prefix, a Flatpak sandbox, and a
+/// prefix EVE has not saved settings in. `prefix` is the selected prefix, if any.
+pub fn troubleshoot(prefix: Option<&Path>) -> Vec<Check> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let mut candidates: Vec<PathBuf> = process::detect_launcher_prefixes()
+        .into_iter()
+        .map(|launcher| launcher.path)
+        .chain(process::steam_library_prefixes())
+        .chain(wine_prefix_candidates(&home))
+        .filter(|p| Some(p.as_path()) != prefix)
+        .collect();
+    // A running launcher's prefix is often also one of the usual locations
+    let mut seen = Vec::new();
+    candidates.retain(|p| {
+        let new = !seen.contains(p);
+        seen.push(p.clone());
+        new
+    });
+
+    vec![
+        check_running(&candidates),
+        check_sandbox(),
+        check_candidates(&candidates),
+        check_settings(prefix),
+    ]
+}
+
+fn check_running(candidates: &[PathBuf]) -> Check {
+    let detected = process::detect_eve_prefixes().unwrap_or_default();
+    let running = process::count_eve_processes();
+
+    let (outcome, detail, fixes) = if !detected.is_empty() {
+        (
+            Outcome::Passed,
+            format!("{} client(s) running with a Wine prefix", detected.len()),
+            Vec::new(),
+        )
+    } else if running > 0 {
+        let mut fixes = vec![Fix::BrowsePrefix];
+        fixes.extend(candidates.iter().cloned().map(Fix::UsePrefix));
+        (
+            Outcome::Failed,
+            "The client is running, but its command line does not name a Wine prefix. \
+             Non-Steam launchers such as Lutris, Bottles or CrossOver often start it this way; \
+             choose the prefix by hand."
+                .to_string(),
+            fixes,
+        )
+    } else {
+        (
+            Outcome::Failed,
+            "No EVE client is running. Start it and log in to a character, then scan \
+             again. Without a running client the app only finds prefixes you chose before."
+                .to_string(),
+            vec![Fix::Rescan],
+        )
+    };
+
+    Check {
+        title: "EVE client running",
+        outcome,
+        detail,
+        fixes,
+    }
+}
+
+fn check_sandbox() -> Check {
+    if process::in_flatpak() {
+        Check {
+            title: "Sandbox",
+            outcome: Outcome::Failed,
+            detail: "This app runs as a Flatpak, which hides other apps' processes and \
+                     most of your home folder. Grant it access to your Steam or Wine \
+                     folders, or choose the prefix by hand."
+                .to_string(),
+            fixes: vec![Fix::OpenUrl(FLATPAK_HELP_URL), Fix::BrowsePrefix],
+        }
+    } else {
+        Check {
+            title: "Sandbox",
+            outcome: Outcome::Passed,
+            detail: "Not running in a Flatpak sandbox".to_string(),
+            fixes: Vec::new(),
+        }
+    }
+}
+
+fn check_candidates(candidates: &[PathBuf]) -> Check {
+    if candidates.is_empty() {
+        Check {
+            title: "Prefixes on disk",
+            outcome: Outcome::Failed,
+            detail: "No prefix with EVE settings in the Steam libraries, ~/.wine, ~/Games, \
+                     Bottles or CrossOver. If EVE is installed elsewhere, choose its prefix (the \
+                     drive_c folder) by hand."
+                .to_string(),
+            fixes: vec![Fix::BrowsePrefix],
+        }
+    } else {
+        Check {
+            title: "Prefixes on disk",
+            outcome: Outcome::Passed,
+            detail: format!("Found {} prefix(es) with EVE settings", candidates.len()),
+            fixes: candidates.iter().cloned().map(Fix::UsePrefix).collect(),
+        }
+    }
+}
+
+fn check_settings(prefix: Option<&Path>) -> Check {
+    let title = "Settings folder";
+    let Some(prefix) = prefix else {
+        return Check {
+            title,
+            outcome: Outcome::Skipped,
+            detail: "No prefix selected".to_string(),
+            fixes: Vec::new(),
+        };
+    };
+
+    if !process::find_settings_dirs(prefix)
+        .unwrap_or_default()
+        .is_empty()
+    {
+        return Check {
+            title,
+            outcome: Outcome::Passed,
+            detail: format!("{} has a settings folder", prefix.display()),
+            fixes: Vec::new(),
+        };
+    }
+
+    if !process::find_eve_base_dirs(prefix).is_empty() {
+        return Check {
+            title,
+            outcome: Outcome::Failed,
+            detail: "EVE has been installed here but has not saved any settings yet. Log \
+                     in to a character and log out once, then scan again."
+                .to_string(),
+            fixes: vec![Fix::Rescan],
+        };
+    }
+
+    Check {
+        title,
+        outcome: Outcome::Failed,
+        detail: format!(
+            "{} has no CCP/EVE folder; it may not be the prefix EVE runs in.",
+            prefix.display()
+        ),
+        fixes: vec![Fix::BrowsePrefix],
+    }
+}
+
+/// Non-Steam Wine prefixes in their usual places that contain EVE settings.
+fn wine_prefix_candidates(home: &Path) -> Vec<PathBuf> {
+    let mut prefixes = vec![home.join(".wine/drive_c")];
+    for games in [
+        home.join("Games"),
+        home.join(".local/share/bottles/bottles"),
+        home.join(".var/app/com.usebottles.bottles/data/bottles/bottles"),
+        home.join(".cxoffice"),
+    ] {
+        if let Ok(entries) = std::fs::read_dir(games) {
+            prefixes.extend(entries.flatten().map(|e| e.path().join("drive_c")));
+        }
+    }
+    prefixes.retain(|p| !process::find_eve_base_dirs(p).is_empty());
+    prefixes.sort();
+    prefixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_settings_under_other_user() -> Result<()> {
+        let dir = tempdir()?;
+        let prefix = dir.path().join("drive_c");
+        let settings = prefix.join("users/alice/AppData/Local/CCP/EVE/c_eve_tq/settings_Default");
+        fs::create_dir_all(&settings)?;
+
+        // Plain Wine prefixes name the user after the login; the scan covers them
+        let check = check_settings(Some(&prefix));
+        assert_eq!(check.outcome, Outcome::Passed);
+        assert!(check.fixes.is_empty());
+
+        assert_eq!(check_settings(None).outcome, Outcome::Skipped);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wine_prefix_candidates() -> Result<()> {
+        let home = tempdir()?;
+        let lutris = home.path().join("Games/eve-online/drive_c");
+        fs::create_dir_all(lutris.join("users/steamuser/AppData/Local/CCP/EVE"))?;
+        fs::create_dir_all(home.path().join("Games/other-game/drive_c/users/me"))?;
+        fs::create_dir_all(home.path().join(".wine/drive_c/users/me"))?;
+
+        assert_eq!(wine_prefix_candidates(home.path()), vec![lutris]);
+        Ok(())
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::discovery::{self, CharacterFile};
+use crate::journal::SyncJournal;
+use crate::process;
+use crate::settings::{self, BackupTrigger, SyncResult, VerificationEntry};
+
+/// Filesystem types a Windows partition is mounted as by the kernel and ntfs-3g
+const WINDOWS_FS_TYPES: &[&str] = &["ntfs", "ntfs3", "fuseblk"];
+
+/// Where Windows keeps EVE settings relative to a user's home
+const WINDOWS_EVE_DIR: &[&str] = &["AppData", "Local", "CCP", "EVE"];
+
+/// Which way files are copied between Windows and the prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the Windows install into the prefix
+    Import,
+    /// From the prefix back to the Windows install
+    Export,
+}
+
+/// A profile on the Windows side and the profile directory in the prefix it
+/// corresponds to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileMapping {
+    /// Profile of the Windows install
+    pub source: PathBuf,
+    /// Profile in the prefix, which may not exist yet
+    pub target: PathBuf,
+}
+
+impl ProfileMapping {
+    /// Whether the import creates the target profile rather than writing into an
+    /// existing one
+    pub fn creates_target(&self) -> bool {
+        !self.target.is_dir()
+    }
+
+    /// Profile files are copied from
+    pub fn from_dir(&self, direction: Direction) -> &Path {
+        match direction {
+            Direction::Import => &self.source,
+            Direction::Export => &self.target,
+        }
+    }
+
+    /// Profile files are copied into
+    pub fn to_dir(&self, direction: Direction) -> &Path {
+        match direction {
+            Direction::Import => &self.target,
+            Direction::Export => &self.source,
+        }
+    }
+}
+
+/// What a transfer did, or would do in a dry run
+#[derive(Debug)]
+pub struct Transfer {
+    /// Backup of the receiving profile, if it existed and this was not a dry run
+    pub backup: Option<PathBuf>,
+    pub results: Vec<SyncResult>,
+    /// Re-read copies compared against their sources; empty for a dry run
+    pub verification: Vec<VerificationEntry>,
+}
+
+/// EVE settings folders on the mounted Windows partitions, one per Windows user.
+pub fn find_windows_eve_dirs() -> Vec<PathBuf> {
+    let mounts = fs::read_to_string("/proc/mounts").unwrap_or_default();
+    parse_windows_mounts(&mounts)
+        .iter()
+        .flat_map(|root| eve_dirs_in(root))
+        .collect()
+}
+
+/// Mount points of Windows filesystems in `/proc/mounts` contents.
+pub fn parse_windows_mounts(mounts: &str) -> Vec<PathBuf> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let fs_type = fields.next()?;
+            WINDOWS_FS_TYPES
+                .contains(&fs_type)
+                .then(|| PathBuf::from(process::unescape_mount_field(mount_point)))
+        })
+        .collect()
+}
+
+/// `Users\<name>\AppData\Local\CCP\EVE` folders under the root of a Windows drive.
+pub fn eve_dirs_in(root: &Path) -> Vec<PathBuf> {
+    let Ok(users) = fs::read_dir(root.join("Users")) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<PathBuf> = users
+        .flatten()
+        .map(|user| {
+            WINDOWS_EVE_DIR
+                .iter()
+                .fold(user.path(), |p, part| p.join(part))
+        })
+        .filter(|dir| dir.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Pairs every profile in a Windows EVE folder with a profile directory in `prefix`.
+///
+/// Install folders are named after where the client is installed, so they rarely match
+/// between Windows and Wine. A profile goes to the install of the same name if the
+/// prefix has one, otherwise to an install for the same server, keeping its profile
+/// name. Profiles with no counterpart get a new install folder under the prefix's
+/// EVE directory.
+pub fn map_profiles(windows_eve: &Path, prefix: &Path) -> Result<Vec<ProfileMapping>> {
+    let mut wine_installs: Vec<PathBuf> = process::find_eve_base_dirs(prefix)
+        .iter()
+        .filter_map(|base| fs::read_dir(base).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .collect();
+    wine_installs.sort();
+
+    let mut mappings = Vec::new();
+    let installs = fs::read_dir(windows_eve)
+        .with_context(|| format!("Failed to read {}", windows_eve.display()))?;
+    for install in installs.flatten().map(|e| e.path()) {
+        let Ok(profiles) = fs::read_dir(&install) else {
+            continue;
+        };
+        let Some(install_name) = install.file_name() else {
+            continue;
+        };
+
+        for source in profiles.flatten().map(|e| e.path()) {
+            let Some(profile) = source.file_name() else {
+                continue;
+            };
+            let is_profile = source.is_dir() && profile.to_string_lossy().starts_with("settings_");
+            if !is_profile || discovery::is_backup_dir(&source) {
+                continue;
+            }
+
+            let server = discovery::server_for(&source);
+            let target_install = wine_installs
+                .iter()
+                .find(|p| p.file_name() == Some(install_name))
+                .or_else(|| {
+                    wine_installs.iter().find(|p| {
+                        server.is_some() && discovery::server_for(&p.join(profile)) == server
+                    })
+                })
+                .cloned()
+                .unwrap_or_else(|| process::default_eve_base_dir(prefix).join(install_name));
+
+            mappings.push(ProfileMapping {
+                target: target_install.join(profile),
+                source,
+            });
+        }
+    }
+    mappings.sort_by(|a, b| a.source.cmp(&b.source));
+    Ok(mappings)
+}
+
+/// Copies the chosen files across the mapping in `direction`, with the guarantees
+/// of a normal sync: an existing receiving profile is backed up first and every
+/// copy is verified against its source. The backup and every copy go into
+/// `journal` first, which only a dry run may leave out. A dry run only reports
+/// what would happen.
+///
+/// Exports never create a profile on the Windows side.
+pub fn transfer(
+    mapping: &ProfileMapping,
+    direction: Direction,
+    files: &[CharacterFile],
+    dry_run: bool,
+    mut journal: Option<&mut SyncJournal>,
+) -> Result<Transfer> {
+    let to_dir = mapping.to_dir(direction);
+    if direction == Direction::Export && !to_dir.is_dir() {
+        bail!(
+            "{} does not exist on the Windows partition",
+            to_dir.display()
+        );
+    }
+
+    let mut backups = HashMap::new();
+    if !dry_run {
+        let Some(journal) = journal.as_deref_mut() else {
+            bail!("A transfer that writes must be journaled");
+        };
+        if to_dir.is_dir() {
+            let backup = journal.backup(to_dir, BackupTrigger::PreSync)?;
+            backups.insert(to_dir.to_path_buf(), backup);
+        } else {
+            fs::create_dir_all(to_dir)
+                .with_context(|| format!("Failed to create {}", to_dir.display()))?;
+        }
+    }
+
+    let mut results = Vec::new();
+    let mut verification = Vec::new();
+    for file in files {
+        let result = settings::copy_to_profile(file, to_dir, dry_run, journal.as_deref_mut())?;
+        if !dry_run {
+            verification.extend(settings::verify_sync(
+                &file.path,
+                std::slice::from_ref(&result),
+                &backups,
+            ));
+        }
+        results.push(result);
+    }
+
+    Ok(Transfer {
+        backup: backups.into_values().next(),
+        results,
+        verification,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::IgnoreRules;
+    use crate::journal;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_windows_mounts() {
+        let mounts = "/dev/nvme0n1p2 / ext4 rw,relatime 0 0\n\
+                      /dev/nvme0n1p3 /mnt/windows ntfs3 rw,uid=1000 0 0\n\
+                      /dev/sda1 /run/media/me/Game\\040Drive fuseblk rw 0 0\n\
+                      tmpfs /tmp tmpfs rw 0 0\n";
+        assert_eq!(
+            parse_windows_mounts(mounts),
+            vec![
+                PathBuf::from("/mnt/windows"),
+                PathBuf::from("/run/media/me/Game Drive")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_and_transfer_profiles() -> Result<()> {
+        let windows = tempdir()?;
+        let eve = windows.path().join("Users/me/AppData/Local/CCP/EVE");
+        let win_tq = eve.join("c_eve_sharedcache_tq_tranquility");
+        let win_sisi = eve.join("c_eve_sharedcache_sisi_singularity");
+        for dir in [
+            win_tq.join("settings_Default"),
+            win_tq.join("settings_PvP"),
+            win_sisi.join("settings_Default"),
+        ] {
+            fs::create_dir_all(&dir)?;
+        }
+        fs::write(win_tq.join("settings_Default/core_char_1.dat"), "windows")?;
+        fs::write(win_tq.join("settings_Default/core_user_2.dat"), "keys")?;
+        assert_eq!(eve_dirs_in(windows.path()), vec![eve.clone()]);
+
+        let prefix = tempdir()?;
+        let base = process::default_eve_base_dir(prefix.path());
+        let wine_tq = base.join("c_ccp_eve_tq_tranquility");
+        fs::create_dir_all(wine_tq.join("settings_Default"))?;
+        fs::write(wine_tq.join("settings_Default/core_char_1.dat"), "wine")?;
+
+        let mappings = map_profiles(&eve, prefix.path())?;
+        let targets: Vec<&PathBuf> = mappings.iter().map(|m| &m.target).collect();
+        assert_eq!(
+            targets,
+            vec![
+                &base.join("c_eve_sharedcache_sisi_singularity/settings_Default"),
+                &wine_tq.join("settings_Default"),
+                &wine_tq.join("settings_PvP"),
+            ]
+        );
+        assert!(!mappings[1].creates_target());
+        assert!(mappings[2].creates_target());
+
+        let files =
+            discovery::discover_character_files(&mappings[1].source, &IgnoreRules::default())?;
+        let dry = transfer(&mappings[1], Direction::Import, &files, true, None)?;
+        assert!(dry.backup.is_none() && dry.verification.is_empty());
+        assert_eq!(
+            fs::read_to_string(wine_tq.join("settings_Default/core_char_1.dat"))?,
+            "wine"
+        );
+
+        let mut journal = journal::scratch(prefix.path());
+        let import = transfer(
+            &mappings[1],
+            Direction::Import,
+            &files,
+            false,
+            Some(&mut journal),
+        )?;
+        assert!(import
+            .backup
+            .is_some_and(|b| b.join("core_char_1.dat").exists()));
+        assert_eq!(import.results.len(), 2);
+        assert!(import.verification.iter().all(|v| v.matches_source));
+        assert_eq!(
+            fs::read_to_string(wine_tq.join("settings_Default/core_char_1.dat"))?,
+            "windows"
+        );
+        assert!(wine_tq.join("settings_Default/core_user_2.dat").exists());
+
+        fs::write(wine_tq.join("settings_Default/core_char_1.dat"), "linux")?;
+        let files =
+            discovery::discover_character_files(&mappings[1].target, &IgnoreRules::default())?;
+        assert!(transfer(&mappings[1], Direction::Export, &files, false, None).is_err());
+        let export = transfer(
+            &mappings[1],
+            Direction::Export,
+            &files,
+            false,
+            Some(&mut journal),
+        )?;
+        assert!(export.verification.iter().all(|v| v.matches_source));
+        assert_eq!(
+            fs::read_to_string(win_tq.join("settings_Default/core_char_1.dat"))?,
+            "linux"
+        );
+
+        // Nothing is created on the Windows side
+        let orphan = ProfileMapping {
+            source: eve.join("c_gone_tq_tranquility/settings_Default"),
+            target: wine_tq.join("settings_Default"),
+        };
+        assert!(transfer(
+            &orphan,
+            Direction::Export,
+            &files,
+            false,
+            Some(&mut journal)
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -4,8 +4,15 @@ use eframe::egui::{
 };
 use std::time::Instant;
 
+use crate::news::NewsFeed;
 use crate::theme::colors;
 
+/// Feed items shown beneath the version
+const MAX_NEWS_ITEMS: usize = 3;
+
+/// Longest news title drawn before it is cut short
+const MAX_NEWS_TITLE: usize = 48;
+
 // E logo SVG
 const E_SVG: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
 <svg width="159" height="127" viewBox="0 0 159 127" xmlns="http://www.w3.org/2000/svg">
@@ -18,6 +25,10 @@ pub struct AboutScreen {
     pub open: bool,
     start_time: Instant,
     logo_texture: Option<TextureHandle>,
+    /// Latest news feed, fetched or cached
+    pub news: Option<NewsFeed>,
+    /// The feed could not be fetched, so `news` is the cached copy
+    pub news_offline: bool,
 }
 
 impl AboutScreen {
@@ -26,6 +37,8 @@ impl AboutScreen {
             open: false,
             start_time: Instant::now(),
             logo_texture: None,
+            news: None,
+            news_offline: false,
         }
     }
 
@@ -221,8 +234,14 @@ impl AboutScreen {
                     Color32::WHITE,
                 );
 
-                // Bottom section with dark backing
-                let bottom_section_top = pack_rect.bottom() - pack_height * 0.24;
+                // Bottom section with dark backing, grown to fit the news
+                let news_items = self.news.as_ref().map_or(&[][..], |feed| {
+                    &feed.items[..feed.items.len().min(MAX_NEWS_ITEMS)]
+                });
+                let news_line = pack_width * 0.06;
+                let news_lines = news_items.len() + usize::from(self.news_offline);
+                let news_height = news_lines as f32 * news_line;
+                let bottom_section_top = pack_rect.bottom() - pack_height * 0.24 - news_height;
                 let bottom_text_rect = Rect::from_min_max(
                     Pos2::new(pack_rect.left() + 10.0, bottom_section_top - 8.0),
                     Pos2::new(pack_rect.right() - 10.0, pack_rect.bottom() - 8.0),
@@ -242,6 +261,48 @@ impl AboutScreen {
                     colors::ELECTRIC_GREEN,
                 );
 
+                // News, linked to its page where it has one
+                let news_top = bottom_section_top + pack_width * 0.10;
+                for (i, item) in news_items.iter().enumerate() {
+                    let mut title: String = item.title.chars().take(MAX_NEWS_TITLE).collect();
+                    if title.len() < item.title.len() {
+                        title.push('…');
+                    }
+                    let line = match &item.date {
+                        Some(date) => format!("{}: {} ({})", item.kind.name(), title, date),
+                        None => format!("{}: {}", item.kind.name(), title),
+                    };
+                    let rect = painter.text(
+                        Pos2::new(pack_rect.center().x, news_top + i as f32 * news_line),
+                        egui::Align2::CENTER_TOP,
+                        line,
+                        FontId::proportional(pack_width * 0.045),
+                        colors::BRIGHT_CYAN,
+                    );
+                    if let Some(url) = &item.url {
+                        let response = ui
+                            .interact(rect, ui.id().with(("news", i)), egui::Sense::click())
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text(url);
+                        if response.clicked() {
+                            let _ = open::that(url);
+                        }
+                    }
+                }
+                if self.news_offline {
+                    painter.text(
+                        Pos2::new(
+                            pack_rect.center().x,
+                            news_top + news_items.len() as f32 * news_line,
+                        ),
+                        egui::Align2::CENTER_TOP,
+                        "offline, showing saved news",
+                        FontId::proportional(pack_width * 0.04),
+                        colors::TEXT_DIM,
+                    );
+                }
+                let bottom_section_top = bottom_section_top + news_height;
+
                 // Tagline
                 painter.text(
                     Pos2::new(pack_rect.center().x, bottom_section_top + pack_width * 0.10),
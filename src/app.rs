@@ -1,66 +1,459 @@
 use eframe::egui;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::about::AboutScreen;
-use crate::config::Config;
-use crate::discovery::{self, CharacterFile, FileType};
+use crate::assets::{self, AssetKey, AssetLoader};
+use crate::baseline::{self, Baseline, ComplianceReport};
+use crate::changelog;
+use crate::config::{
+    Config, ConfigRecovery, IdFormat, LinkSite, NameSource, Palette, PrefixSelection, RestoreGuard,
+    SavedPrefix, TimeDisplay, DEFAULT_FONT_SIZE,
+};
+use crate::crash;
+use crate::diagnostics::Diagnostics;
+use crate::discovery::{self, CharacterFile, FileType, IgnoreRules};
+use crate::duplicates::{self, DuplicateGroup};
+use crate::elevate::{self, ElevatedSync};
 use crate::esi;
-use crate::process::{self, DetectedPrefix};
-use crate::settings;
+use crate::events::{AppEvent, EventBus, EventSender, Scan};
+use crate::gamepad::{GamepadAction, GamepadInput};
+use crate::golden::{self, GoldenImage, GoldenState};
+use crate::health::FileCensus;
+use crate::help::{self, HelpPanel, Topic};
+#[cfg(target_os = "linux")]
+use crate::icon;
+use crate::inspect::{self, KeyPath, TreeDiff};
+use crate::inspector::{Inspector, InspectorAction};
+use crate::journal::{self, IncompleteSync, SyncJournal};
+use crate::lint::{self, Finding, LintReport};
+use crate::marshal::{self, ExportFormat};
+use crate::migrate::{self, MigrationPlan};
+use crate::names::{self, NameProvider};
+use crate::news;
+use crate::notify::{self, NotifyEvent, NtfyTopic, Webhook};
+use crate::plugins::{self, Plugin};
+use crate::preflight;
+use crate::presets::{self, PresetRun, Schedule, SyncPreset};
+use crate::process::{self, DetectedPrefix, PrefixSource};
+use crate::report;
+use crate::scripting;
+use crate::settings::{self, BackupComparison, BackupTrigger};
+use crate::state::{self, AuditEntry, NameCache, TimedOperation, TimingRecord};
+use crate::status::{self, PresetState, PresetStatus, SharedStatus, StatusReport, StatusServer};
 use crate::theme;
+use crate::troubleshoot::{self, Check, Fix, Outcome};
+use crate::widgets::{self, Grouping, PickerDelegate, SelectableItem, SyncPicker, LIST_ICON_SIZE};
+use crate::windows_settings::{self, Direction, ProfileMapping};
 
-/// Represents a selectable item (either a character or user/account)
-#[derive(Clone)]
-struct SelectableItem {
-    file_idx: usize,
-    id: u64,
-    display_name: String,
-    is_default: bool,
-}
+/// Target lists longer than this start collapsed in the confirmation dialog
+const CONFIRM_LIST_COLLAPSE_THRESHOLD: usize = 8;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum Tab {
     Characters,
     Accounts,
+    Launcher,
+}
+
+impl Tab {
+    const ALL: [Tab; 3] = [Tab::Characters, Tab::Accounts, Tab::Launcher];
+
+    fn name(self) -> &'static str {
+        match self {
+            Tab::Characters => "Characters",
+            Tab::Accounts => "Accounts",
+            Tab::Launcher => "Launcher",
+        }
+    }
 }
 
 pub struct PackPreferencesApp {
     config: Config,
     detected_prefixes: Vec<DetectedPrefix>,
+    /// EVE clients the process watcher last saw, None before its first look
+    clients_running: Option<usize>,
     selected_prefix: Option<PathBuf>,
     settings_dir: Option<PathBuf>,
+    /// Discover files across every settings_* profile instead of just `settings_dir`
+    all_profiles_mode: bool,
+    /// Every settings_* profile in the selected prefix
+    profile_dirs: Vec<PathBuf>,
+    copy_profile_target: Option<PathBuf>,
     character_files: Vec<CharacterFile>,
+    /// Incremented per scan so results from superseded scans are dropped
+    scan_generation: u64,
+    scanning: bool,
+    resolving_names: bool,
+    /// Progress of the running name lookup as (done, total)
+    name_progress: Option<(usize, usize)>,
+    /// Character IDs on screen, which the name lookup resolves first
+    visible_ids: Arc<Mutex<Vec<u64>>>,
+    next_name_refresh: Instant,
+    refreshing_names: bool,
+    /// Cached names already re-checked this session
+    refreshed_ids: HashSet<u64>,
+    /// Progress of the running sync as (done, total)
+    sync_progress: Option<(usize, usize)>,
     character_names: HashMap<u64, String>,
-    source_selection: Option<usize>,
-    target_selections: HashSet<usize>,
+    /// Items for the active tab; rebuilt only after files, names or the tab change
+    items_cache: Option<(Tab, Rc<Vec<SelectableItem>>)>,
+    name_cache: NameCache,
+    /// Provider chosen in the config; see [`Self::set_name_provider`]
+    names: Arc<dyn NameProvider>,
+    /// Character IDs ESI did not return a name for
+    unresolved_ids: Vec<u64>,
+    picker: SyncPicker,
     dry_run_mode: bool,
     status_messages: Vec<String>,
-    show_backup_manager: bool,
-    backups: Vec<PathBuf>,
-    pending_confirmation: Option<PendingAction>,
+    /// Trigger the backup manager lists; None lists every backup
+    backup_filter: Option<BackupTrigger>,
+    /// Note for the next Backup Now
+    backup_note: String,
+    /// What has been typed to confirm a full restore
+    restore_typed: String,
+    /// Sync confirmed despite a client running in a target's prefix
+    sync_while_running: bool,
+    /// Pre-flight checklist of the sync being confirmed, run when the dialog opens
+    preflight: Option<Vec<preflight::Check>>,
+    /// Likely names of the backups the sync being confirmed will create, worked out
+    /// when the dialog opens; the real ones are stamped when the sync runs
+    backup_preview: Option<Vec<String>>,
+    /// Executables found in the plugins folder
+    plugins: Vec<Plugin>,
+    /// Plugins the next manual sync runs, by name and in order
+    sync_plugins: Vec<String>,
+    /// Backup name format being edited
+    backup_name_draft: String,
+    /// Duplicate As dialog, if it is open
+    duplicate_draft: Option<DuplicateDraft>,
+    /// Profiles the fallback search found outside the known layouts, to pick from
+    settings_candidates: Vec<PathBuf>,
+    searching_settings: bool,
+    /// Type of the network filesystem the settings directory is on, if it is on one
+    network_fs: Option<String>,
+    /// Set when a prefix is selected, so its first scan restores what was last
+    /// picked there
+    restore_selection: bool,
+    /// Character name typed into the Find box
+    character_lookup: String,
+    finding_character: bool,
+    /// Last lint run, or why the rules could not be loaded
+    lint_report: Option<Result<LintReport, String>>,
+    /// Baseline being put together from the inspector, for export
+    baseline_draft: Baseline,
+    /// Baseline being checked against, and how each file measures up to it
+    baseline: Option<(Baseline, ComplianceReport)>,
+    baseline_error: Option<String>,
+    /// Webhook being edited in the notifications window
+    webhook_draft: Webhook,
+    /// ntfy topic being edited in the notifications window
+    ntfy_draft: NtfyTopic,
+    /// `.rhai` files found in the scripts folder
+    scripts: Vec<PathBuf>,
+    /// Script running in the background, by file name
+    running_script: Option<String>,
+    /// Output of the last script run, ending with its result
+    script_output: Vec<String>,
+    /// Steam Deck layout is active
+    deck_mode: bool,
+    /// `--deck` was passed, overriding detection and config
+    force_deck: bool,
+    golden_images: Vec<GoldenImage>,
+    /// Name typed in the golden images window for a new image
+    new_golden_name: String,
+    /// Last comparison of the current profile with a golden image, by image name
+    golden_comparison: Option<(String, Vec<(String, GoldenState)>)>,
+    /// Name typed in the presets window for saving the current selection
+    new_preset_name: String,
+    /// When scheduled presets are next checked
+    next_preset_check: Instant,
+    /// Scheduled presets with a run in progress
+    running_presets: HashSet<String>,
+    /// Scheduled presets already reported as waiting for clients to close
+    deferred_presets: HashSet<String>,
+    /// Why each scheduled preset's last attempt failed, for the status endpoint
+    preset_errors: HashMap<String, String>,
+    /// What the status endpoint serves; kept current by the app
+    status_report: SharedStatus,
+    status_server: Option<StatusServer>,
+    /// Why the status endpoint could not start
+    status_server_error: Option<String>,
+    /// Prefixes from the system-wide registry; read-only
+    shared_prefixes: Vec<SavedPrefix>,
+    /// Log lines mirrored for the crash reporter
+    crash_log: crash::SharedLog,
+    /// Number of log lines already written to the persistent app log
+    persisted_log_len: usize,
+    /// Backups of the current profile, newest first
+    backups: Vec<BackupEntry>,
+    browsed_backup: Option<BrowsedBackup>,
+    /// Entries shown in the audit log window, if it is open
+    audit_entries: Option<Vec<AuditEntry>>,
+    /// Duplicated profiles in the selected prefix, each with the member to keep
+    duplicate_groups: Option<Vec<(DuplicateGroup, PathBuf)>>,
+    /// Ignore patterns being edited for the selected prefix, one per line
+    ignore_editor: Option<String>,
+    /// Results shown in the detection troubleshooter while it is open
+    troubleshooting: Option<Vec<Check>>,
+    /// Import-from-Windows wizard, if it is open
+    windows_transfer: Option<WindowsTransfer>,
+    /// Decoded settings file open in the inspector
+    inspector: Option<Inspector>,
+    /// Prefix migration wizard, if it is open
+    migration: Option<PrefixMigration>,
+    /// Backups already offered after a suspected settings reset this session
+    offered_reset_backups: HashSet<PathBuf>,
+    /// Modal dialogs waiting to be shown; only the front one is on screen
+    modals: VecDeque<Modal>,
     active_tab: Tab,
-    show_log_window: bool,
     log_paste_url: Option<String>,
-    sync_complete_message: Option<String>,
     update_available: Option<String>,
-    show_update_dialog: bool,
-    show_no_update_dialog: bool,
     about: AboutScreen,
+    /// The news feed was requested this session
+    news_fetched: bool,
+    help: HelpPanel,
+    assets: AssetLoader,
+    diagnostics: Diagnostics,
+    events: EventBus,
+    gamepad: GamepadInput,
+}
+
+/// A backup listed in the backup manager
+struct BackupEntry {
+    path: PathBuf,
+    created: Option<chrono::DateTime<chrono::Local>>,
+    /// None for backups made before triggers were recorded
+    trigger: Option<BackupTrigger>,
+    note: Option<String>,
+}
+
+/// Duplicate As dialog: the file used as a template and the IDs it is copied to
+struct DuplicateDraft {
+    file: CharacterFile,
+    ids_text: String,
+    /// The last ESI check of the IDs
+    check: Option<IdCheck>,
+    checking: bool,
+}
+
+/// ESI's answer for a list of IDs: those that are not characters, or why it could
+/// not be asked
+struct IdCheck {
+    ids: Vec<u64>,
+    unknown: Result<Vec<u64>, String>,
+}
+
+/// Contents of the backup open in the backup browser
+struct BrowsedBackup {
+    path: PathBuf,
+    files: Vec<(CharacterFile, Option<BackupComparison>)>,
+}
+
+/// Verification results of the last real sync
+struct SyncVerification {
+    source: PathBuf,
+    entries: Vec<settings::VerificationEntry>,
+}
+
+/// Modal dialogs, shown one at a time in the order they were opened, and the
+/// tool windows open beside them
+enum Modal {
+    /// Every open tool is shown, whatever dialog is in front
+    Tool(Tool),
+    Confirm(PendingAction),
+    ConfigRecovery(ConfigRecovery),
+    CrashReport(PathBuf),
+    StaleTempFiles(Vec<PathBuf>),
+    /// The last used profile looks reset; offers its newest backup
+    SettingsReset(PathBuf),
+    SyncComplete(String),
+    Verification(SyncVerification),
+    UpdateAvailable(String),
+    UpToDate,
+    /// Changelog markdown for the releases since the last run
+    WhatsNew(String),
+    /// A sync's journal was left behind by a crash
+    IncompleteSync(IncompleteSync),
+    /// Files an operation, named first, left out of place
+    HealthCheck(String, Vec<String>),
+}
+
+impl Modal {
+    /// Whether `other` is the same kind of dialog, or the same tool.
+    fn same_kind(&self, other: &Modal) -> bool {
+        match (self, other) {
+            (Modal::Tool(a), Modal::Tool(b)) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+/// Windows and panels that can stay open alongside each other
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    BackupManager,
+    BackupRetention,
+    BackupNames,
+    Presets,
+    Plugins,
+    Scripts,
+    Lint,
+    Fonts,
+    Baseline,
+    Notifications,
+    Golden,
+    Bookmarks,
+    Log,
+}
+
+/// What to do about an interrupted sync
+#[derive(Clone, Copy)]
+enum SyncRecovery {
+    Finish,
+    RollBack,
+    Discard,
 }
 
 #[derive(Clone)]
 enum PendingAction {
     Sync,
     Restore(PathBuf),
+    RestoreFile(PathBuf, BackupComparison),
+    CopyToProfile(PathBuf),
+    ImportConfig(Box<Config>),
+    DeleteFile(PathBuf),
+    /// Overwrite the selected targets with their files from a golden image
+    ResetToGolden(GoldenImage),
+    /// Populate a fresh Wine prefix from a golden image
+    BootstrapPrefix(GoldenImage, PathBuf),
+    /// Merge duplicated profiles into the chosen one
+    MergeDuplicates(DuplicateGroup, PathBuf),
+    /// Copy files between a Windows install and the prefix
+    WindowsTransfer(ProfileMapping, Direction, Vec<CharacterFile>),
+    /// Copy an old prefix's EVE data into a new one
+    MigratePrefix(MigrationPlan, PathBuf),
+    /// Write a baseline's values into every file that differs from it
+    ApplyBaseline(Vec<Finding>),
 }
 
-impl PackPreferencesApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let config = Config::load().unwrap_or_default();
+/// Prefix migration wizard: the prefix to copy from, the one to copy into and what
+/// that would copy.
+struct PrefixMigration {
+    old: Option<PathBuf>,
+    new: Option<PathBuf>,
+    /// Plan for the chosen pair, or why there is none
+    plan: Option<Result<MigrationPlan, String>>,
+}
+
+impl PrefixMigration {
+    fn replan(&mut self) {
+        self.plan = match (&self.old, &self.new) {
+            (Some(old), Some(new)) => {
+                Some(migrate::plan_migration(old, new).map_err(|e| e.to_string()))
+            }
+            _ => None,
+        };
+    }
+}
+
+/// Windows transfer wizard: a Windows EVE folder, one of its profiles and the files
+/// to copy to or from it.
+struct WindowsTransfer {
+    direction: Direction,
+    /// EVE folders found on mounted Windows partitions, plus any browsed to
+    sources: Vec<PathBuf>,
+    source: Option<PathBuf>,
+    profiles: Vec<ProfileMapping>,
+    profile: usize,
+    files: Vec<CharacterFile>,
+    selected: HashSet<PathBuf>,
+}
+
+impl WindowsTransfer {
+    fn select_source(&mut self, source: PathBuf, prefix: &Path) -> anyhow::Result<()> {
+        if !self.sources.contains(&source) {
+            self.sources.push(source.clone());
+        }
+        self.source = Some(source.clone());
+        self.profiles = Vec::new();
+        self.files = Vec::new();
+        self.selected.clear();
+        self.profiles = windows_settings::map_profiles(&source, prefix)?;
+        self.select_profile(0)
+    }
+
+    fn select_profile(&mut self, index: usize) -> anyhow::Result<()> {
+        self.profile = index;
+        self.files = Vec::new();
+        self.selected.clear();
+        if let Some(mapping) = self.profiles.get(index) {
+            let from = mapping.from_dir(self.direction);
+            // Exports are only offered for profiles that exist in the prefix
+            if from.is_dir() {
+                self.files = discovery::discover_character_files(from, &IgnoreRules::default())?;
+                self.selected = self.files.iter().map(|f| f.path.clone()).collect();
+            }
+        }
+        Ok(())
+    }
+
+    fn set_direction(&mut self, direction: Direction) -> anyhow::Result<()> {
+        self.direction = direction;
+        self.select_profile(self.profile)
+    }
+}
+
+/// Below this window size only the quick-sync view fits
+const MINI_MODE_SIZE: egui::Vec2 = egui::vec2(700.0, 350.0);
+
+/// Window size the quick-sync view shrinks to when switched on
+const MINI_VIEW_SIZE: egui::Vec2 = egui::vec2(420.0, 160.0);
 
-        // Apply custom theme
-        theme::apply_pack_theme(&cc.egui_ctx);
+/// Window size restored when leaving the quick-sync view from a small window
+const FULL_VIEW_SIZE: egui::Vec2 = egui::vec2(800.0, 450.0);
+
+/// Backups kept when a retention limit is first switched on for a trigger
+const DEFAULT_BACKUPS_KEPT: usize = 10;
+
+/// How often scheduled presets are checked for a pending run
+const PRESET_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the process watcher looks for EVE clients starting and stopping
+const PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Character IDs per ESI request while resolving names; small so the first names
+/// show up quickly
+const NAME_BATCH_SIZE: usize = 50;
+
+/// How often a few cached names are re-checked against ESI in the background
+const NAME_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Cached names re-checked per refresh, keeping ESI traffic to a trickle
+const NAME_REFRESH_COUNT: usize = 5;
+
+/// Cached names younger than this (in seconds) are not re-checked
+const NAME_REFRESH_AGE: i64 = 7 * 24 * 60 * 60;
+
+const WEEKDAYS: [chrono::Weekday; 7] = [
+    chrono::Weekday::Mon,
+    chrono::Weekday::Tue,
+    chrono::Weekday::Wed,
+    chrono::Weekday::Thu,
+    chrono::Weekday::Fri,
+    chrono::Weekday::Sat,
+    chrono::Weekday::Sun,
+];
+
+impl PackPreferencesApp {
+    pub fn new(cc: &eframe::CreationContext<'_>, force_deck: bool) -> Self {
+        let first_run = Config::config_path().is_ok_and(|path| !path.exists());
+        let (config, config_recovery) = Config::load_or_recover();
+        let (gamepad, gamepad_error) = GamepadInput::new(cc.egui_ctx.clone());
 
         // Set initial window position from config
         if let Some(ctx) = cc.egui_ctx.clone().into() {
@@ -69,34 +462,145 @@ impl PackPreferencesApp {
                 config.window_x,
                 config.window_y,
             )));
+            apply_window_options(&ctx, &config);
         }
 
         let mut app = Self {
             config,
             detected_prefixes: Vec::new(),
+            clients_running: None,
             selected_prefix: None,
             settings_dir: None,
+            all_profiles_mode: false,
+            profile_dirs: Vec::new(),
+            copy_profile_target: None,
             character_files: Vec::new(),
+            scan_generation: 0,
+            scanning: false,
+            resolving_names: false,
+            name_progress: None,
+            visible_ids: Arc::default(),
+            next_name_refresh: Instant::now() + NAME_REFRESH_INTERVAL,
+            refreshing_names: false,
+            refreshed_ids: HashSet::new(),
+            sync_progress: None,
             character_names: HashMap::new(),
-            source_selection: None,
-            target_selections: HashSet::new(),
+            items_cache: None,
+            name_cache: NameCache::load(),
+            names: Arc::new(names::IdsOnly),
+            unresolved_ids: Vec::new(),
+            picker: SyncPicker::default(),
             dry_run_mode: true,
             status_messages: Vec::new(),
-            show_backup_manager: false,
+            backup_filter: None,
+            backup_note: String::new(),
+            restore_typed: String::new(),
+            sync_while_running: false,
+            preflight: None,
+            backup_preview: None,
+            plugins: plugins::plugins_dir()
+                .map(|dir| plugins::discover_plugins(&dir))
+                .unwrap_or_default(),
+            sync_plugins: Vec::new(),
+            duplicate_draft: None,
+            settings_candidates: Vec::new(),
+            searching_settings: false,
+            network_fs: None,
+            restore_selection: false,
+            character_lookup: String::new(),
+            finding_character: false,
+            backup_name_draft: String::new(),
+            lint_report: None,
+            baseline_draft: Baseline::new("Fleet standard".to_string()),
+            baseline: None,
+            baseline_error: None,
+            webhook_draft: Webhook::new(String::new()),
+            ntfy_draft: NtfyTopic::new(String::new()),
+            scripts: Vec::new(),
+            running_script: None,
+            script_output: Vec::new(),
+            deck_mode: false,
+            force_deck,
+            golden_images: Vec::new(),
+            new_golden_name: String::new(),
+            golden_comparison: None,
+            new_preset_name: String::new(),
+            next_preset_check: Instant::now(),
+            running_presets: HashSet::new(),
+            deferred_presets: HashSet::new(),
+            preset_errors: HashMap::new(),
+            status_report: Arc::new(Mutex::new(StatusReport::new(
+                chrono::Local::now().to_rfc3339(),
+            ))),
+            status_server: None,
+            status_server_error: None,
+            shared_prefixes: Vec::new(),
+            crash_log: crash::SharedLog::default(),
+            persisted_log_len: 0,
             backups: Vec::new(),
-            pending_confirmation: None,
+            browsed_backup: None,
+            audit_entries: None,
+            troubleshooting: None,
+            windows_transfer: None,
+            inspector: None,
+            migration: None,
+            offered_reset_backups: HashSet::new(),
+            ignore_editor: None,
+            duplicate_groups: None,
+            modals: VecDeque::new(),
             active_tab: Tab::Accounts,
-            show_log_window: false,
             log_paste_url: None,
-            sync_complete_message: None,
             update_available: None,
-            show_update_dialog: false,
-            show_no_update_dialog: false,
             about: AboutScreen::new(),
+            news_fetched: false,
+            help: HelpPanel::new(),
+            assets: AssetLoader::new(cc.egui_ctx.clone()),
+            diagnostics: Diagnostics::new(),
+            events: EventBus::new(cc.egui_ctx.clone()),
+            gamepad,
         };
 
-        // Auto-detect on startup
+        if let Some(recovery) = config_recovery {
+            app.modals.push_back(Modal::ConfigRecovery(recovery));
+        }
+        app.apply_fonts(&cc.egui_ctx);
+        app.apply_layout(&cc.egui_ctx);
+        app.migrate_name_cache();
+        app.set_name_provider();
+
+        crash::install_panic_hook(app.crash_log.clone());
+        crash::set_enabled(app.config.crash_reporting);
+        if app.config.crash_reporting {
+            for report in crash::pending_reports() {
+                app.modals.push_back(Modal::CrashReport(report));
+            }
+        }
+
+        app.check_incomplete_syncs();
+        app.show_whats_new(first_run);
+        app.update_status_report();
+        app.apply_status_server();
+
+        // Auto-detect on startup, then keep watching
         app.scan_for_eve();
+        app.start_process_watcher();
+
+        app.status_messages.extend(gamepad_error);
+        match Config::load_shared_prefixes() {
+            Ok(prefixes) => app.shared_prefixes = prefixes,
+            Err(e) => app
+                .status_messages
+                .push(format!("Shared prefix registry ignored: {:#}", e)),
+        }
+
+        if app.config.news_feed {
+            match news::load_cached() {
+                Ok(feed) => app.about.news = feed,
+                Err(e) => app
+                    .status_messages
+                    .push(format!("Saved news ignored: {:#}", e)),
+            }
+        }
 
         // Check for updates on startup
         app.check_for_updates();
@@ -104,31 +608,136 @@ impl PackPreferencesApp {
         app
     }
 
+    /// Applies the app theme, plus the Steam Deck layout when it is on.
+    fn apply_layout(&mut self, ctx: &egui::Context) {
+        let deck_mode =
+            self.force_deck || self.config.deck_mode.unwrap_or_else(process::is_steam_deck);
+
+        theme::apply_theme(ctx, self.config.palette);
+        theme::apply_font_size(ctx, self.config.fonts.size);
+        if deck_mode {
+            theme::apply_deck_layout(ctx);
+            if !self.deck_mode {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Maximized(true));
+            }
+        }
+        self.deck_mode = deck_mode;
+    }
+
+    fn apply_fonts(&mut self, ctx: &egui::Context) {
+        for error in theme::apply_fonts(ctx, &self.config.fonts) {
+            self.status_messages.push(error);
+        }
+    }
+
+    /// Lets the user pick a font file for the interface or monospace text.
+    fn pick_font(&mut self, ctx: &egui::Context, mono: bool) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title(if mono {
+                "Choose Monospace Font"
+            } else {
+                "Choose Interface Font"
+            })
+            .add_filter("Fonts", &["ttf", "otf", "ttc"])
+            .pick_file()
+        else {
+            return;
+        };
+        let path = Some(path.to_string_lossy().to_string());
+        if mono {
+            self.config.fonts.mono_font = path;
+        } else {
+            self.config.fonts.ui_font = path;
+        }
+        let _ = self.config.save();
+        self.apply_fonts(ctx);
+    }
+
     fn scan_for_eve(&mut self) {
         self.status_messages.clear();
         self.status_messages
             .push("Scanning for EVE processes...".to_string());
 
+        // A native install has one settings location whether or not a client runs
+        if let Some(root) = process::native_root() {
+            match process::count_eve_processes() {
+                0 => self
+                    .status_messages
+                    .push("No running EVE instances found".to_string()),
+                running => self
+                    .status_messages
+                    .push(format!("Found {} EVE instance(s)", running)),
+            }
+            self.select_prefix(root);
+            return;
+        }
+
         match process::detect_eve_prefixes() {
             Ok(prefixes) => {
-                self.detected_prefixes = prefixes;
-                if let Some(first) = self.detected_prefixes.first() {
+                let running = prefixes.len();
+                let mut prefixes = prefixes;
+                let launchers: Vec<DetectedPrefix> = process::detect_launcher_prefixes()
+                    .into_iter()
+                    .filter(|l| !prefixes.iter().any(|p| p.path == l.path))
+                    .collect();
+                if !launchers.is_empty() {
+                    self.status_messages
+                        .push(format!("Found {} running EVE launcher(s)", launchers.len()));
+                }
+                prefixes.extend(launchers);
+                let installed: Vec<DetectedPrefix> = process::installed_prefixes()
+                    .into_iter()
+                    .filter(|i| !prefixes.iter().any(|p| p.path == i.path))
+                    .collect();
+                if !installed.is_empty() {
                     self.status_messages.push(format!(
-                        "Found {} EVE instance(s)",
-                        self.detected_prefixes.len()
+                        "Found {} EVE install(s) in Lutris, Bottles or CrossOver",
+                        installed.len()
                     ));
-                    self.select_prefix(first.path.clone());
+                }
+                self.detected_prefixes = prefixes;
+                self.detected_prefixes.extend(installed);
+                if running > 0 {
+                    self.status_messages
+                        .push(format!("Found {} EVE instance(s)", running));
+                    // Stay on the last prefix if it is among those running
+                    let last = self.config.last_prefix_path.as_deref().map(Path::new);
+                    let chosen = self.detected_prefixes[..running]
+                        .iter()
+                        .find(|d| Some(d.path.as_path()) == last)
+                        .unwrap_or(&self.detected_prefixes[0]);
+                    self.select_prefix(chosen.path.clone());
                 } else {
                     self.status_messages
                         .push("No running EVE instances found".to_string());
                     // Try to use last known prefix
-                    if let Some(ref last_path) = self.config.last_prefix_path {
-                        let path = PathBuf::from(last_path);
-                        if path.exists() {
-                            self.status_messages
-                                .push("Using last known prefix".to_string());
-                            self.select_prefix(path);
-                        }
+                    let last_path = self
+                        .config
+                        .last_prefix_path
+                        .as_ref()
+                        .map(PathBuf::from)
+                        .filter(|path| path.exists());
+                    if let Some(path) = last_path {
+                        self.status_messages
+                            .push("Using last known prefix".to_string());
+                        self.select_prefix(path);
+                    } else if let Some(installed) = self.detected_prefixes.first() {
+                        self.status_messages
+                            .push(format!("Using {}", installed.label()));
+                        self.select_prefix(installed.path.clone());
+                    } else if let Some(found) = self.probe_common_prefixes() {
+                        self.select_prefix(found);
+                    } else if let Some(path) = process::steam_library_prefixes()
+                        .into_iter()
+                        .next()
+                        .filter(|_| self.deck_mode)
+                    {
+                        // The Deck keeps games on internal storage or the SD card
+                        self.status_messages
+                            .push(format!("Using Steam library prefix {}", path.display()));
+                        self.select_prefix(path);
+                    } else {
+                        self.run_troubleshooter();
                     }
                 }
             }
@@ -138,19 +747,84 @@ impl PackPreferencesApp {
         }
     }
 
+    /// Looks for EVE settings in the usual prefix locations when neither a running
+    /// client nor a launcher says where EVE is, e.g. on first run. Anything found is
+    /// offered in the Detected list; returns the first.
+    fn probe_common_prefixes(&mut self) -> Option<PathBuf> {
+        let found = process::common_prefixes();
+        let first = found.first()?.path.clone();
+        self.status_messages.push(format!(
+            "Found EVE settings in {} common location(s); using {}",
+            found.len(),
+            first.display()
+        ));
+        if found.len() > 1 {
+            self.status_messages
+                .push("Pick another from the Detected list".to_string());
+        }
+        self.detected_prefixes.extend(found);
+        Some(first)
+    }
+
     fn select_prefix(&mut self, prefix: PathBuf) {
+        let was_last = self.config.last_prefix_path.as_deref() == prefix.to_str();
+        self.remember_selection();
         self.selected_prefix = Some(prefix.clone());
+        self.restore_selection = true;
+        self.searching_settings = false;
+        self.settings_candidates.clear();
         self.config.last_prefix_path = Some(prefix.to_string_lossy().to_string());
+        if process::native_root().is_none() {
+            self.config.record_prefix_use(
+                prefix.to_string_lossy().to_string(),
+                chrono::Utc::now().timestamp(),
+            );
+        }
+
+        let ignore = self.config.ignore_rules(&prefix);
+        self.profile_dirs = process::find_profile_dirs(&prefix).unwrap_or_default();
+        self.profile_dirs.retain(|dir| !ignore.ignores_dir(dir));
+        self.copy_profile_target = None;
+
+        let duplicates = duplicates::find_duplicates(&self.profile_dirs);
+        if !duplicates.is_empty() {
+            self.status_messages.push(format!(
+                "{} profile(s) are duplicated across install folders; see Settings > Duplicate Profiles",
+                duplicates.len()
+            ));
+        }
 
         // Find settings directories
         match process::find_settings_dirs(&prefix) {
             Ok(dirs) => {
-                if let Some(first_dir) = dirs.first() {
-                    self.settings_dir = Some(first_dir.clone());
+                let dirs: Vec<&PathBuf> =
+                    dirs.iter().filter(|dir| !ignore.ignores_dir(dir)).collect();
+                let last = self.config.last_settings_dir.as_deref().map(Path::new);
+                let chosen = dirs
+                    .iter()
+                    .find(|dir| Some(dir.as_path()) == last)
+                    .or(dirs.first());
+                if let Some(dir) = chosen {
+                    let servers = self.profile_servers();
+                    if servers.len() > 1 {
+                        let names: Vec<&str> = servers.iter().map(|s| s.name()).collect();
+                        self.status_messages.push(format!(
+                            "Found settings for {}; using {}. Switch with the Server dropdown",
+                            names.join(", "),
+                            discovery::profile_label(dir)
+                        ));
+                    }
+                    self.settings_dir = Some(dir.to_path_buf());
+                    if was_last {
+                        self.check_settings_reset();
+                    }
                     self.load_character_files();
                 } else {
-                    self.status_messages
-                        .push("No settings directories found".to_string());
+                    self.status_messages.push(
+                        "No settings directories in the usual places; searching the prefix"
+                            .to_string(),
+                    );
+                    self.search_settings_dirs(prefix.clone());
                 }
             }
             Err(e) => {
@@ -162,491 +836,6365 @@ impl PackPreferencesApp {
         let _ = self.config.save();
     }
 
+    /// Servers the prefix's profiles belong to, Tranquility first.
+    fn profile_servers(&self) -> Vec<discovery::Server> {
+        let mut servers: Vec<discovery::Server> = self
+            .profile_dirs
+            .iter()
+            .filter_map(|dir| discovery::server_for(dir))
+            .collect();
+        servers.sort();
+        servers.dedup();
+        servers
+    }
+
+    /// Switches to another settings profile of the selected prefix.
+    fn select_profile(&mut self, dir: PathBuf) {
+        self.status_messages
+            .push(format!("Using profile {}", discovery::profile_label(&dir)));
+        self.config.last_settings_dir = Some(dir.to_string_lossy().to_string());
+        let _ = self.config.save();
+        self.settings_dir = Some(dir);
+        self.copy_profile_target = None;
+        self.load_character_files();
+        if self.is_open(Tool::BackupManager) {
+            self.load_backups();
+        }
+    }
+
+    /// Starts a background scan of the selected settings directory (or every profile
+    /// in all-profiles mode); the result arrives as [`AppEvent::ScanFinished`].
     fn load_character_files(&mut self) {
-        let Some(ref settings_dir) = self.settings_dir else {
+        let Some(settings_dir) = self.settings_dir.clone() else {
             return;
         };
+        let prefix = self.selected_prefix.clone().unwrap_or_default();
+        let all_profiles = self.all_profiles_mode;
+        let ignore = self.config.ignore_rules(&prefix);
 
-        match discovery::discover_character_files(settings_dir) {
-            Ok(files) => {
-                let char_count = files
-                    .iter()
-                    .filter(|f| f.file_type == FileType::Character)
-                    .count();
-                let user_count = files
-                    .iter()
-                    .filter(|f| f.file_type == FileType::User)
-                    .count();
-                self.status_messages.push(format!(
-                    "Found {} character files, {} user files",
-                    char_count, user_count
-                ));
-                self.character_files = files;
-                self.source_selection = None;
-                self.target_selections.clear();
-                self.resolve_names();
-
-                // Auto-select tab based on available files
-                if char_count <= 1 && user_count > 1 {
-                    self.active_tab = Tab::Accounts;
-                } else if char_count > 1 {
-                    self.active_tab = Tab::Characters;
-                }
-            }
-            Err(e) => {
-                self.status_messages
-                    .push(format!("Failed to load files: {}", e));
-            }
+        let network_fs = process::network_filesystem(&settings_dir);
+        if network_fs.is_some() && network_fs != self.network_fs {
+            self.status_messages.push(format!(
+                "Settings are on a network filesystem ({}); copies are flushed to the \
+                 server before they replace a file, so syncs and backups run slower",
+                network_fs.as_deref().unwrap_or_default()
+            ));
         }
+        self.network_fs = network_fs;
+
+        self.scan_generation += 1;
+        self.scanning = true;
+        let generation = self.scan_generation;
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let result = scan_files(&settings_dir, &prefix, all_profiles, &ignore, &events)
+                .map_err(|e| e.to_string());
+            events.send(AppEvent::ScanFinished { generation, result });
+        });
     }
 
-    fn resolve_names(&mut self) {
-        // Get unique character IDs from char files only (user IDs are not character IDs)
-        let char_ids: Vec<u64> = self
-            .character_files
+    fn apply_scan(&mut self, scan: Scan) {
+        let files = scan.files;
+        let char_count = files
             .iter()
             .filter(|f| f.file_type == FileType::Character)
-            .map(|f| f.character_id)
-            .collect();
+            .count();
+        let user_count = files
+            .iter()
+            .filter(|f| f.file_type == FileType::User)
+            .count();
+        let launcher_count = files
+            .iter()
+            .filter(|f| f.file_type == FileType::Launcher)
+            .count();
+        self.status_messages.push(format!(
+            "Found {} character files, {} user files, {} launcher files",
+            char_count, user_count, launcher_count
+        ));
+        self.record_timing(TimingRecord::new(
+            TimedOperation::Scan,
+            files.len(),
+            scan.elapsed,
+        ));
+        self.character_files = files;
+        self.items_cache = None;
+        self.picker.clear();
+        self.resolve_names();
+        self.check_stale_temp_files();
 
-        // First, populate from cache
-        for id in &char_ids {
-            if let Some(name) = self.config.get_cached_name(*id) {
-                self.character_names.insert(*id, name.clone());
-            }
+        // Auto-select tab based on available files
+        if char_count <= 1 && user_count > 1 {
+            self.active_tab = Tab::Accounts;
+        } else if char_count > 1 {
+            self.active_tab = Tab::Characters;
         }
 
-        // Resolve uncached names
-        match esi::resolve_with_cache(&char_ids, &self.config.character_name_cache) {
-            Ok(new_names) => {
-                for (id, name) in new_names {
-                    self.character_names.insert(id, name.clone());
-                    self.config.cache_character_name(id, name);
-                }
-                let _ = self.config.save();
-
-                let resolved = self.character_names.len();
-                let total = char_ids.len();
-                if total > 0 {
-                    self.status_messages
-                        .push(format!("Resolved {}/{} character names", resolved, total));
-                }
-            }
-            Err(e) => {
-                self.status_messages
-                    .push(format!("Name resolution failed: {}", e));
-            }
+        if std::mem::take(&mut self.restore_selection) {
+            self.restore_prefix_selection();
         }
     }
 
-    fn browse_for_prefix(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .set_title("Select Wine Prefix (drive_c directory)")
-            .pick_folder()
-        {
-            self.select_prefix(path);
+    /// Keeps the active tab, source and targets for the selected prefix, to be
+    /// restored when it is selected again. Nothing is kept before its files load.
+    fn remember_selection(&mut self) {
+        let Some(prefix) = &self.selected_prefix else {
+            return;
+        };
+        if self.character_files.is_empty() || self.restore_selection {
+            return;
         }
+        let path_of = |idx: &usize| self.character_files.get(*idx).map(|f| f.path.clone());
+        let mut targets: Vec<PathBuf> = self.picker.targets.iter().filter_map(path_of).collect();
+        targets.sort();
+        let selection = PrefixSelection {
+            tab: Some(self.active_tab.name().to_string()),
+            source: self.picker.source.as_ref().and_then(path_of),
+            targets,
+        };
+        self.config
+            .prefix_selections
+            .insert(prefix.to_string_lossy().to_string(), selection);
     }
 
-    fn get_selectable_items(&self) -> Vec<SelectableItem> {
-        let target_type = match self.active_tab {
-            Tab::Characters => FileType::Character,
-            Tab::Accounts => FileType::User,
+    /// Restores what was picked in the selected prefix when it was last left.
+    /// Files that have since gone are skipped.
+    fn restore_prefix_selection(&mut self) {
+        let Some(prefix) = &self.selected_prefix else {
+            return;
+        };
+        let Some(selection) = self
+            .config
+            .prefix_selections
+            .get(prefix.to_string_lossy().as_ref())
+            .cloned()
+        else {
+            return;
         };
 
-        let mut seen = HashSet::new();
-        let mut result = Vec::new();
+        if let Some(tab) = Tab::ALL
+            .into_iter()
+            .find(|t| selection.tab.as_deref() == Some(t.name()))
+        {
+            self.active_tab = tab;
+        }
+        let index_of = |path: &PathBuf| self.character_files.iter().position(|f| &f.path == path);
+        if let Some(idx) = selection.source.as_ref().and_then(index_of) {
+            self.picker.reveal(idx);
+        }
+        let targets: Vec<usize> = selection.targets.iter().filter_map(index_of).collect();
+        for &idx in &targets {
+            self.picker.set_target(idx, true);
+        }
 
-        for (idx, file) in self.character_files.iter().enumerate() {
-            if file.file_type == target_type
-                && !seen.contains(&(file.character_id, file.is_default))
-            {
-                seen.insert((file.character_id, file.is_default));
+        if !targets.is_empty() {
+            self.status_messages.push(format!(
+                "Restored the last selection in this prefix: {} target(s)",
+                targets.len()
+            ));
+        }
+        let missing = selection.targets.len() - targets.len();
+        if missing > 0 {
+            self.status_messages.push(format!(
+                "{} previously selected target(s) are no longer in the loaded files",
+                missing
+            ));
+        }
+    }
 
-                let display_name = if file.is_default {
-                    match target_type {
-                        FileType::Character => "Default (new characters)".to_string(),
-                        FileType::User => "Default (new accounts)".to_string(),
-                    }
-                } else if target_type == FileType::Character {
-                    self.character_names
-                        .get(&file.character_id)
-                        .cloned()
-                        .unwrap_or_else(|| format!("Character {}", file.character_id))
-                } else {
-                    format!("Account {}", file.character_id)
-                };
+    /// Saves the current source and targets as a preset, replacing one with the same name.
+    fn save_preset(&mut self) {
+        let name = self.new_preset_name.trim().to_string();
+        let Some(source_idx) = self.picker.source else {
+            return;
+        };
+        if name.is_empty() {
+            return;
+        }
 
-                result.push(SelectableItem {
-                    file_idx: idx,
-                    id: file.character_id,
-                    display_name,
-                    is_default: file.is_default,
-                });
+        let preset = SyncPreset {
+            name: name.clone(),
+            source: self.character_files[source_idx].path.clone(),
+            targets: self
+                .planned_target_files()
+                .iter()
+                .map(|f| f.path.clone())
+                .collect(),
+            schedule: None,
+            last_run: None,
+            plugins: self.sync_plugins.clone(),
+        };
+        match self.config.presets.iter_mut().find(|p| p.name == name) {
+            Some(existing) => {
+                existing.source = preset.source;
+                existing.targets = preset.targets;
+                existing.plugins = preset.plugins;
             }
+            None => self.config.presets.push(preset),
         }
+        let _ = self.config.save();
+        self.new_preset_name.clear();
+        self.status_messages
+            .push(format!("Saved preset '{}'", name));
+    }
 
-        result
+    /// Selects a preset's source and targets among the loaded files.
+    fn load_preset(&mut self, preset: &SyncPreset) {
+        let index_of = |path: &PathBuf| self.character_files.iter().position(|f| &f.path == path);
+        let Some(source_idx) = index_of(&preset.source) else {
+            self.status_messages.push(format!(
+                "Preset '{}': source {} is not in the loaded files",
+                preset.name,
+                preset.source.display()
+            ));
+            return;
+        };
+
+        let targets: Vec<usize> = preset.targets.iter().filter_map(index_of).collect();
+        let missing = preset.targets.len() - targets.len();
+        self.sync_plugins = preset.plugins.clone();
+        self.picker.clear();
+        self.picker.set_source(source_idx);
+        for idx in targets {
+            self.picker.set_target(idx, true);
+        }
+        let source_type = self.character_files[source_idx].file_type;
+        self.active_tab = if source_type == FileType::User {
+            Tab::Accounts
+        } else {
+            Tab::Characters
+        };
+
+        self.status_messages
+            .push(format!("Loaded preset '{}'", preset.name));
+        if missing > 0 {
+            self.status_messages.push(format!(
+                "Preset '{}': {} target(s) are not in the loaded files",
+                preset.name, missing
+            ));
+        }
     }
 
-    fn select_all_targets(&mut self) {
-        let items = self.get_selectable_items();
-        for item in items {
-            if Some(item.file_idx) != self.source_selection {
-                self.target_selections.insert(item.file_idx);
+    /// Starts a background run of every scheduled preset that is due.
+    fn run_due_presets(&mut self) {
+        let now = chrono::Local::now().naive_local();
+        for preset in &self.config.presets {
+            if !preset.is_due(now) || self.running_presets.contains(&preset.name) {
+                continue;
             }
+
+            self.running_presets.insert(preset.name.clone());
+            let preset = preset.clone();
+            let backup_options = self.config.backup_options();
+            let events = self.events.sender();
+            thread::spawn(move || {
+                let result =
+                    presets::run_preset(&preset, BackupTrigger::Scheduled, &backup_options)
+                        .map_err(|e| e.to_string());
+                events.send(AppEvent::PresetFinished {
+                    name: preset.name,
+                    result,
+                });
+            });
         }
+        self.update_status_report();
     }
 
-    fn select_none_targets(&mut self) {
-        self.target_selections.clear();
+    /// Refreshes the scheduled presets the status endpoint reports.
+    fn update_status_report(&mut self) {
+        let now = chrono::Local::now().naive_local();
+        let presets = self
+            .config
+            .presets
+            .iter()
+            .filter_map(|preset| {
+                let schedule = preset.schedule?;
+                let state = if self.running_presets.contains(&preset.name) {
+                    PresetState::Running
+                } else if self.deferred_presets.contains(&preset.name) {
+                    PresetState::Deferred
+                } else if preset.is_due(now) {
+                    PresetState::Due
+                } else {
+                    PresetState::Idle
+                };
+                Some(PresetStatus {
+                    name: preset.name.clone(),
+                    schedule: schedule.describe(),
+                    last_run: preset.last_run.clone(),
+                    state,
+                    error: self.preset_errors.get(&preset.name).cloned(),
+                })
+            })
+            .collect();
+        if let Ok(mut report) = self.status_report.lock() {
+            report.set_presets(presets);
+        }
     }
 
-    fn perform_sync(&mut self) {
-        let Some(source_idx) = self.source_selection else {
-            self.status_messages.push("No source selected".to_string());
-            return;
-        };
-
-        if self.target_selections.is_empty() {
-            self.status_messages.push("No targets selected".to_string());
+    /// Starts, moves or stops the status endpoint to match the config.
+    fn apply_status_server(&mut self) {
+        let serving = self.status_server.as_ref().map(|s| s.addr().port());
+        if serving.is_some() && serving == self.config.status_port {
             return;
         }
 
-        let Some(ref settings_dir) = self.settings_dir else {
-            self.status_messages
-                .push("No settings directory".to_string());
+        self.status_server = None;
+        self.status_server_error = None;
+        let Some(port) = self.config.status_port else {
             return;
         };
+        match StatusServer::start(port, Arc::clone(&self.status_report)) {
+            Ok(server) => {
+                self.status_messages
+                    .push(format!("Serving status on {}", server.url()));
+                self.status_server = Some(server);
+            }
+            Err(e) => self.status_server_error = Some(format!("{:#}", e)),
+        }
+    }
 
-        // Create backup first (unless dry run)
-        if !self.dry_run_mode {
-            match settings::create_backup(settings_dir) {
-                Ok(backup_path) => {
-                    self.status_messages.push(format!(
-                        "Created backup: {}",
-                        backup_path
-                            .file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy()
-                    ));
-                }
-                Err(e) => {
-                    self.status_messages.push(format!("Backup failed: {}", e));
-                    return;
-                }
+    /// Checks every loaded file against the shipped and user lint rules.
+    fn run_lint(&mut self) {
+        self.lint_report = Some(
+            lint::load_rules()
+                .map(|rules| lint::run(&rules, &self.character_files))
+                .map_err(|e| format!("{:#}", e)),
+        );
+    }
+
+    /// Writes each finding's fix, one transaction per setting and value.
+    fn fix_findings(&mut self, findings: Vec<Finding>) {
+        let mut groups: Vec<(KeyPath, String, Vec<CharacterFile>)> = Vec::new();
+        for finding in findings {
+            let Some(fix) = finding.fix else { continue };
+            match groups
+                .iter_mut()
+                .find(|(path, text, _)| *path == finding.path && *text == fix)
+            {
+                Some((_, _, files)) => files.push(finding.file),
+                None => groups.push((finding.path, fix, vec![finding.file])),
             }
         }
 
-        // Get the source file
-        let source_file = &self.character_files[source_idx];
+        for (path, text, files) in groups {
+            let paths: Vec<PathBuf> = files.iter().map(|f| f.path.clone()).collect();
+            let fixed = journal::run(
+                "Lint fix",
+                Path::new(""),
+                &paths,
+                true,
+                &self.config.backup_options(),
+                |journal| inspect::bulk_edit(&files, &path, &text, journal),
+            );
+            match fixed {
+                Ok(edit) => {
+                    for backup in edit.backups {
+                        self.handle_event(AppEvent::BackupCreated(backup));
+                    }
+                    if edit.committed {
+                        self.audit("Lint fix", &edit.changed);
+                        self.status_messages.push(format!(
+                            "Set {} to {} in {} file(s)",
+                            path,
+                            text,
+                            edit.changed.len()
+                        ));
+                    } else {
+                        let reason = edit
+                            .results
+                            .iter()
+                            .find(|r| r.message.starts_with("Failed"))
+                            .map(|r| r.message.clone())
+                            .unwrap_or_default();
+                        self.status_messages
+                            .push(format!("Fixing {} changed nothing. {}", path, reason));
+                    }
+                }
+                Err(e) => self
+                    .status_messages
+                    .push(format!("Fixing {} failed: {:#}", path, e)),
+            }
+        }
+    }
 
-        // Get target files (same file type as source)
-        let target_ids: HashSet<u64> = self
-            .target_selections
-            .iter()
-            .map(|&i| self.character_files[i].character_id)
-            .collect();
+    /// Adds a setting from the inspector to the baseline being put together.
+    fn add_to_baseline(&mut self, file_type: FileType, path: KeyPath, value: String) {
+        match self.baseline_draft.add(file_type, &path, value.clone()) {
+            Ok(()) => {
+                self.status_messages
+                    .push(format!("Added {} = {} to the baseline", path, value));
+                self.open_modal(Modal::Tool(Tool::Baseline));
+            }
+            Err(e) => self.status_messages.push(format!("{:#}", e)),
+        }
+    }
 
-        let target_files: Vec<&CharacterFile> = self
-            .character_files
-            .iter()
-            .filter(|f| {
-                f.file_type == source_file.file_type
-                    && target_ids.contains(&f.character_id)
-                    && f.character_id != source_file.character_id
-            })
-            .collect();
+    fn export_baseline(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export Baseline")
+            .set_file_name(format!(
+                "{}.{}",
+                self.baseline_draft.name,
+                baseline::BASELINE_EXTENSION
+            ))
+            .add_filter("Baseline", &[baseline::BASELINE_EXTENSION])
+            .save_file()
+        else {
+            return;
+        };
+        match self.baseline_draft.save(&path) {
+            Ok(()) => self
+                .status_messages
+                .push(format!("Exported baseline to {}", path.display())),
+            Err(e) => self.baseline_error = Some(format!("{:#}", e)),
+        }
+    }
 
-        // Sync
-        match settings::sync_settings(source_file, &target_files, self.dry_run_mode) {
-            Ok(results) => {
-                let mut total_synced = 0;
-                for result in results {
-                    if result.success {
-                        total_synced += 1;
-                        let target_name = result
-                            .target_file
-                            .file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy();
-                        self.status_messages
-                            .push(format!("{}: {}", result.message, target_name));
-                    } else {
-                        self.status_messages.push(result.message);
+    fn load_baseline(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Load Baseline")
+            .add_filter("Baseline", &[baseline::BASELINE_EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+        match Baseline::load(&path) {
+            Ok(loaded) => self.check_baseline(loaded),
+            Err(e) => self.baseline_error = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Checks every loaded file against `baseline`.
+    fn check_baseline(&mut self, baseline: Baseline) {
+        self.baseline_error = None;
+        let report = baseline.check(&self.character_files);
+        self.baseline = Some((baseline, report));
+    }
+
+    fn load_scripts(&mut self) {
+        self.scripts = scripting::scripts_dir()
+            .map(|dir| scripting::discover_scripts(&dir))
+            .unwrap_or_default();
+    }
+
+    /// Runs a script in the background; its output lands in the Scripts window.
+    fn run_script(&mut self, path: PathBuf) {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        self.status_messages
+            .push(format!("Running script {}", name));
+        self.running_script = Some(name.clone());
+        self.script_output.clear();
+        let backup_options = self.config.backup_options();
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let run = scripting::run_script_file(&path, &backup_options);
+            events.send(AppEvent::ScriptFinished { name, run });
+        });
+    }
+
+    fn apply_preset_run(&mut self, name: &str, result: Result<PresetRun, String>) {
+        let message = match result {
+            Ok(PresetRun::Deferred) => {
+                if self.deferred_presets.insert(name.to_string()) {
+                    self.status_messages.push(format!(
+                        "Scheduled preset '{}' is waiting for EVE clients to close",
+                        name
+                    ));
+                }
+                return;
+            }
+            Ok(PresetRun::Synced { backups, results }) => {
+                for backup in backups {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                }
+                let succeeded = results.iter().filter(|r| r.success).count();
+                for result in results.iter().filter(|r| !r.success) {
+                    self.status_messages.push(result.message.clone());
+                }
+
+                if let Some(preset) = self.config.presets.iter().find(|p| p.name == name) {
+                    let record = state::SyncRecord {
+                        timestamp: chrono::Local::now().to_rfc3339(),
+                        source: preset.source.clone(),
+                        targets: results.iter().map(|r| r.target_file.clone()).collect(),
+                        dry_run: false,
+                        succeeded,
+                    };
+                    let _ = state::record_sync(&record);
+                    if let Ok(mut report) = self.status_report.lock() {
+                        report.last_sync = Some(record.timestamp.clone());
                     }
+                    let audited: Vec<PathBuf> = std::iter::once(record.source.clone())
+                        .chain(record.targets.iter().cloned())
+                        .collect();
+                    self.audit("Scheduled sync", &audited);
                 }
+                self.load_character_files();
 
-                let action = if self.dry_run_mode {
-                    "Would sync"
+                if succeeded == results.len() {
+                    self.preset_errors.remove(name);
                 } else {
-                    "Synced"
-                };
-                let message = format!("{} {} files", action, total_synced);
-                self.status_messages.push(message.clone());
-                self.sync_complete_message = Some(message);
+                    let failed = format!(
+                        "{} of {} file(s) failed",
+                        results.len() - succeeded,
+                        results.len()
+                    );
+                    self.notify(
+                        NotifyEvent::Failure,
+                        format!("Scheduled preset '{}': {}", name, failed),
+                    );
+                    self.notify_scheduled(true, format!("'{}': {}", name, failed));
+                    self.preset_errors.insert(name.to_string(), failed);
+                }
+                let synced = format!(
+                    "Scheduled preset '{}' synced {} of {} file(s)",
+                    name,
+                    succeeded,
+                    results.len()
+                );
+                self.notify(NotifyEvent::Sync, synced.clone());
+                if succeeded == results.len() {
+                    self.notify_scheduled(false, synced);
+                }
+                format!(
+                    "Scheduled preset '{}' synced {} of {} file(s)",
+                    name,
+                    succeeded,
+                    results.len()
+                )
             }
             Err(e) => {
-                self.status_messages.push(format!("Sync error: {}", e));
+                let message = format!("Scheduled preset '{}' did not run: {}", name, e);
+                self.notify(NotifyEvent::Failure, message.clone());
+                self.notify_scheduled(true, message.clone());
+                self.preset_errors.insert(name.to_string(), e);
+                message
             }
+        };
+
+        self.deferred_presets.remove(name);
+        if let Some(preset) = self.config.presets.iter_mut().find(|p| p.name == name) {
+            preset.mark_run();
+            let _ = self.config.save();
         }
+        self.update_status_report();
+        self.status_messages.push(message.clone());
+        self.open_modal(Modal::SyncComplete(message));
     }
 
-    fn load_backups(&mut self) {
-        if let Some(ref settings_dir) = self.settings_dir {
-            match settings::list_backups(settings_dir) {
-                Ok(backups) => {
-                    self.backups = backups;
+    /// Tells the configured webhook about `event` in the background, if it wants to
+    /// hear about it. Failures only reach the status log.
+    fn notify(&self, event: NotifyEvent, message: String) {
+        let Some(webhook) = self.config.webhook.clone().filter(|w| w.wants(event)) else {
+            return;
+        };
+        let events = self.events.sender();
+        thread::spawn(move || {
+            if let Err(e) = webhook.send(&message) {
+                events.send(AppEvent::Status(format!("Webhook failed: {:#}", e)));
+            }
+        });
+    }
+
+    /// Pushes the outcome of a scheduled preset to the ntfy topic in the background.
+    fn notify_scheduled(&self, failed: bool, message: String) {
+        let Some(ntfy) = self.config.ntfy.clone().filter(|n| n.wants(failed)) else {
+            return;
+        };
+        let title = if failed {
+            "Scheduled preset failed"
+        } else {
+            "Scheduled preset finished"
+        };
+        let events = self.events.sender();
+        thread::spawn(move || {
+            if let Err(e) = ntfy.send(title, &message, failed) {
+                events.send(AppEvent::Status(format!("ntfy failed: {:#}", e)));
+            }
+        });
+    }
+
+    /// Looks for EVE clients and launchers on a background thread for the life of
+    /// the app, reporting each time the running set changes.
+    fn start_process_watcher(&self) {
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let mut last = None;
+            loop {
+                let mut prefixes = process::detect_eve_prefixes().unwrap_or_default();
+                let running = process::count_eve_processes();
+                for launcher in process::detect_launcher_prefixes() {
+                    if !prefixes.iter().any(|p| p.path == launcher.path) {
+                        prefixes.push(launcher);
+                    }
+                }
+                let seen: Vec<PathBuf> = prefixes.iter().map(|p| p.path.clone()).collect();
+                if last.as_ref() != Some(&(running, seen.clone())) {
+                    last = Some((running, seen));
+                    events.send(AppEvent::ClientsChanged {
+                        running,
+                        prefixes,
+                        installed: process::installed_prefixes(),
+                    });
+                }
+                thread::sleep(PROCESS_POLL_INTERVAL);
+            }
+        });
+    }
+
+    /// The nickname a prefix was bookmarked under, here or in the shared registry.
+    fn prefix_nickname(&self, prefix: &Path) -> Option<&str> {
+        self.config
+            .saved_prefixes
+            .iter()
+            .chain(&self.shared_prefixes)
+            .find(|saved| Path::new(&saved.path) == prefix)
+            .and_then(|saved| saved.alias.as_deref())
+    }
+
+    /// Prefixes of the clients running as of the watcher's last look.
+    fn running_prefixes(&self) -> Vec<PathBuf> {
+        self.detected_prefixes
+            .iter()
+            .filter(|d| d.source == PrefixSource::Process)
+            .map(|d| d.path.clone())
+            .collect()
+    }
+
+    /// Applies what the process watcher saw: the prefixes of running clients and
+    /// launchers replace the previous ones at the top of the detected list, installs
+    /// whose client stopped are listed again, and the first is selected if no prefix
+    /// was.
+    fn apply_running_clients(
+        &mut self,
+        running: usize,
+        prefixes: Vec<DetectedPrefix>,
+        installed: Vec<DetectedPrefix>,
+    ) {
+        if self.clients_running.is_some_and(|before| before != running) {
+            self.status_messages
+                .push(format!("{} EVE client(s) running", running));
+        }
+        self.clients_running = Some(running);
+
+        let first = prefixes.first().map(|p| p.path.clone());
+        process::merge_running(&mut self.detected_prefixes, prefixes, installed);
+        if let Some(path) = first.filter(|_| self.selected_prefix.is_none()) {
+            self.select_prefix(path);
+        }
+    }
+
+    /// Fetches the About screen's news feed in the background, once per session.
+    fn fetch_news(&mut self) {
+        if !self.config.news_feed || self.news_fetched {
+            return;
+        }
+        self.news_fetched = true;
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let result = news::fetch(news::FEED_URL).map_err(|e| format!("{:#}", e));
+            events.send(AppEvent::NewsFetched(result));
+        });
+    }
+
+    fn send_test_push(&mut self) {
+        let ntfy = self.ntfy_draft.clone();
+        self.status_messages
+            .push(format!("Sending test push to {}...", ntfy.url()));
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let message = match ntfy.send("Pack Preferences", "Test notification", false) {
+                Ok(()) => "Test push sent".to_string(),
+                Err(e) => format!("ntfy failed: {:#}", e),
+            };
+            events.send(AppEvent::Status(message));
+        });
+    }
+
+    fn send_test_notification(&mut self) {
+        let webhook = self.webhook_draft.clone();
+        self.status_messages
+            .push("Sending test notification...".to_string());
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let message = match webhook.send("Test notification") {
+                Ok(()) => "Test notification sent".to_string(),
+                Err(e) => format!("Webhook failed: {:#}", e),
+            };
+            events.send(AppEvent::Status(message));
+        });
+    }
+
+    /// Records a mutating operation in the audit log.
+    fn audit(&mut self, action: &str, paths: &[PathBuf]) {
+        if let Err(e) = state::record_audit(&AuditEntry::new(action, paths)) {
+            self.status_messages
+                .push(format!("Failed to write audit log: {}", e));
+        }
+    }
+
+    /// Logs how long an operation took and keeps it for the report.
+    fn record_timing(&mut self, record: TimingRecord) {
+        self.status_messages.push(record.describe());
+        if let Err(e) = state::record_timing(&record) {
+            self.status_messages
+                .push(format!("Failed to record timing: {}", e));
+        }
+    }
+
+    fn process_events(&mut self) {
+        for event in self.events.drain() {
+            self.handle_event(event);
+        }
+    }
+
+    /// Applies a message from a background worker.
+    fn handle_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::Status(message) => self.status_messages.push(message),
+            AppEvent::ScanFinished { generation, result } => {
+                if generation != self.scan_generation {
+                    return;
+                }
+                self.scanning = false;
+                match result {
+                    Ok(scan) => self.apply_scan(scan),
+                    Err(e) => self
+                        .status_messages
+                        .push(format!("Failed to load files: {}", e)),
+                }
+            }
+            AppEvent::NameBatch {
+                done,
+                total,
+                resolution,
+            } => {
+                self.name_progress = Some((done, total));
+                self.apply_name_batch(resolution);
+            }
+            AppEvent::NamesResolved { ids, result } => {
+                self.resolving_names = false;
+                self.name_progress = None;
+                self.apply_resolution(&ids, result);
+            }
+            AppEvent::NamesRefreshed { ids, result } => {
+                self.apply_name_refresh(&ids, result);
+            }
+            AppEvent::SyncProgress { done, total } => {
+                self.sync_progress = (done < total).then_some((done, total));
+            }
+            AppEvent::IdsChecked { ids, result } => self.apply_id_check(ids, result),
+            AppEvent::CharacterFound { name, result } => self.reveal_character(&name, result),
+            AppEvent::SettingsSearched { prefix, dirs } => self.apply_settings_search(prefix, dirs),
+            AppEvent::ElevatedSyncFinished { sync, result } => {
+                self.apply_elevated_sync(sync, result)
+            }
+            AppEvent::ClientsChanged {
+                running,
+                prefixes,
+                installed,
+            } => {
+                self.apply_running_clients(running, prefixes, installed);
+            }
+            AppEvent::BackupCreated(path) => {
+                self.status_messages.push(format!(
+                    "Created backup: {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ));
+                self.notify(
+                    NotifyEvent::Backup,
+                    format!("Created backup {}", path.display()),
+                );
+                self.audit("Create backup", &[path]);
+                if let Ok(mut report) = self.status_report.lock() {
+                    report.last_backup = Some(chrono::Local::now().to_rfc3339());
+                }
+                if self.is_open(Tool::BackupManager) {
+                    self.load_backups();
+                }
+            }
+            AppEvent::NewsFetched(result) => match result {
+                Ok(feed) => {
+                    self.about.news = Some(feed);
+                    self.about.news_offline = false;
                 }
                 Err(e) => {
                     self.status_messages
-                        .push(format!("Failed to list backups: {}", e));
+                        .push(format!("News feed unavailable: {}", e));
+                    if self.about.news.is_none() {
+                        self.about.news = news::load_cached().ok().flatten();
+                    }
+                    self.about.news_offline = self.about.news.is_some();
+                }
+            },
+            AppEvent::PresetFinished { name, result } => {
+                self.running_presets.remove(&name);
+                self.apply_preset_run(&name, result);
+            }
+            AppEvent::ScriptFinished { name, run } => {
+                self.running_script = None;
+                self.script_output = run.output;
+                match run.result {
+                    Ok(value) => {
+                        if !value.is_empty() {
+                            self.script_output.push(format!("=> {}", value));
+                        }
+                        self.status_messages
+                            .push(format!("Script {} finished", name));
+                    }
+                    Err(e) => {
+                        self.script_output.push(format!("Error: {}", e));
+                        self.status_messages
+                            .push(format!("Script {} failed: {}", name, e));
+                    }
+                }
+                // The script may have synced files or created backups
+                self.load_character_files();
+                if self.is_open(Tool::BackupManager) {
+                    self.load_backups();
                 }
             }
         }
     }
 
-    fn restore_backup(&mut self, backup_path: PathBuf) {
+    /// Looks for `.tmp` files left by an interrupted sync and queues them for cleanup.
+    /// Offers the newest backup if the last used profile has suddenly lost its
+    /// files, e.g. because Steam recreated the prefix after a Proton update.
+    fn check_settings_reset(&mut self) {
         let Some(ref settings_dir) = self.settings_dir else {
             return;
         };
 
-        match settings::restore_backup(&backup_path, settings_dir) {
-            Ok(()) => {
-                self.status_messages
-                    .push("Backup restored successfully".to_string());
-                self.load_character_files();
+        match settings::find_reset_backup(settings_dir) {
+            Ok(Some(backup)) => {
+                if self.offered_reset_backups.insert(backup.clone()) {
+                    self.status_messages.push(format!(
+                        "{} has no settings of its own, but its backup {} does",
+                        discovery::profile_label(settings_dir),
+                        backup.file_name().unwrap_or_default().to_string_lossy()
+                    ));
+                    self.open_modal(Modal::SettingsReset(backup));
+                }
             }
-            Err(e) => {
-                self.status_messages.push(format!("Restore failed: {}", e));
+            Ok(None) => {}
+            Err(e) => self
+                .status_messages
+                .push(format!("Failed to check for a settings reset: {}", e)),
+        }
+    }
+
+    /// Offers to recover every sync a crash interrupted.
+    fn check_incomplete_syncs(&mut self) {
+        match journal::incomplete() {
+            Ok(syncs) => {
+                for sync in syncs {
+                    self.modals.push_back(Modal::IncompleteSync(sync));
+                }
             }
+            Err(e) => self
+                .status_messages
+                .push(format!("Failed to check for interrupted syncs: {:#}", e)),
         }
     }
 
-    fn upload_log_to_paste(&mut self, ctx: &egui::Context) {
-        let log_text = self.status_messages.join("\n");
-        if log_text.is_empty() {
-            return;
+    fn recover_sync(&mut self, sync: &IncompleteSync, recovery: SyncRecovery) {
+        match recovery {
+            SyncRecovery::Finish => match sync.finish(&self.config.backup_options()) {
+                Ok(count) => {
+                    self.status_messages.push(format!(
+                        "Finished the interrupted \"{}\": {} file(s) written",
+                        sync.operation, count
+                    ));
+                    let audited: Vec<PathBuf> = std::iter::once(sync.source.clone())
+                        .chain(sync.targets.iter().cloned())
+                        .collect();
+                    self.audit("Finish interrupted sync", &audited);
+                }
+                Err(e) => self
+                    .status_messages
+                    .push(format!("Could not finish \"{}\": {:#}", sync.operation, e)),
+            },
+            SyncRecovery::RollBack => match sync.roll_back() {
+                Ok(restored) => {
+                    self.status_messages.push(format!(
+                        "Rolled back the interrupted \"{}\": {} file(s) put back",
+                        sync.operation,
+                        restored.len()
+                    ));
+                    self.audit("Roll back interrupted sync", &restored);
+                }
+                Err(e) => self.status_messages.push(format!(
+                    "Could not roll back \"{}\": {:#}",
+                    sync.operation, e
+                )),
+            },
+            SyncRecovery::Discard => match sync.discard() {
+                Ok(()) => self.status_messages.push(format!(
+                    "Dismissed the interrupted \"{}\"; its files were left as they are",
+                    sync.operation
+                )),
+                Err(e) => self
+                    .status_messages
+                    .push(format!("Could not dismiss \"{}\": {:#}", sync.operation, e)),
+            },
         }
+        if self.settings_dir.is_some() {
+            self.load_character_files();
+        }
+    }
 
-        let client = reqwest::blocking::Client::new();
-        let form = reqwest::blocking::multipart::Form::new()
-            .text("text", log_text)
-            .text("lang", "text");
+    fn check_stale_temp_files(&mut self) {
+        let Some(ref settings_dir) = self.settings_dir else {
+            return;
+        };
 
-        match client
-            .post("https://pst.plb.so/paste/new")
-            .multipart(form)
-            .send()
-        {
-            Ok(response) => {
-                let url = response.url().to_string();
-                ctx.copy_text(url.clone());
-                self.log_paste_url = Some(url.clone());
+        match settings::find_stale_temp_files(settings_dir) {
+            Ok(files) => {
+                if !files.is_empty() {
+                    self.status_messages.push(format!(
+                        "Found {} leftover temporary file(s) from a previous run",
+                        files.len()
+                    ));
+                    self.open_modal(Modal::StaleTempFiles(files));
+                }
+            }
+            Err(e) => {
                 self.status_messages
-                    .push(format!("Log uploaded (copied to clipboard): {}", url));
+                    .push(format!("Failed to check for temporary files: {}", e));
+            }
+        }
+    }
+
+    fn clean_stale_temp_files(&mut self, files: &[PathBuf]) {
+        match settings::remove_temp_files(files) {
+            Ok(count) => {
+                self.status_messages
+                    .push(format!("Removed {} temporary file(s)", count));
+                self.audit("Remove temporary files", files);
             }
             Err(e) => {
-                self.status_messages.push(format!("Upload failed: {}", e));
+                self.status_messages
+                    .push(format!("Temporary file cleanup failed: {}", e));
             }
         }
     }
 
-    fn check_for_updates(&mut self) {
-        let current_version = env!("CARGO_PKG_VERSION");
+    /// Moves a name cache left in `config.toml` by older versions into the state directory.
+    fn migrate_name_cache(&mut self) {
+        if self.config.character_name_cache.is_empty() {
+            return;
+        }
 
-        let client = reqwest::blocking::Client::builder()
-            .user_agent("PackPreferences")
-            .build();
+        for (id, name) in std::mem::take(&mut self.config.character_name_cache) {
+            self.name_cache.insert(id, name);
+        }
+        if self.name_cache.save().is_ok() {
+            let _ = self.config.save();
+        }
+    }
 
-        let client = match client {
-            Ok(c) => c,
-            Err(_) => return,
-        };
+    fn resolve_names(&mut self) {
+        self.items_cache = None;
 
-        let response = client
-            .get("https://api.github.com/repos/sopleb/PackPreferences/releases/latest")
-            .send();
+        // Get unique character IDs from char files only (user IDs are not character IDs,
+        // and the default file's placeholder ID is not a real character)
+        let mut char_ids: Vec<u64> = self
+            .character_files
+            .iter()
+            .filter(|f| f.file_type == FileType::Character && !f.is_default)
+            .map(|f| f.character_id)
+            .collect();
+        char_ids.sort_unstable();
+        char_ids.dedup();
 
-        if let Ok(resp) = response {
-            if let Ok(json) = resp.json::<serde_json::Value>() {
-                if let Some(tag) = json.get("tag_name").and_then(|v| v.as_str()) {
-                    // Strip 'v' prefix if present for comparison
-                    let latest = tag.trim_start_matches('v');
-                    if Self::version_newer(latest, current_version) {
-                        self.update_available = Some(tag.to_string());
-                        self.show_update_dialog = true;
-                    }
+        if self.config.name_source == NameSource::IdsOnly {
+            self.unresolved_ids.clear();
+            return;
+        }
+
+        // First, populate from cache; local providers are cheap to ask again
+        let online = self.names.is_online();
+        if online {
+            for id in &char_ids {
+                if let Some(name) = self.name_cache.get(*id) {
+                    self.character_names.insert(*id, name.to_string());
                 }
             }
         }
-    }
-
-    fn version_newer(latest: &str, current: &str) -> bool {
-        let parse = |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse().ok()).collect() };
 
-        let latest_parts = parse(latest);
-        let current_parts = parse(current);
+        self.unresolved_ids = self.missing_names(&char_ids);
 
-        for (l, c) in latest_parts.iter().zip(current_parts.iter()) {
-            if l > c {
-                return true;
-            } else if l < c {
-                return false;
+        // Resolve uncached names off the UI thread in small batches, rows on screen
+        // first, so the lists fill in while the rest are still being looked up
+        let mut pending: Vec<u64> = self
+            .character_files
+            .iter()
+            .filter(|f| f.file_type == FileType::Character && !f.is_default)
+            .map(|f| f.character_id)
+            .filter(|id| !online || !self.name_cache.contains(*id))
+            .collect();
+        let mut seen = HashSet::new();
+        pending.retain(|id| seen.insert(*id));
+        let total = pending.len();
+        self.resolving_names = true;
+        self.name_progress = (total > 0).then_some((0, total));
+        let visible = Arc::clone(&self.visible_ids);
+        let names = Arc::clone(&self.names);
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let mut done = 0;
+            let mut result = Ok(esi::Resolution::default());
+            while !pending.is_empty() {
+                let priority = visible.lock().map(|v| v.clone()).unwrap_or_default();
+                let batch = esi::take_batch(&mut pending, &priority, NAME_BATCH_SIZE);
+                match names.resolve(&batch) {
+                    Ok(resolution) => {
+                        done += batch.len();
+                        events.send(AppEvent::NameBatch {
+                            done,
+                            total,
+                            resolution,
+                        });
+                    }
+                    Err(e) => {
+                        result = Err(e.to_string());
+                        break;
+                    }
+                }
             }
+            events.send(AppEvent::NamesResolved {
+                ids: char_ids,
+                result,
+            });
+        });
+    }
+
+    /// Looks up a few of the oldest cached names again in the background, so renamed
+    /// or deleted characters are picked up without a burst of requests at startup.
+    fn refresh_stale_names(&mut self) {
+        if self.refreshing_names || self.resolving_names || !self.names.is_online() {
+            return;
         }
 
-        latest_parts.len() > current_parts.len()
+        let cutoff = chrono::Local::now().timestamp() - NAME_REFRESH_AGE;
+        let ids: Vec<u64> = self
+            .name_cache
+            .stale_ids(cutoff)
+            .into_iter()
+            .filter(|id| !self.refreshed_ids.contains(id))
+            .take(NAME_REFRESH_COUNT)
+            .collect();
+        if ids.is_empty() {
+            return;
+        }
+
+        self.refreshed_ids.extend(&ids);
+        self.refreshing_names = true;
+        let names = Arc::clone(&self.names);
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let result = names.resolve(&ids).map_err(|e| e.to_string());
+            events.send(AppEvent::NamesRefreshed { ids, result });
+        });
     }
-}
 
-impl eframe::App for PackPreferencesApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn apply_name_refresh(&mut self, ids: &[u64], result: Result<esi::Resolution, String>) {
+        self.refreshing_names = false;
+        // A background job stays quiet on failure; the IDs are tried again later
+        let Ok(resolution) = result else {
+            for id in ids {
+                self.refreshed_ids.remove(id);
+            }
+            return;
+        };
+
+        for (id, name) in resolution.names {
+            if let Some(old) = self.name_cache.get(id).filter(|old| *old != name) {
+                self.status_messages
+                    .push(format!("{} is now named {}", old, name));
+            }
+            if self.character_names.contains_key(&id) {
+                self.character_names.insert(id, name.clone());
+            }
+            self.name_cache.insert(id, name);
+        }
+        for id in resolution.not_found {
+            self.character_names.remove(&id);
+            self.name_cache.mark_deleted(id);
+        }
+        self.items_cache = None;
+        let _ = self.name_cache.save();
+    }
+
+    /// Returns the IDs that have neither a name nor a deleted marker.
+    fn missing_names(&self, ids: &[u64]) -> Vec<u64> {
+        ids.iter()
+            .copied()
+            .filter(|id| !self.character_names.contains_key(id) && !self.name_cache.is_deleted(*id))
+            .collect()
+    }
+
+    /// Switches to the name provider chosen in the config and looks names up again.
+    /// A provider that can't be loaded falls back to showing IDs.
+    fn set_name_provider(&mut self) {
+        self.names = match names::provider(&self.config) {
+            Ok(provider) => provider,
+            Err(e) => {
+                self.status_messages
+                    .push(format!("Character names unavailable: {:#}", e));
+                Arc::new(names::IdsOnly)
+            }
+        };
+        self.character_names.clear();
+        self.refreshed_ids.clear();
+        self.items_cache = None;
+        if !self.character_files.is_empty() {
+            self.resolve_names();
+        }
+    }
+
+    /// Asks for the `id,name` file and switches to it.
+    fn pick_names_csv(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .set_title("Select Character Names File")
+            .add_filter("CSV", &["csv", "txt"])
+            .pick_file()
+        {
+            self.config.name_source = NameSource::Csv;
+            self.config.names_csv = Some(path.to_string_lossy().to_string());
+            let _ = self.config.save();
+            self.set_name_provider();
+        }
+    }
+
+    /// Shows the names from one returned batch right away.
+    fn apply_name_batch(&mut self, resolution: esi::Resolution) {
+        self.items_cache = None;
+        let online = self.names.is_online();
+        for (id, name) in resolution.names {
+            self.character_names.insert(id, name.clone());
+            if online {
+                self.name_cache.insert(id, name);
+            }
+        }
+        for id in resolution.not_found {
+            self.name_cache.mark_deleted(id);
+        }
+        self.unresolved_ids = self.missing_names(&self.unresolved_ids);
+    }
+
+    fn apply_resolution(&mut self, char_ids: &[u64], result: Result<esi::Resolution, String>) {
+        match result {
+            Ok(resolution) => self.apply_name_batch(resolution),
+            Err(e) => {
+                self.items_cache = None;
+                self.status_messages
+                    .push(format!("Name resolution failed: {}", e));
+            }
+        }
+        // Keep whatever batches arrived before a failure
+        let _ = self.name_cache.save();
+
+        self.unresolved_ids = self.missing_names(char_ids);
+
+        let total = char_ids.len();
+        if total > 0 {
+            let deleted = char_ids
+                .iter()
+                .filter(|id| self.name_cache.is_deleted(**id))
+                .count();
+            self.status_messages.push(format!(
+                "Resolved {}/{} character names ({} deleted)",
+                total - self.unresolved_ids.len() - deleted,
+                total,
+                deleted
+            ));
+        }
+    }
+
+    /// Stores the edited ignore patterns for the selected prefix and rescans with them.
+    fn save_ignore_patterns(&mut self, text: &str) {
+        let Some(prefix) = self.selected_prefix.clone() else {
+            return;
+        };
+        let patterns: Vec<String> = text
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        let key = prefix.to_string_lossy().to_string();
+        if patterns.is_empty() {
+            self.config.ignore_patterns.remove(&key);
+        } else {
+            self.config.ignore_patterns.insert(key, patterns);
+        }
+        let _ = self.config.save();
+        self.status_messages
+            .push("Saved ignore rules; rescanning".to_string());
+        self.select_prefix(prefix);
+    }
+
+    /// Searches the whole prefix for settings profiles in the background, when none
+    /// are where EVE normally keeps them.
+    fn search_settings_dirs(&mut self, prefix: PathBuf) {
+        self.searching_settings = true;
+        self.settings_candidates.clear();
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let dirs = process::search_settings_dirs(&prefix);
+            events.send(AppEvent::SettingsSearched { prefix, dirs });
+        });
+    }
+
+    fn apply_settings_search(&mut self, prefix: PathBuf, dirs: Vec<PathBuf>) {
+        if self.selected_prefix.as_ref() != Some(&prefix) {
+            return;
+        }
+        self.searching_settings = false;
+        if dirs.is_empty() {
+            self.status_messages
+                .push("No settings directories found".to_string());
+            self.run_troubleshooter();
+        } else {
+            self.status_messages.push(format!(
+                "Found {} settings folder(s) outside the usual layout; pick one",
+                dirs.len()
+            ));
+            self.settings_candidates = dirs;
+        }
+    }
+
+    /// Opens the troubleshooter with fresh results for the selected prefix.
+    fn run_troubleshooter(&mut self) {
+        self.troubleshooting = Some(troubleshoot::troubleshoot(self.selected_prefix.as_deref()));
+    }
+
+    fn apply_fix(&mut self, fix: Fix) {
+        self.troubleshooting = None;
+        match fix {
+            Fix::Rescan => self.scan_for_eve(),
+            Fix::BrowsePrefix => self.browse_for_prefix(),
+            Fix::UsePrefix(prefix) => self.select_prefix(prefix),
+            Fix::OpenUrl(url) => {
+                let _ = open::that(url);
+            }
+        }
+    }
+
+    fn browse_for_prefix(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .set_title("Select Wine Prefix (drive_c directory)")
+            .pick_folder()
+        {
+            self.select_prefix(path);
+        }
+    }
+
+    /// Picks another settings profile of the selected prefix with a folder dialog.
+    fn browse_for_profile(&mut self) {
+        let mut dialog =
+            rfd::FileDialog::new().set_title("Select Settings Profile (settings_* folder)");
+        if let Some(install) = self.settings_dir.as_deref().and_then(Path::parent) {
+            dialog = dialog.set_directory(install);
+        }
+        let Some(dir) = dialog.pick_folder() else {
+            return;
+        };
+        if self.profile_dirs.contains(&dir) {
+            self.select_profile(dir);
+        } else {
+            self.status_messages.push(format!(
+                "{} is not a settings profile of the selected prefix",
+                dir.display()
+            ));
+        }
+    }
+
+    fn export_app_config(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export App Settings")
+            .set_file_name("pack-preferences-config.toml")
+            .save_file()
+        else {
+            return;
+        };
+
+        match self.config.export_to(&self.name_cache.to_map(), &path) {
+            Ok(()) => self
+                .status_messages
+                .push(format!("Exported app settings to {}", path.display())),
+            Err(e) => self.status_messages.push(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// Writes a static HTML page of the roster, sync freshness, backups, operation
+    /// timings and, if a baseline is loaded, compliance.
+    fn export_html_report(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export HTML Report")
+            .set_file_name("eve-settings-report.html")
+            .add_filter("HTML", &["html"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let history = state::read_history().unwrap_or_default();
+        let timings = state::read_timings().unwrap_or_default();
+        let data = report::ReportData {
+            files: &self.character_files,
+            history: &history,
+            timings: &timings,
+            compliance: self.baseline.as_ref().map(|(b, r)| (b, r)),
+            time_display: self.config.time_display,
+        };
+        match report::export_html(&data, |f| self.display_name_for(f), &path) {
+            Ok(()) => self
+                .status_messages
+                .push(format!("Exported report to {}", path.display())),
+            Err(e) => self.status_messages.push(format!("Export failed: {:#}", e)),
+        }
+    }
+
+    /// Writes one value edited in the inspector, backing the profile up first.
+    fn save_inspector_edit(&mut self, inspector: &mut Inspector, path: KeyPath, text: String) {
+        let file = &inspector.file.path;
+        let edited = journal::run(
+            "Edit setting",
+            file,
+            std::slice::from_ref(file),
+            true,
+            &self.config.backup_options(),
+            |journal| inspect::edit_file(&inspector.file, &path, &text, journal),
+        );
+        match edited {
+            Ok(backup) => {
+                self.handle_event(AppEvent::BackupCreated(backup));
+                self.audit("Edit setting", std::slice::from_ref(&inspector.file.path));
+                self.status_messages.push(format!(
+                    "Set {} to {} for {}",
+                    path,
+                    text.trim(),
+                    inspector.title
+                ));
+                inspector.reload();
+            }
+            Err(e) => inspector.edit_failed(&path, text, format!("{:#}", e)),
+        }
+    }
+
+    /// Applies one inspector value to several files as a single transaction.
+    fn bulk_edit_setting(
+        &mut self,
+        inspector: &mut Inspector,
+        path: KeyPath,
+        text: String,
+        targets: Vec<CharacterFile>,
+    ) {
+        let paths: Vec<PathBuf> = targets.iter().map(|t| t.path.clone()).collect();
+        let edited = journal::run(
+            "Bulk edit setting",
+            &inspector.file.path,
+            &paths,
+            true,
+            &self.config.backup_options(),
+            |journal| inspect::bulk_edit(&targets, &path, &text, journal),
+        );
+        match edited {
+            Ok(edit) => {
+                for backup in edit.backups {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                }
+                if edit.committed {
+                    self.audit("Bulk edit setting", &edit.changed);
+                    self.status_messages.push(format!(
+                        "Set {} to {} in {} of {} file(s)",
+                        path,
+                        text.trim(),
+                        edit.changed.len(),
+                        targets.len()
+                    ));
+                } else {
+                    self.status_messages
+                        .push(format!("Bulk edit of {} failed; no files changed", path));
+                }
+                inspector.bulk_finished(edit.results);
+            }
+            Err(e) => {
+                self.status_messages
+                    .push(format!("Bulk edit of {} failed: {:#}", path, e));
+            }
+        }
+    }
+
+    /// Writes a settings file's decoded tree as JSON or YAML, picked by the
+    /// extension chosen in the save dialog.
+    fn export_decoded(&mut self, file: &Path) {
+        let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+        let mut dialog = rfd::FileDialog::new()
+            .set_title("Export Decoded Settings")
+            .set_file_name(format!("{}.json", stem));
+        for format in ExportFormat::ALL {
+            dialog = dialog.add_filter(format.name(), &[format.extension()]);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+
+        let format = ExportFormat::ALL
+            .into_iter()
+            .find(|f| {
+                path.extension().is_some_and(|ext| {
+                    ext == f.extension() || (ext == "yml" && *f == ExportFormat::Yaml)
+                })
+            })
+            .unwrap_or(ExportFormat::Json);
+        match marshal::export_decoded(file, &path, format) {
+            Ok(()) => self.status_messages.push(format!(
+                "Exported decoded {} to {}",
+                file.file_name().unwrap_or_default().to_string_lossy(),
+                path.display()
+            )),
+            Err(e) => self.status_messages.push(format!("Export failed: {:#}", e)),
+        }
+    }
+
+    /// Loads a config file and asks for confirmation before replacing the current one.
+    fn import_app_config(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import App Settings")
+            .add_filter("TOML", &["toml"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        match Config::import_from(&path) {
+            Ok(imported) => {
+                self.open_modal(Modal::Confirm(PendingAction::ImportConfig(Box::new(
+                    imported,
+                ))));
+            }
+            Err(e) => self.status_messages.push(format!("Import failed: {}", e)),
+        }
+    }
+
+    /// Replaces the config with an imported one and re-applies everything startup
+    /// and the settings menu apply when a setting changes.
+    fn apply_imported_config(&mut self, ctx: &egui::Context, imported: Config) {
+        let was_mini = self.config.mini_mode;
+        self.config = imported;
+        self.name_cache
+            .replace_names(std::mem::take(&mut self.config.character_name_cache));
+        if let Err(e) = self.name_cache.save() {
+            self.status_messages
+                .push(format!("Failed to save imported names: {}", e));
+        }
+        self.set_name_provider();
+        crash::set_enabled(self.config.crash_reporting);
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+            self.config.window_x,
+            self.config.window_y,
+        )));
+        apply_window_options(ctx, &self.config);
+        if self.config.mini_mode != was_mini {
+            let size = if self.config.mini_mode {
+                MINI_VIEW_SIZE
+            } else {
+                FULL_VIEW_SIZE
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        }
+        self.apply_fonts(ctx);
+        self.apply_layout(ctx);
+
+        if !self.config.news_feed {
+            self.about.news = None;
+            self.about.news_offline = false;
+            self.news_fetched = false;
+        }
+        self.update_status_report();
+        self.apply_status_server();
+
+        match self.config.save() {
+            Ok(()) => {
+                self.status_messages
+                    .push("Imported app settings".to_string());
+                if let Ok(path) = Config::config_path() {
+                    self.audit("Import app settings", &[path]);
+                }
+            }
+            Err(e) => self
+                .status_messages
+                .push(format!("Failed to save imported settings: {}", e)),
+        }
+    }
+
+    fn load_audit_log(&mut self) {
+        match state::read_audit() {
+            Ok((entries, skipped)) => {
+                if skipped > 0 {
+                    self.status_messages
+                        .push(format!("Skipped {} damaged audit log line(s)", skipped));
+                }
+                self.audit_entries = Some(entries);
+            }
+            Err(e) => self
+                .status_messages
+                .push(format!("Failed to read audit log: {}", e)),
+        }
+    }
+
+    fn export_audit_log(&mut self, entries: &[AuditEntry]) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export Audit Log")
+            .set_file_name("pack-preferences-audit.txt")
+            .save_file()
+        else {
+            return;
+        };
+
+        match std::fs::write(&path, state::format_audit(entries)) {
+            Ok(()) => self
+                .status_messages
+                .push(format!("Exported audit log to {}", path.display())),
+            Err(e) => self.status_messages.push(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn export_prefix_bookmarks(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Export Prefix Bookmarks")
+            .set_file_name("pack-preferences-prefixes.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let result = self
+            .config
+            .export_prefix_bundle()
+            .and_then(|json| std::fs::write(&path, json).map_err(Into::into));
+        match result {
+            Ok(()) => self
+                .status_messages
+                .push(format!("Exported bookmarks to {}", path.display())),
+            Err(e) => self.status_messages.push(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn import_prefix_bookmarks(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_title("Import Prefix Bookmarks")
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        let result = std::fs::read_to_string(&path)
+            .map_err(Into::into)
+            .and_then(|json| self.config.import_prefix_bundle(&json));
+        match result {
+            Ok((prefixes, presets)) => {
+                self.status_messages.push(format!(
+                    "Imported {} bookmarked prefix(es) and {} preset(s)",
+                    prefixes, presets
+                ));
+                let _ = self.config.save();
+                self.update_status_report();
+            }
+            Err(e) => self.status_messages.push(format!("Import failed: {}", e)),
+        }
+    }
+
+    /// Returns the items for the active tab, rebuilding the cached list if stale.
+    fn selectable_items(&mut self) -> Rc<Vec<SelectableItem>> {
+        if let Some((tab, items)) = &self.items_cache {
+            if *tab == self.active_tab {
+                return Rc::clone(items);
+            }
+        }
+
+        let items = Rc::new(self.build_selectable_items());
+        self.items_cache = Some((self.active_tab, Rc::clone(&items)));
+        items
+    }
+
+    fn build_selectable_items(&self) -> Vec<SelectableItem> {
+        let target_type = match self.active_tab {
+            Tab::Characters => FileType::Character,
+            Tab::Accounts => FileType::User,
+            Tab::Launcher => FileType::Launcher,
+        };
+
+        let mut seen = HashSet::new();
+        let mut result = Vec::new();
+
+        for (idx, file) in self.character_files.iter().enumerate() {
+            let key = (file.settings_dir(), file.character_id, file.is_default);
+            if file.file_type == target_type && !seen.contains(&key) {
+                seen.insert(key);
+
+                result.push(SelectableItem {
+                    file_idx: idx,
+                    id: file.character_id,
+                    display_name: self.display_name_for(file),
+                    is_default: file.is_default,
+                    settings_dir: file.settings_dir().to_path_buf(),
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Returns the label shown for a file: resolved name, account number or default marker.
+    fn display_name_for(&self, file: &CharacterFile) -> String {
+        if file.is_default {
+            match file.file_type {
+                FileType::Character => "Default (new characters)".to_string(),
+                FileType::User | FileType::Launcher => "Default (new accounts)".to_string(),
+            }
+        } else {
+            match file.file_type {
+                FileType::Character if self.name_cache.is_deleted(file.character_id) => {
+                    "Deleted character".to_string()
+                }
+                FileType::Character => self
+                    .character_names
+                    .get(&file.character_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Character {}", file.character_id)),
+                FileType::User => format!("Account {}", file.character_id),
+                FileType::Launcher => format!("Launcher account {}", file.character_id),
+            }
+        }
+    }
+
+    /// Returns the files that a sync with the current selection would overwrite.
+    fn planned_target_files(&self) -> Vec<&CharacterFile> {
+        let Some(source_idx) = self.picker.source else {
+            return Vec::new();
+        };
+        let source_file = &self.character_files[source_idx];
+
+        // Get target files (same file type as source), keyed by profile so the
+        // same character in another profile is a valid target
+        let target_keys: HashSet<(&Path, u64)> = self
+            .picker
+            .targets
+            .iter()
+            .map(|&i| {
+                let f = &self.character_files[i];
+                (f.settings_dir(), f.character_id)
+            })
+            .collect();
+
+        self.character_files
+            .iter()
+            .filter(|f| {
+                f.file_type == source_file.file_type
+                    && target_keys.contains(&(f.settings_dir(), f.character_id))
+                    && f.path != source_file.path
+            })
+            .collect()
+    }
+
+    /// Returns the distinct settings directories the planned sync will write to.
+    fn planned_target_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = self
+            .planned_target_files()
+            .iter()
+            .map(|f| f.settings_dir().to_path_buf())
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        dirs
+    }
+
+    /// Syncs the selection as root for target profiles owned by another account.
+    /// pkexec blocks while polkit asks for the password, so it runs on a worker
+    /// thread; the result arrives as [`AppEvent::ElevatedSyncFinished`]. The sync
+    /// is journaled and its profiles locked as for a normal sync; plugins are not
+    /// used.
+    fn sync_elevated(&mut self) {
+        let Some(source_idx) = self.picker.source else {
+            return;
+        };
+        let source = self.character_files[source_idx].path.clone();
+        let targets: Vec<PathBuf> = self
+            .planned_target_files()
+            .iter()
+            .map(|f| f.path.clone())
+            .collect();
+        let backup_options = self.config.backup_options();
+        let started =
+            ElevatedSync::plan(&source, &targets, Some(&backup_options)).and_then(|sync| {
+                let journal = SyncJournal::begin(&source, &targets, false, &backup_options)?;
+                Ok((sync, journal))
+            });
+        let (sync, journal) = match started {
+            Ok(started) => started,
+            Err(e) => {
+                self.status_messages
+                    .push(format!("Elevated sync cancelled: {:#}", e));
+                return;
+            }
+        };
+
+        self.status_messages.push(format!(
+            "Waiting for authorization to sync {} file(s) as administrator",
+            targets.len()
+        ));
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let result = sync.run(journal).map_err(|e| format!("{:#}", e));
+            events.send(AppEvent::ElevatedSyncFinished { sync, result });
+        });
+    }
+
+    fn apply_elevated_sync(&mut self, sync: ElevatedSync, result: Result<(), String>) {
+        if let Err(e) = result {
+            self.notify(NotifyEvent::Failure, format!("Elevated sync failed: {}", e));
+            self.status_messages
+                .push(format!("Elevated sync failed: {}", e));
+            return;
+        }
+
+        for (_, backup) in &sync.backups {
+            self.handle_event(AppEvent::BackupCreated(backup.clone()));
+        }
+        let results: Vec<settings::SyncResult> = sync
+            .targets
+            .iter()
+            .map(|target| settings::SyncResult {
+                target_file: target.clone(),
+                success: true,
+                message: "Copied as administrator".to_string(),
+            })
+            .collect();
+        let backups: HashMap<PathBuf, PathBuf> = sync.backups.iter().cloned().collect();
+        let entries = settings::verify_sync(&sync.source, &results, &backups);
+        let verified = entries.iter().filter(|e| e.matches_source).count();
+
+        let audited: Vec<PathBuf> = std::iter::once(sync.source.clone())
+            .chain(sync.targets.iter().cloned())
+            .collect();
+        self.audit("Sync settings as administrator", &audited);
+        let record = state::SyncRecord {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            source: sync.source.clone(),
+            targets: sync.targets.clone(),
+            dry_run: false,
+            succeeded: verified,
+        };
+        if let Err(e) = state::record_sync(&record) {
+            self.status_messages
+                .push(format!("Failed to record sync history: {}", e));
+        }
+        if let Ok(mut report) = self.status_report.lock() {
+            report.last_sync = Some(record.timestamp.clone());
+        }
+
+        let message = format!(
+            "Synced {} of {} file(s) as administrator",
+            verified,
+            sync.targets.len()
+        );
+        self.notify(NotifyEvent::Sync, message.clone());
+        self.status_messages.push(message.clone());
+        self.open_modal(Modal::SyncComplete(message));
+        self.open_modal(Modal::Verification(SyncVerification {
+            source: sync.source,
+            entries,
+        }));
+        self.load_character_files();
+    }
+
+    fn perform_sync(&mut self) {
+        let Some(source_idx) = self.picker.source else {
+            self.status_messages.push("No source selected".to_string());
+            return;
+        };
+
+        if self.picker.targets.is_empty() {
+            self.status_messages.push("No targets selected".to_string());
+            return;
+        }
+
+        if self.settings_dir.is_none() {
+            self.status_messages
+                .push("No settings directory".to_string());
+            return;
+        }
+
+        let sync_plugins = match plugins::select(&self.plugins, &self.sync_plugins) {
+            Ok(selected) => selected,
+            Err(e) => {
+                self.status_messages.push(format!("Sync cancelled: {}", e));
+                return;
+            }
+        };
+
+        // Note what the profiles hold; a sync only ever overwrites files
+        let target_dirs = self.planned_target_dirs();
+        let census = (!self.dry_run_mode).then(|| FileCensus::take(&target_dirs));
+
+        // Get the source file and the files it will overwrite
+        let source_file = self.character_files[source_idx].clone();
+        let target_files: Vec<CharacterFile> =
+            self.planned_target_files().into_iter().cloned().collect();
+        let target_refs: Vec<&CharacterFile> = target_files.iter().collect();
+
+        // Sync, journaled so a crash part way through can be recovered from, after
+        // backing up every affected profile (unless dry run)
+        let events = self.events.sender();
+        let progress = |done, total| events.send(AppEvent::SyncProgress { done, total });
+        let started = Instant::now();
+        let mut backup_time = Duration::ZERO;
+        let mut backups = HashMap::new();
+        let synced = if self.dry_run_mode {
+            settings::sync_settings_transformed(
+                &source_file,
+                &target_refs,
+                true,
+                &sync_plugins,
+                None,
+                progress,
+            )
+        } else {
+            journal::sync(
+                &source_file,
+                &target_refs,
+                &sync_plugins,
+                BackupTrigger::PreSync,
+                &self.config.backup_options(),
+                |dir, backup, elapsed| {
+                    backup_time += elapsed;
+                    self.record_timing(TimingRecord::new(
+                        TimedOperation::Backup,
+                        settings::count_files(dir),
+                        elapsed,
+                    ));
+                    self.handle_event(AppEvent::BackupCreated(backup.to_path_buf()));
+                },
+                progress,
+            )
+            .map(|journaled| {
+                backups = journaled.backups;
+                journaled.sync
+            })
+        };
+        let elapsed = started.elapsed().saturating_sub(backup_time);
+        match synced {
+            Ok(sync) => {
+                let target_paths: Vec<PathBuf> =
+                    sync.results.iter().map(|r| r.target_file.clone()).collect();
+                let mut verification = None;
+                if !self.dry_run_mode {
+                    let entries = if sync_plugins.is_empty() {
+                        settings::verify_sync(&source_file.path, &sync.results, &backups)
+                    } else {
+                        settings::verify_transformed_sync(&sync, &backups)
+                    };
+                    verification = Some(SyncVerification {
+                        source: source_file.path.clone(),
+                        entries,
+                    });
+                }
+
+                let mut total_synced = 0;
+                for result in sync.results {
+                    if result.success {
+                        total_synced += 1;
+                        let target_name = result
+                            .target_file
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy();
+                        self.status_messages
+                            .push(format!("{}: {}", result.message, target_name));
+                    } else {
+                        self.status_messages.push(result.message);
+                    }
+                }
+
+                let audited: Vec<PathBuf> = std::iter::once(source_file.path.clone())
+                    .chain(target_paths.iter().cloned())
+                    .collect();
+                let record = state::SyncRecord {
+                    timestamp: chrono::Local::now().to_rfc3339(),
+                    source: source_file.path.clone(),
+                    targets: target_paths,
+                    dry_run: self.dry_run_mode,
+                    succeeded: total_synced,
+                };
+                if let Err(e) = state::record_sync(&record) {
+                    self.status_messages
+                        .push(format!("Failed to record sync history: {}", e));
+                }
+                if !self.dry_run_mode {
+                    let source_name = self.display_name_for(&source_file);
+                    self.audit("Sync settings", &audited);
+                    self.record_timing(TimingRecord::new(
+                        TimedOperation::Sync,
+                        record.targets.len(),
+                        elapsed,
+                    ));
+                    if let Ok(mut report) = self.status_report.lock() {
+                        report.last_sync = Some(record.timestamp.clone());
+                    }
+
+                    let total = record.targets.len();
+                    self.notify(
+                        NotifyEvent::Sync,
+                        format!(
+                            "Synced {} to {} of {} file(s)",
+                            source_name, total_synced, total
+                        ),
+                    );
+                    if total_synced < total {
+                        self.notify(
+                            NotifyEvent::Failure,
+                            format!(
+                                "{} of {} file(s) failed to sync from {}",
+                                total - total_synced,
+                                total,
+                                source_name
+                            ),
+                        );
+                    }
+                }
+
+                let action = if self.dry_run_mode {
+                    "Would sync"
+                } else {
+                    "Synced"
+                };
+                let message = format!("{} {} files", action, total_synced);
+                self.status_messages.push(message.clone());
+                self.open_modal(Modal::SyncComplete(message));
+                if let Some(verification) = verification {
+                    self.open_modal(Modal::Verification(verification));
+                }
+                if let Some(census) = census {
+                    self.check_health("the sync", census, &target_dirs);
+                }
+            }
+            Err(e) => {
+                if !self.dry_run_mode {
+                    self.notify(NotifyEvent::Failure, format!("Sync failed: {:#}", e));
+                }
+                self.status_messages.push(format!("Sync error: {:#}", e));
+            }
+        }
+    }
+
+    /// Copies the selected source file into another profile, backing that profile up first.
+    fn copy_source_to_profile(&mut self, target_dir: PathBuf) {
+        let Some(source_idx) = self.picker.source else {
+            self.status_messages.push("No source selected".to_string());
+            return;
+        };
+
+        let source_file = &self.character_files[source_idx];
+        let source_name = self.display_name_for(source_file);
+        let source_path = source_file.path.clone();
+        let copied = if self.dry_run_mode {
+            settings::copy_to_profile(source_file, &target_dir, true, None).map(|r| (None, r))
+        } else {
+            let target = target_dir.join(source_path.file_name().unwrap_or_default());
+            journal::run(
+                "Copy to profile",
+                &source_path,
+                &[target],
+                false,
+                &self.config.backup_options(),
+                |journal| {
+                    let backup = journal.backup(&target_dir, BackupTrigger::PreSync)?;
+                    let result =
+                        settings::copy_to_profile(source_file, &target_dir, false, Some(journal))?;
+                    Ok((Some(backup), result))
+                },
+            )
+        };
+        match copied {
+            Ok((backup, result)) => {
+                if let Some(backup) = backup {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                }
+                let message = format!(
+                    "{} {} in {}",
+                    result.message,
+                    source_name,
+                    discovery::profile_label(&target_dir)
+                );
+                self.status_messages.push(message.clone());
+                if result.success {
+                    self.open_modal(Modal::SyncComplete(message));
+                    if !self.dry_run_mode {
+                        self.audit("Copy to profile", &[source_path, result.target_file]);
+                        self.load_character_files();
+                    }
+                }
+            }
+            Err(e) => {
+                self.status_messages.push(format!("Copy failed: {}", e));
+            }
+        }
+    }
+
+    /// Removes an obsolete settings file (e.g. of a deleted character) after a backup.
+    fn delete_settings_file(&mut self, path: PathBuf) {
+        let deleted = journal::run(
+            "Delete settings file",
+            &path,
+            std::slice::from_ref(&path),
+            true,
+            &self.config.backup_options(),
+            |journal| settings::delete_settings_file(&path, journal),
+        );
+        match deleted {
+            Ok(backup_path) => {
+                self.status_messages.push(format!(
+                    "Deleted {} (backup: {})",
+                    path.file_name().unwrap_or_default().to_string_lossy(),
+                    backup_path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                ));
+                self.audit("Delete settings file", &[path, backup_path]);
+                self.load_character_files();
+            }
+            Err(e) => {
+                self.status_messages.push(format!("Delete failed: {}", e));
+            }
+        }
+    }
+
+    /// Queues a modal dialog. A dialog of the same kind that is already queued is
+    /// replaced instead of shown twice.
+    fn open_modal(&mut self, modal: Modal) {
+        match self.modals.iter_mut().find(|m| m.same_kind(&modal)) {
+            Some(existing) => *existing = modal,
+            None => self.modals.push_back(modal),
+        }
+    }
+
+    fn is_open(&self, tool: Tool) -> bool {
+        self.modals
+            .iter()
+            .any(|m| matches!(m, Modal::Tool(open) if *open == tool))
+    }
+
+    fn close_tool(&mut self, tool: Tool) {
+        self.modals
+            .retain(|m| !matches!(m, Modal::Tool(open) if *open == tool));
+    }
+
+    fn toggle_tool(&mut self, tool: Tool) {
+        if self.is_open(tool) {
+            self.close_tool(tool);
+        } else {
+            self.open_modal(Modal::Tool(tool));
+        }
+    }
+
+    /// Whether a dialog is queued, as opposed to only tools being open.
+    fn dialog_open(&self) -> bool {
+        self.modals.iter().any(|m| !matches!(m, Modal::Tool(_)))
+    }
+
+    fn run_pending_action(&mut self, ctx: &egui::Context, action: PendingAction) {
+        match action {
+            PendingAction::Sync => self.perform_sync(),
+            PendingAction::Restore(path) => self.restore_backup(path),
+            PendingAction::RestoreFile(path, _) => self.restore_backup_file(path),
+            PendingAction::CopyToProfile(dir) => self.copy_source_to_profile(dir),
+            PendingAction::ImportConfig(imported) => self.apply_imported_config(ctx, *imported),
+            PendingAction::DeleteFile(path) => self.delete_settings_file(path),
+            PendingAction::ResetToGolden(image) => self.reset_to_golden(&image),
+            PendingAction::BootstrapPrefix(image, prefix) => self.bootstrap_prefix(&image, prefix),
+            PendingAction::MergeDuplicates(group, canonical) => {
+                self.merge_duplicates(&group, &canonical)
+            }
+            PendingAction::WindowsTransfer(mapping, direction, files) => {
+                self.transfer_windows_files(&mapping, direction, &files)
+            }
+            PendingAction::MigratePrefix(plan, new) => self.migrate_prefix(&plan, new),
+            PendingAction::ApplyBaseline(findings) => {
+                self.fix_findings(findings);
+                if let Some((baseline, _)) = self.baseline.take() {
+                    self.check_baseline(baseline);
+                }
+            }
+        }
+    }
+
+    /// Shows the modal dialog at the front of the queue. Tools are drawn with the
+    /// rest of the layout.
+    fn show_modal(&mut self, ctx: &egui::Context) {
+        let Some(modal) = self
+            .modals
+            .iter()
+            .position(|m| !matches!(m, Modal::Tool(_)))
+            .and_then(|front| self.modals.remove(front))
+        else {
+            return;
+        };
+
+        let keep_open = match &modal {
+            // Never picked above
+            Modal::Tool(_) => true,
+            Modal::Confirm(action) => {
+                let mut answer = None;
+                // Cleared by a guard that is still waiting to be satisfied
+                let mut confirmable = true;
+                let mut elevated = false;
+                egui::Window::new("Confirm")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        match action {
+                            PendingAction::Sync => {
+                                ui.label("Are you sure you want to sync settings?");
+                                if let Some(source) =
+                                    self.picker.source.map(|i| &self.character_files[i])
+                                {
+                                    ui.label(format!(
+                                        "Source: {} ({})",
+                                        self.display_name_for(source),
+                                        source
+                                            .path
+                                            .file_name()
+                                            .unwrap_or_default()
+                                            .to_string_lossy()
+                                    ));
+                                }
+
+                                let targets = self.planned_target_files();
+                                egui::CollapsingHeader::new(format!(
+                                    "Files to be written ({})",
+                                    targets.len()
+                                ))
+                                .default_open(targets.len() <= CONFIRM_LIST_COLLAPSE_THRESHOLD)
+                                .show(ui, |ui| {
+                                    egui::ScrollArea::vertical()
+                                        .id_salt("confirm_targets_scroll")
+                                        .max_height(150.0)
+                                        .show(ui, |ui| {
+                                            for target in &targets {
+                                                ui.label(format!(
+                                                    "{}  ({})",
+                                                    target
+                                                        .path
+                                                        .file_name()
+                                                        .unwrap_or_default()
+                                                        .to_string_lossy(),
+                                                    self.display_name_for(target)
+                                                ));
+                                            }
+                                        });
+                                });
+
+                                if let Some(warning) = self.picker.source.and_then(|i| {
+                                    server_mismatch_warning(
+                                        self.character_files[i].settings_dir(),
+                                        &self.planned_target_dirs(),
+                                    )
+                                }) {
+                                    ui.colored_label(theme::error(), warning);
+                                }
+
+                                if !self.dry_run_mode {
+                                    let target_paths: Vec<PathBuf> =
+                                        targets.iter().map(|t| t.path.clone()).collect();
+                                    let at_risk = process::files_in_prefixes(
+                                        &target_paths,
+                                        &self.running_prefixes(),
+                                    );
+                                    if !at_risk.is_empty() {
+                                        ui.colored_label(
+                                            theme::error(),
+                                            format!(
+                                                "An EVE client is running in this prefix. It rewrites \
+                                                 its settings when it exits, undoing the sync of \
+                                                 these {} file(s):",
+                                                at_risk.len()
+                                            ),
+                                        );
+                                        egui::ScrollArea::vertical()
+                                            .id_salt("confirm_at_risk_scroll")
+                                            .max_height(80.0)
+                                            .show(ui, |ui| {
+                                                for path in &at_risk {
+                                                    ui.label(format!(
+                                                        "  {}",
+                                                        path.file_name()
+                                                            .unwrap_or_default()
+                                                            .to_string_lossy()
+                                                    ));
+                                                }
+                                            });
+                                        ui.checkbox(&mut self.sync_while_running, "Sync anyway")
+                                            .on_hover_text(
+                                                "Only if those characters are not logged in \
+                                                 and won't be before the client closes",
+                                            );
+                                        confirmable = self.sync_while_running;
+                                    } else if self.clients_running.unwrap_or(0)
+                                        > self.running_prefixes().len()
+                                    {
+                                        ui.colored_label(
+                                            theme::error(),
+                                            "An EVE client is running whose prefix could not be \
+                                             found; close it first if it uses these settings.",
+                                        );
+                                    }
+                                    let source = self
+                                        .picker
+                                        .source
+                                        .map(|i| self.character_files[i].path.clone());
+                                    if let Some(source) = source {
+                                        let checks = self.preflight.get_or_insert_with(|| {
+                                            preflight::run(&source, &target_paths, true)
+                                        });
+                                        ui.label("Pre-flight checks:");
+                                        for check in checks.iter() {
+                                            let (mark, color) = match check.outcome {
+                                                Outcome::Passed => ("✔", theme::success()),
+                                                Outcome::Failed => ("✖", theme::error()),
+                                                Outcome::Skipped => ("–", theme::colors::TEXT_DIM),
+                                            };
+                                            ui.horizontal(|ui| {
+                                                ui.colored_label(color, mark);
+                                                ui.label(check.title)
+                                                    .on_hover_text(&check.detail);
+                                            });
+                                            if check.outcome == Outcome::Failed {
+                                                ui.colored_label(color, &check.detail);
+                                            }
+                                        }
+                                        if !checks.iter().all(preflight::Check::passed) {
+                                            // Only unwritable profiles can be synced as
+                                            // root, and the running-client guard still holds
+                                            let foreign = if preflight::only_unwritable(checks)
+                                                && confirmable
+                                            {
+                                                let mut dirs: Vec<&Path> = target_paths
+                                                    .iter()
+                                                    .filter_map(|t| t.parent())
+                                                    .collect();
+                                                dirs.sort();
+                                                dirs.dedup();
+                                                elevate::root_owned(&dirs)
+                                            } else {
+                                                Vec::new()
+                                            };
+                                            confirmable = false;
+                                            ui.horizontal(|ui| {
+                                                if ui.button("Check Again").clicked() {
+                                                    self.preflight = None;
+                                                }
+                                                if !foreign.is_empty()
+                                                    && ui
+                                                        .add_enabled(
+                                                            elevate::available()
+                                                                && self.sync_plugins.is_empty(),
+                                                            egui::Button::new(
+                                                                "Sync as Administrator...",
+                                                            ),
+                                                        )
+                                                        .on_hover_text(format!(
+                                                            "{} profile(s) are owned by root, \
+                                                             usually after an install run with \
+                                                             sudo. Backs up and copies the \
+                                                             files as root through pkexec, \
+                                                             which asks for your password once.",
+                                                            foreign.len()
+                                                        ))
+                                                        .on_disabled_hover_text(
+                                                            "Needs pkexec (polkit), outside Flatpak, \
+                                                             and a sync without plugins",
+                                                        )
+                                                        .clicked()
+                                                {
+                                                    elevated = true;
+                                                }
+                                            });
+                                        }
+                                    }
+                                    ui.label("This will overwrite target settings.");
+                                    ui.label("A backup will be created first, named like:")
+                                        .on_hover_text(
+                                            "The exact name depends on the time the sync starts",
+                                        );
+                                    let dirs = self.planned_target_dirs();
+                                    let names = self.backup_preview.get_or_insert_with(|| {
+                                        let options = self.config.backup_options();
+                                        dirs.iter()
+                                            .filter_map(|dir| {
+                                                settings::next_backup_path(
+                                                    dir,
+                                                    BackupTrigger::PreSync,
+                                                    None,
+                                                    &options,
+                                                )
+                                                .ok()
+                                            })
+                                            .map(|path| {
+                                                path.file_name()
+                                                    .unwrap_or_default()
+                                                    .to_string_lossy()
+                                                    .to_string()
+                                            })
+                                            .collect()
+                                    });
+                                    for name in names.iter() {
+                                        ui.label(format!("  {}", name));
+                                    }
+                                }
+                            }
+                            PendingAction::Restore(path) => {
+                                ui.label("Are you sure you want to restore this backup?");
+                                ui.label(format!(
+                                    "{}",
+                                    path.file_name().unwrap_or_default().to_string_lossy()
+                                ));
+                                if let Some(phrase) = self
+                                    .config
+                                    .restore_guard
+                                    .phrase(path, self.config.time_display)
+                                {
+                                    ui.label("Every file in the profile will be replaced.");
+                                    ui.horizontal(|ui| {
+                                        ui.label("Type");
+                                        ui.label(egui::RichText::new(&phrase).strong());
+                                        ui.label("to confirm:");
+                                    });
+                                    ui.text_edit_singleline(&mut self.restore_typed);
+                                    confirmable =
+                                        self.restore_typed.trim().eq_ignore_ascii_case(&phrase);
+                                }
+                            }
+                            PendingAction::RestoreFile(path, comparison) => {
+                                ui.label("Restore this file from the backup?");
+                                ui.label(path.file_name().unwrap_or_default().to_string_lossy());
+                                ui.label(describe_comparison(comparison));
+                                if let Some(keys) = &comparison.keys {
+                                    show_key_changes(ui, keys);
+                                }
+                                ui.label("A backup of the current profile will be created first.");
+                            }
+                            PendingAction::CopyToProfile(target_dir) => {
+                                if let Some(source) =
+                                    self.picker.source.map(|i| &self.character_files[i])
+                                {
+                                    ui.label(format!(
+                                        "Copy {} from {} to {}?",
+                                        self.display_name_for(source),
+                                        discovery::profile_label(source.settings_dir()),
+                                        discovery::profile_label(target_dir)
+                                    ));
+                                    if let Some(warning) = server_mismatch_warning(
+                                        source.settings_dir(),
+                                        std::slice::from_ref(target_dir),
+                                    ) {
+                                        ui.colored_label(theme::error(), warning);
+                                    }
+                                }
+                                if !self.dry_run_mode {
+                                    ui.label(
+                                        "A backup of the target profile will be created first.",
+                                    );
+                                }
+                            }
+                            PendingAction::DeleteFile(path) => {
+                                ui.label("Delete this settings file?");
+                                ui.label(path.file_name().unwrap_or_default().to_string_lossy());
+                                ui.label(
+                                    "A backup of its settings directory will be created first.",
+                                );
+                            }
+                            PendingAction::ResetToGolden(image) => {
+                                let targets = self.selected_target_files();
+                                ui.label(format!(
+                                    "Reset {} selected file(s) to golden image '{}'?",
+                                    targets.len(),
+                                    image.name
+                                ));
+                                egui::ScrollArea::vertical()
+                                    .id_salt("golden_targets_scroll")
+                                    .max_height(150.0)
+                                    .show(ui, |ui| {
+                                        for target in &targets {
+                                            let from = image
+                                                .file_for(target)
+                                                .and_then(|p| p.file_name().map(|n| n.to_owned()))
+                                                .map(|n| n.to_string_lossy().to_string())
+                                                .unwrap_or_else(|| "no match".to_string());
+                                            ui.label(format!(
+                                                "{}  <-  {}",
+                                                self.display_name_for(target),
+                                                from
+                                            ));
+                                        }
+                                    });
+                                ui.label(
+                                    "A backup of each affected profile will be created first.",
+                                );
+                            }
+                            PendingAction::BootstrapPrefix(image, prefix) => {
+                                ui.label(format!(
+                                    "Populate this prefix from golden image '{}'?",
+                                    image.name
+                                ));
+                                ui.label(prefix.display().to_string());
+                                if let Ok(dir) = image.bootstrap_dir(prefix) {
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "Settings will be written to {}",
+                                            dir.display()
+                                        ))
+                                        .color(theme::colors::TEXT_DIM),
+                                    );
+                                }
+                                ui.label("Do this before the first client launch in the prefix.");
+                            }
+                            PendingAction::ApplyBaseline(findings) => {
+                                let mut files: Vec<&Path> =
+                                    findings.iter().map(|f| f.file.path.as_path()).collect();
+                                files.dedup();
+                                ui.label(format!(
+                                    "Change {} setting(s) in {} file(s) to match the baseline?",
+                                    findings.len(),
+                                    files.len()
+                                ));
+                                ui.label(
+                                    "Each affected profile is backed up first. Close the EVE \
+                                     client, or it may overwrite the changes.",
+                                );
+                            }
+                            PendingAction::MigratePrefix(plan, new) => {
+                                ui.label(format!(
+                                    "Copy {} file(s) of EVE data into {}?",
+                                    plan.file_count(),
+                                    new.display()
+                                ));
+                                ui.label(
+                                    "Folders that already exist there are backed up first; \
+                                     files with the same name are overwritten.",
+                                );
+                                ui.label("Close the EVE client before migrating.");
+                            }
+                            PendingAction::WindowsTransfer(mapping, direction, files) => {
+                                let to_dir = mapping.to_dir(*direction);
+                                ui.label(match direction {
+                                    Direction::Import => format!(
+                                        "Import {} file(s) from Windows into {}?",
+                                        files.len(),
+                                        discovery::profile_label(to_dir)
+                                    ),
+                                    Direction::Export => format!(
+                                        "Export {} file(s) to the Windows profile {}?",
+                                        files.len(),
+                                        discovery::profile_label(to_dir)
+                                    ),
+                                });
+                                ui.label(to_dir.display().to_string());
+                                if self.dry_run_mode {
+                                    ui.label("Dry run: nothing will be written.");
+                                } else if !to_dir.is_dir() {
+                                    ui.label("The profile does not exist yet and will be created.");
+                                } else {
+                                    ui.label(
+                                        "Files with the same name are overwritten; the profile is backed up first.",
+                                    );
+                                }
+                            }
+                            PendingAction::MergeDuplicates(group, canonical) => {
+                                ui.label(format!(
+                                    "Merge {} copies of {} into this one?",
+                                    group.dirs.len(),
+                                    group.profile
+                                ));
+                                ui.label(canonical.display().to_string());
+                                match group.plan(canonical) {
+                                    Ok(plan) => {
+                                        ui.label(format!(
+                                            "{} newer file(s) will be copied in.",
+                                            plan.len()
+                                        ));
+                                    }
+                                    Err(e) => {
+                                        ui.colored_label(theme::error(), e.to_string());
+                                    }
+                                }
+                                ui.label(
+                                    "Every copy is backed up first; the others are then removed.",
+                                );
+                            }
+                            PendingAction::ImportConfig(imported) => {
+                                ui.label(
+                                    "Replace the current app settings with the imported file?",
+                                );
+                                let current = Config {
+                                    character_name_cache: self.name_cache.to_map(),
+                                    ..self.config.clone()
+                                };
+                                let changes = current.diff(imported);
+                                if changes.is_empty() {
+                                    ui.label("No differences from the current settings.");
+                                }
+                                egui::ScrollArea::vertical()
+                                    .id_salt("config_diff_scroll")
+                                    .max_height(150.0)
+                                    .show(ui, |ui| {
+                                        for change in changes {
+                                            ui.label(change);
+                                        }
+                                    });
+                            }
+                        }
+
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            // Big targets for touch, and Cancel focused so a stray
+                            // gamepad A press never confirms by accident
+                            let min_size = if self.deck_mode {
+                                egui::vec2(140.0, 56.0)
+                            } else {
+                                egui::Vec2::ZERO
+                            };
+                            if ui
+                                .add_enabled(
+                                    confirmable,
+                                    egui::Button::new("Yes").min_size(min_size),
+                                )
+                                .clicked()
+                            {
+                                answer = Some(true);
+                            }
+                            let cancel = ui.add(egui::Button::new("Cancel").min_size(min_size));
+                            if self.deck_mode && ui.memory(|m| m.focused().is_none()) {
+                                cancel.request_focus();
+                            }
+                            if cancel.clicked() {
+                                answer = Some(false);
+                            }
+                        });
+                        if self.deck_mode {
+                            ui.label(
+                                egui::RichText::new("A: choose   D-pad: move   B: cancel")
+                                    .color(theme::colors::TEXT_DIM),
+                            );
+                        }
+                        if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                            answer = Some(false);
+                        }
+                    });
+                if answer.is_some() || elevated {
+                    self.restore_typed.clear();
+                    self.sync_while_running = false;
+                    self.preflight = None;
+                    self.backup_preview = None;
+                }
+                if answer == Some(true) {
+                    self.run_pending_action(ctx, action.clone());
+                }
+                if elevated {
+                    self.sync_elevated();
+                }
+                answer.is_none() && !elevated
+            }
+            Modal::ConfigRecovery(recovery) => {
+                let mut close = false;
+                egui::Window::new("Settings File Damaged")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label("config.toml could not be read:");
+                        ui.label(
+                            egui::RichText::new(&recovery.error).color(theme::colors::TEXT_DIM),
+                        );
+                        ui.add_space(6.0);
+                        match &recovery.backup_path {
+                            Some(path) => {
+                                ui.label(format!("The original was saved as {}", path.display()));
+                            }
+                            None => {
+                                ui.label("The original file could not be backed up.");
+                            }
+                        }
+                        if recovery.recovered.is_empty() {
+                            ui.label("Nothing could be recovered; defaults are in use.");
+                        } else {
+                            ui.label(format!("Recovered: {}", recovery.recovered.join(", ")));
+                        }
+                        ui.add_space(10.0);
+                        if ui.button("OK").clicked() {
+                            close = true;
+                        }
+                    });
+                !close
+            }
+            Modal::CrashReport(report_path) => {
+                let mut handled = false;
+                egui::Window::new("Crash Report")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label("Pack Preferences crashed during a previous run.");
+                        ui.label("The report contains no character names or IDs.");
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Open GitHub Issue").clicked() {
+                                if let Ok(report) = std::fs::read_to_string(report_path) {
+                                    let names = self
+                                        .name_cache
+                                        .names()
+                                        .chain(self.character_names.values().map(String::as_str));
+                                    let report = crash::scrub_names(&report, names);
+                                    let _ = open::that(crash::issue_url(&report));
+                                }
+                                handled = true;
+                            }
+                            if ui.button("Dismiss").clicked() {
+                                handled = true;
+                            }
+                        });
+                    });
+                if handled {
+                    let _ = crash::dismiss_report(report_path);
+                }
+                !handled
+            }
+            Modal::StaleTempFiles(files) => {
+                let mut answer = None;
+                egui::Window::new("Leftover Temporary Files")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label("A previous sync was interrupted and left these files behind:");
+                        egui::ScrollArea::vertical()
+                            .id_salt("stale_temp_scroll")
+                            .max_height(120.0)
+                            .show(ui, |ui| {
+                                for path in files {
+                                    ui.label(
+                                        path.file_name().unwrap_or_default().to_string_lossy(),
+                                    );
+                                }
+                            });
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Delete").clicked() {
+                                answer = Some(true);
+                            }
+                            if ui.button("Ignore").clicked() {
+                                answer = Some(false);
+                            }
+                        });
+                    });
+                if answer == Some(true) {
+                    self.clean_stale_temp_files(files);
+                }
+                answer.is_none()
+            }
+            Modal::SettingsReset(backup) => {
+                let mut answer = None;
+                egui::Window::new("Settings Reset?")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(
+                            "The profile you used last time no longer has any character or \
+                             account settings, but a backup of it does. This usually means \
+                             Steam or Proton recreated the prefix.",
+                        );
+                        let files = discovery::discover_backup_files(backup)
+                            .map(|files| files.len())
+                            .unwrap_or(0);
+                        ui.label(format!(
+                            "Newest backup: {} ({} file(s))",
+                            backup.file_name().unwrap_or_default().to_string_lossy(),
+                            files
+                        ));
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Restore Backup").clicked() {
+                                answer = Some(true);
+                            }
+                            if ui.button("Not Now").clicked() {
+                                answer = Some(false);
+                            }
+                        });
+                    });
+                if answer == Some(true) {
+                    self.restore_backup(backup.clone());
+                }
+                answer.is_none()
+            }
+            Modal::SyncComplete(message) => {
+                let mut close = false;
+                egui::Window::new("Done")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(message);
+                        ui.add_space(10.0);
+                        if ui.button("OK").clicked() {
+                            close = true;
+                        }
+                    });
+                !close
+            }
+            Modal::HealthCheck(operation, findings) => {
+                let mut close = false;
+                egui::Window::new("Health Check")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(format!(
+                            "After {}, the profiles do not hold the files expected:",
+                            operation
+                        ));
+                        for finding in findings {
+                            ui.colored_label(theme::error(), finding);
+                        }
+                        ui.label("The backup taken first still has every file.");
+                        ui.add_space(10.0);
+                        if ui.button("OK").clicked() {
+                            close = true;
+                        }
+                    });
+                !close
+            }
+            Modal::Verification(verification) => {
+                let mut close = false;
+                egui::Window::new("Sync Verification")
+                    .collapsible(false)
+                    .default_size([560.0, 260.0])
+                    .show(ctx, |ui| {
+                        egui::ScrollArea::vertical()
+                            .id_salt("verification_scroll")
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("verification_grid")
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        ui.strong("Target");
+                                        ui.strong("SHA-256");
+                                        ui.strong("Match");
+                                        ui.strong("Previous version");
+                                        ui.end_row();
+
+                                        for entry in &verification.entries {
+                                            ui.label(
+                                                entry
+                                                    .target_file
+                                                    .file_name()
+                                                    .unwrap_or_default()
+                                                    .to_string_lossy(),
+                                            );
+                                            let hash =
+                                                entry.hash.as_deref().unwrap_or("unreadable");
+                                            ui.label(hash.get(..16).unwrap_or(hash))
+                                                .on_hover_text(hash);
+                                            if entry.matches_source {
+                                                ui.colored_label(theme::success(), "OK");
+                                            } else {
+                                                ui.colored_label(theme::error(), "MISMATCH");
+                                            }
+                                            match &entry.backup_file {
+                                                Some(path) => {
+                                                    ui.label(
+                                                        path.parent()
+                                                            .and_then(|p| p.file_name())
+                                                            .unwrap_or_default()
+                                                            .to_string_lossy(),
+                                                    )
+                                                    .on_hover_text(path.to_string_lossy());
+                                                }
+                                                None => {
+                                                    ui.label("none");
+                                                }
+                                            }
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Export Report").clicked() {
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .set_title("Save Sync Report")
+                                    .set_file_name("sync_report.txt")
+                                    .save_file()
+                                {
+                                    let report = settings::format_sync_report(
+                                        &verification.source,
+                                        &verification.entries,
+                                    );
+                                    match std::fs::write(&path, report) {
+                                        Ok(()) => self
+                                            .status_messages
+                                            .push(format!("Report saved: {}", path.display())),
+                                        Err(e) => self
+                                            .status_messages
+                                            .push(format!("Failed to save report: {}", e)),
+                                    }
+                                }
+                            }
+                            if ui.button("Close").clicked() {
+                                close = true;
+                            }
+                        });
+                    });
+                !close
+            }
+            Modal::UpdateAvailable(version) => {
+                let mut close = false;
+                egui::Window::new("Update Available")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(format!("A new version ({}) is available!", version));
+                        ui.label(format!("Current version: v{}", env!("CARGO_PKG_VERSION")));
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("Download").clicked() {
+                                let _ = open::that(
+                                    "https://github.com/sopleb/PackPreferences/releases/latest",
+                                );
+                                close = true;
+                            }
+                            if ui.button("Later").clicked() {
+                                close = true;
+                            }
+                        });
+                    });
+                !close
+            }
+            Modal::UpToDate => {
+                let mut close = false;
+                egui::Window::new("Up to Date")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label("You're running the latest version.");
+                        ui.label(format!("Current version: v{}", env!("CARGO_PKG_VERSION")));
+                        ui.add_space(10.0);
+                        if ui.button("OK").clicked() {
+                            close = true;
+                        }
+                    });
+                !close
+            }
+            Modal::IncompleteSync(sync) => {
+                let mut answer = None;
+                let mut later = false;
+                egui::Window::new("Interrupted Operation")
+                    .collapsible(false)
+                    .resizable(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .show(ctx, |ui| {
+                        ui.label(format!(
+                            "{} was interrupted before all {} of its file(s) were written.",
+                            sync.operation,
+                            sync.targets.len()
+                        ));
+                        if sync.touched.is_empty() {
+                            ui.label("No settings file had been changed yet.");
+                        } else {
+                            ui.label(format!(
+                                "{} file(s) may be half updated:",
+                                sync.touched.len()
+                            ));
+                            egui::ScrollArea::vertical()
+                                .id_salt("incomplete_sync_scroll")
+                                .max_height(120.0)
+                                .show(ui, |ui| {
+                                    for path in &sync.touched {
+                                        ui.label(
+                                            path.file_name().unwrap_or_default().to_string_lossy(),
+                                        );
+                                    }
+                                });
+                        }
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(sync.can_finish(), egui::Button::new("Finish"))
+                                .on_hover_text("Copy the source over every target again")
+                                .on_disabled_hover_text(
+                                    "The source is gone, or was not copied as is",
+                                )
+                                .clicked()
+                            {
+                                answer = Some(SyncRecovery::Finish);
+                            }
+                            if ui
+                                .add_enabled(sync.can_roll_back(), egui::Button::new("Roll Back"))
+                                .on_hover_text(
+                                    "Restore the targets from the backup taken before it started",
+                                )
+                                .on_disabled_hover_text("It stopped before its backup finished")
+                                .clicked()
+                            {
+                                answer = Some(SyncRecovery::RollBack);
+                            }
+                            if ui
+                                .button("Leave As Is")
+                                .on_hover_text("Forget it without changing any file")
+                                .clicked()
+                            {
+                                answer = Some(SyncRecovery::Discard);
+                            }
+                            if ui
+                                .button("Later")
+                                .on_hover_text("Ask again next time the app starts")
+                                .clicked()
+                            {
+                                later = true;
+                            }
+                        });
+                    });
+                if let Some(recovery) = answer {
+                    self.recover_sync(sync, recovery);
+                }
+                answer.is_none() && !later
+            }
+            Modal::WhatsNew(notes) => {
+                let mut close = false;
+                egui::Window::new("What's New")
+                    .collapsible(false)
+                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                    .default_width(420.0)
+                    .show(ctx, |ui| {
+                        egui::ScrollArea::vertical()
+                            .max_height(320.0)
+                            .show(ui, |ui| help::render_markdown(ui, notes));
+                        ui.add_space(10.0);
+                        if ui.button("OK").clicked() {
+                            close = true;
+                        }
+                    });
+                !close
+            }
+        };
+
+        if keep_open {
+            self.modals.push_front(modal);
+        }
+    }
+
+    fn load_backups(&mut self) {
+        if let Some(ref settings_dir) = self.settings_dir {
+            match settings::list_backups(settings_dir) {
+                Ok(backups) => {
+                    self.backups = backups
+                        .into_iter()
+                        .map(|path| BackupEntry {
+                            created: settings::backup_created(&path),
+                            trigger: settings::backup_trigger(&path),
+                            note: settings::backup_note(&path),
+                            path,
+                        })
+                        .collect();
+                }
+                Err(e) => {
+                    self.status_messages
+                        .push(format!("Failed to list backups: {}", e));
+                }
+            }
+        }
+    }
+
+    /// Backs up the current profile by hand, with the note typed beside the button.
+    fn backup_now(&mut self) {
+        let Some(settings_dir) = self.settings_dir.clone() else {
+            return;
+        };
+        let started = Instant::now();
+        match settings::create_noted_backup(
+            &settings_dir,
+            BackupTrigger::Manual,
+            Some(&self.backup_note),
+            &self.config.backup_options(),
+        ) {
+            Ok(backup) => {
+                self.backup_note.clear();
+                self.record_timing(TimingRecord::new(
+                    TimedOperation::Backup,
+                    settings::count_files(&settings_dir),
+                    started.elapsed(),
+                ));
+                self.handle_event(AppEvent::BackupCreated(backup));
+            }
+            Err(e) => self.status_messages.push(format!("Backup failed: {}", e)),
+        }
+    }
+
+    /// Deletes the current profile's backups beyond the retention limits.
+    fn prune_backups(&mut self) {
+        let Some(settings_dir) = self.settings_dir.clone() else {
+            return;
+        };
+        match settings::prune_backups(&settings_dir, &self.config.backup_retention) {
+            Ok(removed) => {
+                self.status_messages
+                    .push(format!("Deleted {} old backup(s)", removed.len()));
+                if !removed.is_empty() {
+                    self.audit("Prune backups", &removed);
+                }
+                self.load_backups();
+            }
+            Err(e) => self
+                .status_messages
+                .push(format!("Failed to prune backups: {:#}", e)),
+        }
+    }
+
+    fn restore_backup(&mut self, backup_path: PathBuf) {
+        let Some(ref settings_dir) = self.settings_dir else {
+            return;
+        };
+
+        let census = FileCensus::restored(&backup_path, settings_dir);
+        let restored = journal::run(
+            "Restore backup",
+            &backup_path,
+            std::slice::from_ref(settings_dir),
+            true,
+            &self.config.backup_options(),
+            |journal| settings::restore_backup(&backup_path, settings_dir, journal),
+        );
+        match restored {
+            Ok(()) => {
+                self.status_messages
+                    .push("Backup restored successfully".to_string());
+                let paths = [backup_path, settings_dir.clone()];
+                self.audit("Restore backup", &paths);
+                self.check_health("the restore", census, &paths[1..]);
+                self.load_character_files();
+            }
+            Err(e) => {
+                self.status_messages.push(format!("Restore failed: {}", e));
+            }
+        }
+    }
+
+    /// Opens `file` in the settings inspector, with the other files of its type to
+    /// search across.
+    fn inspect(&mut self, file: &CharacterFile, title: String) {
+        let peers = self
+            .character_files
+            .iter()
+            .filter(|f| f.file_type == file.file_type && f.path != file.path)
+            .map(|f| (self.display_name_for(f), f.clone()))
+            .collect();
+        self.inspector = Some(Inspector::open(file.clone(), title, peers));
+    }
+
+    /// Copies `file` to new IDs in its profile, e.g. for alts that have not logged
+    /// in yet.
+    fn duplicate_file(&mut self, file: &CharacterFile, ids: &[u64]) {
+        let results = if self.dry_run_mode {
+            settings::duplicate_for_ids(file, ids, true, None)
+        } else {
+            // Only IDs without settings are written, so the source can be copied
+            // to them again if this is interrupted
+            let targets: Vec<PathBuf> = ids
+                .iter()
+                .filter_map(|&id| discovery::settings_file_name(file.file_type, id))
+                .map(|name| file.settings_dir().join(name))
+                .filter(|path| !path.exists())
+                .collect();
+            let duplicated = journal::run(
+                "Duplicate settings file",
+                &file.path,
+                &targets,
+                false,
+                &self.config.backup_options(),
+                |journal| {
+                    let backup = journal.backup(file.settings_dir(), BackupTrigger::PreSync)?;
+                    Ok((
+                        backup,
+                        settings::duplicate_for_ids(file, ids, false, Some(journal)),
+                    ))
+                },
+            );
+            match duplicated {
+                Ok((backup, results)) => {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                    results
+                }
+                Err(e) => {
+                    self.status_messages
+                        .push(format!("Duplicate failed: {:#}", e));
+                    return;
+                }
+            }
+        };
+        let mut touched = vec![file.path.clone()];
+        for result in results {
+            self.status_messages.push(format!(
+                "{} {}",
+                result.message,
+                result
+                    .target_file
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            ));
+            if result.success {
+                touched.push(result.target_file);
+            }
+        }
+        self.status_messages.push(format!(
+            "Duplicated {} to {} of {} ID(s)",
+            self.display_name_for(file),
+            touched.len() - 1,
+            ids.len()
+        ));
+        if !self.dry_run_mode && touched.len() > 1 {
+            self.audit("Duplicate settings file", &touched);
+            self.load_character_files();
+        }
+    }
+
+    /// Selects the character named in the Find box as the source, asking the name
+    /// provider when it is not one of the names already known.
+    fn find_character(&mut self) {
+        let name = self.character_lookup.trim().to_string();
+        if name.is_empty() || self.finding_character {
+            return;
+        }
+        let known = self
+            .character_names
+            .iter()
+            .find(|(_, known)| known.to_lowercase() == name.to_lowercase())
+            .map(|(id, known)| (*id, known.clone()));
+        if known.is_some() {
+            self.reveal_character(&name, Ok(known));
+            return;
+        }
+
+        self.finding_character = true;
+        let names = Arc::clone(&self.names);
+        let events = self.events.sender();
+        thread::spawn(move || {
+            let result = names.find_character(&name).map_err(|e| format!("{:#}", e));
+            events.send(AppEvent::CharacterFound { name, result });
+        });
+    }
+
+    /// Selects and scrolls to the settings file of a character the Find box
+    /// looked up.
+    fn reveal_character(&mut self, name: &str, result: Result<Option<(u64, String)>, String>) {
+        self.finding_character = false;
+        let (id, found) = match result {
+            Ok(Some(found)) => found,
+            Ok(None) => {
+                self.status_messages
+                    .push(format!("No character named '{}' found", name));
+                return;
+            }
+            Err(e) => {
+                self.status_messages
+                    .push(format!("Looking up '{}' failed: {}", name, e));
+                return;
+            }
+        };
+        if !self.character_names.contains_key(&id) {
+            self.apply_name_batch(esi::Resolution {
+                names: HashMap::from([(id, found.clone())]),
+                not_found: Vec::new(),
+            });
+        }
+
+        let items = self.selectable_items();
+        match items.iter().find(|item| item.id == id && !item.is_default) {
+            Some(item) => {
+                if !self.picker.matches(item) {
+                    self.picker.filter.clear();
+                }
+                self.picker.reveal(item.file_idx);
+                self.status_messages
+                    .push(format!("Selected {} as the source", found));
+            }
+            None if !self.all_profiles_mode && self.profile_dirs.len() > 1 => {
+                self.status_messages.push(format!(
+                    "{} ({}) has no settings file in this profile; try All profiles",
+                    found, id
+                ));
+            }
+            None => self
+                .status_messages
+                .push(format!("{} ({}) has no settings file here", found, id)),
+        }
+    }
+
+    /// Takes in ESI's answer for the IDs typed into Duplicate As.
+    fn apply_id_check(&mut self, ids: Vec<u64>, result: Result<esi::Resolution, String>) {
+        let unknown = result.map(|resolution| {
+            let unknown = ids
+                .iter()
+                .copied()
+                .filter(|id| !resolution.names.contains_key(id))
+                .collect();
+            self.apply_name_batch(resolution);
+            unknown
+        });
+        if let Some(draft) = self.duplicate_draft.as_mut() {
+            draft.checking = false;
+            draft.check = Some(IdCheck { ids, unknown });
+        }
+    }
+
+    /// Checks the profiles in `dirs` hold what `expected` says the operation should
+    /// have left, raising anything amiss.
+    fn check_health(
+        &mut self,
+        operation: &str,
+        expected: anyhow::Result<FileCensus>,
+        dirs: &[PathBuf],
+    ) {
+        let findings = match expected.and_then(|expected| {
+            FileCensus::take(dirs).map(|found| (expected.compare(&found), found))
+        }) {
+            Ok((findings, found)) if findings.is_empty() => {
+                self.status_messages.push(format!(
+                    "Health check: {} character and {} user file(s) in place",
+                    found.count(FileType::Character),
+                    found.count(FileType::User)
+                ));
+                return;
+            }
+            Ok((findings, _)) => findings,
+            Err(e) => {
+                self.status_messages
+                    .push(format!("Health check failed: {:#}", e));
+                return;
+            }
+        };
+        for finding in &findings {
+            self.status_messages
+                .push(format!("Health check: {}", finding));
+        }
+        self.notify(
+            NotifyEvent::Failure,
+            format!(
+                "Health check after {} found {} problem(s)",
+                operation,
+                findings.len()
+            ),
+        );
+        self.open_modal(Modal::HealthCheck(operation.to_string(), findings));
+    }
+
+    fn browse_backup(&mut self, backup_path: PathBuf) {
+        let Some(settings_dir) = self.settings_dir.clone() else {
+            return;
+        };
+
+        match discovery::discover_backup_files(&backup_path) {
+            Ok(files) => {
+                let files = files
+                    .into_iter()
+                    .map(|file| {
+                        let comparison =
+                            settings::compare_with_live(&file.path, &settings_dir).ok();
+                        (file, comparison)
+                    })
+                    .collect();
+                self.browsed_backup = Some(BrowsedBackup {
+                    path: backup_path,
+                    files,
+                });
+            }
+            Err(e) => {
+                self.status_messages
+                    .push(format!("Failed to read backup: {}", e));
+            }
+        }
+    }
+
+    fn restore_backup_file(&mut self, backup_file: PathBuf) {
+        let Some(ref settings_dir) = self.settings_dir else {
+            return;
+        };
+
+        let live_file = settings_dir.join(backup_file.file_name().unwrap_or_default());
+        let restored = journal::run(
+            "Restore file from backup",
+            &backup_file,
+            std::slice::from_ref(&live_file),
+            false,
+            &self.config.backup_options(),
+            |journal| settings::restore_backup_file(&backup_file, settings_dir, journal),
+        );
+        match restored {
+            Ok(safety_backup) => {
+                self.status_messages.push(format!(
+                    "Restored {} (previous version saved in {})",
+                    backup_file
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy(),
+                    safety_backup
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                ));
+                self.audit(
+                    "Restore file from backup",
+                    &[live_file, backup_file.clone(), safety_backup],
+                );
+                self.load_backups();
+                self.load_character_files();
+            }
+            Err(e) => {
+                self.status_messages.push(format!("Restore failed: {}", e));
+            }
+        }
+        if let Some(path) = self.browsed_backup.as_ref().map(|b| b.path.clone()) {
+            self.browse_backup(path);
+        }
+    }
+
+    fn end_diagnostics(&mut self, ctx: &egui::Context) {
+        let assets = &self.assets;
+        let unresolved = self.unresolved_ids.len();
+        let scanning = self.scanning;
+        self.diagnostics.end_frame(ctx, || {
+            vec![
+                ("Portrait loader", format!("{} pending", assets.pending())),
+                ("Name resolution", format!("{} unresolved", unresolved)),
+                (
+                    "File scan",
+                    if scanning { "running" } else { "idle" }.to_string(),
+                ),
+            ]
+        });
+    }
+
+    /// Compact view with just a preset picker, a Sync button and the latest status.
+    fn show_mini(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let selected = self
+                    .config
+                    .mini_preset
+                    .clone()
+                    .filter(|name| self.config.presets.iter().any(|p| &p.name == name));
+                let mut choice = selected.clone();
+                egui::ComboBox::from_id_salt("mini_preset")
+                    .width(160.0)
+                    .selected_text(choice.as_deref().unwrap_or("Choose a preset"))
+                    .show_ui(ui, |ui| {
+                        for preset in &self.config.presets {
+                            ui.selectable_value(
+                                &mut choice,
+                                Some(preset.name.clone()),
+                                &preset.name,
+                            );
+                        }
+                    });
+                if choice != selected {
+                    self.config.mini_preset = choice.clone();
+                    let _ = self.config.save();
+                }
+
+                let preset = choice
+                    .and_then(|name| self.config.presets.iter().find(|p| p.name == name))
+                    .cloned();
+                let can_sync = preset.is_some() && !self.scanning && self.sync_progress.is_none();
+                if ui
+                    .add_enabled(can_sync, egui::Button::new("Sync"))
+                    .clicked()
+                {
+                    if let Some(preset) = preset {
+                        self.load_preset(&preset);
+                        if self.picker.has_selection() {
+                            self.check_stale_temp_files();
+                            self.open_modal(Modal::Confirm(PendingAction::Sync));
+                        }
+                    }
+                }
+                ui.checkbox(&mut self.dry_run_mode, "Dry run");
+            });
+
+            if let Some((done, total)) = self.sync_progress {
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total as f32)
+                        .text(format!("{}/{}", done, total)),
+                );
+            } else if self.scanning {
+                ui.spinner();
+            }
+            if let Some(last) = self.status_messages.last() {
+                ui.label(egui::RichText::new(last).color(theme::colors::TEXT_DIM));
+            }
+            if self.config.presets.is_empty() {
+                ui.label("Save a preset in the full view first.");
+            }
+
+            ui.with_layout(egui::Layout::bottom_up(egui::Align::RIGHT), |ui| {
+                if ui.small_button("Full view").clicked() {
+                    self.config.mini_mode = false;
+                    let _ = self.config.save();
+                    let size = ui.ctx().screen_rect().size();
+                    if size.x < MINI_MODE_SIZE.x || size.y < MINI_MODE_SIZE.y {
+                        ui.ctx()
+                            .send_viewport_cmd(egui::ViewportCommand::InnerSize(FULL_VIEW_SIZE));
+                    }
+                }
+            });
+        });
+    }
+
+    /// Returns the files ticked in the target list, whatever the source.
+    fn selected_target_files(&self) -> Vec<&CharacterFile> {
+        let mut targets: Vec<usize> = self.picker.targets.iter().copied().collect();
+        targets.sort_unstable();
+        targets.iter().map(|&i| &self.character_files[i]).collect()
+    }
+
+    fn load_golden_images(&mut self) {
+        match golden::list_golden() {
+            Ok(images) => self.golden_images = images,
+            Err(e) => self
+                .status_messages
+                .push(format!("Failed to list golden images: {}", e)),
+        }
+    }
+
+    fn save_golden_image(&mut self) {
+        let Some(settings_dir) = self.settings_dir.clone() else {
+            return;
+        };
+        match golden::create_golden(&self.new_golden_name, &settings_dir) {
+            Ok(image) => {
+                self.status_messages.push(format!(
+                    "Saved {} as golden image '{}'",
+                    discovery::profile_label(&settings_dir),
+                    image.name
+                ));
+                self.audit("Create golden image", &[image.path]);
+                self.new_golden_name.clear();
+            }
+            Err(e) => self
+                .status_messages
+                .push(format!("Failed to save golden image: {}", e)),
+        }
+        self.load_golden_images();
+    }
+
+    fn compare_golden(&mut self, image: &GoldenImage) {
+        let Some(ref settings_dir) = self.settings_dir else {
+            return;
+        };
+        match image.compare(settings_dir) {
+            Ok(states) => self.golden_comparison = Some((image.name.clone(), states)),
+            Err(e) => self
+                .status_messages
+                .push(format!("Comparison failed: {}", e)),
+        }
+    }
+
+    fn reset_to_golden(&mut self, image: &GoldenImage) {
+        let targets = self.selected_target_files();
+        let paths: Vec<PathBuf> = targets.iter().map(|t| t.path.clone()).collect();
+        let reset = journal::run(
+            "Reset to golden image",
+            &image.path,
+            &paths,
+            true,
+            &self.config.backup_options(),
+            |journal| image.reset(&targets, journal),
+        );
+        match reset {
+            Ok((backups, results)) => {
+                for backup in backups {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                }
+                let mut audited = Vec::new();
+                for result in results {
+                    let name = result
+                        .target_file
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string();
+                    self.status_messages
+                        .push(format!("{}: {}", result.message, name));
+                    if result.success {
+                        audited.push(result.target_file);
+                    }
+                }
+                self.audit("Reset to golden image", &audited);
+                self.load_character_files();
+            }
+            Err(e) => self.status_messages.push(format!("Reset failed: {}", e)),
+        }
+        self.golden_comparison = None;
+    }
+
+    /// Asks for a freshly created prefix to populate from a golden image.
+    fn pick_bootstrap_prefix(&mut self, image: GoldenImage) {
+        if let Some(prefix) = rfd::FileDialog::new()
+            .set_title("Select New Wine Prefix (drive_c directory)")
+            .pick_folder()
+        {
+            self.open_modal(Modal::Confirm(PendingAction::BootstrapPrefix(
+                image, prefix,
+            )));
+        }
+    }
+
+    fn bootstrap_prefix(&mut self, image: &GoldenImage, prefix: PathBuf) {
+        match image.bootstrap(&prefix) {
+            Ok(settings_dir) => {
+                self.status_messages.push(format!(
+                    "Populated {} from golden image '{}'",
+                    settings_dir.display(),
+                    image.name
+                ));
+                self.audit("Bootstrap prefix from golden image", &[settings_dir]);
+                self.select_prefix(prefix);
+            }
+            Err(e) => self
+                .status_messages
+                .push(format!("Bootstrap failed: {}", e)),
+        }
+    }
+
+    fn find_duplicate_profiles(&mut self) {
+        let groups = duplicates::find_duplicates(&self.profile_dirs)
+            .into_iter()
+            .map(|group| {
+                let canonical = group.suggested_canonical().to_path_buf();
+                (group, canonical)
+            })
+            .collect();
+        self.duplicate_groups = Some(groups);
+    }
+
+    fn merge_duplicates(&mut self, group: &DuplicateGroup, canonical: &Path) {
+        let merged = journal::run(
+            "Merge duplicate profiles",
+            canonical,
+            &group.dirs,
+            true,
+            &self.config.backup_options(),
+            |journal| group.merge(canonical, journal),
+        );
+        match merged {
+            Ok(outcome) => {
+                for backup in outcome.backups {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                }
+                self.status_messages.push(format!(
+                    "Merged {} into {}: {} file(s) copied, {} duplicate(s) removed",
+                    group.profile,
+                    discovery::profile_label(canonical),
+                    outcome.copied.len(),
+                    outcome.removed.len()
+                ));
+                let mut audited = outcome.copied;
+                audited.extend(outcome.removed);
+                self.audit("Merge duplicate profiles", &audited);
+            }
+            Err(e) => self.status_messages.push(format!("Merge failed: {}", e)),
+        }
+        if let Some(prefix) = self.selected_prefix.clone() {
+            self.select_prefix(prefix);
+        }
+        self.duplicate_groups = None;
+    }
+
+    fn open_windows_transfer(&mut self, direction: Direction) {
+        let Some(prefix) = self.selected_prefix.clone() else {
+            return;
+        };
+        let mut wizard = WindowsTransfer {
+            direction,
+            sources: windows_settings::find_windows_eve_dirs(),
+            source: None,
+            profiles: Vec::new(),
+            profile: 0,
+            files: Vec::new(),
+            selected: HashSet::new(),
+        };
+        if let Some(source) = wizard.sources.first().cloned() {
+            if let Err(e) = wizard.select_source(source, &prefix) {
+                self.status_messages
+                    .push(format!("Failed to read Windows settings: {}", e));
+            }
+        }
+        self.windows_transfer = Some(wizard);
+    }
+
+    /// Asks for a Windows drive or EVE folder when none was found mounted.
+    fn browse_windows_source(&mut self, wizard: &mut WindowsTransfer) {
+        let Some(prefix) = self.selected_prefix.clone() else {
+            return;
+        };
+        let Some(picked) = rfd::FileDialog::new()
+            .set_title("Select Windows Drive or its CCP\\EVE Folder")
+            .pick_folder()
+        else {
+            return;
+        };
+        // A drive root holds the EVE folders of its users; anything else is taken
+        // to be an EVE folder itself
+        let found = windows_settings::eve_dirs_in(&picked);
+        let source = found.first().cloned().unwrap_or(picked);
+        for dir in found {
+            if !wizard.sources.contains(&dir) {
+                wizard.sources.push(dir);
+            }
+        }
+        if let Err(e) = wizard.select_source(source, &prefix) {
+            self.status_messages
+                .push(format!("Failed to read Windows settings: {}", e));
+        }
+    }
+
+    fn transfer_windows_files(
+        &mut self,
+        mapping: &ProfileMapping,
+        direction: Direction,
+        files: &[CharacterFile],
+    ) {
+        let dry_run = self.dry_run_mode;
+        let (action, verb) = match (direction, dry_run) {
+            (Direction::Import, false) => ("Import from Windows", "Imported"),
+            (Direction::Import, true) => ("Import from Windows", "Would import"),
+            (Direction::Export, false) => ("Export to Windows", "Exported"),
+            (Direction::Export, true) => ("Export to Windows", "Would export"),
+        };
+        let to_dir = mapping.to_dir(direction).to_path_buf();
+
+        let transferred = if dry_run {
+            windows_settings::transfer(mapping, direction, files, true, None)
+        } else {
+            let targets: Vec<PathBuf> = files
+                .iter()
+                .filter_map(|f| f.path.file_name())
+                .map(|name| to_dir.join(name))
+                .collect();
+            journal::run(
+                action,
+                mapping.from_dir(direction),
+                &targets,
+                true,
+                &self.config.backup_options(),
+                |journal| {
+                    windows_settings::transfer(mapping, direction, files, false, Some(journal))
+                },
+            )
+        };
+        match transferred {
+            Ok(transfer) => {
+                if let Some(backup) = transfer.backup {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                }
+                let mut succeeded = 0;
+                for result in &transfer.results {
+                    if result.success {
+                        succeeded += 1;
+                    }
+                    self.status_messages.push(format!(
+                        "{}: {}",
+                        result.message,
+                        result.target_file.display()
+                    ));
+                }
+                let message = format!(
+                    "{} {} of {} file(s) into {}",
+                    verb,
+                    succeeded,
+                    transfer.results.len(),
+                    discovery::profile_label(&to_dir)
+                );
+                self.status_messages.push(message.clone());
+                self.open_modal(Modal::SyncComplete(message));
+
+                if !dry_run {
+                    let written: Vec<PathBuf> = transfer
+                        .results
+                        .into_iter()
+                        .filter(|r| r.success)
+                        .map(|r| r.target_file)
+                        .collect();
+                    self.audit(action, &written);
+                    self.open_modal(Modal::Verification(SyncVerification {
+                        source: mapping.from_dir(direction).to_path_buf(),
+                        entries: transfer.verification,
+                    }));
+                }
+            }
+            Err(e) => {
+                self.status_messages
+                    .push(format!("{} failed: {}", action, e));
+                if direction == Direction::Export {
+                    self.status_messages.push(
+                        "A Windows partition is mounted read-only while Windows is hibernated \
+                         or Fast Startup is on; shut Windows down fully to write to it."
+                            .to_string(),
+                    );
+                }
+            }
+        }
+
+        if !dry_run {
+            if let Some(prefix) = self.selected_prefix.clone() {
+                self.select_prefix(prefix);
+            }
+            self.windows_transfer = None;
+        }
+    }
+
+    fn open_migration(&mut self) {
+        self.migration = Some(PrefixMigration {
+            old: None,
+            new: self.selected_prefix.clone(),
+            plan: None,
+        });
+    }
+
+    fn migrate_prefix(&mut self, plan: &MigrationPlan, new: PathBuf) {
+        let old = self
+            .migration
+            .as_ref()
+            .and_then(|m| m.old.clone())
+            .unwrap_or_default();
+        let folders: Vec<PathBuf> = plan.items.iter().map(|i| i.to.clone()).collect();
+        let migrated = journal::run(
+            "Migrate prefix",
+            &old,
+            &folders,
+            true,
+            &self.config.backup_options(),
+            |journal| plan.migrate(journal),
+        );
+        match migrated {
+            Ok(outcome) => {
+                for backup in outcome.backups {
+                    self.handle_event(AppEvent::BackupCreated(backup));
+                }
+                self.status_messages.push(format!(
+                    "Migrated {} file(s) of EVE data into {} (Wine user {} → {})",
+                    outcome.copied,
+                    new.display(),
+                    plan.old_user,
+                    plan.new_user
+                ));
+                self.audit("Migrate prefix", &folders);
+                self.select_prefix(new);
+                self.migration = None;
+            }
+            Err(e) => self
+                .status_messages
+                .push(format!("Migration failed: {}", e)),
+        }
+    }
+
+    fn upload_log_to_paste(&mut self, ctx: &egui::Context) {
+        let log_text = self.status_messages.join("\n");
+        if log_text.is_empty() {
+            return;
+        }
+
+        let client = reqwest::blocking::Client::new();
+        let form = reqwest::blocking::multipart::Form::new()
+            .text("text", log_text)
+            .text("lang", "text");
+
+        match client
+            .post("https://pst.plb.so/paste/new")
+            .multipart(form)
+            .send()
+        {
+            Ok(response) => {
+                let url = response.url().to_string();
+                ctx.copy_text(url.clone());
+                self.log_paste_url = Some(url.clone());
+                self.status_messages
+                    .push(format!("Log uploaded (copied to clipboard): {}", url));
+            }
+            Err(e) => {
+                self.status_messages.push(format!("Upload failed: {}", e));
+            }
+        }
+    }
+
+    fn check_for_updates(&mut self) {
+        let current_version = env!("CARGO_PKG_VERSION");
+
+        let client = reqwest::blocking::Client::builder()
+            .user_agent("PackPreferences")
+            .build();
+
+        let client = match client {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+
+        let response = client
+            .get("https://api.github.com/repos/sopleb/PackPreferences/releases/latest")
+            .send();
+
+        if let Ok(resp) = response {
+            if let Ok(json) = resp.json::<serde_json::Value>() {
+                if let Some(tag) = json.get("tag_name").and_then(|v| v.as_str()) {
+                    // Strip 'v' prefix if present for comparison
+                    let latest = tag.trim_start_matches('v');
+                    if changelog::version_newer(latest, current_version) {
+                        self.update_available = Some(tag.to_string());
+                        self.open_modal(Modal::UpdateAvailable(tag.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Shows the changelog entries added since the version that last ran, then
+    /// records this one. A fresh install has nothing to catch up on.
+    fn show_whats_new(&mut self, first_run: bool) {
+        let current = env!("CARGO_PKG_VERSION");
+        if self.config.last_run_version.as_deref() == Some(current) {
+            return;
+        }
+        if !first_run {
+            let notes = changelog::whats_new(self.config.last_run_version.as_deref());
+            if !notes.is_empty() {
+                self.modals.push_back(Modal::WhatsNew(notes));
+            }
+        }
+        self.config.last_run_version = Some(current.to_string());
+        let _ = self.config.save();
+    }
+}
+
+fn fix_label(fix: &Fix) -> String {
+    match fix {
+        Fix::Rescan => "Scan Again".to_string(),
+        Fix::BrowsePrefix => "Choose Prefix...".to_string(),
+        Fix::UsePrefix(prefix) => format!("Use {}", prefix.display()),
+        Fix::OpenUrl(_) => "Open Guide".to_string(),
+    }
+}
+
+/// Applies the window level and decoration settings to the native window.
+fn apply_window_options(ctx: &egui::Context, config: &Config) {
+    let level = if config.always_on_top {
+        egui::WindowLevel::AlwaysOnTop
+    } else {
+        egui::WindowLevel::Normal
+    };
+    ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+    ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(!config.borderless));
+}
+
+/// One-line summary of how a backed-up file differs from the live one. Files that
+/// decode are described by key; others by size and first differing byte.
+fn describe_comparison(comparison: &BackupComparison) -> String {
+    match (
+        comparison.live_size,
+        comparison.first_difference,
+        &comparison.keys,
+    ) {
+        (None, _, _) => format!("{} bytes; no longer in the profile", comparison.backup_size),
+        (Some(_), None, _) => "Identical to the current file".to_string(),
+        (Some(_), Some(_), Some(keys)) if keys.is_empty() => {
+            "Same settings as the current file, stored differently".to_string()
+        }
+        (Some(_), Some(_), Some(keys)) => format!(
+            "Restoring adds {} key(s), removes {} and changes {}",
+            keys.added.len(),
+            keys.removed.len(),
+            keys.changed.len()
+        ),
+        (Some(live_size), Some(offset), None) => format!(
+            "{} bytes (current: {}); first difference at byte {}",
+            comparison.backup_size, live_size, offset
+        ),
+    }
+}
+
+/// Lists the keys a restore would add, remove and change.
+fn show_key_changes(ui: &mut egui::Ui, keys: &TreeDiff) {
+    egui::ScrollArea::vertical()
+        .id_salt("restore_keys_scroll")
+        .max_height(150.0)
+        .show(ui, |ui| {
+            let groups = [
+                ("added", &keys.added),
+                ("removed", &keys.removed),
+                ("changed", &keys.changed),
+            ];
+            for (kind, paths) in groups {
+                for path in paths {
+                    ui.monospace(format!("{:<8}{}", kind, path));
+                }
+            }
+        });
+}
+
+/// Describes a sync that crosses game servers, whose settings key formats can differ.
+fn server_mismatch_warning(source_dir: &Path, target_dirs: &[PathBuf]) -> Option<String> {
+    let source = discovery::server_for(source_dir)?;
+    let mut others: Vec<&str> = target_dirs
+        .iter()
+        .filter_map(|dir| discovery::server_for(dir))
+        .filter(|server| *server != source)
+        .map(|server| server.name())
+        .collect();
+    others.sort_unstable();
+    others.dedup();
+    if others.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "Warning: the source is on {} but targets are on {}. Settings keys can differ \
+         between server versions.",
+        source.name(),
+        others.join(", ")
+    ))
+}
+
+/// Discovers client and launcher settings files; runs on a scan worker thread.
+fn scan_files(
+    settings_dir: &Path,
+    prefix: &Path,
+    all_profiles: bool,
+    ignore: &IgnoreRules,
+    events: &EventSender,
+) -> anyhow::Result<Scan> {
+    let started = Instant::now();
+    let mut files = if all_profiles {
+        let mut dirs = process::find_profile_dirs(prefix)?;
+        if !dirs.iter().any(|dir| dir == settings_dir) {
+            // Browsed to or searched for, or there is no prefix
+            dirs = process::sibling_profile_dirs(settings_dir)?;
+        }
+        events.send(AppEvent::Status(format!(
+            "Scanning {} settings profiles",
+            dirs.len()
+        )));
+        discovery::discover_across_profiles(&dirs, ignore)?
+    } else {
+        discovery::discover_character_files(settings_dir, ignore)?
+    };
+
+    // Launcher settings live outside the client profiles
+    for dir in process::find_launcher_dirs(prefix) {
+        let launcher_files = discovery::discover_launcher_files(&dir)?;
+        files.extend(launcher_files.into_iter().filter(|f| !ignore.ignores(f)));
+    }
+
+    Ok(Scan {
+        files,
+        elapsed: started.elapsed(),
+    })
+}
+
+impl PickerDelegate for PackPreferencesApp {
+    /// Draws the portrait for a list entry, or a placeholder for defaults, accounts
+    /// and deleted characters.
+    fn item_icon(&mut self, ui: &mut egui::Ui, item: &SelectableItem) {
+        let file = &self.character_files[item.file_idx];
+        if file.file_type == FileType::Character
+            && !file.is_default
+            && !self.name_cache.is_deleted(file.character_id)
+        {
+            self.assets.show(
+                ui,
+                AssetKey::CharacterPortrait(file.character_id),
+                LIST_ICON_SIZE,
+            );
+        } else {
+            assets::placeholder(ui, egui::vec2(LIST_ICON_SIZE, LIST_ICON_SIZE));
+        }
+    }
+
+    /// Shows the formatted ID; clicking copies the raw digits. Characters also get a
+    /// link to the configured lookup site.
+    fn item_id(&mut self, ui: &mut egui::Ui, item: &SelectableItem) {
+        let text = format!("[{}]", self.config.id_format.format(item.id));
+        let response = ui
+            .add(
+                egui::Label::new(egui::RichText::new(text).color(theme::colors::TEXT_DIM))
+                    .sense(egui::Sense::click()),
+            )
+            .on_hover_text("Click to copy");
+        if response.clicked() {
+            ui.ctx().copy_text(item.id.to_string());
+            self.status_messages
+                .push(format!("Copied ID {} to clipboard", item.id));
+        }
+
+        let file = &self.character_files[item.file_idx];
+        if let Some(site) = self.config.id_link {
+            if file.file_type == FileType::Character {
+                ui.hyperlink_to(site.name(), site.character_url(item.id));
+            }
+        }
+    }
+
+    /// Names the profile a default file belongs to, explains what it does and
+    /// offers a preview of what it hands down.
+    fn item_default(&mut self, ui: &mut egui::Ui, item: &SelectableItem) {
+        let file = self.character_files[item.file_idx].clone();
+        let profile = discovery::profile_label(&item.settings_dir);
+        ui.label(egui::RichText::new(format!("[{}]", profile)).color(theme::colors::TEXT_DIM));
+        let (who, what) = match file.file_type {
+            FileType::Character => (
+                "character",
+                "window layout, overview, chat channels and other per-character settings",
+            ),
+            FileType::User | FileType::Launcher => (
+                "account",
+                "keybinds, graphics, audio and other per-account settings",
+            ),
+        };
+        ui.label(egui::RichText::new("(?)").color(theme::colors::TEXT_DIM))
+            .on_hover_text(format!(
+                "A new {} that logs in with profile {} has no file of its own yet, so the \
+                 client starts it from this one: its {}. The login and character selection \
+                 screens use it too. Sync into it to give new {}s your setup.",
+                who, profile, what, who
+            ));
+        if ui
+            .small_button("Preview")
+            .on_hover_text(format!("Inspect what a new {} would inherit", who))
+            .clicked()
+        {
+            let title = format!("What a new {} inherits ({})", who, profile);
+            self.inspect(&file, title);
+        }
+    }
+
+    /// Context menu shown on list entries; offers cleanup for deleted characters.
+    fn item_context_menu(&mut self, ui: &mut egui::Ui, item: &SelectableItem) {
+        let file = self.character_files[item.file_idx].clone();
+        if file.file_type == FileType::Character && self.name_cache.is_deleted(file.character_id) {
+            ui.label("ESI reports this character no longer exists.");
+            if ui.button("Delete settings file...").clicked() {
+                let action = PendingAction::DeleteFile(file.path.clone());
+                self.open_modal(Modal::Confirm(action));
+                ui.close_menu();
+            }
+        } else {
+            ui.label(file.path.file_name().unwrap_or_default().to_string_lossy());
+        }
+        if ui.button("Inspect...").clicked() {
+            ui.close_menu();
+            let title = self.display_name_for(&file);
+            self.inspect(&file, title);
+        }
+        if ui
+            .button("Export Decoded...")
+            .on_hover_text("Write the decoded settings as JSON or YAML for diffing")
+            .clicked()
+        {
+            ui.close_menu();
+            self.export_decoded(&file.path);
+        }
+        if file.file_type != FileType::Launcher
+            && ui
+                .button("Duplicate As...")
+                .on_hover_text(
+                    "Copy these settings to a new ID, e.g. an alt that has not logged in yet",
+                )
+                .clicked()
+        {
+            ui.close_menu();
+            self.duplicate_draft = Some(DuplicateDraft {
+                file: file.clone(),
+                ids_text: String::new(),
+                check: None,
+                checking: false,
+            });
+        }
+
+        // Killboards keep pages for deleted characters too, so offer them either way
+        if file.file_type == FileType::Character && !file.is_default {
+            ui.separator();
+            for site in LinkSite::ALL {
+                if ui.button(format!("Open on {}", site.name())).clicked() {
+                    let _ = open::that(site.character_url(file.character_id));
+                    ui.close_menu();
+                }
+            }
+        }
+    }
+}
+
+impl eframe::App for PackPreferencesApp {
+    fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        for action in self.gamepad.feed(ctx, raw_input) {
+            match action {
+                GamepadAction::Sync => {
+                    if self.picker.has_selection() && !self.dialog_open() {
+                        self.open_modal(Modal::Confirm(PendingAction::Sync));
+                    }
+                }
+                GamepadAction::NextTab | GamepadAction::PreviousTab => {
+                    let tabs = [Tab::Characters, Tab::Accounts, Tab::Launcher];
+                    let current = tabs.iter().position(|t| *t == self.active_tab).unwrap_or(0);
+                    let step = if action == GamepadAction::NextTab {
+                        1
+                    } else {
+                        tabs.len() - 1
+                    };
+                    self.active_tab = tabs[(current + step) % tabs.len()];
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.diagnostics.begin_frame(ctx);
+        self.assets.poll(ctx);
+        self.process_events();
+
+        // Check scheduled presets once a minute, even while the window is idle
+        if Instant::now() >= self.next_preset_check {
+            self.next_preset_check = Instant::now() + PRESET_CHECK_INTERVAL;
+            self.run_due_presets();
+        }
+        if self.config.presets.iter().any(|p| p.schedule.is_some()) {
+            ctx.request_repaint_after(PRESET_CHECK_INTERVAL);
+        }
+
+        // Trickle-refresh cached names, a few at a time
+        if Instant::now() >= self.next_name_refresh {
+            self.next_name_refresh = Instant::now() + NAME_REFRESH_INTERVAL;
+            self.refresh_stale_names();
+        }
+        ctx.request_repaint_after(NAME_REFRESH_INTERVAL);
+
+        // Persist new log lines to the state directory
+        if self.persisted_log_len > self.status_messages.len() {
+            self.persisted_log_len = 0;
+        }
+        if self.persisted_log_len < self.status_messages.len() {
+            let _ = state::append_log(&self.status_messages[self.persisted_log_len..]);
+            self.persisted_log_len = self.status_messages.len();
+        }
+
+        // Keep the crash reporter's copy of the log current
+        if self.config.crash_reporting {
+            if let Ok(mut log) = self.crash_log.lock() {
+                if log.len() != self.status_messages.len() {
+                    log.clone_from(&self.status_messages);
+                }
+            }
+        }
+
         // Show about screen if open
         self.about.show(ctx);
+        self.help.show(ctx);
+
+        // Show log window if open
+        let mut show_log = self.is_open(Tool::Log);
+        if show_log {
+            egui::Window::new("Log")
+                .open(&mut show_log)
+                .default_size([500.0, 300.0])
+                .show(ctx, |ui| {
+                    // Header with actions
+                    ui.horizontal(|ui| {
+                        if ui.button("Copy Log").clicked() {
+                            self.upload_log_to_paste(ctx);
+                        }
+                        if ui.button("Clear").clicked() {
+                            self.status_messages.clear();
+                            self.log_paste_url = None;
+                        }
+                        if let Some(ref url) = self.log_paste_url {
+                            ui.label(format!("Paste: {}", url));
+                        }
+                    });
+                    ui.separator();
+
+                    // Log content
+                    egui::ScrollArea::vertical()
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for msg in &self.status_messages {
+                                ui.label(egui::RichText::new(msg).monospace());
+                            }
+                        });
+                });
+            if !show_log {
+                self.close_tool(Tool::Log);
+            }
+        }
+
+        self.show_modal(ctx);
+
+        // Browse the files inside a backup and restore them one at a time
+        if let Some(browsed) = self.browsed_backup.take() {
+            let mut open = true;
+            let mut restore = None;
+            egui::Window::new("Backup Contents")
+                .open(&mut open)
+                .default_size([520.0, 260.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        browsed
+                            .path
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy(),
+                    );
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .id_salt("backup_files_scroll")
+                        .show(ui, |ui| {
+                            egui::Grid::new("backup_files_grid")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (file, comparison) in &browsed.files {
+                                        ui.label(self.display_name_for(file));
+                                        match comparison {
+                                            Some(comparison) => {
+                                                ui.label(describe_comparison(comparison));
+                                            }
+                                            None => {
+                                                ui.label("unreadable");
+                                            }
+                                        }
+                                        let can_restore = comparison
+                                            .as_ref()
+                                            .is_some_and(|c| c.first_difference.is_some());
+                                        if ui
+                                            .add_enabled(
+                                                can_restore,
+                                                egui::Button::new("Restore..."),
+                                            )
+                                            .clicked()
+                                        {
+                                            restore = comparison.clone().map(|c| {
+                                                PendingAction::RestoreFile(file.path.clone(), c)
+                                            });
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                });
+            if open {
+                self.browsed_backup = Some(browsed);
+            }
+            if let Some(action) = restore {
+                self.open_modal(Modal::Confirm(action));
+            }
+        }
+
+        // Show duplicated profiles
+        if let Some(mut groups) = self.duplicate_groups.take() {
+            let mut open = true;
+            let mut merge = None;
+            egui::Window::new("Duplicate Profiles")
+                .open(&mut open)
+                .default_size([560.0, 300.0])
+                .show(ctx, |ui| {
+                    if groups.is_empty() {
+                        ui.label("No profile is duplicated across install folders.");
+                    }
+                    egui::ScrollArea::vertical()
+                        .id_salt("duplicates_scroll")
+                        .show(ui, |ui| {
+                            for (i, (group, canonical)) in groups.iter_mut().enumerate() {
+                                ui.strong(format!(
+                                    "{}: {} copies sharing {} character(s)",
+                                    group.profile,
+                                    group.dirs.len(),
+                                    group.shared_ids.len()
+                                ));
+                                ui.label("Keep:");
+                                for dir in &group.dirs {
+                                    ui.radio_value(
+                                        canonical,
+                                        dir.clone(),
+                                        discovery::profile_label(dir),
+                                    )
+                                    .on_hover_text(dir.display().to_string());
+                                }
+                                if ui.button("Merge...").clicked() {
+                                    merge = Some(i);
+                                }
+                                ui.separator();
+                            }
+                        });
+                });
+            if let Some(i) = merge {
+                let (group, canonical) = groups[i].clone();
+                self.open_modal(Modal::Confirm(PendingAction::MergeDuplicates(
+                    group, canonical,
+                )));
+            }
+            if open {
+                self.duplicate_groups = Some(groups);
+            }
+        }
+
+        // Show the prefix migration wizard
+        if let Some(mut migration) = self.migration.take() {
+            let mut open = true;
+            let mut changed = false;
+            let mut confirm = false;
+            egui::Window::new("Migrate Prefix")
+                .open(&mut open)
+                .default_size([560.0, 300.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        "Copies all EVE settings and launcher data from an old prefix into a \
+                         new one, e.g. after Steam recreated the prefix for a new Proton \
+                         version. Keep a copy of the old prefix's drive_c folder to migrate \
+                         from.",
+                    );
+                    ui.add_space(4.0);
+                    egui::Grid::new("migration_grid").show(ui, |ui| {
+                        for (label, title, slot) in [
+                            (
+                                "Old prefix:",
+                                "Select Old Wine Prefix (drive_c directory)",
+                                &mut migration.old,
+                            ),
+                            (
+                                "New prefix:",
+                                "Select New Wine Prefix (drive_c directory)",
+                                &mut migration.new,
+                            ),
+                        ] {
+                            ui.label(label);
+                            ui.label(
+                                slot.as_ref()
+                                    .map(|p| p.display().to_string())
+                                    .unwrap_or_else(|| "Not chosen".to_string()),
+                            );
+                            if ui.button("Browse...").clicked() {
+                                if let Some(path) =
+                                    rfd::FileDialog::new().set_title(title).pick_folder()
+                                {
+                                    *slot = Some(path);
+                                    changed = true;
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                    ui.separator();
+                    match &migration.plan {
+                        None => {
+                            ui.label("Choose both prefixes to see what would be copied.");
+                        }
+                        Some(Err(e)) => {
+                            ui.colored_label(theme::error(), e);
+                        }
+                        Some(Ok(plan)) => {
+                            if plan.old_user != plan.new_user {
+                                ui.label(format!(
+                                    "Wine user {} in the old prefix becomes {} in the new one.",
+                                    plan.old_user, plan.new_user
+                                ));
+                            }
+                            for item in &plan.items {
+                                ui.label(format!("{} file(s)", item.files))
+                                    .on_hover_text(format!(
+                                        "{}\n→ {}",
+                                        item.from.display(),
+                                        item.to.display()
+                                    ));
+                                ui.label(
+                                    egui::RichText::new(item.to.display().to_string())
+                                        .color(theme::colors::TEXT_DIM),
+                                );
+                            }
+                            if ui.button("Migrate...").clicked() {
+                                confirm = true;
+                            }
+                        }
+                    }
+                });
+
+            if changed {
+                migration.replan();
+            }
+            if confirm {
+                if let (Some(Ok(plan)), Some(new)) = (&migration.plan, &migration.new) {
+                    self.open_modal(Modal::Confirm(PendingAction::MigratePrefix(
+                        plan.clone(),
+                        new.clone(),
+                    )));
+                }
+            }
+            if open {
+                self.migration = Some(migration);
+            }
+        }
+
+        // Show the settings inspector
+        if let Some(mut inspector) = self.inspector.take() {
+            let mut open = true;
+            match inspector.show(ctx, &mut open) {
+                Some(InspectorAction::Save(path, text)) => {
+                    self.save_inspector_edit(&mut inspector, path, text);
+                }
+                Some(InspectorAction::BulkEdit {
+                    path,
+                    text,
+                    targets,
+                }) => self.bulk_edit_setting(&mut inspector, path, text, targets),
+                Some(InspectorAction::AddToBaseline(path, value)) => {
+                    self.add_to_baseline(inspector.file.file_type, path, value);
+                }
+                None => {}
+            }
+            if open {
+                self.inspector = Some(inspector);
+            }
+        }
+
+        // Show the Windows transfer wizard
+        if let Some(mut wizard) = self.windows_transfer.take() {
+            let mut open = true;
+            let mut browse = false;
+            let mut direction_choice = None;
+            let mut source_choice = None;
+            let mut profile_choice = None;
+            let mut transfer = false;
+            egui::Window::new("Windows Settings")
+                .open(&mut open)
+                .default_size([560.0, 380.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        "Copies settings between a Windows install on a mounted partition and \
+                         this prefix. Mount the Windows drive first.",
+                    );
+                    ui.horizontal(|ui| {
+                        for (direction, label) in [
+                            (Direction::Import, "Import from Windows"),
+                            (Direction::Export, "Export to Windows"),
+                        ] {
+                            if ui.radio(wizard.direction == direction, label).clicked() {
+                                direction_choice = Some(direction);
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Windows EVE folder:");
+                        let selected = wizard
+                            .source
+                            .as_ref()
+                            .map(|s| s.display().to_string())
+                            .unwrap_or_else(|| "None found".to_string());
+                        egui::ComboBox::from_id_salt("windows_source")
+                            .selected_text(selected)
+                            .width(320.0)
+                            .show_ui(ui, |ui| {
+                                for source in &wizard.sources {
+                                    let checked = wizard.source.as_ref() == Some(source);
+                                    if ui
+                                        .selectable_label(checked, source.display().to_string())
+                                        .clicked()
+                                    {
+                                        source_choice = Some(source.clone());
+                                    }
+                                }
+                            });
+                        if ui.button("Browse...").clicked() {
+                            browse = true;
+                        }
+                    });
+
+                    if wizard.source.is_some() && wizard.profiles.is_empty() {
+                        ui.label("This folder has no settings profiles.");
+                    }
+                    for (i, mapping) in wizard.profiles.iter().enumerate() {
+                        let windows = discovery::profile_label(&mapping.source);
+                        let label = match (wizard.direction, mapping.creates_target()) {
+                            (Direction::Import, false) => format!(
+                                "{}  →  {}",
+                                windows,
+                                discovery::profile_label(&mapping.target)
+                            ),
+                            (Direction::Import, true) => format!(
+                                "{}  →  {} (new)",
+                                windows,
+                                discovery::profile_label(&mapping.target)
+                            ),
+                            (Direction::Export, false) => format!(
+                                "{}  →  {}",
+                                discovery::profile_label(&mapping.target),
+                                windows
+                            ),
+                            (Direction::Export, true) => {
+                                format!("{} (not in this prefix)", windows)
+                            }
+                        };
+                        if ui
+                            .radio(wizard.profile == i, label)
+                            .on_hover_text(mapping.target.display().to_string())
+                            .clicked()
+                        {
+                            profile_choice = Some(i);
+                        }
+                    }
+
+                    if !wizard.files.is_empty() {
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            if ui.small_button("All").clicked() {
+                                wizard.selected =
+                                    wizard.files.iter().map(|f| f.path.clone()).collect();
+                            }
+                            if ui.small_button("None").clicked() {
+                                wizard.selected.clear();
+                            }
+                        });
+                        egui::ScrollArea::vertical()
+                            .id_salt("windows_transfer_files")
+                            .max_height(200.0)
+                            .show(ui, |ui| {
+                                for file in &wizard.files {
+                                    let mut checked = wizard.selected.contains(&file.path);
+                                    let label = format!(
+                                        "{}  ({})",
+                                        self.display_name_for(file),
+                                        file.path.file_name().unwrap_or_default().to_string_lossy()
+                                    );
+                                    if ui.checkbox(&mut checked, label).changed() {
+                                        if checked {
+                                            wizard.selected.insert(file.path.clone());
+                                        } else {
+                                            wizard.selected.remove(&file.path);
+                                        }
+                                    }
+                                }
+                            });
+                    }
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        let label = match wizard.direction {
+                            Direction::Import => "Import...",
+                            Direction::Export => "Export...",
+                        };
+                        if ui
+                            .add_enabled(!wizard.selected.is_empty(), egui::Button::new(label))
+                            .clicked()
+                        {
+                            transfer = true;
+                        }
+                        ui.checkbox(&mut self.dry_run_mode, "Dry Run Mode");
+                    });
+                });
+
+            let prefix = self.selected_prefix.clone().unwrap_or_default();
+            let result = match (direction_choice, source_choice, profile_choice) {
+                (Some(direction), ..) => wizard.set_direction(direction),
+                (None, Some(source), _) => wizard.select_source(source, &prefix),
+                (None, None, Some(i)) => wizard.select_profile(i),
+                (None, None, None) => Ok(()),
+            };
+            if let Err(e) = result {
+                self.status_messages
+                    .push(format!("Failed to read Windows settings: {}", e));
+            }
+            if browse {
+                self.browse_windows_source(&mut wizard);
+            }
+            if transfer {
+                if let Some(mapping) = wizard.profiles.get(wizard.profile) {
+                    let files = wizard
+                        .files
+                        .iter()
+                        .filter(|f| wizard.selected.contains(&f.path))
+                        .cloned()
+                        .collect();
+                    self.open_modal(Modal::Confirm(PendingAction::WindowsTransfer(
+                        mapping.clone(),
+                        wizard.direction,
+                        files,
+                    )));
+                }
+            }
+            if open {
+                self.windows_transfer = Some(wizard);
+            }
+        }
+
+        // Show the ignore rules editor
+        if let Some(mut text) = self.ignore_editor.take() {
+            let mut open = true;
+            let mut save = false;
+            egui::Window::new("Ignore Rules")
+                .open(&mut open)
+                .default_size([420.0, 260.0])
+                .show(ctx, |ui| {
+                    if let Some(prefix) = &self.selected_prefix {
+                        ui.label(
+                            egui::RichText::new(prefix.display().to_string())
+                                .color(theme::colors::TEXT_DIM),
+                        );
+                    }
+                    ui.label(
+                        "One pattern per line. Files whose name, profile folder or ID \
+                         matches are hidden. * matches anything, ? one character.",
+                    );
+                    ui.add(
+                        egui::TextEdit::multiline(&mut text)
+                            .hint_text("settings_old_*\ncore_char_90000001.dat\n2112345678")
+                            .desired_rows(6)
+                            .desired_width(f32::INFINITY)
+                            .code_editor(),
+                    );
+                    if ui.button("Save and Rescan").clicked() {
+                        save = true;
+                    }
+                });
+            if save {
+                self.save_ignore_patterns(&text);
+            } else if open {
+                self.ignore_editor = Some(text);
+            }
+        }
+
+        // Show the detection troubleshooter
+        if let Some(checks) = self.troubleshooting.take() {
+            let mut open = true;
+            let mut chosen = None;
+            let mut recheck = false;
+            egui::Window::new("Nothing Detected?")
+                .open(&mut open)
+                .default_size([520.0, 320.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        "The scan found nothing to sync. These checks cover the usual causes:",
+                    );
+                    ui.separator();
+                    egui::ScrollArea::vertical()
+                        .id_salt("troubleshoot_scroll")
+                        .show(ui, |ui| {
+                            for check in &checks {
+                                let (mark, color) = match check.outcome {
+                                    Outcome::Passed => ("✔", theme::success()),
+                                    Outcome::Failed => ("✖", theme::error()),
+                                    Outcome::Skipped => ("–", theme::colors::TEXT_DIM),
+                                };
+                                ui.horizontal(|ui| {
+                                    ui.colored_label(color, mark);
+                                    ui.strong(check.title);
+                                });
+                                ui.label(&check.detail);
+                                ui.horizontal_wrapped(|ui| {
+                                    for fix in &check.fixes {
+                                        if ui.button(fix_label(fix)).clicked() {
+                                            chosen = Some(fix.clone());
+                                        }
+                                    }
+                                });
+                                ui.add_space(6.0);
+                            }
+                        });
+                    ui.separator();
+                    if ui.button("Check Again").clicked() {
+                        recheck = true;
+                    }
+                });
+            if open {
+                self.troubleshooting = Some(checks);
+            }
+            if let Some(fix) = chosen {
+                self.apply_fix(fix);
+            } else if recheck {
+                self.run_troubleshooter();
+            }
+        }
+
+        // Show the audit log
+        if let Some(entries) = self.audit_entries.take() {
+            let mut open = true;
+            let mut refresh = false;
+            egui::Window::new("Audit Log")
+                .open(&mut open)
+                .default_size([620.0, 320.0])
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Refresh").clicked() {
+                            refresh = true;
+                        }
+                        if ui.button("Export...").clicked() {
+                            self.export_audit_log(&entries);
+                        }
+                        ui.label(format!("{} entries", entries.len()));
+                    });
+                    ui.separator();
+                    egui::ScrollArea::both()
+                        .id_salt("audit_scroll")
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for entry in &entries {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{}  {}  {}",
+                                        self.config.time_display.format_rfc3339(&entry.timestamp),
+                                        entry.user,
+                                        entry.action
+                                    ))
+                                    .strong(),
+                                );
+                                for file in &entry.files {
+                                    let hash = file.hash.as_deref().unwrap_or("-");
+                                    ui.label(
+                                        egui::RichText::new(format!(
+                                            "    {}  {}",
+                                            hash.get(..16).unwrap_or(hash),
+                                            file.path.display()
+                                        ))
+                                        .monospace()
+                                        .color(theme::colors::TEXT_DIM),
+                                    )
+                                    .on_hover_text(hash);
+                                }
+                            }
+                        });
+                });
+            if open {
+                self.audit_entries = Some(entries);
+            }
+            if refresh {
+                self.load_audit_log();
+            }
+        }
+
+        // Show sync plugins
+        let mut show_plugins = self.is_open(Tool::Plugins);
+        if show_plugins {
+            egui::Window::new("Sync Plugins")
+                .open(&mut show_plugins)
+                .default_size([420.0, 240.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        "Plugins are programs in the plugins folder. Each gets the settings \
+                         file on stdin and writes the file to save to the target on stdout. \
+                         Ticked plugins run in order on every manual sync; presets remember \
+                         their own.",
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Open Plugins Folder").clicked() {
+                            if let Ok(dir) = plugins::plugins_dir() {
+                                let _ = std::fs::create_dir_all(&dir);
+                                let _ = open::that(dir);
+                            }
+                        }
+                        if ui.button("Reload").clicked() {
+                            self.plugins = plugins::plugins_dir()
+                                .map(|dir| plugins::discover_plugins(&dir))
+                                .unwrap_or_default();
+                        }
+                    });
+                    ui.separator();
+                    if self.plugins.is_empty() {
+                        ui.label("No plugins installed");
+                    }
+                    for plugin in &self.plugins {
+                        let mut enabled = self.sync_plugins.contains(&plugin.name);
+                        if ui
+                            .checkbox(&mut enabled, &plugin.name)
+                            .on_hover_text(plugin.path.display().to_string())
+                            .changed()
+                        {
+                            if enabled {
+                                self.sync_plugins.push(plugin.name.clone());
+                            } else {
+                                self.sync_plugins.retain(|name| name != &plugin.name);
+                            }
+                        }
+                    }
+                    let missing: Vec<&str> = self
+                        .sync_plugins
+                        .iter()
+                        .filter(|name| !self.plugins.iter().any(|p| &p.name == *name))
+                        .map(String::as_str)
+                        .collect();
+                    if !missing.is_empty() {
+                        ui.colored_label(
+                            theme::error(),
+                            format!("Missing from the plugins folder: {}", missing.join(", ")),
+                        );
+                        if ui.button("Forget Missing").clicked() {
+                            let available = &self.plugins;
+                            self.sync_plugins
+                                .retain(|name| available.iter().any(|p| &p.name == name));
+                        }
+                    }
+                });
+            if !show_plugins {
+                self.close_tool(Tool::Plugins);
+            }
+        }
+
+        // Show notification settings
+        let mut show_notifications = self.is_open(Tool::Notifications);
+        if show_notifications {
+            egui::Window::new("Notifications")
+                .open(&mut show_notifications)
+                .default_width(420.0)
+                .show(ctx, |ui| {
+                    ui.label(
+                        "Post a message to a Discord or Slack incoming webhook when files \
+                         are synced or backed up, or something fails.",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Webhook URL:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.webhook_draft.url)
+                                .desired_width(f32::INFINITY)
+                                .hint_text("https://discord.com/api/webhooks/..."),
+                        );
+                    });
+                    ui.checkbox(&mut self.webhook_draft.on_sync, "Syncs");
+                    ui.checkbox(&mut self.webhook_draft.on_backup, "Backups")
+                        .on_hover_text("Every backup, including one per profile before a sync");
+                    ui.checkbox(&mut self.webhook_draft.on_failure, "Failures");
+                    ui.horizontal(|ui| {
+                        let has_url = !self.webhook_draft.url.trim().is_empty();
+                        if ui.button("Save").clicked() {
+                            self.webhook_draft.url = self.webhook_draft.url.trim().to_string();
+                            self.config.webhook = has_url.then(|| self.webhook_draft.clone());
+                            let _ = self.config.save();
+                            self.status_messages.push(if has_url {
+                                "Webhook saved".to_string()
+                            } else {
+                                "Webhook removed".to_string()
+                            });
+                        }
+                        if ui
+                            .add_enabled(has_url, egui::Button::new("Send Test"))
+                            .clicked()
+                        {
+                            self.send_test_notification();
+                        }
+                    });
+                    ui.separator();
+                    ui.label(
+                        "Push to an ntfy topic when a scheduled preset finishes or fails. \
+                         Anyone who knows a topic on a public server can read it, so pick \
+                         a hard-to-guess name or use a token.",
+                    );
+                    egui::Grid::new("ntfy_settings")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("Server:");
+                            ui.text_edit_singleline(&mut self.ntfy_draft.server);
+                            ui.end_row();
+                            ui.label("Topic:");
+                            ui.text_edit_singleline(&mut self.ntfy_draft.topic);
+                            ui.end_row();
+                            ui.label("Token:");
+                            let mut token = self.ntfy_draft.token.clone().unwrap_or_default();
+                            if ui
+                                .add(egui::TextEdit::singleline(&mut token).password(true))
+                                .changed()
+                            {
+                                self.ntfy_draft.token = (!token.is_empty()).then_some(token);
+                            }
+                            ui.end_row();
+                        });
+                    ui.checkbox(&mut self.ntfy_draft.on_success, "Finished presets");
+                    ui.checkbox(&mut self.ntfy_draft.on_failure, "Failed presets");
+                    ui.horizontal(|ui| {
+                        let has_topic = !self.ntfy_draft.topic.trim().is_empty();
+                        if ui.button("Save").clicked() {
+                            self.ntfy_draft.topic = self.ntfy_draft.topic.trim().to_string();
+                            if self.ntfy_draft.server.trim().is_empty() {
+                                self.ntfy_draft.server = notify::DEFAULT_NTFY_SERVER.to_string();
+                            }
+                            self.config.ntfy = has_topic.then(|| self.ntfy_draft.clone());
+                            let _ = self.config.save();
+                            self.status_messages.push(if has_topic {
+                                "ntfy topic saved".to_string()
+                            } else {
+                                "ntfy topic removed".to_string()
+                            });
+                        }
+                        if ui
+                            .add_enabled(has_topic, egui::Button::new("Send Test"))
+                            .clicked()
+                        {
+                            self.send_test_push();
+                        }
+                    });
+                });
+            if !show_notifications {
+                self.close_tool(Tool::Notifications);
+            }
+        }
+
+        // Show scripts
+        let mut show_scripts = self.is_open(Tool::Scripts);
+        if show_scripts {
+            let mut run = None;
+            egui::Window::new("Scripts")
+                .open(&mut show_scripts)
+                .default_size([460.0, 320.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        "Rhai scripts in the scripts folder can list prefixes, profiles and \
+                         characters, create backups and sync files. See the README for the \
+                         functions available.",
+                    );
+                    ui.horizontal(|ui| {
+                        if ui.button("Open Scripts Folder").clicked() {
+                            if let Ok(dir) = scripting::scripts_dir() {
+                                let _ = std::fs::create_dir_all(&dir);
+                                let _ = open::that(dir);
+                            }
+                        }
+                        if ui.button("Reload").clicked() {
+                            self.load_scripts();
+                        }
+                    });
+                    ui.separator();
+                    if self.scripts.is_empty() {
+                        ui.label("No scripts found");
+                    }
+                    for script in &self.scripts {
+                        let name = script.file_name().unwrap_or_default().to_string_lossy();
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(
+                                    self.running_script.is_none(),
+                                    egui::Button::new("Run"),
+                                )
+                                .clicked()
+                            {
+                                run = Some(script.clone());
+                            }
+                            ui.label(name.as_ref())
+                                .on_hover_text(script.display().to_string());
+                        });
+                    }
+                    if let Some(ref name) = self.running_script {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(format!("Running {}...", name));
+                        });
+                    }
+                    if !self.script_output.is_empty() {
+                        ui.separator();
+                        egui::ScrollArea::vertical()
+                            .max_height(160.0)
+                            .stick_to_bottom(true)
+                            .show(ui, |ui| {
+                                for line in &self.script_output {
+                                    ui.monospace(line);
+                                }
+                            });
+                    }
+                });
+            if let Some(path) = run {
+                self.run_script(path);
+            }
+            if !show_scripts {
+                self.close_tool(Tool::Scripts);
+            }
+        }
 
-        // Show log window if open
-        let mut show_log = self.show_log_window;
-        if show_log {
-            egui::Window::new("Log")
-                .open(&mut show_log)
-                .default_size([500.0, 300.0])
+        // Show settings lint
+        let mut show_lint = self.is_open(Tool::Lint);
+        if show_lint {
+            let mut fix = None;
+            egui::Window::new("Settings Lint")
+                .open(&mut show_lint)
+                .default_size([520.0, 380.0])
                 .show(ctx, |ui| {
-                    // Header with actions
+                    ui.label(
+                        "Flags questionable settings across all loaded characters. Fixes back \
+                         up each profile before rewriting its files.",
+                    );
                     ui.horizontal(|ui| {
-                        if ui.button("Copy Log").clicked() {
-                            self.upload_log_to_paste(ctx);
+                        if ui.button("Check Again").clicked() {
+                            self.run_lint();
                         }
-                        if ui.button("Clear").clicked() {
-                            self.status_messages.clear();
-                            self.log_paste_url = None;
+                        if ui
+                            .button("Edit Rules")
+                            .on_hover_text("Add rules, or override or disable shipped ones")
+                            .clicked()
+                        {
+                            if let Ok(path) = lint::rules_path() {
+                                if !path.exists() {
+                                    let _ = std::fs::write(&path, lint::RULES_TEMPLATE);
+                                }
+                                let _ = open::that(path);
+                            }
                         }
-                        if let Some(ref url) = self.log_paste_url {
-                            ui.label(format!("Paste: {}", url));
+                        if let Some(Ok(report)) = &self.lint_report {
+                            if ui.button("Copy Report").clicked() {
+                                let text = report.to_text(|f| self.display_name_for(f));
+                                ui.ctx().copy_text(text);
+                            }
                         }
                     });
                     ui.separator();
+                    let report = match &self.lint_report {
+                        None => return,
+                        Some(Err(e)) => {
+                            ui.colored_label(theme::error(), e);
+                            return;
+                        }
+                        Some(Ok(report)) => report,
+                    };
+                    ui.label(format!(
+                        "{} finding(s) in {} file(s)",
+                        report.findings.len(),
+                        report.files_checked
+                    ));
+                    egui::ScrollArea::vertical()
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            let mut rules: Vec<&str> =
+                                report.findings.iter().map(|f| f.rule.as_str()).collect();
+                            rules.dedup();
+                            for rule in rules {
+                                let findings: Vec<&Finding> =
+                                    report.findings.iter().filter(|f| f.rule == rule).collect();
+                                ui.horizontal(|ui| {
+                                    ui.strong(format!("{} ({})", rule, findings.len()));
+                                    let fixable = findings.iter().any(|f| f.fix.is_some());
+                                    if ui
+                                        .add_enabled(fixable, egui::Button::new("Fix All"))
+                                        .clicked()
+                                    {
+                                        fix = Some(findings.iter().map(|&f| f.clone()).collect());
+                                    }
+                                });
+                                for finding in findings {
+                                    ui.horizontal(|ui| {
+                                        ui.add_space(12.0);
+                                        ui.label(self.display_name_for(&finding.file));
+                                        ui.monospace(format!(
+                                            "{} = {}",
+                                            finding.path, finding.value
+                                        ));
+                                        if let Some(value) = &finding.fix {
+                                            if ui
+                                                .small_button("Fix")
+                                                .on_hover_text(format!("Set to {}", value))
+                                                .clicked()
+                                            {
+                                                fix = Some(vec![finding.clone()]);
+                                            }
+                                        }
+                                    });
+                                }
+                            }
+                            for (path, error) in &report.errors {
+                                ui.colored_label(
+                                    theme::error(),
+                                    format!("Could not check {}: {}", path.display(), error),
+                                );
+                            }
+                        });
+                });
+            if let Some(findings) = fix {
+                self.fix_findings(findings);
+                self.run_lint();
+            }
+            if !show_lint {
+                self.close_tool(Tool::Lint);
+            }
+        }
 
-                    // Log content
+        // Show font settings
+        let mut show_fonts = self.is_open(Tool::Fonts);
+        if show_fonts {
+            let mut pick = None;
+            let mut reset = None;
+            let mut size_changed = false;
+            egui::Window::new("Fonts")
+                .open(&mut show_fonts)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    egui::Grid::new("fonts_grid")
+                        .num_columns(3)
+                        .spacing([10.0, 6.0])
+                        .show(ui, |ui| {
+                            for (label, path, mono) in [
+                                ("Interface:", &self.config.fonts.ui_font, false),
+                                ("Monospace:", &self.config.fonts.mono_font, true),
+                            ] {
+                                ui.label(label);
+                                match path {
+                                    Some(path) => {
+                                        let name = Path::new(path)
+                                            .file_name()
+                                            .map(|n| n.to_string_lossy().to_string())
+                                            .unwrap_or_else(|| path.clone());
+                                        ui.label(name).on_hover_text(path);
+                                    }
+                                    None => {
+                                        ui.label(
+                                            egui::RichText::new("Default")
+                                                .color(theme::colors::TEXT_DIM),
+                                        );
+                                    }
+                                }
+                                ui.horizontal(|ui| {
+                                    if ui.button("Choose...").clicked() {
+                                        pick = Some(mono);
+                                    }
+                                    if ui
+                                        .add_enabled(path.is_some(), egui::Button::new("Default"))
+                                        .clicked()
+                                    {
+                                        reset = Some(mono);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                            ui.label("Text size:");
+                            size_changed = ui
+                                .add(
+                                    egui::DragValue::new(&mut self.config.fonts.size)
+                                        .range(8.0..=24.0)
+                                        .speed(0.1)
+                                        .suffix(" pt"),
+                                )
+                                .changed();
+                            if ui.button("Default").clicked() {
+                                self.config.fonts.size = DEFAULT_FONT_SIZE;
+                                size_changed = true;
+                            }
+                            ui.end_row();
+                        });
+                    ui.label(
+                        egui::RichText::new(
+                            "The monospace font is used for the log, audit log and inspector.",
+                        )
+                        .small()
+                        .color(theme::colors::TEXT_DIM),
+                    );
+                });
+            if let Some(mono) = pick {
+                self.pick_font(ctx, mono);
+            }
+            if let Some(mono) = reset {
+                if mono {
+                    self.config.fonts.mono_font = None;
+                } else {
+                    self.config.fonts.ui_font = None;
+                }
+                let _ = self.config.save();
+                self.apply_fonts(ctx);
+            }
+            if size_changed {
+                let _ = self.config.save();
+                self.apply_layout(ctx);
+            }
+            if !show_fonts {
+                self.close_tool(Tool::Fonts);
+            }
+        }
+
+        // Show backup retention
+        let mut show_backup_retention = self.is_open(Tool::BackupRetention);
+        if show_backup_retention {
+            let mut changed = false;
+            let mut prune = false;
+            egui::Window::new("Backup Retention")
+                .open(&mut show_backup_retention)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("Backups to keep per profile, newest first:");
+                    egui::Grid::new("backup_retention_grid")
+                        .num_columns(2)
+                        .spacing([10.0, 6.0])
+                        .show(ui, |ui| {
+                            for trigger in BackupTrigger::ALL {
+                                let mut limited =
+                                    self.config.backup_retention.contains_key(&trigger);
+                                if ui.checkbox(&mut limited, trigger.name()).changed() {
+                                    if limited {
+                                        self.config
+                                            .backup_retention
+                                            .insert(trigger, DEFAULT_BACKUPS_KEPT);
+                                    } else {
+                                        self.config.backup_retention.remove(&trigger);
+                                    }
+                                    changed = true;
+                                }
+                                match self.config.backup_retention.get_mut(&trigger) {
+                                    Some(keep) => {
+                                        changed |= ui
+                                            .add(egui::DragValue::new(keep).range(1..=999))
+                                            .changed();
+                                    }
+                                    None => {
+                                        ui.label(
+                                            egui::RichText::new("Keep all")
+                                                .color(theme::colors::TEXT_DIM),
+                                        );
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    ui.label(
+                        egui::RichText::new(
+                            "Older backups are deleted after each new one. Backups from \
+                             before triggers were recorded are always kept.",
+                        )
+                        .small()
+                        .color(theme::colors::TEXT_DIM),
+                    );
+                    ui.add_space(6.0);
+                    if ui
+                        .add_enabled(
+                            self.settings_dir.is_some() && !self.config.backup_retention.is_empty(),
+                            egui::Button::new("Prune Now"),
+                        )
+                        .on_hover_text("Apply these limits to the current profile's backups")
+                        .clicked()
+                    {
+                        prune = true;
+                    }
+                });
+            if changed {
+                let _ = self.config.save();
+            }
+            if prune {
+                self.prune_backups();
+            }
+            if !show_backup_retention {
+                self.close_tool(Tool::BackupRetention);
+            }
+        }
+
+        // Show the backup name format
+        let mut show_backup_names = self.is_open(Tool::BackupNames);
+        if show_backup_names {
+            let mut save = false;
+            egui::Window::new("Backup Names")
+                .open(&mut show_backup_names)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("New backup folders are named:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.backup_name_draft)
+                            .desired_width(320.0)
+                            .font(egui::TextStyle::Monospace),
+                    );
+                    match settings::validate_backup_name_format(&self.backup_name_draft) {
+                        Ok(()) => {
+                            let example = settings::format_backup_name(
+                                &self.backup_name_draft,
+                                "settings_Default",
+                                chrono::Utc::now(),
+                                BackupTrigger::Manual,
+                                Some("before patch"),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!("e.g. {}", example))
+                                    .color(theme::colors::TEXT_DIM),
+                            );
+                        }
+                        Err(e) => {
+                            ui.colored_label(theme::error(), e.to_string());
+                        }
+                    }
+                    ui.add_space(6.0);
+                    ui.label(
+                        egui::RichText::new(
+                            "{profile} settings folder, {date} YYYYMMDD and {time} HHMMSS in \
+                             UTC, {trigger} manual, pre-sync, pre-restore or scheduled, \
+                             {note} the backup's note, if any",
+                        )
+                        .small()
+                        .color(theme::colors::TEXT_DIM),
+                    );
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        let valid =
+                            settings::validate_backup_name_format(&self.backup_name_draft).is_ok();
+                        if ui.add_enabled(valid, egui::Button::new("Save")).clicked() {
+                            save = true;
+                        }
+                        if ui.button("Default").clicked() {
+                            self.backup_name_draft =
+                                settings::DEFAULT_BACKUP_NAME_FORMAT.to_string();
+                        }
+                    });
+                });
+            if save {
+                self.config.backup_name_format = self.backup_name_draft.clone();
+                let _ = self.config.save();
+                self.load_backups();
+                self.status_messages.push(format!(
+                    "Backups will be named {}",
+                    self.config.backup_name_format
+                ));
+            }
+            if !show_backup_names {
+                self.close_tool(Tool::BackupNames);
+            }
+        }
+
+        // Show the profiles the fallback search found
+        if !self.settings_candidates.is_empty() {
+            let mut open = true;
+            let mut chosen = None;
+            egui::Window::new("Settings Found")
+                .open(&mut open)
+                .default_size([520.0, 200.0])
+                .show(ctx, |ui| {
+                    ui.label(
+                        "EVE's settings are not in the usual AppData/Local/CCP/EVE folder of \
+                         this prefix, but these folders hold character files:",
+                    );
+                    ui.separator();
                     egui::ScrollArea::vertical()
-                        .stick_to_bottom(true)
+                        .id_salt("settings_candidates_scroll")
                         .show(ui, |ui| {
-                            for msg in &self.status_messages {
-                                ui.label(msg);
+                            for dir in &self.settings_candidates {
+                                ui.horizontal(|ui| {
+                                    if ui.button("Use").clicked() {
+                                        chosen = Some(dir.clone());
+                                    }
+                                    let shown = self
+                                        .selected_prefix
+                                        .as_deref()
+                                        .and_then(|prefix| dir.strip_prefix(prefix).ok())
+                                        .unwrap_or(dir);
+                                    ui.label(shown.display().to_string())
+                                        .on_hover_text(dir.display().to_string());
+                                });
                             }
                         });
                 });
-            self.show_log_window = show_log;
+            if let Some(dir) = chosen {
+                self.profile_dirs = std::mem::take(&mut self.settings_candidates);
+                self.select_profile(dir);
+            } else if !open {
+                self.settings_candidates.clear();
+            }
         }
 
-        // Handle pending confirmations
-        if let Some(action) = self.pending_confirmation.clone() {
-            egui::Window::new("Confirm")
-                .collapsible(false)
+        // Show the duplicate-as dialog
+        if let Some(mut draft) = self.duplicate_draft.take() {
+            let mut open = true;
+            let mut create = None;
+            let mut check = None;
+            egui::Window::new("Duplicate As")
+                .open(&mut open)
                 .resizable(false)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .show(ctx, |ui| {
-                    match &action {
-                        PendingAction::Sync => {
-                            ui.label("Are you sure you want to sync settings?");
-                            if !self.dry_run_mode {
-                                ui.label("This will overwrite target settings.");
-                                ui.label("A backup will be created first.");
+                    ui.label(format!(
+                        "Copy the settings of {} to new IDs in {}.",
+                        self.display_name_for(&draft.file),
+                        discovery::profile_label(draft.file.settings_dir())
+                    ));
+                    ui.label("New IDs, one per line or separated by commas:");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut draft.ids_text)
+                            .desired_rows(3)
+                            .desired_width(280.0),
+                    );
+                    let ids = match settings::parse_id_list(&draft.ids_text) {
+                        Ok(ids) => ids,
+                        Err(e) => {
+                            ui.colored_label(theme::error(), e.to_string());
+                            Vec::new()
+                        }
+                    };
+                    let (existing, mut new): (Vec<u64>, Vec<u64>) = ids.iter().partition(|&&id| {
+                        discovery::settings_file_name(draft.file.file_type, id)
+                            .is_some_and(|name| draft.file.settings_dir().join(name).exists())
+                    });
+                    if !existing.is_empty() {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{} already have settings and are skipped",
+                                existing.len()
+                            ))
+                            .color(theme::colors::TEXT_DIM),
+                        );
+                    }
+
+                    // Character IDs are checked with ESI first, so a typo makes no file
+                    let validate =
+                        draft.file.file_type == FileType::Character && self.names.is_online();
+                    let checked = draft.check.as_ref().filter(|c| c.ids == ids);
+                    let mut unchecked = false;
+                    if validate && !new.is_empty() {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(!draft.checking, egui::Button::new("Check IDs"))
+                                .on_hover_text("Look the IDs up with ESI")
+                                .clicked()
+                            {
+                                check = Some(ids.clone());
                             }
+                            if draft.checking {
+                                ui.spinner();
+                            }
+                        });
+                        match checked.map(|c| &c.unknown) {
+                            Some(Ok(unknown)) => {
+                                if !unknown.is_empty() {
+                                    let listed: Vec<String> =
+                                        unknown.iter().map(u64::to_string).collect();
+                                    ui.colored_label(
+                                        theme::error(),
+                                        format!("Not characters: {}", listed.join(", ")),
+                                    );
+                                }
+                                new.retain(|id| !unknown.contains(id));
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(theme::error(), format!("Check failed: {}", e));
+                                unchecked = true;
+                            }
+                            None => new.clear(),
                         }
-                        PendingAction::Restore(path) => {
-                            ui.label("Are you sure you want to restore this backup?");
-                            ui.label(format!(
-                                "{}",
-                                path.file_name().unwrap_or_default().to_string_lossy()
-                            ));
+                    }
+
+                    let label = if unchecked {
+                        format!("Create {} Without Checking", new.len())
+                    } else {
+                        format!("Create {}", new.len())
+                    };
+                    if ui
+                        .add_enabled(!new.is_empty(), egui::Button::new(label))
+                        .clicked()
+                    {
+                        create = Some(new);
+                    }
+                });
+            if let Some(ids) = check {
+                draft.checking = true;
+                let names = Arc::clone(&self.names);
+                let events = self.events.sender();
+                thread::spawn(move || {
+                    let mut result = Ok(esi::Resolution::default());
+                    for batch in ids.chunks(NAME_BATCH_SIZE) {
+                        match (names.resolve(batch), result.as_mut()) {
+                            (Ok(found), Ok(resolution)) => {
+                                resolution.names.extend(found.names);
+                                resolution.not_found.extend(found.not_found);
+                            }
+                            (Err(e), _) => {
+                                result = Err(e.to_string());
+                                break;
+                            }
+                            _ => {}
+                        }
+                    }
+                    events.send(AppEvent::IdsChecked { ids, result });
+                });
+            }
+            match create {
+                Some(ids) => self.duplicate_file(&draft.file, &ids),
+                None if open => self.duplicate_draft = Some(draft),
+                None => {}
+            }
+        }
+
+        // Show the fleet baseline
+        let mut show_baseline = self.is_open(Tool::Baseline);
+        if show_baseline {
+            let mut apply = None;
+            egui::Window::new("Fleet Baseline")
+                .open(&mut show_baseline)
+                .default_size([520.0, 420.0])
+                .show(ctx, |ui| {
+                    ui.heading("Build");
+                    ui.label(
+                        "Right-click settings in the inspector to add them with their current \
+                         value, then export the baseline for members to load.",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.text_edit_singleline(&mut self.baseline_draft.name);
+                    });
+                    let mut remove = None;
+                    for (i, setting) in self.baseline_draft.settings.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("Remove").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.weak(format!("{:?}", setting.file).to_lowercase());
+                            ui.monospace(format!("{} = {}", setting.key, setting.value));
+                        });
+                    }
+                    if let Some(i) = remove {
+                        self.baseline_draft.settings.remove(i);
+                    }
+                    ui.horizontal(|ui| {
+                        let has_settings = !self.baseline_draft.settings.is_empty();
+                        if ui
+                            .add_enabled(has_settings, egui::Button::new("Export..."))
+                            .clicked()
+                        {
+                            self.export_baseline();
+                        }
+                        if ui
+                            .add_enabled(has_settings, egui::Button::new("Check Here"))
+                            .on_hover_text("Check this machine's characters against it")
+                            .clicked()
+                        {
+                            self.check_baseline(self.baseline_draft.clone());
+                        }
+                    });
+
+                    ui.separator();
+                    ui.heading("Check");
+                    ui.horizontal(|ui| {
+                        if ui.button("Load Baseline...").clicked() {
+                            self.load_baseline();
+                        }
+                        if let Some((baseline, report)) = &self.baseline {
+                            if ui.button("Check Again").clicked() {
+                                self.check_baseline(baseline.clone());
+                                return;
+                            }
+                            if ui.button("Copy Report").clicked() {
+                                let text = report.to_text(baseline, |f| self.display_name_for(f));
+                                ui.ctx().copy_text(text);
+                            }
+                            let fixes = report.fixes();
+                            if ui
+                                .add_enabled(!fixes.is_empty(), egui::Button::new("Apply Baseline"))
+                                .clicked()
+                            {
+                                apply = Some(fixes);
+                            }
+                        }
+                    });
+                    if let Some(ref error) = self.baseline_error {
+                        ui.colored_label(theme::error(), error);
+                    }
+                    let Some((baseline, report)) = &self.baseline else {
+                        return;
+                    };
+                    let compliant = report
+                        .characters
+                        .iter()
+                        .filter(|c| c.is_compliant())
+                        .count();
+                    ui.label(format!(
+                        "{}: {} of {} file(s) compliant",
+                        baseline.name,
+                        compliant,
+                        report.characters.len()
+                    ));
+                    if !baseline.description.is_empty() {
+                        ui.weak(&baseline.description);
+                    }
+                    egui::ScrollArea::vertical()
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            for c in &report.characters {
+                                let color = if c.is_compliant() {
+                                    theme::success()
+                                } else {
+                                    theme::error()
+                                };
+                                ui.colored_label(
+                                    color,
+                                    format!(
+                                        "{}  {}/{}",
+                                        self.display_name_for(&c.file),
+                                        c.passed(),
+                                        c.checked
+                                    ),
+                                );
+                                for finding in &c.failing {
+                                    ui.horizontal(|ui| {
+                                        ui.add_space(12.0);
+                                        ui.monospace(format!(
+                                            "{} is {}",
+                                            finding.rule, finding.value
+                                        ));
+                                    });
+                                }
+                                for key in &c.missing {
+                                    ui.horizontal(|ui| {
+                                        ui.add_space(12.0);
+                                        ui.weak(format!("{} is missing", key));
+                                    });
+                                }
+                            }
+                            for (path, error) in &report.errors {
+                                ui.colored_label(
+                                    theme::error(),
+                                    format!("Could not check {}: {}", path.display(), error),
+                                );
+                            }
+                        });
+                });
+            if let Some(findings) = apply {
+                self.open_modal(Modal::Confirm(PendingAction::ApplyBaseline(findings)));
+            }
+            if !show_baseline {
+                self.close_tool(Tool::Baseline);
+            }
+        }
+
+        // Show sync presets
+        let mut show_presets = self.is_open(Tool::Presets);
+        if show_presets {
+            egui::Window::new("Sync Presets")
+                .open(&mut show_presets)
+                .default_size([520.0, 260.0])
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.new_preset_name)
+                                .hint_text("preset name")
+                                .desired_width(160.0),
+                        );
+                        let can_save =
+                            self.picker.has_selection() && !self.new_preset_name.trim().is_empty();
+                        if ui
+                            .add_enabled(can_save, egui::Button::new("Save Current Selection"))
+                            .clicked()
+                        {
+                            self.save_preset();
+                        }
+                    });
+                    ui.separator();
+
+                    let mut load = None;
+                    let mut remove_idx = None;
+                    let mut changed = false;
+                    egui::ScrollArea::vertical()
+                        .id_salt("presets_scroll")
+                        .show(ui, |ui| {
+                            if self.config.presets.is_empty() {
+                                ui.label("No presets saved");
+                            }
+                            for (idx, preset) in self.config.presets.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.strong(&preset.name);
+                                    ui.label(format!(
+                                        "{} -> {} target(s)",
+                                        preset
+                                            .source
+                                            .file_name()
+                                            .unwrap_or_default()
+                                            .to_string_lossy(),
+                                        preset.targets.len()
+                                    ))
+                                    .on_hover_text(preset.source.display().to_string());
+                                    if ui.button("Load").clicked() {
+                                        load = Some(preset.clone());
+                                    }
+                                    if ui.button("Remove").clicked() {
+                                        remove_idx = Some(idx);
+                                    }
+                                });
+                                if !self.plugins.is_empty() || !preset.plugins.is_empty() {
+                                    ui.horizontal_wrapped(|ui| {
+                                        ui.label("Plugins:");
+                                        for plugin in &self.plugins {
+                                            let mut enabled = preset.plugins.contains(&plugin.name);
+                                            if ui.checkbox(&mut enabled, &plugin.name).changed() {
+                                                if enabled {
+                                                    preset.plugins.push(plugin.name.clone());
+                                                } else {
+                                                    preset.plugins.retain(|n| n != &plugin.name);
+                                                }
+                                                changed = true;
+                                            }
+                                        }
+                                        for name in &preset.plugins {
+                                            if !self.plugins.iter().any(|p| &p.name == name) {
+                                                ui.colored_label(
+                                                    theme::error(),
+                                                    format!("{} (missing)", name),
+                                                );
+                                            }
+                                        }
+                                    });
+                                }
+                                ui.horizontal(|ui| {
+                                    let mut scheduled = preset.schedule.is_some();
+                                    if ui
+                                        .checkbox(&mut scheduled, "Run")
+                                        .on_hover_text(
+                                            "Run unattended while this app is open and no EVE \
+                                             client is running. A dry run must pass first, and \
+                                             affected profiles are backed up.",
+                                        )
+                                        .changed()
+                                    {
+                                        preset.schedule = scheduled.then(Schedule::default);
+                                        preset.mark_run();
+                                        changed = true;
+                                    }
+                                    let Some(schedule) = preset.schedule.as_mut() else {
+                                        return;
+                                    };
+                                    let before = *schedule;
+                                    egui::ComboBox::from_id_salt(("preset_day", idx))
+                                        .selected_text(match schedule.weekday {
+                                            Some(day) => presets::weekday_name(day),
+                                            None => "Every day",
+                                        })
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(
+                                                &mut schedule.weekday,
+                                                None,
+                                                "Every day",
+                                            );
+                                            for day in WEEKDAYS {
+                                                ui.selectable_value(
+                                                    &mut schedule.weekday,
+                                                    Some(day),
+                                                    presets::weekday_name(day),
+                                                );
+                                            }
+                                        });
+                                    ui.label("at");
+                                    ui.add(egui::DragValue::new(&mut schedule.hour).range(0..=23));
+                                    ui.label(":");
+                                    ui.add(
+                                        egui::DragValue::new(&mut schedule.minute).range(0..=59),
+                                    );
+                                    if *schedule != before {
+                                        changed = true;
+                                    }
+                                    if let Some(ref last) = preset.last_run {
+                                        ui.label(
+                                            egui::RichText::new(format!(
+                                                "last: {}",
+                                                self.config.time_display.format_rfc3339(last)
+                                            ))
+                                            .color(theme::colors::TEXT_DIM),
+                                        );
+                                    }
+                                });
+                                ui.separator();
+                            }
+                        });
+
+                    if let Some(idx) = remove_idx {
+                        self.config.presets.remove(idx);
+                        changed = true;
+                    }
+                    if changed {
+                        let _ = self.config.save();
+                    }
+                    if let Some(preset) = load {
+                        self.load_preset(&preset);
+                    }
+                });
+            if !show_presets {
+                self.close_tool(Tool::Presets);
+            }
+        }
+
+        // Show golden images
+        let mut show_golden = self.is_open(Tool::Golden);
+        if show_golden {
+            egui::Window::new("Golden Images")
+                .open(&mut show_golden)
+                .default_size([520.0, 300.0])
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.new_golden_name)
+                                .hint_text("image name")
+                                .desired_width(160.0),
+                        );
+                        let can_save =
+                            self.settings_dir.is_some() && !self.new_golden_name.trim().is_empty();
+                        if ui
+                            .add_enabled(can_save, egui::Button::new("Save Current Profile"))
+                            .on_hover_text("Store every settings file in the current profile")
+                            .clicked()
+                        {
+                            self.save_golden_image();
+                        }
+                    });
+                    ui.separator();
+
+                    let mut compare = None;
+                    let mut reset = None;
+                    let mut delete = None;
+                    let mut bootstrap = None;
+                    if self.golden_images.is_empty() {
+                        ui.label("No golden images saved");
+                    }
+                    let can_reset = !self.picker.targets.is_empty();
+                    for image in &self.golden_images {
+                        ui.horizontal(|ui| {
+                            ui.strong(&image.name);
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "from {}",
+                                    discovery::profile_label(&image.source)
+                                ))
+                                .color(theme::colors::TEXT_DIM),
+                            )
+                            .on_hover_text(self.config.time_display.format_rfc3339(&image.created));
+                            if ui.button("Compare").clicked() {
+                                compare = Some(image.clone());
+                            }
+                            if ui
+                                .add_enabled(can_reset, egui::Button::new("Reset Targets"))
+                                .on_hover_text("Overwrite the files ticked in the target list")
+                                .clicked()
+                            {
+                                reset = Some(image.clone());
+                            }
+                            if ui
+                                .button("Bootstrap Prefix...")
+                                .on_hover_text(
+                                    "Populate a new Wine prefix before its first client launch",
+                                )
+                                .clicked()
+                            {
+                                bootstrap = Some(image.clone());
+                            }
+                            if ui.button("Delete").clicked() {
+                                delete = Some(image.clone());
+                            }
+                        });
+                    }
+
+                    if let Some((name, states)) = &self.golden_comparison {
+                        ui.separator();
+                        ui.label(format!("Current profile against '{}':", name));
+                        egui::ScrollArea::vertical()
+                            .id_salt("golden_compare_scroll")
+                            .max_height(150.0)
+                            .show(ui, |ui| {
+                                for (file, state) in states {
+                                    let color = match state {
+                                        GoldenState::Same => theme::colors::TEXT_DIM,
+                                        _ => theme::colors::CYAN,
+                                    };
+                                    ui.label(
+                                        egui::RichText::new(format!("{}  {}", file, state.label()))
+                                            .color(color),
+                                    );
+                                }
+                            });
+                    }
+
+                    if let Some(image) = compare {
+                        self.compare_golden(&image);
+                    }
+                    if let Some(image) = reset {
+                        self.open_modal(Modal::Confirm(PendingAction::ResetToGolden(image)));
+                    }
+                    if let Some(image) = bootstrap {
+                        self.pick_bootstrap_prefix(image);
+                    }
+                    if let Some(image) = delete {
+                        if let Err(e) = image.delete() {
+                            self.status_messages.push(e.to_string());
+                        }
+                        self.golden_comparison = None;
+                        self.load_golden_images();
+                    }
+                });
+            if !show_golden {
+                self.close_tool(Tool::Golden);
+            }
+        }
+
+        // Show prefix bookmarks
+        let mut show_bookmarks = self.is_open(Tool::Bookmarks);
+        if show_bookmarks {
+            egui::Window::new("Prefix Bookmarks")
+                .open(&mut show_bookmarks)
+                .default_size([520.0, 240.0])
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        let current = self.selected_prefix.clone();
+                        if ui
+                            .add_enabled(current.is_some(), egui::Button::new("Bookmark Current"))
+                            .clicked()
+                        {
+                            if let Some(prefix) = current {
+                                self.config
+                                    .save_prefix(prefix.to_string_lossy().to_string());
+                                let _ = self.config.save();
+                            }
+                        }
+                        if ui.button("Export...").clicked() {
+                            self.export_prefix_bookmarks();
+                        }
+                        if ui.button("Import...").clicked() {
+                            self.import_prefix_bookmarks();
+                        }
+                    });
+                    ui.separator();
+
+                    let mut use_prefix = None;
+                    let mut remove_idx = None;
+                    let mut changed = false;
+                    egui::ScrollArea::vertical()
+                        .id_salt("bookmarks_scroll")
+                        .show(ui, |ui| {
+                            if self.config.saved_prefixes.is_empty() {
+                                ui.label("No bookmarked or recent prefixes");
+                            }
+                            for (idx, saved) in self.config.saved_prefixes.iter_mut().enumerate() {
+                                ui.label(&saved.path);
+                                ui.horizontal(|ui| {
+                                    let mut alias = saved.alias.clone().unwrap_or_default();
+                                    ui.label("Alias:");
+                                    if ui
+                                        .add(
+                                            egui::TextEdit::singleline(&mut alias)
+                                                .desired_width(120.0),
+                                        )
+                                        .changed()
+                                    {
+                                        saved.alias = Some(alias).filter(|a| !a.is_empty());
+                                        changed = true;
+                                    }
+
+                                    let mut tags = saved.tags.join(", ");
+                                    ui.label("Tags:");
+                                    let response = ui.add(
+                                        egui::TextEdit::singleline(&mut tags).desired_width(140.0),
+                                    );
+                                    if response.changed() {
+                                        saved.tags =
+                                            tags.split(',').map(|t| t.trim().to_string()).collect();
+                                    }
+                                    if response.lost_focus() {
+                                        saved.tags.retain(|t| !t.is_empty());
+                                        changed = true;
+                                    }
+
+                                    if ui
+                                        .checkbox(&mut saved.favorite, "Favorite")
+                                        .on_hover_text(
+                                            "Favorites are kept; other prefixes are forgotten \
+                                             once enough newer ones have been used",
+                                        )
+                                        .changed()
+                                    {
+                                        changed = true;
+                                    }
+                                    if ui.button("Use").clicked() {
+                                        use_prefix = Some(PathBuf::from(&saved.path));
+                                    }
+                                    if ui.button("Remove").clicked() {
+                                        remove_idx = Some(idx);
+                                    }
+                                });
+                                ui.separator();
+                            }
+                        });
+
+                    if !self.shared_prefixes.is_empty() {
+                        ui.separator();
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Shared ({})",
+                                Config::shared_registry_path().display()
+                            ))
+                            .color(theme::colors::CYAN),
+                        );
+                        for shared in &self.shared_prefixes {
+                            ui.horizontal(|ui| {
+                                if ui.button("Use").clicked() {
+                                    use_prefix = Some(PathBuf::from(&shared.path));
+                                }
+                                ui.label(shared.alias.as_deref().unwrap_or(&shared.path))
+                                    .on_hover_text(&shared.path);
+                                if !shared.tags.is_empty() {
+                                    ui.label(
+                                        egui::RichText::new(shared.tags.join(", "))
+                                            .color(theme::colors::TEXT_DIM),
+                                    );
+                                }
+                            });
                         }
                     }
 
-                    ui.add_space(10.0);
-                    ui.horizontal(|ui| {
-                        if ui.button("Yes").clicked() {
-                            match action {
-                                PendingAction::Sync => self.perform_sync(),
-                                PendingAction::Restore(path) => self.restore_backup(path),
-                            }
-                            self.pending_confirmation = None;
-                        }
-                        if ui.button("Cancel").clicked() {
-                            self.pending_confirmation = None;
-                        }
-                    });
-                });
-        }
-
-        // Show sync complete dialog
-        if let Some(message) = self.sync_complete_message.clone() {
-            egui::Window::new("Done")
-                .collapsible(false)
-                .resizable(false)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-                .show(ctx, |ui| {
-                    ui.label(&message);
-                    ui.add_space(10.0);
-                    if ui.button("OK").clicked() {
-                        self.sync_complete_message = None;
+                    if let Some(idx) = remove_idx {
+                        self.config.saved_prefixes.remove(idx);
+                        changed = true;
+                    }
+                    if changed {
+                        let _ = self.config.save();
+                    }
+                    if let Some(prefix) = use_prefix {
+                        self.select_prefix(prefix);
                     }
                 });
-        }
-
-        // Show update available dialog
-        if self.show_update_dialog {
-            if let Some(ref version) = self.update_available.clone() {
-                egui::Window::new("Update Available")
-                    .collapsible(false)
-                    .resizable(false)
-                    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-                    .show(ctx, |ui| {
-                        ui.label(format!("A new version ({}) is available!", version));
-                        ui.label(format!("Current version: v{}", env!("CARGO_PKG_VERSION")));
-                        ui.add_space(10.0);
-                        ui.horizontal(|ui| {
-                            if ui.button("Download").clicked() {
-                                let _ = open::that(
-                                    "https://github.com/sopleb/PackPreferences/releases/latest",
-                                );
-                                self.show_update_dialog = false;
-                            }
-                            if ui.button("Later").clicked() {
-                                self.show_update_dialog = false;
-                            }
-                        });
-                    });
+            if !show_bookmarks {
+                self.close_tool(Tool::Bookmarks);
             }
         }
 
-        // Show no update available dialog
-        if self.show_no_update_dialog {
-            egui::Window::new("Up to Date")
-                .collapsible(false)
-                .resizable(false)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-                .show(ctx, |ui| {
-                    ui.label("You're running the latest version.");
-                    ui.label(format!("Current version: v{}", env!("CARGO_PKG_VERSION")));
-                    ui.add_space(10.0);
-                    if ui.button("OK").clicked() {
-                        self.show_no_update_dialog = false;
-                    }
-                });
+        let size = ctx.screen_rect().size();
+        if self.config.mini_mode || size.x < MINI_MODE_SIZE.x || size.y < MINI_MODE_SIZE.y {
+            self.show_mini(ctx);
+            self.end_diagnostics(ctx);
+            return;
         }
 
         egui::CentralPanel::default().show(ctx, |ui| {
             // App title with Log and About buttons
             ui.horizontal(|ui| {
-                theme::styled_title(ui);
+                let title = ui.scope(theme::styled_title).response;
+                if self.config.borderless
+                    && ui
+                        .interact(title.rect, ui.id().with("title_drag"), egui::Sense::drag())
+                        .on_hover_text("Drag to move the window")
+                        .drag_started()
+                {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                }
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("About").clicked() {
                         self.about.open = true;
+                        self.fetch_news();
+                    }
+                    if ui.button("Help").clicked() {
+                        self.help.open = !self.help.open;
                     }
                     if ui.button("Log").clicked() {
-                        self.show_log_window = !self.show_log_window;
+                        self.toggle_tool(Tool::Log);
                     }
+                    if ui
+                        .button("Mini")
+                        .on_hover_text("Compact view with just a preset and a Sync button")
+                        .clicked()
+                    {
+                        self.config.mini_mode = true;
+                        let _ = self.config.save();
+                        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(MINI_VIEW_SIZE));
+                    }
+                    ui.menu_button("Settings", |ui| {
+                        if ui.button("Export Settings...").clicked() {
+                            ui.close_menu();
+                            self.export_app_config();
+                        }
+                        if ui.button("Import Settings...").clicked() {
+                            ui.close_menu();
+                            self.import_app_config();
+                        }
+                        if ui
+                            .add_enabled(
+                                !self.character_files.is_empty(),
+                                egui::Button::new("Export HTML Report..."),
+                            )
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.export_html_report();
+                        }
+                        if ui
+                            .add_enabled(
+                                self.selected_prefix.is_some(),
+                                egui::Button::new("Ignore Rules..."),
+                            )
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            let patterns = self
+                                .selected_prefix
+                                .as_ref()
+                                .and_then(|p| {
+                                    self.config
+                                        .ignore_patterns
+                                        .get(p.to_string_lossy().as_ref())
+                                })
+                                .map(|patterns| patterns.join("\n"))
+                                .unwrap_or_default();
+                            self.ignore_editor = Some(patterns);
+                        }
+                        if ui
+                            .add_enabled(
+                                self.selected_prefix.is_some(),
+                                egui::Button::new("Duplicate Profiles..."),
+                            )
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            self.find_duplicate_profiles();
+                        }
+                        // Wine prefixes only: a native install has nothing to copy across
+                        if process::native_root().is_none() {
+                            if ui
+                                .add_enabled(
+                                    self.selected_prefix.is_some(),
+                                    egui::Button::new("Import from Windows..."),
+                                )
+                                .clicked()
+                            {
+                                ui.close_menu();
+                                self.open_windows_transfer(Direction::Import);
+                            }
+                            if ui
+                                .add_enabled(
+                                    self.selected_prefix.is_some(),
+                                    egui::Button::new("Export to Windows..."),
+                                )
+                                .clicked()
+                            {
+                                ui.close_menu();
+                                self.open_windows_transfer(Direction::Export);
+                            }
+                            if ui.button("Migrate Prefix...").clicked() {
+                                ui.close_menu();
+                                self.open_migration();
+                            }
+                        }
+                        if ui.button("Notifications...").clicked() {
+                            ui.close_menu();
+                            self.webhook_draft = self
+                                .config
+                                .webhook
+                                .clone()
+                                .unwrap_or_else(|| Webhook::new(String::new()));
+                            self.ntfy_draft = self
+                                .config
+                                .ntfy
+                                .clone()
+                                .unwrap_or_else(|| NtfyTopic::new(String::new()));
+                            self.open_modal(Modal::Tool(Tool::Notifications));
+                        }
+                        if ui.button("Scripts...").clicked() {
+                            ui.close_menu();
+                            self.load_scripts();
+                            self.open_modal(Modal::Tool(Tool::Scripts));
+                        }
+                        if ui.button("Fleet Baseline...").clicked() {
+                            ui.close_menu();
+                            self.open_modal(Modal::Tool(Tool::Baseline));
+                        }
+                        if ui.button("Lint Settings...").clicked() {
+                            ui.close_menu();
+                            self.run_lint();
+                            self.open_modal(Modal::Tool(Tool::Lint));
+                        }
+                        if ui.button("Troubleshoot Detection...").clicked() {
+                            ui.close_menu();
+                            self.run_troubleshooter();
+                        }
+                        if ui.button("Backup Names...").clicked() {
+                            ui.close_menu();
+                            self.backup_name_draft = self.config.backup_name_format.clone();
+                            self.open_modal(Modal::Tool(Tool::BackupNames));
+                        }
+                        if ui.button("Audit Log...").clicked() {
+                            ui.close_menu();
+                            self.load_audit_log();
+                        }
+                        #[cfg(target_os = "linux")]
+                        if ui.button("Add to Applications Menu").clicked() {
+                            ui.close_menu();
+                            match icon::install_desktop_entry() {
+                                Ok(path) => self
+                                    .status_messages
+                                    .push(format!("Added desktop entry {}", path.display())),
+                                Err(e) => self
+                                    .status_messages
+                                    .push(format!("Failed to add desktop entry: {:#}", e)),
+                            }
+                        }
+                        ui.separator();
+                        if ui
+                            .checkbox(&mut self.config.crash_reporting, "Crash reports")
+                            .on_hover_text(
+                                "Save a local crash report and offer to file it on next launch",
+                            )
+                            .changed()
+                        {
+                            crash::set_enabled(self.config.crash_reporting);
+                            let _ = self.config.save();
+                        }
+                        if ui
+                            .checkbox(&mut self.config.news_feed, "News on About screen")
+                            .on_hover_text(
+                                "Fetch release notes and known issues to show on the About screen",
+                            )
+                            .changed()
+                        {
+                            let _ = self.config.save();
+                            if !self.config.news_feed {
+                                self.about.news = None;
+                                self.about.news_offline = false;
+                                self.news_fetched = false;
+                            }
+                        }
+                        let mut serve_status = self.config.status_port.is_some();
+                        if ui
+                            .checkbox(&mut serve_status, "Status endpoint")
+                            .on_hover_text(
+                                "Serve health, last sync and backup times and scheduled \
+                                 presets as JSON on localhost, for monitoring",
+                            )
+                            .changed()
+                        {
+                            self.config.status_port =
+                                serve_status.then_some(status::DEFAULT_STATUS_PORT);
+                            let _ = self.config.save();
+                            self.apply_status_server();
+                        }
+                        if let Some(mut port) = self.config.status_port {
+                            ui.horizontal(|ui| {
+                                ui.label("Port:");
+                                let response =
+                                    ui.add(egui::DragValue::new(&mut port).range(1024..=65535));
+                                if response.lost_focus() || response.drag_stopped() {
+                                    self.config.status_port = Some(port);
+                                    let _ = self.config.save();
+                                    self.apply_status_server();
+                                } else if response.changed() {
+                                    self.config.status_port = Some(port);
+                                }
+                            });
+                            if let Some(ref server) = self.status_server {
+                                let url = server.url();
+                                ui.hyperlink_to(&url, &url);
+                            }
+                            if let Some(ref error) = self.status_server_error {
+                                ui.colored_label(theme::error(), error);
+                            }
+                        }
+                        ui.separator();
+                        ui.label("Show times in:");
+                        let mut changed = false;
+                        for zone in TimeDisplay::ALL {
+                            changed |= ui
+                                .radio_value(&mut self.config.time_display, zone, zone.name())
+                                .changed();
+                        }
+                        ui.label("ID format:");
+                        for format in IdFormat::ALL {
+                            changed |= ui
+                                .radio_value(&mut self.config.id_format, format, format.name())
+                                .changed();
+                        }
+                        ui.label("Link character IDs to:");
+                        changed |= ui
+                            .radio_value(&mut self.config.id_link, None, "Nothing")
+                            .changed();
+                        for site in LinkSite::ALL {
+                            changed |= ui
+                                .radio_value(&mut self.config.id_link, Some(site), site.name())
+                                .changed();
+                        }
+                        if changed {
+                            let _ = self.config.save();
+                        }
+                        ui.separator();
+                        ui.label("Before a full restore:")
+                            .on_hover_text("What restoring a whole backup asks for before it runs");
+                        for guard in RestoreGuard::ALL {
+                            if ui
+                                .radio_value(&mut self.config.restore_guard, guard, guard.name())
+                                .changed()
+                            {
+                                let _ = self.config.save();
+                            }
+                        }
+                        ui.separator();
+                        ui.label("Character names from:");
+                        let mut source = self.config.name_source;
+                        for option in NameSource::ALL {
+                            ui.radio_value(&mut source, option, option.name());
+                        }
+                        if source != self.config.name_source {
+                            if source == NameSource::Csv && self.config.names_csv.is_none() {
+                                ui.close_menu();
+                                self.pick_names_csv();
+                            } else {
+                                self.config.name_source = source;
+                                let _ = self.config.save();
+                                self.set_name_provider();
+                            }
+                        }
+                        if self.config.name_source == NameSource::Csv
+                            && ui.button("Choose Names File...").clicked()
+                        {
+                            ui.close_menu();
+                            self.pick_names_csv();
+                        }
+                        ui.separator();
+                        ui.label("Palette:");
+                        let mut palette_changed = false;
+                        for palette in Palette::ALL {
+                            palette_changed |= ui
+                                .radio_value(&mut self.config.palette, palette, palette.name())
+                                .changed();
+                        }
+                        if palette_changed {
+                            let _ = self.config.save();
+                            self.apply_layout(ui.ctx());
+                        }
+                        if ui.button("Fonts...").clicked() {
+                            ui.close_menu();
+                            self.open_modal(Modal::Tool(Tool::Fonts));
+                        }
+                        ui.label("Steam Deck layout:");
+                        let mut deck_changed = false;
+                        for (mode, name) in
+                            [(None, "Detect"), (Some(true), "On"), (Some(false), "Off")]
+                        {
+                            deck_changed |= ui
+                                .radio_value(&mut self.config.deck_mode, mode, name)
+                                .changed();
+                        }
+                        if deck_changed {
+                            let _ = self.config.save();
+                            self.apply_layout(ui.ctx());
+                        }
+                        ui.separator();
+                        let mut window_changed = ui
+                            .checkbox(&mut self.config.always_on_top, "Always on top")
+                            .on_hover_text("Float above the EVE clients")
+                            .changed();
+                        window_changed |= ui
+                            .checkbox(&mut self.config.borderless, "Hide title bar")
+                            .on_hover_text("Drag the app title to move the window")
+                            .changed();
+                        if window_changed {
+                            let _ = self.config.save();
+                            apply_window_options(ui.ctx(), &self.config);
+                        }
+                    });
                     // Show update indicator or check button
                     if self.update_available.is_some() {
                         if ui
@@ -656,12 +7204,14 @@ impl eframe::App for PackPreferencesApp {
                             )
                             .clicked()
                         {
-                            self.show_update_dialog = true;
+                            if let Some(version) = self.update_available.clone() {
+                                self.open_modal(Modal::UpdateAvailable(version));
+                            }
                         }
                     } else if ui.button("Check Updates").clicked() {
                         self.check_for_updates();
                         if self.update_available.is_none() {
-                            self.show_no_update_dialog = true;
+                            self.open_modal(Modal::UpToDate);
                         }
                     }
                 });
@@ -672,22 +7222,267 @@ impl eframe::App for PackPreferencesApp {
 
             // Prefix selection
             ui.horizontal(|ui| {
-                ui.label("Wine Prefix:");
-                let prefix_text = self
-                    .selected_prefix
-                    .as_ref()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "Not selected".to_string());
-                ui.add(egui::TextEdit::singleline(&mut prefix_text.as_str()).desired_width(400.0));
-
-                if ui.button("Browse").clicked() {
+                let native = process::native_root().is_some();
+                if native {
+                    // No prefix to choose on Windows or macOS, just where the settings live
+                    ui.label("EVE Settings:");
+                    let base_text = self
+                        .selected_prefix
+                        .as_deref()
+                        .map(|p| process::default_eve_base_dir(p).display().to_string())
+                        .unwrap_or_else(|| "Not found".to_string());
+                    ui.add(
+                        egui::TextEdit::singleline(&mut base_text.as_str()).desired_width(400.0),
+                    );
+                } else {
+                    ui.label("Wine Prefix:");
+                    let prefix_text = self
+                        .selected_prefix
+                        .as_ref()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "Not selected".to_string());
+                    ui.add(
+                        egui::TextEdit::singleline(&mut prefix_text.as_str()).desired_width(400.0),
+                    );
+                }
+                if !native && !self.detected_prefixes.is_empty() {
+                    let mut chosen = None;
+                    let selected_text = self
+                        .selected_prefix
+                        .as_deref()
+                        .and_then(|p| self.prefix_nickname(p))
+                        .map(|nickname| {
+                            format!("{} ({} detected)", nickname, self.detected_prefixes.len())
+                        })
+                        .unwrap_or_else(|| format!("Detected ({})", self.detected_prefixes.len()));
+                    egui::ComboBox::from_id_salt("detected_prefixes")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for detected in &self.detected_prefixes {
+                                let selected =
+                                    self.selected_prefix.as_ref() == Some(&detected.path);
+                                let text = match self.prefix_nickname(&detected.path) {
+                                    Some(nickname) => {
+                                        format!("{} - {}", nickname, detected.label())
+                                    }
+                                    None => detected.label(),
+                                };
+                                if ui.selectable_label(selected, text).clicked() {
+                                    chosen = Some(detected.path.clone());
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "Prefixes of running clients and launchers, Lutris, Bottles or \
+                             CrossOver installs and common locations; \
+                             nicknames come from Bookmarks",
+                        );
+                    if let Some(path) = chosen {
+                        self.select_prefix(path);
+                    }
+                }
+
+                if !native && ui.button("Browse").clicked() {
                     self.browse_for_prefix();
                 }
                 if ui.button("Scan").clicked() {
                     self.scan_for_eve();
                 }
+                if !native {
+                    let mut chosen = None;
+                    ui.menu_button("Recent", |ui| {
+                        let label = |saved: &SavedPrefix| match &saved.alias {
+                            Some(alias) => format!("{} - {}", alias, saved.path),
+                            None => saved.path.clone(),
+                        };
+                        ui.label(egui::RichText::new("Favorites").color(theme::colors::TEXT_DIM));
+                        let favorites: Vec<&SavedPrefix> = self
+                            .config
+                            .saved_prefixes
+                            .iter()
+                            .filter(|p| p.favorite)
+                            .collect();
+                        if favorites.is_empty() {
+                            ui.label("None yet; mark them in Bookmarks");
+                        }
+                        for saved in favorites {
+                            if ui.button(label(saved)).clicked() {
+                                chosen = Some(PathBuf::from(&saved.path));
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        ui.label(egui::RichText::new("Recent").color(theme::colors::TEXT_DIM));
+                        for saved in self.config.recent_prefixes() {
+                            let when = chrono::DateTime::from_timestamp(
+                                saved.last_used.unwrap_or_default(),
+                                0,
+                            )
+                            .map(|t| self.config.time_display.format(&t))
+                            .unwrap_or_default();
+                            if ui
+                                .button(label(saved))
+                                .on_hover_text(format!("Last used {}", when))
+                                .clicked()
+                            {
+                                chosen = Some(PathBuf::from(&saved.path));
+                                ui.close_menu();
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Switch to a favorite or recently used prefix");
+                    if let Some(path) = chosen {
+                        self.select_prefix(path);
+                    }
+                    if ui.button("Bookmarks").clicked() {
+                        self.toggle_tool(Tool::Bookmarks);
+                    }
+                }
+                if ui
+                    .checkbox(&mut self.all_profiles_mode, "All profiles")
+                    .on_hover_text("Show files from every settings_* profile, grouped by profile")
+                    .changed()
+                {
+                    self.load_character_files();
+                }
+                match self.clients_running {
+                    Some(0) => {
+                        ui.label(
+                            egui::RichText::new("No client running").color(theme::colors::TEXT_DIM),
+                        )
+                        .on_hover_text("Updates as EVE clients start and stop");
+                    }
+                    Some(running) => {
+                        ui.colored_label(
+                            theme::success(),
+                            format!("{} client(s) running", running),
+                        )
+                        .on_hover_text("Updates as EVE clients start and stop");
+                    }
+                    None => {}
+                }
+                if self.scanning {
+                    ui.spinner();
+                }
+                if self.searching_settings {
+                    ui.spinner();
+                    ui.label(
+                        egui::RichText::new("Searching the prefix for settings...")
+                            .color(theme::colors::TEXT_DIM),
+                    );
+                }
+                if let Some(fs_type) = &self.network_fs {
+                    ui.colored_label(
+                        theme::error(),
+                        format!("⚠ Network filesystem ({})", fs_type),
+                    )
+                    .on_hover_text(
+                        "The settings directory is on network storage. Every file \
+                             operation waits on the server, and renames may not be atomic \
+                             there, so each copy is flushed before it replaces a file and \
+                             server-side cloning is not used. Check the verification after \
+                             each sync; local storage is faster and safer.",
+                    );
+                }
             });
 
+            // Server and profile selection, when the prefix has more than one
+            if self.profile_dirs.len() > 1 && !self.all_profiles_mode {
+                let servers = self.profile_servers();
+                let current_server = self.settings_dir.as_deref().and_then(discovery::server_for);
+                ui.horizontal(|ui| {
+                    if servers.len() > 1 {
+                        ui.label("Server:");
+                        let mut chosen = None;
+                        egui::ComboBox::from_id_salt("settings_server")
+                            .selected_text(current_server.map_or("Other", |s| s.name()))
+                            .show_ui(ui, |ui| {
+                                for &server in &servers {
+                                    let selected = current_server == Some(server);
+                                    if ui.selectable_label(selected, server.name()).clicked()
+                                        && !selected
+                                    {
+                                        chosen = Some(server);
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text(
+                                "Each server's client keeps its own settings; test server \
+                                 profiles are managed apart from Tranquility's",
+                            );
+                        if let Some(dir) = chosen.and_then(|server| {
+                            self.profile_dirs
+                                .iter()
+                                .filter(|dir| discovery::server_for(dir) == Some(server))
+                                .min_by_key(|dir| {
+                                    dir.file_name() != Some(process::DEFAULT_PROFILE.as_ref())
+                                })
+                                .cloned()
+                        }) {
+                            self.select_profile(dir);
+                        }
+                    }
+                    ui.label("Profile:");
+                    let selected_text = self
+                        .settings_dir
+                        .as_deref()
+                        .map(discovery::profile_label)
+                        .unwrap_or_else(|| "None".to_string());
+                    let mut chosen = None;
+                    egui::ComboBox::from_id_salt("settings_profile")
+                        .selected_text(selected_text)
+                        .width(280.0)
+                        .show_ui(ui, |ui| {
+                            for dir in self.profile_dirs.iter().filter(|dir| {
+                                servers.len() < 2 || discovery::server_for(dir) == current_server
+                            }) {
+                                let selected = self.settings_dir.as_ref() == Some(dir);
+                                if ui
+                                    .selectable_label(selected, discovery::profile_label(dir))
+                                    .clicked()
+                                    && !selected
+                                {
+                                    chosen = Some(dir.clone());
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text(
+                            "EVE keeps a settings_* folder per profile made in its settings; \
+                             syncs and backups work on the one chosen here",
+                        );
+                    if let Some(dir) = chosen {
+                        self.select_profile(dir);
+                    }
+                });
+            }
+
+            // The profile a sync or backup will actually touch
+            if let Some(settings_dir) = self
+                .settings_dir
+                .clone()
+                .filter(|_| !self.all_profiles_mode)
+            {
+                ui.horizontal(|ui| {
+                    ui.label("Settings folder:");
+                    ui.label(egui::RichText::new(settings_dir.display().to_string()).strong())
+                        .on_hover_text(discovery::profile_label(&settings_dir));
+                    if ui.button("Open").clicked() {
+                        let _ = open::that(&settings_dir);
+                    }
+                    if ui
+                        .link("Change")
+                        .on_hover_text("Pick another settings_* folder of this prefix")
+                        .clicked()
+                    {
+                        self.browse_for_profile();
+                    }
+                });
+            }
+
             ui.separator();
 
             // Tab selection styled as buttons
@@ -701,10 +7496,16 @@ impl eframe::App for PackPreferencesApp {
                 .iter()
                 .filter(|f| f.file_type == FileType::User)
                 .count();
+            let launcher_count = self
+                .character_files
+                .iter()
+                .filter(|f| f.file_type == FileType::Launcher)
+                .count();
 
             ui.horizontal(|ui| {
                 let char_selected = self.active_tab == Tab::Characters;
                 let acct_selected = self.active_tab == Tab::Accounts;
+                let launcher_selected = self.active_tab == Tab::Launcher;
 
                 // Custom tab styling: selected = black text on teal background
                 let char_text = if char_selected {
@@ -718,10 +7519,14 @@ impl eframe::App for PackPreferencesApp {
                 } else {
                     egui::Button::new(char_text)
                 };
-                if ui.add(char_button).clicked() && !char_selected {
+                if ui
+                    .add(char_button)
+                    .on_hover_text("Per-character settings: window layouts, overview, chat")
+                    .clicked()
+                    && !char_selected
+                {
                     self.active_tab = Tab::Characters;
-                    self.source_selection = None;
-                    self.target_selections.clear();
+                    self.picker.clear();
                 }
 
                 let acct_text = if acct_selected {
@@ -735,139 +7540,354 @@ impl eframe::App for PackPreferencesApp {
                 } else {
                     egui::Button::new(acct_text)
                 };
-                if ui.add(acct_button).clicked() && !acct_selected {
+                if ui
+                    .add(acct_button)
+                    .on_hover_text("Per-account settings: keybinds, graphics, audio")
+                    .clicked()
+                    && !acct_selected
+                {
                     self.active_tab = Tab::Accounts;
-                    self.source_selection = None;
-                    self.target_selections.clear();
+                    self.picker.clear();
+                }
+
+                // Only offer the launcher tab when launcher settings exist
+                if launcher_count > 0 || launcher_selected {
+                    let launcher_text = if launcher_selected {
+                        egui::RichText::new(format!("Launcher ({})", launcher_count))
+                            .color(egui::Color32::BLACK)
+                    } else {
+                        egui::RichText::new(format!("Launcher ({})", launcher_count))
+                    };
+                    let launcher_button = if launcher_selected {
+                        egui::Button::new(launcher_text).fill(theme::colors::CYAN)
+                    } else {
+                        egui::Button::new(launcher_text)
+                    };
+                    if ui.add(launcher_button).clicked() && !launcher_selected {
+                        self.active_tab = Tab::Launcher;
+                        self.picker.clear();
+                    }
                 }
+                help::help_button(ui, &mut self.help, Topic::Files);
             });
 
             ui.separator();
 
-            let items = self.get_selectable_items();
+            if let Some((done, total)) = self.name_progress {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Resolving character names: {}/{}",
+                            done, total
+                        ))
+                        .color(theme::colors::TEXT_DIM),
+                    );
+                });
+            } else if self.active_tab == Tab::Characters && !self.unresolved_ids.is_empty() {
+                ui.horizontal(|ui| {
+                    egui::CollapsingHeader::new(
+                        egui::RichText::new(format!(
+                            "{} unresolved character name(s)",
+                            self.unresolved_ids.len()
+                        ))
+                        .color(theme::colors::TEXT_DIM),
+                    )
+                    .id_salt("unresolved_ids")
+                    .show(ui, |ui| {
+                        let ids: Vec<String> = self
+                            .unresolved_ids
+                            .iter()
+                            .map(|id| id.to_string())
+                            .collect();
+                        ui.label(ids.join(", "));
+                    });
+                    if ui
+                        .add_enabled(!self.resolving_names, egui::Button::new("Retry resolution"))
+                        .clicked()
+                    {
+                        self.resolve_names();
+                    }
+                });
+            }
+
+            let items = self.selectable_items();
             let type_label = match self.active_tab {
                 Tab::Characters => "Character",
                 Tab::Accounts => "Account",
+                Tab::Launcher => "Launcher",
+            };
+
+            self.picker.show_filter(ui);
+            if self.active_tab == Tab::Characters {
+                ui.horizontal(|ui| {
+                    ui.label("Find:");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.character_lookup)
+                            .hint_text("character name")
+                            .desired_width(200.0),
+                    );
+                    let entered =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui
+                        .add_enabled(!self.finding_character, egui::Button::new("Find"))
+                        .on_hover_text(
+                            "Look the name up and select its settings file as the source",
+                        )
+                        .clicked()
+                        || entered
+                    {
+                        self.find_character();
+                    }
+                    if self.finding_character {
+                        ui.spinner();
+                    }
+                });
+            }
+
+            let grouping = if self.all_profiles_mode {
+                Grouping::Profile
+            } else {
+                Grouping::None
             };
 
             // Source and Target side-by-side
             ui.columns(2, |columns| {
                 // Left column: Source selection
                 columns[0].heading("Source (copy FROM):");
-                egui::ScrollArea::vertical()
-                    .id_salt("source_scroll")
-                    .max_height(180.0)
-                    .show(&mut columns[0], |ui| {
-                        let source_items: Vec<_> = items.iter().filter(|i| !i.is_default).collect();
-                        if source_items.is_empty() {
-                            ui.label(format!("No {} files found", type_label.to_lowercase()));
-                        }
-                        for item in source_items {
-                            let selected = self.source_selection == Some(item.file_idx);
-                            if ui
-                                .radio(selected, format!("{}  [{}]", item.display_name, item.id))
-                                .clicked()
-                            {
-                                self.source_selection = Some(item.file_idx);
-                                self.target_selections.remove(&item.file_idx);
-                            }
-                        }
-                    });
-
-                // Right column: Target selection
-                columns[1].heading("Targets (copy TO):");
-                egui::ScrollArea::vertical()
-                    .id_salt("target_scroll")
-                    .max_height(150.0)
-                    .show(&mut columns[1], |ui| {
-                        for item in &items {
-                            // Can't select source as target
-                            if self.source_selection == Some(item.file_idx) {
-                                continue;
-                            }
-
-                            let label = if item.is_default {
-                                item.display_name.clone()
-                            } else {
-                                format!("{}  [{}]", item.display_name, item.id)
-                            };
+                let mut picker = std::mem::take(&mut self.picker);
+                picker.show_source(
+                    &mut columns[0],
+                    &items,
+                    grouping,
+                    &format!("No {} files found", type_label.to_lowercase()),
+                    self,
+                );
 
-                            let mut selected = self.target_selections.contains(&item.file_idx);
-                            if ui.checkbox(&mut selected, label).changed() {
-                                if selected {
-                                    self.target_selections.insert(item.file_idx);
-                                } else {
-                                    self.target_selections.remove(&item.file_idx);
+                // Copy the source into another profile in one step
+                let source_dir = picker
+                    .source
+                    .map(|i| self.character_files[i].settings_dir().to_path_buf());
+                if let Some(source_dir) = source_dir
+                    .filter(|dir| self.profile_dirs.len() > 1 && self.profile_dirs.contains(dir))
+                {
+                    columns[0].horizontal(|ui| {
+                        let selected_text = self
+                            .copy_profile_target
+                            .as_deref()
+                            .map(discovery::profile_label)
+                            .unwrap_or_else(|| "Copy to profile...".to_string());
+                        egui::ComboBox::from_id_salt("copy_profile_target")
+                            .selected_text(selected_text)
+                            .width(180.0)
+                            .show_ui(ui, |ui| {
+                                for dir in &self.profile_dirs {
+                                    if *dir != source_dir {
+                                        ui.selectable_value(
+                                            &mut self.copy_profile_target,
+                                            Some(dir.clone()),
+                                            discovery::profile_label(dir),
+                                        );
+                                    }
                                 }
+                            });
+                        let target = self
+                            .copy_profile_target
+                            .clone()
+                            .filter(|dir| *dir != source_dir);
+                        if ui
+                            .add_enabled(target.is_some(), egui::Button::new("Copy"))
+                            .clicked()
+                        {
+                            if let Some(target) = target {
+                                self.open_modal(Modal::Confirm(PendingAction::CopyToProfile(
+                                    target,
+                                )));
                             }
                         }
                     });
+                }
 
-                columns[1].horizontal(|ui| {
-                    if ui.button("Select All").clicked() {
-                        self.select_all_targets();
-                    }
-                    if ui.button("Select None").clicked() {
-                        self.select_none_targets();
-                    }
-                });
+                // Right column: Target selection
+                columns[1].heading("Targets (copy TO):");
+                picker.show_targets(&mut columns[1], &items, grouping, self);
+                self.picker = picker;
             });
+            let visible = self.picker.take_visible();
+            if self.resolving_names {
+                if let Ok(mut ids) = self.visible_ids.lock() {
+                    *ids = visible;
+                }
+            }
 
             ui.separator();
 
             // Options and actions
             ui.horizontal(|ui| {
-                ui.checkbox(&mut self.dry_run_mode, "Dry Run Mode");
+                ui.checkbox(&mut self.dry_run_mode, "Dry Run Mode")
+                    .on_hover_text(Topic::DryRun.tooltip());
+                help::help_button(ui, &mut self.help, Topic::DryRun);
                 ui.add_space(20.0);
 
-                let sync_enabled =
-                    self.source_selection.is_some() && !self.target_selections.is_empty();
+                let sync_enabled = self.picker.has_selection() && self.sync_progress.is_none();
                 if ui
                     .add_enabled(sync_enabled, egui::Button::new("Sync Settings"))
                     .clicked()
                 {
-                    self.pending_confirmation = Some(PendingAction::Sync);
+                    self.check_stale_temp_files();
+                    self.open_modal(Modal::Confirm(PendingAction::Sync));
+                }
+                if let Some((done, total)) = self.sync_progress {
+                    ui.add(
+                        egui::ProgressBar::new(done as f32 / total as f32)
+                            .desired_width(120.0)
+                            .text(format!("{}/{}", done, total)),
+                    );
+                }
+
+                if ui.button("Presets").clicked() {
+                    self.toggle_tool(Tool::Presets);
+                }
+                let plugins_label = if self.sync_plugins.is_empty() {
+                    "Plugins".to_string()
+                } else {
+                    format!("Plugins ({})", self.sync_plugins.len())
+                };
+                if ui
+                    .button(plugins_label)
+                    .on_hover_text("Programs that rewrite the source on its way to each target")
+                    .clicked()
+                {
+                    self.toggle_tool(Tool::Plugins);
+                }
+                if ui.button("Golden Images").clicked() {
+                    self.toggle_tool(Tool::Golden);
+                    if self.is_open(Tool::Golden) {
+                        self.load_golden_images();
+                    }
                 }
 
-                if ui.button("Manage Backups").clicked() {
-                    self.show_backup_manager = !self.show_backup_manager;
-                    if self.show_backup_manager {
+                if ui
+                    .button("Manage Backups")
+                    .on_hover_text(Topic::Backups.tooltip())
+                    .clicked()
+                {
+                    self.toggle_tool(Tool::BackupManager);
+                    if self.is_open(Tool::BackupManager) {
                         self.load_backups();
                     }
                 }
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.backup_note)
+                        .hint_text("Note (optional)")
+                        .desired_width(140.0),
+                );
+                if ui
+                    .add_enabled(self.settings_dir.is_some(), egui::Button::new("Backup Now"))
+                    .on_hover_text(
+                        "Snapshot the current profile now, e.g. before experimenting in game",
+                    )
+                    .clicked()
+                {
+                    self.backup_now();
+                }
             });
 
             // Backup manager
-            if self.show_backup_manager {
+            if self.is_open(Tool::BackupManager) {
                 ui.separator();
-                ui.heading("Backups:");
+                ui.horizontal(|ui| {
+                    ui.heading("Backups:");
+                    egui::ComboBox::from_id_salt("backup_filter")
+                        .selected_text(self.backup_filter.map_or("All", |t| t.name()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.backup_filter, None, "All");
+                            for trigger in BackupTrigger::ALL {
+                                ui.selectable_value(
+                                    &mut self.backup_filter,
+                                    Some(trigger),
+                                    trigger.name(),
+                                );
+                            }
+                        });
+                    if ui
+                        .button("Retention...")
+                        .on_hover_text("How many backups of each kind to keep")
+                        .clicked()
+                    {
+                        self.open_modal(Modal::Tool(Tool::BackupRetention));
+                    }
+                });
+                let shown: Vec<&BackupEntry> = self
+                    .backups
+                    .iter()
+                    .filter(|b| self.backup_filter.is_none() || b.trigger == self.backup_filter)
+                    .collect();
+                if shown.is_empty() {
+                    ui.label("No backups found");
+                }
+                let row_height = widgets::list_row_height(ui);
+                let mut restore = None;
+                let mut browse = None;
                 egui::ScrollArea::vertical()
                     .id_salt("backup_scroll")
                     .max_height(80.0)
-                    .show(ui, |ui| {
-                        if self.backups.is_empty() {
-                            ui.label("No backups found");
-                        }
-                        for backup in self.backups.clone() {
+                    .show_rows(ui, row_height, shown.len(), |ui, range| {
+                        for backup in &shown[range] {
                             ui.horizontal(|ui| {
+                                ui.set_height(row_height);
                                 let name = backup
+                                    .path
                                     .file_name()
                                     .unwrap_or_default()
                                     .to_string_lossy()
                                     .to_string();
-                                ui.label(&name);
+                                match backup.created {
+                                    Some(time) => {
+                                        ui.label(self.config.time_display.format(&time))
+                                            .on_hover_text(&name);
+                                    }
+                                    None => {
+                                        ui.label(&name);
+                                    }
+                                }
+                                if let Some(trigger) = backup.trigger {
+                                    ui.label(
+                                        egui::RichText::new(trigger.tag())
+                                            .small()
+                                            .color(theme::colors::TEXT_DIM),
+                                    );
+                                }
+                                if let Some(note) = &backup.note {
+                                    ui.label(
+                                        egui::RichText::new(note)
+                                            .italics()
+                                            .color(theme::colors::TEXT_DIM),
+                                    );
+                                }
                                 if ui.button("Restore").clicked() {
-                                    self.pending_confirmation =
-                                        Some(PendingAction::Restore(backup));
+                                    restore = Some(backup.path.clone());
+                                }
+                                if ui.button("Files...").clicked() {
+                                    browse = Some(backup.path.clone());
                                 }
                             });
                         }
                     });
+                if let Some(backup) = restore {
+                    self.open_modal(Modal::Confirm(PendingAction::Restore(backup)));
+                }
+                if let Some(backup) = browse {
+                    self.browse_backup(backup);
+                }
             }
         });
+
+        self.end_diagnostics(ctx);
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.remember_selection();
         let _ = self.config.save();
     }
 }
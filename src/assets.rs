@@ -0,0 +1,180 @@
+use eframe::egui::{self, ColorImage, TextureHandle, TextureOptions};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::config::Config;
+use crate::theme::colors;
+
+const IMAGE_SERVER: &str = "https://images.evetech.net";
+
+/// Edge length, in pixels, requested from the image server
+const IMAGE_SIZE: u32 = 64;
+
+/// Remote image identified by the EVE entity it depicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssetKey {
+    CharacterPortrait(u64),
+}
+
+impl AssetKey {
+    fn url(&self) -> String {
+        match self {
+            AssetKey::CharacterPortrait(id) => {
+                format!(
+                    "{}/characters/{}/portrait?size={}",
+                    IMAGE_SERVER, id, IMAGE_SIZE
+                )
+            }
+        }
+    }
+
+    fn cache_file(&self) -> String {
+        match self {
+            AssetKey::CharacterPortrait(id) => format!("portrait_{}.img", id),
+        }
+    }
+}
+
+enum AssetState {
+    Loading,
+    Ready(TextureHandle),
+    Failed,
+}
+
+/// Loads images on a background thread with an on-disk cache. The UI asks for
+/// textures each frame and draws a placeholder until they arrive.
+pub struct AssetLoader {
+    states: HashMap<AssetKey, AssetState>,
+    request_tx: Sender<AssetKey>,
+    result_rx: Receiver<(AssetKey, Option<ColorImage>)>,
+}
+
+impl AssetLoader {
+    pub fn new(ctx: egui::Context) -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<AssetKey>();
+        let (result_tx, result_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let cache_dir = Config::state_dir().ok().map(|d| d.join("images"));
+            let client = reqwest::blocking::Client::new();
+
+            for key in request_rx {
+                let image = load_image(&client, cache_dir.as_ref(), key);
+                if result_tx.send((key, image)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Self {
+            states: HashMap::new(),
+            request_tx,
+            result_rx,
+        }
+    }
+
+    /// Uploads images finished by the worker. Call once per frame.
+    pub fn poll(&mut self, ctx: &egui::Context) {
+        while let Ok((key, image)) = self.result_rx.try_recv() {
+            let state = match image {
+                Some(image) => AssetState::Ready(ctx.load_texture(
+                    key.cache_file(),
+                    image,
+                    TextureOptions::LINEAR,
+                )),
+                None => AssetState::Failed,
+            };
+            self.states.insert(key, state);
+        }
+    }
+
+    /// Returns the texture if loaded, queueing it for loading on first request.
+    pub fn texture(&mut self, key: AssetKey) -> Option<&TextureHandle> {
+        if let Entry::Vacant(entry) = self.states.entry(key) {
+            entry.insert(AssetState::Loading);
+            let _ = self.request_tx.send(key);
+        }
+
+        match self.states.get(&key) {
+            Some(AssetState::Ready(texture)) => Some(texture),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of images still being loaded.
+    pub fn pending(&self) -> usize {
+        self.states
+            .values()
+            .filter(|s| matches!(s, AssetState::Loading))
+            .count()
+    }
+
+    /// Draws the image at the given size, or a placeholder while it loads.
+    pub fn show(&mut self, ui: &mut egui::Ui, key: AssetKey, size: f32) {
+        let size = egui::vec2(size, size);
+        match self.texture(key) {
+            Some(texture) => {
+                ui.image((texture.id(), size));
+            }
+            None => placeholder(ui, size),
+        }
+    }
+}
+
+/// Draws an empty icon-sized slot.
+pub fn placeholder(ui: &mut egui::Ui, size: egui::Vec2) {
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    ui.painter()
+        .rect_filled(rect, egui::Rounding::same(2.0), colors::WIDGET_BG);
+}
+
+/// Reads an image from the disk cache, downloading it first if missing.
+fn load_image(
+    client: &reqwest::blocking::Client,
+    cache_dir: Option<&PathBuf>,
+    key: AssetKey,
+) -> Option<ColorImage> {
+    let cache_path = cache_dir.map(|d| d.join(key.cache_file()));
+
+    let bytes = match cache_path.as_ref().and_then(|p| fs::read(p).ok()) {
+        Some(bytes) => bytes,
+        None => {
+            let response = client.get(key.url()).send().ok()?;
+            if !response.status().is_success() {
+                return None;
+            }
+            let bytes = response.bytes().ok()?.to_vec();
+            if let Some(path) = &cache_path {
+                let _ = path.parent().map(fs::create_dir_all);
+                let _ = fs::write(path, &bytes);
+            }
+            bytes
+        }
+    };
+
+    let image = image::load_from_memory(&bytes).ok()?.to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Some(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_urls() {
+        assert_eq!(
+            AssetKey::CharacterPortrait(90000001).url(),
+            "https://images.evetech.net/characters/90000001/portrait?size=64"
+        );
+        assert_eq!(
+            AssetKey::CharacterPortrait(90000001).cache_file(),
+            "portrait_90000001.img"
+        );
+    }
+}
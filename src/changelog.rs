@@ -0,0 +1,85 @@
+//! The embedded changelog, and what in it is new since the version a user last ran.
+
+/// CHANGELOG.md, one `## <version>` section per release, newest first
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+/// Whether dotted version `latest` is newer than `current`. Missing trailing parts
+/// count as older, so 1.2.1 is newer than 1.2.
+pub fn version_newer(latest: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse().ok()).collect() };
+
+    let latest_parts = parse(latest);
+    let current_parts = parse(current);
+
+    for (l, c) in latest_parts.iter().zip(current_parts.iter()) {
+        if l > c {
+            return true;
+        } else if l < c {
+            return false;
+        }
+    }
+
+    latest_parts.len() > current_parts.len()
+}
+
+/// Release sections of a changelog as (version, notes), in file order.
+fn releases(text: &str) -> Vec<(&str, &str)> {
+    let mut releases = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("\n## ") {
+        let section = &rest[start + 4..];
+        let (version, body) = section.split_once('\n').unwrap_or((section, ""));
+        let end = body.find("\n## ").unwrap_or(body.len());
+        releases.push((version.trim().trim_start_matches('v'), body[..end].trim()));
+        rest = &body[end..];
+    }
+    releases
+}
+
+/// Markdown for every release after `last_run` up to and including `current`, with
+/// a heading per release; empty when nothing is listed for that range. `None`
+/// stands for a build from before the version was recorded, and gets the notes
+/// for `current` alone.
+fn notes_between(text: &str, last_run: Option<&str>, current: &str) -> String {
+    releases(text)
+        .into_iter()
+        .filter(|(version, _)| !version_newer(version, current))
+        .filter(|(version, _)| match last_run {
+            Some(last_run) => version_newer(version, last_run),
+            None => *version == current,
+        })
+        .map(|(version, notes)| format!("## {}\n\n{}\n", version, notes))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// What's new in this build since `last_run`.
+pub fn whats_new(last_run: Option<&str>) -> String {
+    notes_between(CHANGELOG, last_run, env!("CARGO_PKG_VERSION"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "# Changelog\n\nIntro\n\n## 0.3.0\n\n- three\n\n## v0.2.1\n\n- two point one\n\n## 0.2.0\n- two\n";
+
+    #[test]
+    fn test_notes_between() {
+        assert_eq!(
+            releases(TEXT),
+            vec![
+                ("0.3.0", "- three"),
+                ("0.2.1", "- two point one"),
+                ("0.2.0", "- two")
+            ]
+        );
+        let notes = notes_between(TEXT, Some("0.2.0"), "0.2.1");
+        assert_eq!(notes, "## 0.2.1\n\n- two point one\n");
+        assert!(notes_between(TEXT, Some("0.1.0"), "0.3.0").contains("- two\n"));
+        assert_eq!(notes_between(TEXT, None, "0.2.0"), "## 0.2.0\n\n- two\n");
+        assert!(notes_between(TEXT, Some("0.3.0"), "0.3.0").is_empty());
+        assert!(version_newer("1.2.1", "1.2"));
+        assert!(!version_newer("1.2.0", "1.10.0"));
+    }
+}
@@ -0,0 +1,221 @@
+//! Headless command line for cron jobs and scripts. Runs a saved preset without
+//! opening a window and reports the outcome through a stable exit code:
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Every target synced |
+//! | 1 | The run failed: the dry run or a backup failed, or no target synced |
+//! | 2 | Some targets synced, others failed |
+//! | 3 | Nothing to do: targets already match |
+//! | 4 | Environment error: config, preset or settings files unusable |
+//! | 5 | Deferred: an EVE client is running, so nothing was written; try again later |
+//! | 64 | Bad command line |
+//!
+//! `--quiet` prints nothing on success. `--json-errors` writes each error to
+//! stderr as one JSON object per line, `{"code": 2, "error": "...", "file": "..."}`.
+
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::presets::{self, PresetRun};
+use crate::settings::{self, BackupTrigger};
+use crate::state::{self, AuditEntry};
+
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILED: i32 = 1;
+pub const EXIT_PARTIAL: i32 = 2;
+pub const EXIT_NOTHING_TO_DO: i32 = 3;
+pub const EXIT_ENVIRONMENT: i32 = 4;
+pub const EXIT_DEFERRED: i32 = 5;
+pub const EXIT_USAGE: i32 = 64;
+
+const USAGE: &str = "\
+Usage: pack-preferences [--deck]
+       pack-preferences --run-preset <name> [--quiet] [--json-errors]";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    run_preset: Option<String>,
+    quiet: bool,
+    json_errors: bool,
+}
+
+/// Parses the arguments after the program name. Arguments the GUI understands
+/// are accepted and ignored.
+fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--run-preset" => match args.next() {
+                Some(name) => options.run_preset = Some(name.clone()),
+                None => return Err("--run-preset needs a preset name".to_string()),
+            },
+            "--quiet" => options.quiet = true,
+            "--json-errors" => options.json_errors = true,
+            "--deck" => {}
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+    if options.run_preset.is_none() && (options.quiet || options.json_errors) {
+        return Err("--quiet and --json-errors only apply to --run-preset".to_string());
+    }
+    Ok(options)
+}
+
+struct Reporter {
+    quiet: bool,
+    json_errors: bool,
+}
+
+impl Reporter {
+    fn info(&self, message: &str) {
+        if !self.quiet {
+            println!("{}", message);
+        }
+    }
+
+    fn error(&self, code: i32, message: &str, file: Option<&PathBuf>) {
+        if self.json_errors {
+            let mut error = serde_json::json!({ "code": code, "error": message });
+            if let Some(file) = file {
+                error["file"] = serde_json::json!(file);
+            }
+            eprintln!("{}", error);
+        } else {
+            match file {
+                Some(file) => eprintln!("{}: {}", file.display(), message),
+                None => eprintln!("{}", message),
+            }
+        }
+    }
+
+    /// Reports a run-ending error and returns its exit code.
+    fn fail(&self, code: i32, message: &str) -> i32 {
+        self.error(code, message, None);
+        code
+    }
+}
+
+/// Runs the command line, returning the exit code, or None when the arguments
+/// ask for the GUI.
+pub fn run(args: &[String]) -> Option<i32> {
+    let options = match parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return Some(EXIT_USAGE);
+        }
+    };
+    let name = options.run_preset?;
+    let reporter = Reporter {
+        quiet: options.quiet,
+        json_errors: options.json_errors,
+    };
+    Some(run_preset(&name, &reporter))
+}
+
+fn run_preset(name: &str, reporter: &Reporter) -> i32 {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => return reporter.fail(EXIT_ENVIRONMENT, &format!("{:#}", e)),
+    };
+    let Some(preset) = config.presets.iter().find(|p| p.name == name) else {
+        return reporter.fail(EXIT_ENVIRONMENT, &format!("No preset named '{}'", name));
+    };
+    let (source, targets) = match preset.resolve() {
+        Ok(files) => files,
+        Err(e) => return reporter.fail(EXIT_ENVIRONMENT, &format!("{:#}", e)),
+    };
+
+    // Plugins can change the source on its way, so only a plain copy can be
+    // known to be a no-op up front
+    if preset.plugins.is_empty() {
+        let source_hash = settings::file_hash(&source.path).ok();
+        if source_hash.is_some()
+            && targets
+                .iter()
+                .all(|t| settings::file_hash(&t.path).ok() == source_hash)
+        {
+            reporter.info(&format!("Preset '{}': targets already match", name));
+            return EXIT_NOTHING_TO_DO;
+        }
+    }
+
+    let (backups, results) =
+        match presets::run_preset(preset, BackupTrigger::Scheduled, &config.backup_options()) {
+            Ok(PresetRun::Deferred) => {
+                reporter.info(&format!(
+                    "Preset '{}': an EVE client is running, nothing synced",
+                    name
+                ));
+                return EXIT_DEFERRED;
+            }
+            Ok(PresetRun::Synced { backups, results }) => (backups, results),
+            Err(e) => return reporter.fail(EXIT_FAILED, &format!("{:#}", e)),
+        };
+
+    for backup in backups {
+        reporter.info(&format!("Created backup {}", backup.display()));
+        let _ = state::record_audit(&AuditEntry::new("Create backup", &[backup]));
+    }
+    let succeeded = results.iter().filter(|r| r.success).count();
+    for result in results.iter().filter(|r| !r.success) {
+        reporter.error(EXIT_PARTIAL, &result.message, Some(&result.target_file));
+    }
+
+    let record = state::SyncRecord {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        source: preset.source.clone(),
+        targets: results.iter().map(|r| r.target_file.clone()).collect(),
+        dry_run: false,
+        succeeded,
+    };
+    let _ = state::record_sync(&record);
+    let audited: Vec<PathBuf> = std::iter::once(record.source.clone())
+        .chain(record.targets.iter().cloned())
+        .collect();
+    let _ = state::record_audit(&AuditEntry::new("Command-line sync", &audited));
+
+    reporter.info(&format!(
+        "Preset '{}' synced {} of {} file(s)",
+        name,
+        succeeded,
+        results.len()
+    ));
+    match succeeded {
+        n if n == results.len() => EXIT_OK,
+        0 => EXIT_FAILED,
+        _ => EXIT_PARTIAL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse(&args(&["--deck"])), Ok(Options::default()));
+        assert_eq!(
+            parse(&args(&[
+                "--run-preset",
+                "Fleet",
+                "--quiet",
+                "--json-errors"
+            ])),
+            Ok(Options {
+                run_preset: Some("Fleet".to_string()),
+                quiet: true,
+                json_errors: true,
+            })
+        );
+        assert!(parse(&args(&["--run-preset"])).is_err());
+        assert!(parse(&args(&["--quiet"])).is_err());
+        assert!(parse(&args(&["--sync"])).is_err());
+    }
+}
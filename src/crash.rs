@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use std::backtrace::Backtrace;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
+
+use crate::config::Config;
+
+const ISSUE_URL: &str = "https://github.com/sopleb/PackPreferences/issues/new";
+
+/// Number of log lines included in a crash report
+const LOG_TAIL: usize = 20;
+
+/// Keeps GitHub's prefilled-issue URL under browser length limits
+const MAX_ISSUE_BODY: usize = 6000;
+
+/// Log lines shared with the panic hook, mirrored from the UI log.
+pub type SharedLog = Arc<Mutex<Vec<String>>>;
+
+static INSTALL: Once = Once::new();
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns crash report writing on or off; the hook stays installed but inert when off.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn crash_dir() -> Result<PathBuf> {
+    let dir = Config::state_dir()?.join("crashes");
+    fs::create_dir_all(&dir).context("Failed to create crash directory")?;
+    Ok(dir)
+}
+
+/// Installs a panic hook that writes a crash report before the default hook runs.
+/// Only the first call installs the hook.
+pub fn install_panic_hook(log: SharedLog) {
+    INSTALL.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if ENABLED.load(Ordering::Relaxed) {
+                write_report(info, &log);
+            }
+            default_hook(info);
+        }));
+    });
+}
+
+fn write_report(info: &panic::PanicHookInfo<'_>, log: &SharedLog) {
+    let backtrace = Backtrace::force_capture().to_string();
+    let lines = log.lock().map(|l| l.clone()).unwrap_or_default();
+    let report = build_report(&info.to_string(), &backtrace, &lines);
+
+    if let Ok(dir) = crash_dir() {
+        let name = format!("crash_{}.txt", crate::settings::name_stamp());
+        let _ = fs::write(dir.join(name), report);
+    }
+}
+
+/// Assembles a crash report from the panic message, backtrace and recent log lines.
+pub fn build_report(panic_message: &str, backtrace: &str, log: &[String]) -> String {
+    let tail = &log[log.len().saturating_sub(LOG_TAIL)..];
+    let log_lines: Vec<String> = tail.iter().map(|l| sanitize(l)).collect();
+
+    format!(
+        "Pack Preferences v{}\nOS: {} ({})\n\nPanic: {}\n\nRecent log:\n{}\n\nBacktrace:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        os_description(),
+        std::env::consts::ARCH,
+        sanitize(panic_message),
+        log_lines.join("\n"),
+        sanitize(backtrace)
+    )
+}
+
+fn os_description() -> String {
+    fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|release| {
+            release.lines().find_map(|line| {
+                line.strip_prefix("PRETTY_NAME=")
+                    .map(|v| v.trim_matches('"').to_string())
+            })
+        })
+        .unwrap_or_else(|| std::env::consts::OS.to_string())
+}
+
+/// Removes identifying details: the home directory and character/account IDs.
+pub fn sanitize(line: &str) -> String {
+    let line = match dirs::home_dir() {
+        Some(home) => line.replace(&*home.to_string_lossy(), "~"),
+        None => line.to_string(),
+    };
+
+    // IDs are long digit runs; shorter numbers (counts, line numbers) are kept
+    let mut result = String::with_capacity(line.len());
+    let mut digits = String::new();
+    for c in line.chars().chain(std::iter::once('\0')) {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        if digits.len() >= 7 {
+            result.push_str("<id>");
+        } else {
+            result.push_str(&digits);
+        }
+        digits.clear();
+        if c != '\0' {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Replaces every occurrence of a known character name with `<name>`. The panic hook
+/// cannot see the name cache, so this runs on a report before it leaves the machine.
+pub fn scrub_names<'a>(report: &str, names: impl IntoIterator<Item = &'a str>) -> String {
+    let mut names: Vec<&str> = names.into_iter().filter(|n| !n.trim().is_empty()).collect();
+    // Longest first, so a name containing another is replaced whole
+    names.sort_unstable_by_key(|n| std::cmp::Reverse(n.len()));
+    names.into_iter().fold(report.to_string(), |report, name| {
+        report.replace(name, "<name>")
+    })
+}
+
+/// Lists crash reports written by previous runs, oldest first.
+pub fn pending_reports() -> Vec<PathBuf> {
+    let Ok(dir) = crash_dir() else {
+        return Vec::new();
+    };
+
+    let mut reports: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort();
+    reports
+}
+
+/// Deletes a crash report once the user has acted on it.
+pub fn dismiss_report(path: &Path) -> Result<()> {
+    fs::remove_file(path).context("Failed to remove crash report")
+}
+
+/// Builds a GitHub "new issue" URL prefilled with the crash report.
+pub fn issue_url(report: &str) -> String {
+    let mut body = report.to_string();
+    if body.len() > MAX_ISSUE_BODY {
+        let mut cut = MAX_ISSUE_BODY;
+        while !body.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        body.truncate(cut);
+        body.push_str("\n[truncated]");
+    }
+    let body = format!("```\n{}\n```", body);
+
+    reqwest::Url::parse_with_params(
+        ISSUE_URL,
+        &[("title", "Crash report"), ("body", body.as_str())],
+    )
+    .map(|u| u.to_string())
+    .unwrap_or_else(|_| ISSUE_URL.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_ids() {
+        assert_eq!(
+            sanitize("Copied successfully: core_char_2112345678.dat"),
+            "Copied successfully: core_char_<id>.dat"
+        );
+        assert_eq!(sanitize("Synced 12 files"), "Synced 12 files");
+    }
+
+    #[test]
+    fn test_scrub_names() {
+        let report = "Recent log:\nSynced Alpha Bravo to Alpha\nPanic: Bravo";
+        assert_eq!(
+            scrub_names(report, ["Alpha", "Alpha Bravo", ""]),
+            "Recent log:\nSynced <name> to <name>\nPanic: Bravo"
+        );
+    }
+
+    #[test]
+    fn test_issue_url_truncates() {
+        let report = "x".repeat(MAX_ISSUE_BODY * 2);
+        let url = issue_url(&report);
+        assert!(url.starts_with(ISSUE_URL));
+        assert!(url.contains("truncated"));
+        assert!(url.len() < MAX_ISSUE_BODY * 2);
+    }
+}
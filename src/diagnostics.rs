@@ -0,0 +1,125 @@
+use eframe::egui::{self, Key, Modifiers};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::theme::colors;
+
+/// Number of frames kept for the frame-time statistics
+const HISTORY: usize = 240;
+
+/// Hidden overlay for diagnosing repaint and CPU usage, toggled with Ctrl+Shift+D.
+pub struct Diagnostics {
+    open: bool,
+    /// Time between frames, in milliseconds
+    frame_intervals: VecDeque<f32>,
+    /// Time spent inside `update`, in milliseconds
+    update_times: VecDeque<f32>,
+    update_start: Option<Instant>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            frame_intervals: VecDeque::with_capacity(HISTORY),
+            update_times: VecDeque::with_capacity(HISTORY),
+            update_start: None,
+        }
+    }
+
+    /// Call at the start of `update`: handles the shortcut and starts timing.
+    pub fn begin_frame(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_key(Modifiers::CTRL | Modifiers::SHIFT, Key::D)) {
+            self.open = !self.open;
+        }
+
+        push_bounded(
+            &mut self.frame_intervals,
+            ctx.input(|i| i.unstable_dt) * 1000.0,
+        );
+        self.update_start = Some(Instant::now());
+    }
+
+    /// Call at the end of `update`. `tasks` describes background task states and is
+    /// only evaluated while the overlay is open.
+    pub fn end_frame(
+        &mut self,
+        ctx: &egui::Context,
+        tasks: impl FnOnce() -> Vec<(&'static str, String)>,
+    ) {
+        if let Some(start) = self.update_start.take() {
+            push_bounded(
+                &mut self.update_times,
+                start.elapsed().as_secs_f32() * 1000.0,
+            );
+        }
+
+        if !self.open {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("diagnostics_overlay"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .order(egui::Order::Tooltip)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new("Diagnostics").color(colors::ELECTRIC_GREEN));
+                    let (avg, max) = stats(&self.frame_intervals);
+                    ui.label(format!(
+                        "Frame interval: avg {:.1} ms, max {:.1} ms",
+                        avg, max
+                    ));
+                    let (avg, max) = stats(&self.update_times);
+                    ui.label(format!("Update time: avg {:.2} ms, max {:.2} ms", avg, max));
+
+                    ui.separator();
+                    ui.label("Repaint causes:");
+                    let causes = ctx.repaint_causes();
+                    if causes.is_empty() {
+                        ui.label("  none");
+                    }
+                    for cause in causes {
+                        ui.label(format!("  {}", cause));
+                    }
+
+                    ui.separator();
+                    ui.label("Background tasks:");
+                    for (name, status) in tasks() {
+                        ui.label(format!("  {}: {}", name, status));
+                    }
+                });
+            });
+    }
+}
+
+fn push_bounded(values: &mut VecDeque<f32>, value: f32) {
+    if values.len() == HISTORY {
+        values.pop_front();
+    }
+    values.push_back(value);
+}
+
+/// Returns (average, maximum) of the recorded values.
+fn stats(values: &VecDeque<f32>) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let sum: f32 = values.iter().sum();
+    let max = values.iter().copied().fold(0.0, f32::max);
+    (sum / values.len() as f32, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut values = VecDeque::new();
+        for i in 0..(HISTORY + 10) {
+            push_bounded(&mut values, i as f32);
+        }
+        assert_eq!(values.len(), HISTORY);
+        assert_eq!(stats(&values).1, (HISTORY + 9) as f32);
+    }
+}
@@ -0,0 +1,161 @@
+use eframe::egui;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use crate::discovery::CharacterFile;
+use crate::elevate::ElevatedSync;
+use crate::esi::Resolution;
+use crate::news::NewsFeed;
+use crate::presets::PresetRun;
+use crate::process::DetectedPrefix;
+use crate::scripting::ScriptRun;
+
+/// Messages sent to the UI thread. Background work never touches app state
+/// directly; it reports back through one of these and `update()` applies it.
+pub enum AppEvent {
+    /// Line for the status log
+    Status(String),
+    /// A file scan finished; `generation` identifies the scan that was started
+    ScanFinished {
+        generation: u64,
+        result: Result<Scan, String>,
+    },
+    /// One batch of an ESI name lookup returned; `done` of `total` IDs are looked up
+    NameBatch {
+        done: usize,
+        total: usize,
+        resolution: Resolution,
+    },
+    /// ESI name lookup finished for `ids`; names were already delivered in batches
+    NamesResolved {
+        ids: Vec<u64>,
+        result: Result<Resolution, String>,
+    },
+    /// Background re-check of cached names for `ids` finished
+    NamesRefreshed {
+        ids: Vec<u64>,
+        result: Result<Resolution, String>,
+    },
+    SyncProgress {
+        done: usize,
+        total: usize,
+    },
+    BackupCreated(PathBuf),
+    /// A scheduled preset finished or was deferred
+    PresetFinished {
+        name: String,
+        result: Result<PresetRun, String>,
+    },
+    /// A script from the Scripts window finished
+    ScriptFinished {
+        name: String,
+        run: ScriptRun,
+    },
+    /// The About screen's news feed was fetched
+    NewsFetched(Result<NewsFeed, String>),
+    /// IDs typed into Duplicate As were looked up
+    IdsChecked {
+        ids: Vec<u64>,
+        result: Result<Resolution, String>,
+    },
+    /// The fallback search of `prefix` for settings profiles finished
+    SettingsSearched {
+        prefix: PathBuf,
+        dirs: Vec<PathBuf>,
+    },
+    /// A character name typed into the Find box was looked up
+    CharacterFound {
+        name: String,
+        result: Result<Option<(u64, String)>, String>,
+    },
+    /// A sync run as root through pkexec finished or was refused
+    ElevatedSyncFinished {
+        sync: ElevatedSync,
+        result: Result<(), String>,
+    },
+    /// The process watcher saw EVE clients start or stop; `prefixes` are those of
+    /// the clients now running, then those of running launchers. `installed` are
+    /// the Lutris, Bottles and CrossOver installs, running or not.
+    ClientsChanged {
+        running: usize,
+        prefixes: Vec<DetectedPrefix>,
+        installed: Vec<DetectedPrefix>,
+    },
+}
+
+/// Files found by a scan
+pub struct Scan {
+    pub files: Vec<CharacterFile>,
+    /// How long the scan took
+    pub elapsed: Duration,
+}
+
+/// Sending half of the event bus; cheap to clone into worker threads.
+#[derive(Clone)]
+pub struct EventSender {
+    tx: Sender<AppEvent>,
+    ctx: egui::Context,
+}
+
+impl EventSender {
+    /// Queues an event and wakes the UI so it is handled on the next frame.
+    pub fn send(&self, event: AppEvent) {
+        if self.tx.send(event).is_ok() {
+            self.ctx.request_repaint();
+        }
+    }
+}
+
+/// Channel between background workers and the UI thread.
+pub struct EventBus {
+    sender: EventSender,
+    rx: Receiver<AppEvent>,
+}
+
+impl EventBus {
+    pub fn new(ctx: egui::Context) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            sender: EventSender { tx, ctx },
+            rx,
+        }
+    }
+
+    pub fn sender(&self) -> EventSender {
+        self.sender.clone()
+    }
+
+    /// Takes every event queued since the last call.
+    pub fn drain(&self) -> Vec<AppEvent> {
+        self.rx.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_from_workers_are_drained_in_order() {
+        let bus = EventBus::new(egui::Context::default());
+        let sender = bus.sender();
+        std::thread::spawn(move || {
+            sender.send(AppEvent::SyncProgress { done: 1, total: 2 });
+            sender.send(AppEvent::SyncProgress { done: 2, total: 2 });
+        })
+        .join()
+        .unwrap();
+
+        let progress: Vec<usize> = bus
+            .drain()
+            .into_iter()
+            .filter_map(|e| match e {
+                AppEvent::SyncProgress { done, .. } => Some(done),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress, vec![1, 2]);
+        assert!(bus.drain().is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use eframe::egui;
+use std::sync::mpsc::Receiver;
+
+/// App-level commands bound to gamepad buttons that have no keyboard equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub enum GamepadAction {
+    /// Start: ask to sync the current selection
+    Sync,
+    /// Right bumper
+    NextTab,
+    /// Left bumper
+    PreviousTab,
+}
+
+/// A pressed gamepad button, already translated for the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+enum Press {
+    Key(egui::Key),
+    Action(GamepadAction),
+}
+
+/// Reads controllers on a background thread and feeds their buttons into egui as
+/// keyboard input, so lists and dialogs navigate the same way they do with the
+/// arrow keys, Enter and Escape.
+pub struct GamepadInput {
+    presses: Option<Receiver<Press>>,
+}
+
+impl GamepadInput {
+    /// Starts listening for controllers. Returns an explanation alongside if gamepad
+    /// support is unavailable; the app then simply ignores controllers.
+    pub fn new(ctx: egui::Context) -> (Self, Option<String>) {
+        match listen(ctx) {
+            Ok(presses) => (
+                Self {
+                    presses: Some(presses),
+                },
+                None,
+            ),
+            Err(reason) => (Self { presses: None }, Some(reason)),
+        }
+    }
+
+    /// Appends the key events for buttons pressed since the last frame to `raw_input`
+    /// and returns any app-level actions.
+    pub fn feed(&self, ctx: &egui::Context, raw_input: &mut egui::RawInput) -> Vec<GamepadAction> {
+        let Some(presses) = &self.presses else {
+            return Vec::new();
+        };
+
+        let mut actions = Vec::new();
+        for press in presses.try_iter() {
+            match press {
+                Press::Key(key) => {
+                    // Arrow keys only move focus once something has it; start with Tab
+                    let nothing_focused = ctx.memory(|m| m.focused().is_none());
+                    let key = if nothing_focused && is_arrow(key) {
+                        egui::Key::Tab
+                    } else {
+                        key
+                    };
+                    for pressed in [true, false] {
+                        raw_input.events.push(egui::Event::Key {
+                            key,
+                            physical_key: None,
+                            pressed,
+                            repeat: false,
+                            modifiers: egui::Modifiers::NONE,
+                        });
+                    }
+                }
+                Press::Action(action) => actions.push(action),
+            }
+        }
+        actions
+    }
+}
+
+fn is_arrow(key: egui::Key) -> bool {
+    matches!(
+        key,
+        egui::Key::ArrowUp | egui::Key::ArrowDown | egui::Key::ArrowLeft | egui::Key::ArrowRight
+    )
+}
+
+#[cfg(feature = "gamepad")]
+fn listen(ctx: egui::Context) -> Result<Receiver<Press>, String> {
+    use gilrs::{Button, EventType, Gilrs};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        // Gilrs is created on the thread that polls it
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => {
+                let _ = ready_tx.send(Ok(()));
+                gilrs
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(format!("Gamepad support unavailable: {}", e)));
+                return;
+            }
+        };
+
+        loop {
+            let Some(event) = gilrs.next_event_blocking(None) else {
+                continue;
+            };
+            let EventType::ButtonPressed(button, _) = event.event else {
+                continue;
+            };
+            let press = match button {
+                Button::DPadUp => Press::Key(egui::Key::ArrowUp),
+                Button::DPadDown => Press::Key(egui::Key::ArrowDown),
+                Button::DPadLeft => Press::Key(egui::Key::ArrowLeft),
+                Button::DPadRight => Press::Key(egui::Key::ArrowRight),
+                Button::South => Press::Key(egui::Key::Enter),
+                Button::East => Press::Key(egui::Key::Escape),
+                Button::Start => Press::Action(GamepadAction::Sync),
+                Button::LeftTrigger => Press::Action(GamepadAction::PreviousTab),
+                Button::RightTrigger => Press::Action(GamepadAction::NextTab),
+                _ => continue,
+            };
+            if tx.send(press).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+        }
+    });
+
+    ready_rx
+        .recv()
+        .map_err(|_| "Gamepad thread exited".to_string())?
+        .map(|()| rx)
+}
+
+#[cfg(not(feature = "gamepad"))]
+fn listen(_ctx: egui::Context) -> Result<Receiver<Press>, String> {
+    Err("Built without gamepad support".to_string())
+}
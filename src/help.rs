@@ -0,0 +1,353 @@
+use eframe::egui::{self, text::LayoutJob, FontId, TextFormat};
+
+/// Subjects covered by the Help panel. Each has a short tooltip for the control it
+/// explains and a longer markdown page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    Files,
+    DryRun,
+    Defaults,
+    Backups,
+    Presets,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 5] = [
+        Topic::Files,
+        Topic::DryRun,
+        Topic::Defaults,
+        Topic::Backups,
+        Topic::Presets,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Topic::Files => "Character and account files",
+            Topic::DryRun => "Dry run",
+            Topic::Defaults => "Default files",
+            Topic::Backups => "Backups",
+            Topic::Presets => "Presets and golden images",
+        }
+    }
+
+    /// One or two sentences, shown on hover.
+    pub fn tooltip(self) -> &'static str {
+        match self {
+            Topic::Files => {
+                "Character files hold window layouts, overview and chat setup; account files \
+                 hold keybinds, graphics and audio. See Help for details."
+            }
+            Topic::DryRun => "Report what a sync would copy without writing any files",
+            Topic::Defaults => {
+                "Template settings the client uses for characters and accounts it has no \
+                 file for yet"
+            }
+            Topic::Backups => "Every real sync backs up the affected profiles first",
+            Topic::Presets => "Saved syncs you can run again in one step or on a schedule",
+        }
+    }
+
+    fn page(self) -> &'static str {
+        match self {
+            Topic::Files => FILES,
+            Topic::DryRun => DRY_RUN,
+            Topic::Defaults => DEFAULTS,
+            Topic::Backups => BACKUPS,
+            Topic::Presets => PRESETS,
+        }
+    }
+}
+
+const FILES: &str = "\
+# Character and account files
+
+The EVE client keeps its settings in plain files inside each profile folder \
+(`settings_Default` unless you created others in the launcher).
+
+## core_char_<id>.dat
+
+One per character, named after the character ID. Holds everything tied to that \
+pilot:
+- window positions and sizes, and which windows are open
+- overview tabs and columns, and brackets
+- chat channels and their window layout
+- drone, fleet and market window settings
+
+Sync these on the **Characters** tab to give alts the same layout as your main.
+
+## core_user_<id>.dat
+
+One per account, named after the account ID. Holds settings that follow you to any \
+character on that account:
+- keyboard shortcuts
+- graphics, audio and camera options
+- general interface settings such as UI scale
+
+Sync these on the **Accounts** tab. The account ID is not shown anywhere in game; \
+the app guesses which account a character belongs to from when their files were \
+last written together.
+
+## Launcher
+
+Some installs also keep launcher settings in the profile. They only sync with each \
+other and only appear when present.
+";
+
+const DRY_RUN: &str = "\
+# Dry run
+
+With **Dry Run Mode** ticked, a sync goes through the same checks as a real one and \
+lists each target with what would happen to it, but writes nothing and makes no \
+backup.
+
+Use it the first time you sync, or whenever you are unsure which files a selection \
+covers. Untick it and sync again to apply the changes.
+
+Scheduled presets always do a dry run first and only write if every target passes.
+";
+
+const DEFAULTS: &str = "\
+# Default files
+
+`core_char__.dat` and `core_user__.dat`, with no ID in the name, are the default \
+files. The client uses them for a character or account that has no file of its \
+own yet, and for the login and character selection screens.
+
+Copying your setup over the defaults means new characters start with it instead \
+of the stock layout. Defaults are listed after the other files of each type, with \
+the profile they belong to; **Preview** opens one in the inspector to show what a \
+new character or account would inherit.
+
+Golden image resets also fall back to the image's default file for characters the \
+image does not know.
+";
+
+const BACKUPS: &str = "\
+# Backups
+
+Before a real sync writes anything, each affected profile folder is copied to a \
+numbered backup next to it. **Manage Backups** lists them, shows how a backup \
+differs from the live files and restores them. **Backup Now** takes one \
+whenever you like, with an optional note to tell it apart later.
+
+Close the EVE client before syncing: it rewrites its settings when you log out and \
+would overwrite the synced files.
+";
+
+const PRESETS: &str = "\
+# Presets and golden images
+
+A **preset** remembers a source file and its targets so the same sync can be run \
+again later. Presets can run on a daily or weekly schedule while the app is open; \
+a scheduled run waits until no EVE client is running.
+
+A **golden image** is a stored copy of a whole profile. Compare a profile against \
+it to see what drifted, reset characters back to it, or use it to set up a fresh \
+prefix that has never run the client.
+";
+
+/// Window explaining the settings files and the app's options.
+pub struct HelpPanel {
+    pub open: bool,
+    topic: Topic,
+}
+
+impl HelpPanel {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            topic: Topic::Files,
+        }
+    }
+
+    pub fn open_at(&mut self, topic: Topic) {
+        self.open = true;
+        self.topic = topic;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        egui::Window::new("Help")
+            .open(&mut open)
+            .default_size([560.0, 380.0])
+            .show(ctx, |ui| {
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| {
+                        ui.set_width(170.0);
+                        for topic in Topic::ALL {
+                            ui.selectable_value(&mut self.topic, topic, topic.title());
+                        }
+                    });
+                    ui.separator();
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        render_markdown(ui, self.topic.page());
+                    });
+                });
+            });
+        self.open = open;
+    }
+}
+
+/// Small "?" button that opens the Help panel at `topic`, with the topic's tooltip.
+pub fn help_button(ui: &mut egui::Ui, panel: &mut HelpPanel, topic: Topic) {
+    if ui
+        .small_button("?")
+        .on_hover_text(topic.tooltip())
+        .clicked()
+    {
+        panel.open_at(topic);
+    }
+}
+
+/// A block of the small markdown subset the help pages use.
+#[derive(Debug, PartialEq)]
+enum Block {
+    Heading(u8, String),
+    Bullet(String),
+    Paragraph(String),
+}
+
+/// Splits text into headings, bullets and paragraphs. Consecutive plain lines are
+/// joined into one paragraph; a blank line ends it.
+fn parse_blocks(text: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some(bullet) = line.strip_prefix("- ") {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Bullet(bullet.to_string()));
+        } else if line.starts_with('#') {
+            flush(&mut paragraph, &mut blocks);
+            let level = line.chars().take_while(|&c| c == '#').count();
+            blocks.push(Block::Heading(
+                level as u8,
+                line[level..].trim().to_string(),
+            ));
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// Inline styles within a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Span {
+    Plain,
+    Bold,
+    Code,
+}
+
+/// Splits text on `**bold**` and `` `code` `` markers. An unclosed marker is kept
+/// as plain text.
+fn parse_spans(text: &str) -> Vec<(Span, &str)> {
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let next = [("**", Span::Bold), ("`", Span::Code)]
+            .into_iter()
+            .filter_map(|(marker, span)| {
+                let start = rest.find(marker)?;
+                let len = rest[start + marker.len()..].find(marker)?;
+                Some((start, marker.len(), len, span))
+            })
+            .min_by_key(|(start, ..)| *start);
+
+        let Some((start, marker, len, span)) = next else {
+            spans.push((Span::Plain, rest));
+            break;
+        };
+        if start > 0 {
+            spans.push((Span::Plain, &rest[..start]));
+        }
+        spans.push((span, &rest[start + marker..start + marker + len]));
+        rest = &rest[start + 2 * marker + len..];
+    }
+    spans
+}
+
+fn inline_job(ui: &egui::Ui, text: &str, font: FontId) -> LayoutJob {
+    let visuals = ui.visuals();
+    let mut job = LayoutJob::default();
+    for (span, text) in parse_spans(text) {
+        let format = match span {
+            Span::Plain => TextFormat::simple(font.clone(), visuals.text_color()),
+            Span::Bold => TextFormat::simple(font.clone(), visuals.strong_text_color()),
+            Span::Code => TextFormat {
+                background: visuals.code_bg_color,
+                ..TextFormat::simple(FontId::monospace(font.size * 0.9), visuals.text_color())
+            },
+        };
+        job.append(text, 0.0, format);
+    }
+    job.wrap.max_width = ui.available_width();
+    job
+}
+
+pub fn render_markdown(ui: &mut egui::Ui, text: &str) {
+    let body = egui::TextStyle::Body.resolve(ui.style());
+    for block in parse_blocks(text) {
+        match block {
+            Block::Heading(level, heading) => {
+                ui.add_space(6.0);
+                let size = if level == 1 { 20.0 } else { 16.0 };
+                ui.label(egui::RichText::new(heading).size(size).strong());
+            }
+            Block::Bullet(item) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("•");
+                    ui.label(inline_job(ui, &item, body.clone()));
+                });
+            }
+            Block::Paragraph(paragraph) => {
+                ui.add_space(4.0);
+                ui.label(inline_job(ui, &paragraph, body.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_blocks() {
+        let blocks = parse_blocks("# Title\n\nFirst line\nsecond line\n- one\n- two\n\n## Next");
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Heading(1, "Title".to_string()),
+                Block::Paragraph("First line second line".to_string()),
+                Block::Bullet("one".to_string()),
+                Block::Bullet("two".to_string()),
+                Block::Heading(2, "Next".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        assert_eq!(
+            parse_spans("Tick **Dry Run** to keep `core_char_1.dat` as is"),
+            vec![
+                (Span::Plain, "Tick "),
+                (Span::Bold, "Dry Run"),
+                (Span::Plain, " to keep "),
+                (Span::Code, "core_char_1.dat"),
+                (Span::Plain, " as is"),
+            ]
+        );
+        assert_eq!(parse_spans("a ** b"), vec![(Span::Plain, "a ** b")]);
+    }
+}
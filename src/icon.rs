@@ -0,0 +1,99 @@
+//! Application icon, drawn from the E logo at every size docks and task switchers
+//! ask for, plus the desktop entry that lets Linux launchers and Wayland
+//! compositors match the window to it.
+
+use anyhow::{Context, Result};
+use eframe::egui::IconData;
+use std::fs;
+use std::path::PathBuf;
+
+/// Identifies the window to compositors; the desktop entry is named after it
+pub const APP_ID: &str = "pack-preferences";
+
+/// Edge lengths, in pixels, the icon is rendered at
+pub const SIZES: [u32; 7] = [16, 24, 32, 48, 64, 128, 256];
+
+/// The E logo on a rounded dark teal tile, on a 256 pixel grid
+const ICON_SVG: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<svg width="256" height="256" viewBox="0 0 256 256" xmlns="http://www.w3.org/2000/svg">
+  <rect x="8" y="8" width="240" height="240" rx="44" ry="44" fill="#0F2D37" stroke="#1E5A64" stroke-width="8"/>
+  <g transform="translate(49.5 65) scale(0.99)">
+    <path fill="#00FF96" d="m 28.68,90.67 c 0,0 -28.68,0 -28.68,0 0,0 0,36.41 0,36.41 0,0 158.65,0 158.65,0 0,0 0,-27.03 0,-27.03 0,0 -125.93,0 -129.97,0 0,-2.53 0,-9.38 0,-9.38 z"/>
+    <path fill="#00FF96" d="m 0,36.3 c 0,0 28.68,0 28.68,0 0,0 0,-6.76 0,-9.27 4.04,0 129.97,0 129.97,0 0,0 0,-27.03 0,-27.03 C 158.65,0 0,0 0,0 Z"/>
+    <path fill="#00FF96" d="m 0,77.06 c 0,0 158.65,0 158.65,0 0,0 0,-27.02 0,-27.02 0,0 -158.65,0 -158.65,0 z"/>
+  </g>
+</svg>"##;
+
+/// Grid the SVG is drawn on
+const ICON_GRID: f32 = 256.0;
+
+/// Renders the icon `size` pixels square.
+pub fn render(size: u32) -> Option<IconData> {
+    let svg = nsvg::parse_str(ICON_SVG, nsvg::Units::Pixel, 96.0).ok()?;
+    let (width, height, rgba) = svg.rasterize_to_raw_rgba(size as f32 / ICON_GRID).ok()?;
+    Some(IconData {
+        rgba,
+        width,
+        height,
+    })
+}
+
+/// The window icon. Only one image can be handed to the window system, so this
+/// is the largest and it scales it down; launchers pick sharper sizes from the
+/// desktop entry's icons.
+pub fn window_icon() -> IconData {
+    SIZES
+        .last()
+        .and_then(|&size| render(size))
+        .unwrap_or_default()
+}
+
+/// Desktop entry for launching `exec`
+pub fn desktop_entry(exec: &str) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Pack Preferences\n\
+         Comment=Replicate EVE Online settings across characters\n\
+         Exec=\"{}\"\n\
+         Icon={}\n\
+         Terminal=false\n\
+         Categories=Game;Utility;\n\
+         StartupWMClass={}\n",
+        exec.replace('\\', "\\\\").replace('"', "\\\""),
+        APP_ID,
+        APP_ID
+    )
+}
+
+/// Writes the icon at every size into the user's hicolor theme and a desktop entry
+/// for this executable, so the app shows up in application menus. Returns the path
+/// of the desktop entry.
+pub fn install_desktop_entry() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().context("Could not determine the data directory")?;
+    for size in SIZES {
+        let icon = render(size).with_context(|| format!("Failed to render {0}x{0} icon", size))?;
+        let dir = data_dir
+            .join("icons/hicolor")
+            .join(format!("{0}x{0}", size))
+            .join("apps");
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.png", APP_ID));
+        image::save_buffer(
+            &path,
+            &icon.rgba,
+            icon.width,
+            icon.height,
+            image::ExtendedColorType::Rgba8,
+        )
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    let exe = std::env::current_exe().context("Could not locate the running executable")?;
+    let dir = data_dir.join("applications");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}.desktop", APP_ID));
+    fs::write(&path, desktop_entry(&exe.to_string_lossy()))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,490 @@
+use eframe::egui;
+
+use crate::discovery::CharacterFile;
+use crate::inspect::{self, KeyPath, Match, Segment};
+use crate::marshal::{self, Value};
+use crate::settings::SyncResult;
+use crate::theme;
+
+/// Something the caller should write, since that takes a backup and goes in the
+/// audit log
+pub enum InspectorAction {
+    /// Set one value in the inspected file
+    Save(KeyPath, String),
+    /// Set one value in several files at once
+    BulkEdit {
+        path: KeyPath,
+        text: String,
+        targets: Vec<CharacterFile>,
+    },
+    /// Expect the value at this path, as it is now, in the fleet baseline
+    AddToBaseline(KeyPath, String),
+}
+
+/// Asked for from a leaf's buttons or menu, beyond saving it
+enum LeafRequest {
+    ApplyTo(KeyPath, String),
+    AddToBaseline(KeyPath, String),
+}
+
+/// A value being edited in the inspector
+struct Edit {
+    path: KeyPath,
+    text: String,
+    error: Option<String>,
+}
+
+/// One value being applied to several files. `selected` covers the inspected file
+/// followed by each peer.
+struct Bulk {
+    path: KeyPath,
+    text: String,
+    selected: Vec<bool>,
+    results: Option<Vec<SyncResult>>,
+}
+
+/// Search hits in one file, or why it could not be decoded
+struct FileMatches {
+    title: String,
+    matches: Result<Vec<Match>, String>,
+}
+
+/// Window showing one settings file's decoded tree. Scalars can be edited here or
+/// applied to other characters; the caller writes them, see [`InspectorAction`].
+pub struct Inspector {
+    pub file: CharacterFile,
+    pub title: String,
+    tree: Result<Value, String>,
+    editing: Option<Edit>,
+    bulk: Option<Bulk>,
+    /// Other files of the same type, searched when `all_characters` is set
+    /// and offered as bulk edit targets
+    peers: Vec<(String, CharacterFile)>,
+    /// Decoded peers, read the first time they are searched
+    peer_trees: Option<Vec<(String, Result<Value, String>)>>,
+    query: String,
+    all_characters: bool,
+    results: Vec<FileMatches>,
+}
+
+impl Inspector {
+    pub fn open(file: CharacterFile, title: String, peers: Vec<(String, CharacterFile)>) -> Self {
+        let mut inspector = Self {
+            file,
+            title,
+            tree: Err(String::new()),
+            editing: None,
+            bulk: None,
+            peers,
+            peer_trees: None,
+            query: String::new(),
+            all_characters: false,
+            results: Vec::new(),
+        };
+        inspector.reload();
+        inspector
+    }
+
+    /// Decodes the file again, e.g. after an edit was written.
+    pub fn reload(&mut self) {
+        self.tree = marshal::read_file(&self.file.path).map_err(|e| format!("{:#}", e));
+        self.editing = None;
+        self.peer_trees = None;
+        self.search();
+    }
+
+    /// Shows how a bulk edit went and picks up the new values.
+    pub fn bulk_finished(&mut self, results: Vec<SyncResult>) {
+        if let Some(bulk) = &mut self.bulk {
+            bulk.results = Some(results);
+        }
+        self.reload();
+    }
+
+    /// Title for a file this inspector knows, falling back to its path.
+    fn title_of(&self, path: &std::path::Path) -> String {
+        if path == self.file.path {
+            return self.title.clone();
+        }
+        self.peers
+            .iter()
+            .find(|(_, f)| f.path == path)
+            .map(|(title, _)| title.clone())
+            .unwrap_or_else(|| path.display().to_string())
+    }
+
+    /// Reruns the query against this file and, if asked, every peer.
+    fn search(&mut self) {
+        self.results.clear();
+        if self.query.trim().is_empty() {
+            return;
+        }
+        self.results.push(FileMatches {
+            title: self.title.clone(),
+            matches: search_tree(&self.tree, &self.query),
+        });
+        if !self.all_characters {
+            return;
+        }
+        let peers = &self.peers;
+        let peer_trees = self.peer_trees.get_or_insert_with(|| {
+            peers
+                .iter()
+                .map(|(title, file)| {
+                    let tree = marshal::read_file(&file.path).map_err(|e| format!("{:#}", e));
+                    (title.clone(), tree)
+                })
+                .collect()
+        });
+        for (title, tree) in peer_trees.iter() {
+            self.results.push(FileMatches {
+                title: title.clone(),
+                matches: search_tree(tree, &self.query),
+            });
+        }
+    }
+
+    /// Keeps the failed value open for correction, with the reason next to it.
+    pub fn edit_failed(&mut self, path: &KeyPath, text: String, error: String) {
+        self.editing = Some(Edit {
+            path: path.clone(),
+            text,
+            error: Some(error),
+        });
+    }
+
+    /// Draws the window, and the bulk edit window while one is open.
+    pub fn show(&mut self, ctx: &egui::Context, open: &mut bool) -> Option<InspectorAction> {
+        let mut save = None;
+        let mut request = None;
+        egui::Window::new(format!("Inspect {}", self.title))
+            .id(egui::Id::new("settings_inspector"))
+            .open(open)
+            .default_size([480.0, 420.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(self.file.path.display().to_string())
+                            .color(theme::colors::TEXT_DIM),
+                    );
+                    if ui.button("Reload").clicked() {
+                        self.reload();
+                    }
+                });
+                ui.label(
+                    "Edits back up the profile before the file is rewritten. Right-click a \
+                     setting to add it to the fleet baseline.",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Search:");
+                    let changed = ui
+                        .add(
+                            egui::TextEdit::singleline(&mut self.query)
+                                .hint_text("key or value, or key=value"),
+                        )
+                        .changed();
+                    let toggled = ui
+                        .add_enabled(
+                            !self.peers.is_empty(),
+                            egui::Checkbox::new(&mut self.all_characters, "All characters"),
+                        )
+                        .on_hover_text("Also search every other file of this type")
+                        .changed();
+                    if changed || toggled {
+                        self.search();
+                    }
+                });
+                ui.separator();
+                if !self.query.trim().is_empty() {
+                    show_results(ui, &self.results);
+                    return;
+                }
+                match &self.tree {
+                    Err(e) => {
+                        ui.colored_label(theme::error(), e);
+                    }
+                    Ok(tree) => {
+                        egui::ScrollArea::vertical()
+                            .auto_shrink([false, false])
+                            .show(ui, |ui| {
+                                show_node(
+                                    ui,
+                                    "/",
+                                    tree,
+                                    &KeyPath::default(),
+                                    &mut self.editing,
+                                    &mut save,
+                                    &mut request,
+                                );
+                            });
+                    }
+                }
+            });
+
+        let mut action = save.map(|(path, text)| InspectorAction::Save(path, text));
+        match request {
+            Some(LeafRequest::ApplyTo(path, text)) => {
+                let mut selected = vec![false; self.peers.len() + 1];
+                selected[0] = true;
+                self.bulk = Some(Bulk {
+                    path,
+                    text,
+                    selected,
+                    results: None,
+                });
+            }
+            Some(LeafRequest::AddToBaseline(path, text)) => {
+                action = Some(InspectorAction::AddToBaseline(path, text));
+            }
+            None => {}
+        }
+        let bulk_action = self.show_bulk(ctx);
+        action.or(bulk_action)
+    }
+
+    fn show_bulk(&mut self, ctx: &egui::Context) -> Option<InspectorAction> {
+        let mut bulk = self.bulk.take()?;
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new("Apply to Characters")
+            .id(egui::Id::new("settings_bulk_edit"))
+            .open(&mut open)
+            .default_size([420.0, 360.0])
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(bulk.path.to_string()).monospace());
+                ui.horizontal(|ui| {
+                    ui.label("New value:");
+                    ui.text_edit_singleline(&mut bulk.text);
+                });
+                ui.label("Files that reject the value, or lack the setting, stop the whole edit.");
+                ui.horizontal(|ui| {
+                    if ui.small_button("All").clicked() {
+                        bulk.selected.iter_mut().for_each(|s| *s = true);
+                    }
+                    if ui.small_button("None").clicked() {
+                        bulk.selected.iter_mut().for_each(|s| *s = false);
+                    }
+                });
+                egui::ScrollArea::vertical()
+                    .max_height(180.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        let titles =
+                            std::iter::once(&self.title).chain(self.peers.iter().map(|(t, _)| t));
+                        for (selected, title) in bulk.selected.iter_mut().zip(titles) {
+                            ui.checkbox(selected, title.as_str());
+                        }
+                    });
+
+                let targets: Vec<CharacterFile> = std::iter::once(&self.file)
+                    .chain(self.peers.iter().map(|(_, f)| f))
+                    .zip(&bulk.selected)
+                    .filter(|(_, selected)| **selected)
+                    .map(|(f, _)| f.clone())
+                    .collect();
+                let label = format!("Apply to {} file(s)", targets.len());
+                if ui
+                    .add_enabled(!targets.is_empty(), egui::Button::new(label))
+                    .clicked()
+                {
+                    action = Some(InspectorAction::BulkEdit {
+                        path: bulk.path.clone(),
+                        text: bulk.text.clone(),
+                        targets,
+                    });
+                }
+
+                if let Some(results) = &bulk.results {
+                    ui.separator();
+                    for result in results {
+                        let color = if result.success {
+                            theme::success()
+                        } else {
+                            theme::error()
+                        };
+                        ui.colored_label(
+                            color,
+                            format!("{}: {}", self.title_of(&result.target_file), result.message),
+                        );
+                    }
+                }
+            });
+        if open {
+            self.bulk = Some(bulk);
+        }
+        action
+    }
+}
+
+fn search_tree(tree: &Result<Value, String>, query: &str) -> Result<Vec<Match>, String> {
+    tree.as_ref()
+        .map(|tree| inspect::search(tree, query))
+        .map_err(Clone::clone)
+}
+
+/// Lists hits grouped by file, leaving out files with none.
+fn show_results(ui: &mut egui::Ui, results: &[FileMatches]) {
+    let total: usize = results
+        .iter()
+        .filter_map(|r| r.matches.as_ref().ok())
+        .map(Vec::len)
+        .sum();
+    let files = results
+        .iter()
+        .filter(|r| r.matches.as_ref().is_ok_and(|m| !m.is_empty()))
+        .count();
+    ui.label(format!(
+        "{} matches in {} of {} files",
+        total,
+        files,
+        results.len()
+    ));
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for result in results {
+                match &result.matches {
+                    Ok(matches) if matches.is_empty() => {}
+                    Ok(matches) => {
+                        egui::CollapsingHeader::new(format!(
+                            "{}  ({})",
+                            result.title,
+                            matches.len()
+                        ))
+                        .id_salt(("search", &result.title))
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for m in matches {
+                                ui.horizontal(|ui| {
+                                    ui.monospace(format!("{}:", m.path));
+                                    ui.monospace(inspect::display_scalar(&m.value));
+                                });
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        ui.colored_label(theme::error(), format!("{}: {}", result.title, e));
+                    }
+                }
+            }
+        });
+}
+
+fn show_node(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &Value,
+    path: &KeyPath,
+    editing: &mut Option<Edit>,
+    save: &mut Option<(KeyPath, String)>,
+    request: &mut Option<LeafRequest>,
+) {
+    let children: Vec<(String, &Value, Segment)> = match value {
+        Value::Dict(entries) => entries
+            .iter()
+            .map(|(k, v)| (inspect::display_scalar(k), v, Segment::Key(k.clone())))
+            .collect(),
+        Value::Tuple(items) | Value::List(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("[{}]", i), v, Segment::Index(i)))
+            .collect(),
+        _ => {
+            show_leaf(ui, label, value, path, editing, save, request);
+            return;
+        }
+    };
+
+    egui::CollapsingHeader::new(format!("{}  ({})", label, inspect::display_scalar(value)))
+        .id_salt(path.to_string())
+        .default_open(path.0.is_empty())
+        .show(ui, |ui| {
+            for (label, child, segment) in children {
+                let path = path.child(segment);
+                show_node(ui, &label, child, &path, editing, save, request);
+            }
+        });
+}
+
+fn show_leaf(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &Value,
+    path: &KeyPath,
+    editing: &mut Option<Edit>,
+    save: &mut Option<(KeyPath, String)>,
+    request: &mut Option<LeafRequest>,
+) {
+    // Only offer editing where a typed value could be accepted
+    let editable = inspect::parse_as(value, &inspect::display_scalar(value)).is_ok();
+    ui.horizontal(|ui| {
+        let label = ui.label(format!("{}:", label));
+        if editable {
+            label.context_menu(|ui| {
+                if ui.button("Add to Baseline").clicked() {
+                    ui.close_menu();
+                    *request = Some(LeafRequest::AddToBaseline(
+                        path.clone(),
+                        inspect::display_scalar(value),
+                    ));
+                }
+            });
+        }
+        if let Value::Bool(current) = value {
+            let mut checked = *current;
+            if ui.checkbox(&mut checked, "").changed() {
+                *save = Some((path.clone(), checked.to_string()));
+            }
+            if ui.small_button("Apply to...").clicked() {
+                *request = Some(LeafRequest::ApplyTo(path.clone(), current.to_string()));
+            }
+            return;
+        }
+
+        match editing.as_mut().filter(|edit| &edit.path == path) {
+            Some(edit) => {
+                let response = ui.text_edit_singleline(&mut edit.text);
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.button("Save").clicked() || submitted {
+                    match inspect::parse_as(value, &edit.text) {
+                        Ok(_) => *save = Some((path.clone(), edit.text.clone())),
+                        Err(e) => edit.error = Some(e.to_string()),
+                    }
+                }
+                if ui.button("Cancel").clicked() {
+                    *editing = None;
+                }
+            }
+            None => {
+                ui.monospace(inspect::display_scalar(value));
+                ui.weak(value.type_name());
+                if editable && ui.small_button("Edit").clicked() {
+                    *editing = Some(Edit {
+                        path: path.clone(),
+                        text: inspect::display_scalar(value),
+                        error: None,
+                    });
+                }
+                if editable
+                    && ui
+                        .small_button("Apply to...")
+                        .on_hover_text("Set this value in other characters' files")
+                        .clicked()
+                {
+                    *request = Some(LeafRequest::ApplyTo(
+                        path.clone(),
+                        inspect::display_scalar(value),
+                    ));
+                }
+            }
+        }
+    });
+    if let Some(error) = editing
+        .as_ref()
+        .filter(|edit| &edit.path == path)
+        .and_then(|edit| edit.error.as_ref())
+    {
+        ui.colored_label(theme::error(), error);
+    }
+}
@@ -1,27 +1,50 @@
 mod about;
 mod app;
-mod config;
-mod discovery;
-mod esi;
-mod process;
-mod settings;
+mod assets;
+mod changelog;
+mod cli;
+mod crash;
+mod diagnostics;
+mod events;
+mod gamepad;
+mod help;
+mod icon;
+mod inspector;
 mod theme;
+mod widgets;
+
+use pack_preferences_core::{
+    baseline, config, discovery, duplicates, elevate, esi, golden, health, inspect, journal, lint,
+    marshal, migrate, names, news, notify, plugins, preflight, presets, process, report, scripting,
+    settings, state, status, troubleshoot, windows_settings,
+};
 
 use anyhow::Result;
 use eframe::egui;
+use std::sync::Arc;
 
 fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    // Force the Steam Deck layout regardless of detection and config
+    let force_deck = args.iter().any(|arg| arg == "--deck");
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([800.0, 450.0])
-            .with_min_inner_size([700.0, 350.0]),
+            .with_min_inner_size([320.0, 160.0])
+            .with_icon(Arc::new(icon::window_icon()))
+            .with_app_id(icon::APP_ID),
         ..Default::default()
     };
 
     eframe::run_native(
         "Pack Preferences",
         options,
-        Box::new(|cc| Ok(Box::new(app::PackPreferencesApp::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(app::PackPreferencesApp::new(cc, force_deck)))),
     )
     .map_err(|e| anyhow::anyhow!("Failed to run application: {}", e))?;
 
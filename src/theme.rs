@@ -1,4 +1,12 @@
-use eframe::egui::{self, Color32, Rounding, Stroke, Style, Visuals};
+use anyhow::{bail, Context, Result};
+use eframe::egui::{
+    self, Color32, FontData, FontDefinitions, FontFamily, Rounding, Stroke, Style, Visuals,
+};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::{FontConfig, Palette, DEFAULT_FONT_SIZE};
 
 // Color palette based on menthol cigarette pack aesthetic
 #[allow(dead_code)]
@@ -34,7 +42,64 @@ pub mod colors {
     pub const SELECTION_DIM: Color32 = Color32::from_rgb(0, 100, 80);
 }
 
-pub fn apply_pack_theme(ctx: &egui::Context) {
+/// Colours that carry meaning, which each palette picks for itself
+pub struct Accents {
+    pub success: Color32,
+    pub warning: Color32,
+    pub error: Color32,
+    /// Outline of hovered widgets
+    pub hover: Color32,
+    /// Outline of pressed widgets
+    pub active: Color32,
+    pub selection: Color32,
+    pub selection_stroke: Color32,
+}
+
+const PACK_ACCENTS: Accents = Accents {
+    success: colors::NEON_GREEN,
+    warning: Color32::from_rgb(255, 143, 0),
+    error: Color32::LIGHT_RED,
+    hover: colors::ELECTRIC_GREEN,
+    active: colors::NEON_GREEN,
+    selection: colors::SELECTION,
+    selection_stroke: colors::ELECTRIC_GREEN,
+};
+
+/// Okabe-Ito colours, lightened to read on the teal background. Success, warning
+/// and error differ in brightness as well as hue, and nothing relies on telling
+/// red from green.
+const COLOR_SAFE_ACCENTS: Accents = Accents {
+    success: Color32::from_rgb(86, 180, 233),
+    warning: Color32::from_rgb(240, 228, 66),
+    error: Color32::from_rgb(240, 130, 60),
+    hover: Color32::from_rgb(240, 228, 66),
+    active: Color32::from_rgb(230, 159, 0),
+    selection: Color32::from_rgb(0, 114, 178),
+    selection_stroke: Color32::from_rgb(86, 180, 233),
+};
+
+static COLOR_SAFE: AtomicBool = AtomicBool::new(false);
+
+/// Accent colours of the palette last applied
+pub fn accents() -> &'static Accents {
+    if COLOR_SAFE.load(Ordering::Relaxed) {
+        &COLOR_SAFE_ACCENTS
+    } else {
+        &PACK_ACCENTS
+    }
+}
+
+pub fn success() -> Color32 {
+    accents().success
+}
+
+pub fn error() -> Color32 {
+    accents().error
+}
+
+pub fn apply_theme(ctx: &egui::Context, palette: Palette) {
+    COLOR_SAFE.store(palette == Palette::ColorSafe, Ordering::Relaxed);
+    let accents = accents();
     let mut style = Style::default();
 
     // Customize visuals
@@ -58,13 +123,13 @@ pub fn apply_pack_theme(ctx: &egui::Context) {
     visuals.widgets.inactive.rounding = Rounding::same(4.0);
 
     visuals.widgets.hovered.bg_fill = colors::WIDGET_BG_HOVER;
-    visuals.widgets.hovered.fg_stroke = Stroke::new(1.5, colors::ELECTRIC_GREEN);
-    visuals.widgets.hovered.bg_stroke = Stroke::new(1.5, colors::ELECTRIC_GREEN);
+    visuals.widgets.hovered.fg_stroke = Stroke::new(1.5, accents.hover);
+    visuals.widgets.hovered.bg_stroke = Stroke::new(1.5, accents.hover);
     visuals.widgets.hovered.rounding = Rounding::same(4.0);
 
     visuals.widgets.active.bg_fill = colors::WIDGET_BG_ACTIVE;
-    visuals.widgets.active.fg_stroke = Stroke::new(2.0, colors::NEON_GREEN);
-    visuals.widgets.active.bg_stroke = Stroke::new(2.0, colors::NEON_GREEN);
+    visuals.widgets.active.fg_stroke = Stroke::new(2.0, accents.active);
+    visuals.widgets.active.bg_stroke = Stroke::new(2.0, accents.active);
     visuals.widgets.active.rounding = Rounding::same(4.0);
 
     visuals.widgets.open.bg_fill = colors::WIDGET_BG_ACTIVE;
@@ -73,8 +138,12 @@ pub fn apply_pack_theme(ctx: &egui::Context) {
     visuals.widgets.open.rounding = Rounding::same(4.0);
 
     // Selection color
-    visuals.selection.bg_fill = colors::SELECTION;
-    visuals.selection.stroke = Stroke::new(1.0, colors::ELECTRIC_GREEN);
+    visuals.selection.bg_fill = accents.selection;
+    visuals.selection.stroke = Stroke::new(1.0, accents.selection_stroke);
+
+    // Status colours
+    visuals.warn_fg_color = accents.warning;
+    visuals.error_fg_color = accents.error;
 
     // Hyperlinks
     visuals.hyperlink_color = colors::BRIGHT_CYAN;
@@ -103,6 +172,75 @@ pub fn apply_pack_theme(ctx: &egui::Context) {
     ctx.set_style(style);
 }
 
+/// First bytes of TrueType, OpenType, Apple TrueType and collection files
+const FONT_SIGNATURES: [&[u8]; 4] = [b"\x00\x01\x00\x00", b"OTTO", b"true", b"ttcf"];
+
+/// Puts the configured font files in front of egui's own, which stay behind them
+/// as fallbacks for missing glyphs. The built-in monospace font is Hack, compiled
+/// into the binary. Returns why any configured font could not be used.
+pub fn apply_fonts(ctx: &egui::Context, fonts: &FontConfig) -> Vec<String> {
+    let mut definitions = FontDefinitions::default();
+    let mut errors = Vec::new();
+    for (path, family, key) in [
+        (&fonts.ui_font, FontFamily::Proportional, "user-ui"),
+        (&fonts.mono_font, FontFamily::Monospace, "user-mono"),
+    ] {
+        let Some(path) = path else { continue };
+        match read_font(Path::new(path)) {
+            Ok(data) => {
+                definitions
+                    .font_data
+                    .insert(key.to_string(), FontData::from_owned(data));
+                definitions
+                    .families
+                    .entry(family)
+                    .or_default()
+                    .insert(0, key.to_string());
+            }
+            Err(e) => errors.push(format!("{:#}", e)),
+        }
+    }
+    ctx.set_fonts(definitions);
+    errors
+}
+
+/// Reads a font file, refusing anything that is not one: egui only finds out
+/// when it first draws text, and then panics.
+fn read_font(path: &Path) -> Result<Vec<u8>> {
+    let data = fs::read(path).with_context(|| format!("Failed to read font {}", path.display()))?;
+    if !FONT_SIGNATURES.iter().any(|sig| data.starts_with(sig)) {
+        bail!("{} is not a TrueType or OpenType font", path.display());
+    }
+    Ok(data)
+}
+
+/// Scales every text style so body text is `size` points. Applied on top of
+/// [`apply_theme`].
+pub fn apply_font_size(ctx: &egui::Context, size: f32) {
+    let scale = size / DEFAULT_FONT_SIZE;
+    ctx.style_mut(|style| {
+        for font in style.text_styles.values_mut() {
+            font.size *= scale;
+        }
+    });
+}
+
+/// Enlarges text, spacing and hit targets for touch and gamepad use on a Steam Deck.
+/// Applied on top of [`apply_theme`].
+pub fn apply_deck_layout(ctx: &egui::Context) {
+    ctx.style_mut(|style| {
+        for font in style.text_styles.values_mut() {
+            font.size *= 1.3;
+        }
+        style.spacing.item_spacing = egui::vec2(12.0, 10.0);
+        style.spacing.button_padding = egui::vec2(18.0, 12.0);
+        style.spacing.interact_size.y = 40.0;
+        style.spacing.icon_width = 24.0;
+        style.spacing.icon_width_inner = 14.0;
+        style.spacing.scroll.bar_width = 16.0;
+    });
+}
+
 /// Returns the app title with styled colors for the header
 pub fn styled_title(ui: &mut egui::Ui) {
     ui.horizontal(|ui| {
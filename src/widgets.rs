@@ -0,0 +1,365 @@
+use eframe::egui;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::discovery;
+use crate::help;
+use crate::theme;
+
+/// Portrait size in the source/target lists
+pub const LIST_ICON_SIZE: f32 = 16.0;
+
+/// Represents a selectable item (either a character or user/account)
+#[derive(Clone)]
+pub struct SelectableItem {
+    pub file_idx: usize,
+    pub id: u64,
+    pub display_name: String,
+    pub is_default: bool,
+    /// Settings profile the file lives in
+    pub settings_dir: PathBuf,
+}
+
+/// Supplies the per-item decorations a [`SyncPicker`] cannot draw on its own.
+pub trait PickerDelegate {
+    /// Draws the icon in front of an entry.
+    fn item_icon(&mut self, ui: &mut egui::Ui, item: &SelectableItem);
+    /// Fills the right-click menu of an entry.
+    fn item_context_menu(&mut self, ui: &mut egui::Ui, item: &SelectableItem);
+    /// Draws the ID after an entry's name.
+    fn item_id(&mut self, ui: &mut egui::Ui, item: &SelectableItem) {
+        ui.label(format!("[{}]", item.id));
+    }
+    /// Draws what follows a default file's name in place of an ID.
+    fn item_default(&mut self, _ui: &mut egui::Ui, _item: &SelectableItem) {}
+}
+
+/// Two-pane source/target picker: one source radio list and one target checkbox list.
+#[derive(Default)]
+pub struct SyncPicker {
+    pub source: Option<usize>,
+    pub targets: HashSet<usize>,
+    /// Case-insensitive filter applied to names and IDs
+    pub filter: String,
+    /// IDs of the rows drawn this frame
+    visible: Vec<u64>,
+    /// Source to scroll into view on the next frame
+    scroll_to: Option<usize>,
+}
+
+impl SyncPicker {
+    /// Clears the source and targets; the filter is kept.
+    pub fn clear(&mut self) {
+        self.source = None;
+        self.targets.clear();
+    }
+
+    /// Takes the IDs of the rows drawn since the last call, source list first.
+    pub fn take_visible(&mut self) -> Vec<u64> {
+        std::mem::take(&mut self.visible)
+    }
+
+    /// Returns true when both a source and at least one target are selected.
+    pub fn has_selection(&self) -> bool {
+        self.source.is_some() && !self.targets.is_empty()
+    }
+
+    /// Makes `file_idx` the source; it can no longer be a target.
+    pub fn set_source(&mut self, file_idx: usize) {
+        self.source = Some(file_idx);
+        self.targets.remove(&file_idx);
+    }
+
+    /// Makes `file_idx` the source and scrolls the source list to it.
+    pub fn reveal(&mut self, file_idx: usize) {
+        self.set_source(file_idx);
+        self.scroll_to = Some(file_idx);
+    }
+
+    pub fn set_target(&mut self, file_idx: usize, selected: bool) {
+        if selected && self.source != Some(file_idx) {
+            self.targets.insert(file_idx);
+        } else {
+            self.targets.remove(&file_idx);
+        }
+    }
+
+    /// Selects every target that passes the filter, except the source.
+    pub fn select_all(&mut self, items: &[SelectableItem]) {
+        let visible: Vec<usize> = items
+            .iter()
+            .filter(|item| self.matches(item))
+            .map(|item| item.file_idx)
+            .collect();
+        for file_idx in visible {
+            self.set_target(file_idx, true);
+        }
+    }
+
+    pub fn select_none(&mut self) {
+        self.targets.clear();
+    }
+
+    /// Returns true if the item passes the current filter.
+    pub fn matches(&self, item: &SelectableItem) -> bool {
+        let filter = self.filter.trim();
+        filter.is_empty()
+            || item
+                .display_name
+                .to_lowercase()
+                .contains(&filter.to_lowercase())
+            || item.id.to_string().contains(filter)
+    }
+
+    /// Draws the filter text field.
+    pub fn show_filter(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text("name or ID")
+                    .desired_width(200.0),
+            );
+            if !self.filter.is_empty() && ui.small_button("Clear").clicked() {
+                self.filter.clear();
+            }
+        });
+    }
+
+    /// Draws the source list. Defaults are never offered as a source.
+    pub fn show_source(
+        &mut self,
+        ui: &mut egui::Ui,
+        items: &[SelectableItem],
+        grouping: Grouping,
+        empty_label: &str,
+        delegate: &mut impl PickerDelegate,
+    ) {
+        let rows = list_rows(
+            items.iter().filter(|i| !i.is_default && self.matches(i)),
+            grouping,
+        );
+        let row_height = list_row_height(ui);
+        if rows.is_empty() {
+            ui.label(empty_label);
+        }
+        let mut area = egui::ScrollArea::vertical()
+            .id_salt("source_scroll")
+            .max_height(180.0);
+        let revealed = self.scroll_to.take().and_then(|file_idx| {
+            rows.iter()
+                .position(|row| matches!(row, ListRow::Item(item) if item.file_idx == file_idx))
+        });
+        if let Some(row) = revealed {
+            let spacing = ui.spacing().item_spacing.y;
+            area = area.vertical_scroll_offset(row as f32 * (row_height + spacing));
+        }
+        area.show_rows(ui, row_height, rows.len(), |ui, range| {
+            for row in &rows[range] {
+                let item = match row {
+                    ListRow::Header(text) => {
+                        list_header(ui, text, row_height);
+                        continue;
+                    }
+                    ListRow::Item(item) => *item,
+                };
+                self.visible.push(item.id);
+                let selected = self.source == Some(item.file_idx);
+                let response = ui
+                    .horizontal(|ui| {
+                        ui.set_height(row_height);
+                        delegate.item_icon(ui, item);
+                        let response = ui.radio(selected, &item.display_name);
+                        delegate.item_id(ui, item);
+                        default_hint(response, item)
+                    })
+                    .inner;
+                response.context_menu(|ui| delegate.item_context_menu(ui, item));
+                if response.clicked() {
+                    self.set_source(item.file_idx);
+                }
+            }
+        });
+    }
+
+    /// Draws the target list with its Select All / Select None buttons.
+    pub fn show_targets(
+        &mut self,
+        ui: &mut egui::Ui,
+        items: &[SelectableItem],
+        grouping: Grouping,
+        delegate: &mut impl PickerDelegate,
+    ) {
+        // Can't select source as target
+        let rows = list_rows(
+            items
+                .iter()
+                .filter(|i| self.source != Some(i.file_idx) && self.matches(i)),
+            grouping,
+        );
+        let row_height = list_row_height(ui);
+        egui::ScrollArea::vertical()
+            .id_salt("target_scroll")
+            .max_height(150.0)
+            .show_rows(ui, row_height, rows.len(), |ui, range| {
+                for row in &rows[range] {
+                    let item = match row {
+                        ListRow::Header(text) => {
+                            list_header(ui, text, row_height);
+                            continue;
+                        }
+                        ListRow::Item(item) => *item,
+                    };
+
+                    self.visible.push(item.id);
+                    let mut selected = self.targets.contains(&item.file_idx);
+                    let response = ui
+                        .horizontal(|ui| {
+                            ui.set_height(row_height);
+                            delegate.item_icon(ui, item);
+                            let response = ui.checkbox(&mut selected, &item.display_name);
+                            if item.is_default {
+                                delegate.item_default(ui, item);
+                            } else {
+                                delegate.item_id(ui, item);
+                            }
+                            default_hint(response, item)
+                        })
+                        .inner;
+                    response.context_menu(|ui| delegate.item_context_menu(ui, item));
+                    if response.changed() {
+                        self.set_target(item.file_idx, selected);
+                    }
+                }
+            });
+
+        ui.horizontal(|ui| {
+            if ui.button("Select All").clicked() {
+                self.select_all(items);
+            }
+            if ui.button("Select None").clicked() {
+                self.select_none();
+            }
+        });
+    }
+}
+
+/// How list entries are split into headed groups
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    None,
+    /// One group per settings profile, in all-profiles mode
+    Profile,
+}
+
+impl SelectableItem {
+    fn group_label(&self, grouping: Grouping) -> Option<String> {
+        match grouping {
+            Grouping::None => None,
+            Grouping::Profile => Some(discovery::profile_label(&self.settings_dir)),
+        }
+    }
+}
+
+/// One row of a virtualized source/target list
+enum ListRow<'a> {
+    /// Group heading
+    Header(String),
+    Item(&'a SelectableItem),
+}
+
+/// Flatten items into list rows, inserting a heading whenever the group changes
+fn list_rows<'a>(
+    items: impl Iterator<Item = &'a SelectableItem>,
+    grouping: Grouping,
+) -> Vec<ListRow<'a>> {
+    let mut rows = Vec::new();
+    let mut current = None;
+    for item in items {
+        let label = item.group_label(grouping);
+        if label.is_some() && label != current {
+            rows.extend(label.clone().map(ListRow::Header));
+            current = label;
+        }
+        rows.push(ListRow::Item(item));
+    }
+    rows
+}
+
+/// Fixed height of every row so `ScrollArea::show_rows` can skip offscreen ones
+pub fn list_row_height(ui: &egui::Ui) -> f32 {
+    ui.spacing().interact_size.y.max(LIST_ICON_SIZE)
+}
+
+/// Draw a group heading at list row height
+fn list_header(ui: &mut egui::Ui, text: &str, row_height: f32) {
+    ui.horizontal(|ui| {
+        ui.set_height(row_height);
+        ui.label(egui::RichText::new(text).color(theme::colors::CYAN));
+    });
+}
+
+/// Explain default files when hovering over one
+fn default_hint(response: egui::Response, item: &SelectableItem) -> egui::Response {
+    if item.is_default {
+        response.on_hover_text(help::Topic::Defaults.tooltip())
+    } else {
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(file_idx: usize, name: &str, profile: &str) -> SelectableItem {
+        SelectableItem {
+            file_idx,
+            id: 90000000 + file_idx as u64,
+            display_name: name.to_string(),
+            is_default: false,
+            settings_dir: PathBuf::from(profile),
+        }
+    }
+
+    #[test]
+    fn source_is_never_a_target() {
+        let items = vec![item(0, "Alpha", "a"), item(1, "Beta", "a")];
+        let mut picker = SyncPicker::default();
+        picker.select_all(&items);
+        picker.set_source(0);
+        assert!(!picker.targets.contains(&0));
+
+        picker.set_target(0, true);
+        picker.select_all(&items);
+        assert_eq!(picker.targets, HashSet::from([1]));
+        assert!(picker.has_selection());
+    }
+
+    #[test]
+    fn filter_limits_select_all() {
+        let items = vec![item(0, "Alpha", "a"), item(1, "Beta", "a")];
+        let mut picker = SyncPicker {
+            filter: "alp".to_string(),
+            ..Default::default()
+        };
+        picker.select_all(&items);
+        assert_eq!(picker.targets, HashSet::from([0]));
+
+        picker.filter = "90000001".to_string();
+        assert!(picker.matches(&items[1]));
+        assert!(!picker.matches(&items[0]));
+    }
+
+    #[test]
+    fn rows_get_a_header_per_profile() {
+        let items = [item(0, "A", "a"), item(1, "B", "a"), item(2, "C", "b")];
+        let rows = list_rows(items.iter(), Grouping::Profile);
+        let headers = rows
+            .iter()
+            .filter(|r| matches!(r, ListRow::Header(_)))
+            .count();
+        assert_eq!((rows.len(), headers), (5, 2));
+        assert_eq!(list_rows(items.iter(), Grouping::None).len(), 3);
+    }
+}